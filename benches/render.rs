@@ -0,0 +1,96 @@
+//! Benchmarks of rendering the [corpus](katex::bench::corpus).
+//!
+//! Run `cargo bench --features bench`, with `--no-default-features` and
+//! another backend feature to compare JS backends.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use katex::{bench::corpus, Opts, Renderer};
+
+fn bench_startup(c: &mut Criterion) {
+    c.bench_function("startup", |b| b.iter(|| Renderer::new().unwrap()));
+}
+
+fn bench_render(c: &mut Criterion) {
+    let renderer = Renderer::new().unwrap();
+    let mut group = c.benchmark_group("render");
+    for formula in corpus() {
+        let opts = Opts::builder()
+            .display_mode(formula.display_mode)
+            .build()
+            .unwrap();
+        group.bench_function(formula.name, |b| {
+            b.iter(|| {
+                renderer
+                    .render_with_opts(black_box(formula.input), &opts)
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_corpus(c: &mut Criterion) {
+    let renderer = Renderer::new().unwrap();
+    let formulas: Vec<_> = corpus()
+        .iter()
+        .map(|f| {
+            let opts = Opts::builder()
+                .display_mode(f.display_mode)
+                .build()
+                .unwrap();
+            (f.input, opts)
+        })
+        .collect();
+    c.bench_function("corpus", |b| {
+        b.iter(|| {
+            for (input, opts) in &formulas {
+                renderer.render_with_opts(black_box(input), opts).unwrap();
+            }
+        })
+    });
+}
+
+/// An `aligned` block of `rows` steps, like auto-generated proofs.
+fn aligned_proof(rows: usize) -> String {
+    let rows: Vec<_> = (0..rows)
+        .map(|i| {
+            format!(
+                r"f_{{{i}}}(x) &= \sum_{{k=0}}^{{{i}}} \binom{{{i}}}{{k}} x^k + \frac{{a_{{{i}}}}}{{b}}"
+            )
+        })
+        .collect();
+    format!(r"\begin{{aligned}}{}\end{{aligned}}", rows.join(r" \\ "))
+}
+
+fn bench_large_input(c: &mut Criterion) {
+    let renderer = Renderer::new().unwrap();
+    let opts = Opts::builder().display_mode(true).build().unwrap();
+    let input = aligned_proof(1_500);
+    let mut group = c.benchmark_group(format!("large_input/{}KB", input.len() / 1000));
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("borrowed", |b| {
+        b.iter(|| renderer.render_with_opts(black_box(&input), &opts).unwrap())
+    });
+    // The baseline, copying the input before passing it to the JS engine as
+    // this crate did before borrowing it. Compare them on a backend reading
+    // borrowed input in place, e.g. with `--no-default-features --features
+    // bench,rquickjs`: quick-js copies borrowed input itself, so there the
+    // baseline copies twice.
+    group.bench_function("copied", |b| {
+        b.iter(|| {
+            let input = black_box(&input).clone();
+            renderer.render_with_opts(&input, &opts).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_startup,
+    bench_render,
+    bench_corpus,
+    bench_large_input
+);
+criterion_main!(benches);
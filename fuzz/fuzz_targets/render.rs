@@ -0,0 +1,23 @@
+//! Render arbitrary input with the hardened options.
+//!
+//! Errors are expected, but rendering must neither panic nor hang.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&flags, input)) = data.split_first() else {
+        return;
+    };
+    let Ok(input) = std::str::from_utf8(input) else {
+        return;
+    };
+    let mut opts = katex::Opts::hardened();
+    opts.set_display_mode(flags & 1 != 0);
+    opts.set_throw_on_error(flags & 2 != 0);
+    if flags & 4 != 0 {
+        opts.set_output_type(katex::OutputType::Mathml);
+    }
+    let _ = katex::render_with_opts(input, &opts);
+});
@@ -0,0 +1,84 @@
+#!/usr/bin/env python3
+"""Generate src/layout/metrics.rs, the KaTeX font metrics used for layout.
+
+The data comes from the fontMetricsData table of the bundled KaTeX, whose
+version is recorded in the output. Run it again after upgrading KaTeX.
+"""
+
+import re
+from pathlib import Path
+
+ROOT = Path(__file__).parent
+SOURCE = ROOT / "vendor" / "katex.min.js"
+VERSION = ROOT / "KATEX-VERSION"
+OUTPUT = ROOT / "src" / "layout" / "metrics.rs"
+# The fonts used by the layout, with the name of their table.
+FONTS = {
+    "Main-Regular": "MAIN_REGULAR",
+    "Main-Bold": "MAIN_BOLD",
+    "Math-Italic": "MATH_ITALIC",
+    "Math-BoldItalic": "MATH_BOLD_ITALIC",
+    "AMS-Regular": "AMS_REGULAR",
+    "Size1-Regular": "SIZE1_REGULAR",
+}
+
+
+def font_table(source, font):
+    """Return the metrics of `font`, by code point."""
+    start = source.index(f'"{font}":{{') + len(font) + 4
+    end = source.index("}", start)
+    metrics = {}
+    for code, values in re.findall(r"(\d+):\[([^\]]*)\]", source[start:end]):
+        # Depth, height, italic correction, skew and width.
+        depth, height, _, _, width = (float(v) for v in values.split(","))
+        metrics[int(code)] = (depth, height, width)
+    return metrics
+
+
+def num(value):
+    # Keep a digit after the point, so that whole numbers are floats in Rust.
+    s = f"{value:.5f}".rstrip("0")
+    return s + "0" if s.endswith(".") else s
+
+
+def rows(entries, per_line):
+    lines = []
+    for i in range(0, len(entries), per_line):
+        lines.append("    " + " ".join(entries[i : i + per_line]))
+    return "\n".join(lines)
+
+
+def main():
+    source = SOURCE.read_text()
+    version = VERSION.read_text().strip()
+    tables = []
+    for font, name in FONTS.items():
+        metrics = font_table(source, font)
+        entries = [
+            f"('\\u{{{cp:04X}}}', {num(d)}, {num(h)}, {num(w)}),"
+            for cp, (d, h, w) in sorted(metrics.items())
+        ]
+        tables.append(
+            f"""/// Metrics of `KaTeX_{font}`.
+#[rustfmt::skip]
+pub(super) const {name}: &[Metrics] = &[
+{rows(entries, 3)}
+];
+"""
+        )
+    body = "\n".join(tables)
+    OUTPUT.write_text(
+        f"""//! Font metrics of KaTeX {version}.
+//!
+//! Generated by `gen_font_metrics.py`, do not edit.
+
+/// A character, with its depth, height and width in ems, sorted by
+/// character.
+pub(super) type Metrics = (char, f64, f64, f64);
+
+{body}"""
+    )
+
+
+if __name__ == "__main__":
+    main()
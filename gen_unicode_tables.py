@@ -0,0 +1,82 @@
+#!/usr/bin/env python3
+"""Generate src/unicode/tables.rs, the Unicode data used for NFC normalization.
+
+The data comes from the unicodedata module of the running Python, whose
+Unicode version is recorded in the output.
+"""
+
+import unicodedata
+from pathlib import Path
+
+OUTPUT = Path(__file__).parent / "src" / "unicode" / "tables.rs"
+# Hangul syllables are composed and decomposed algorithmically.
+HANGUL = range(0xAC00, 0xD7A4)
+
+
+def char(cp):
+    return f"'\\u{{{cp:04X}}}'"
+
+
+def rows(entries, per_line):
+    lines = []
+    for i in range(0, len(entries), per_line):
+        lines.append("    " + " ".join(entries[i : i + per_line]))
+    return "\n".join(lines)
+
+
+def main():
+    decompositions = []
+    compositions = []
+    for cp in range(0x110000):
+        if cp in HANGUL:
+            continue
+        mapping = unicodedata.decomposition(chr(cp))
+        if not mapping or mapping.startswith("<"):
+            continue
+        parts = [int(p, 16) for p in mapping.split()]
+        first, second = parts[0], parts[1] if len(parts) > 1 else 0
+        decompositions.append((cp, first, second))
+        # Excluded compositions do not survive a round trip through NFC.
+        if second and unicodedata.normalize("NFC", chr(first) + chr(second)) == chr(cp):
+            compositions.append((first, second, cp))
+    compositions.sort()
+
+    classes = []
+    for cp in range(0x110000):
+        ccc = unicodedata.combining(chr(cp))
+        if not ccc:
+            continue
+        if classes and classes[-1][1] == cp - 1 and classes[-1][2] == ccc:
+            classes[-1][1] = cp
+        else:
+            classes.append([cp, cp, ccc])
+
+    OUTPUT.write_text(
+        f"""//! Unicode {unicodedata.unidata_version} data for NFC normalization.
+//!
+//! Generated by `gen_unicode_tables.py`, do not edit.
+
+/// Canonical decompositions, sorted, with `'\\0'` as the second character of
+/// singletons.
+#[rustfmt::skip]
+pub(super) const DECOMPOSITIONS: &[(char, char, char)] = &[
+{rows([f"({char(c)}, {char(a)}, {char(b)})," for c, a, b in decompositions], 3)}
+];
+
+/// Primary compositions, sorted by the pair of characters.
+#[rustfmt::skip]
+pub(super) const COMPOSITIONS: &[(char, char, char)] = &[
+{rows([f"({char(a)}, {char(b)}, {char(c)})," for a, b, c in compositions], 3)}
+];
+
+/// Ranges of characters with a non-zero canonical combining class.
+#[rustfmt::skip]
+pub(super) const COMBINING_CLASSES: &[(char, char, u8)] = &[
+{rows([f"({char(s)}, {char(e)}, {k})," for s, e, k in classes], 3)}
+];
+"""
+    )
+
+
+if __name__ == "__main__":
+    main()
@@ -1,7 +1,406 @@
-var global;
-try {
-    global = Function('return this')();
-} catch (e) {
-    global = window;
-}
-global.katexRenderToString = katex.renderToString;
+// Entry points of katex-rs, defined in the `__katex_rs` namespace so that
+// they are the only global, e.g. in the page of the wasm-js backend. The
+// namespace is also callable as `__katex_rs(name, ...args)`, for engines which
+// only call global functions.
+(function () {
+    var global;
+    try {
+        global = Function('return this')();
+    } catch (e) {
+        global = window;
+    }
+    var katexRs = function (name) {
+        return katexRs[name].apply(null, Array.prototype.slice.call(arguments, 1));
+    };
+    katexRs.version = katex.version;
+    // Buffer console messages for `takeLogs`, and route them to the
+    // `__katexRsConsole` host function if registered, and to the original console
+    // otherwise. This also provides a console to engines without one.
+    var logs = [];
+    var MAX_LOGS = 1000;
+    (function () {
+        var original = global.console;
+        var console = {};
+        ["log", "info", "warn", "error", "debug"].forEach(function (level) {
+            console[level] = function () {
+                var message = Array.prototype.map.call(arguments, String).join(" ");
+                if (logs.length < MAX_LOGS) {
+                    logs.push(level + "\t" + message);
+                }
+                if (typeof global.__katexRsConsole === "function") {
+                    global.__katexRsConsole(level, message);
+                } else if (original && typeof original[level] === "function") {
+                    original[level].apply(original, arguments);
+                }
+            };
+        });
+        global.console = console;
+    })();
+    // Lex a macro body into the reversed tokens of KaTeX's object form of macros.
+    // KaTeX does not export its lexer, so it is borrowed from a parse tree.
+    var macroLexer = null;
+    function macroTokens(body) {
+        if (macroLexer === null) {
+            macroLexer = katex.__parse("x")[0].loc.lexer;
+        }
+        var lexer = new macroLexer.constructor(body, macroLexer.settings);
+        var tokens = [];
+        for (var token = lexer.lex(); token.text !== "EOF"; token = lexer.lex()) {
+            tokens.push(token);
+        }
+        return tokens.reverse();
+    }
+    // Names of the user macros looked up by the last render, if tracked.
+    var usedMacros = [];
+    // Convert the options passed from Rust to KaTeX settings.
+    function prepareOpts(opts) {
+        // Macros with parameters are passed as `{body, numArgs, unexpandable}`.
+        if (opts && opts.macros) {
+            Object.keys(opts.macros).forEach(function (name) {
+                var macro = opts.macros[name];
+                if (!macro || typeof macro.body !== "string") {
+                    return;
+                }
+                var numArgs = macro.numArgs;
+                if (numArgs === undefined) {
+                    // Same inference as KaTeX for string bodies.
+                    var stripped = macro.body.replace(/##/g, "");
+                    numArgs = 0;
+                    while (stripped.indexOf("#" + (numArgs + 1)) !== -1) {
+                        numArgs++;
+                    }
+                }
+                opts.macros[name] = {
+                    tokens: macroTokens(macro.body),
+                    numArgs: numArgs,
+                    unexpandable: macro.unexpandable === true
+                };
+            });
+        }
+        // Record the lookups of the user macros for `renderToString`, in order
+        // of first use. Macros defined by the input, e.g. with `\def`, are not
+        // recorded.
+        usedMacros = [];
+        if (opts && opts.trackMacros) {
+            delete opts.trackMacros;
+            var used = usedMacros;
+            var macros = opts.macros || {};
+            var userMacros = Object.create(null);
+            Object.keys(macros).forEach(function (name) {
+                userMacros[name] = true;
+            });
+            opts.macros = new Proxy(macros, {
+                get: function (target, name) {
+                    if (typeof name === "string" && userMacros[name] === true && used.indexOf(name) === -1) {
+                        used.push(name);
+                    }
+                    return target[name];
+                }
+            });
+        }
+        // Strict overrides are compiled into a strict callback, falling back to
+        // the `strict` option for the other codes.
+        if (opts && opts.strictOverrides) {
+            var strictOverrides = opts.strictOverrides;
+            var strict = opts.strict === undefined ? "warn" : opts.strict;
+            delete opts.strictOverrides;
+            opts.strict = function (errorCode) {
+                if (Object.prototype.hasOwnProperty.call(strictOverrides, errorCode)) {
+                    return strictOverrides[errorCode];
+                }
+                return typeof strict === "function" ? strict.apply(this, arguments) : strict;
+            };
+        }
+        // A list of trusted commands is compiled into a trust callback.
+        if (opts && Array.isArray(opts.trust)) {
+            var trustedCommands = opts.trust;
+            opts.trust = function (context) {
+                return trustedCommands.indexOf(context.command) !== -1;
+            };
+        }
+        // Otherwise, ask the `__katexRsTrust` host function if registered.
+        if (opts && opts.trust === undefined && typeof global.__katexRsTrust === "function") {
+            opts.trust = function (context) {
+                return global.__katexRsTrust(
+                    context.command,
+                    context.url === undefined ? "" : String(context.url),
+                    context.protocol === undefined ? "" : String(context.protocol)
+                ) === "true";
+            };
+        }
+        return opts;
+    }
+    // Return the size of a string in UTF-8.
+    function utf8Size(s) {
+        var size = 0;
+        for (var i = 0; i < s.length; i++) {
+            var c = s.charCodeAt(i);
+            // A surrogate pair takes 4 bytes, 2 per code unit.
+            size += c < 0x80 ? 1 : c < 0x800 || (c >= 0xd800 && c < 0xe000) ? 2 : 3;
+        }
+        return size;
+    }
+    // Return "height\tdepth\tmacros\thtml", where the height and the depth of the
+    // formula in em are empty without HTML output, and the tracked macros are
+    // separated by newlines, which KaTeX never looks up. Return "tooLarge\tsize"
+    // instead if the HTML exceeds `maxOutputBytes`, without copying it out.
+    katexRs.renderToString = function (input, opts) {
+        logs = [];
+        var maxOutputBytes = opts && opts.maxOutputBytes;
+        if (opts) {
+            delete opts.maxOutputBytes;
+        }
+        opts = prepareOpts(opts);
+        var tree = katex.__renderToDomTree(input, opts);
+        var metrics = opts && opts.output === "mathml" ? "\t" : tree.height + "\t" + tree.depth;
+        var markup = tree.toMarkup();
+        // Each UTF-16 code unit takes at most 3 bytes.
+        if (typeof maxOutputBytes === "number" && markup.length * 3 > maxOutputBytes) {
+            var size = utf8Size(markup);
+            if (size > maxOutputBytes) {
+                return "tooLarge\t" + size;
+            }
+        }
+        return metrics + "\t" + usedMacros.join("\n") + "\t" + markup;
+    };
+    // Render into a DOM element with `katex.render`, replacing its children, for
+    // `katex::wasm::render_to_element`. The output is never copied out, so
+    // `maxOutputBytes` does not apply.
+    katexRs.renderToElement = function (input, opts, element) {
+        logs = [];
+        if (opts) {
+            delete opts.maxOutputBytes;
+        }
+        katex.render(input, element, prepareOpts(opts));
+        return "";
+    };
+    // Render a trivial formula, to detect a broken engine after an error.
+    katexRs.healthCheck = function () {
+        katex.renderToString("x");
+        return "ok";
+    };
+    // Return the console messages logged since the last render, as "level\tmessage"
+    // entries separated by NUL characters.
+    katexRs.takeLogs = function () {
+        var joined = logs.join("\u0000");
+        logs = [];
+        return joined;
+    };
+    // Return the glyphs missing from the KaTeX fonts, as "text\tfont\tname" lines
+    // for characters without metrics in the font `name`, and "text\tscript\tname"
+    // lines for characters of the script `name` which KaTeX leaves to system fonts.
+    katexRs.missingGlyphs = function (input, opts) {
+        opts = prepareOpts(opts);
+        var lines = [];
+        var warn = global.console.warn;
+        global.console.warn = function (message) {
+            var match = /^No character metrics for '([^]*)' in style '([^']*)'/.exec(String(message));
+            if (match) {
+                lines.push(match[1] + "\tfont\t" + match[2]);
+            } else {
+                warn.apply(this, arguments);
+            }
+        };
+        var tree;
+        try {
+            tree = katex.__renderToHTMLTree(input, opts);
+        } finally {
+            global.console.warn = warn;
+        }
+        function walk(node) {
+            if (!node || typeof node !== "object") {
+                return;
+            }
+            if (typeof node.text === "string" && Array.isArray(node.classes)) {
+                node.classes.forEach(function (c) {
+                    if (/_fallback$/.test(c)) {
+                        lines.push(node.text + "\tscript\t" + c.slice(0, -"_fallback".length));
+                    }
+                });
+            }
+            if (Array.isArray(node.children)) {
+                node.children.forEach(walk);
+            }
+        }
+        walk(tree);
+        return lines.join("\n");
+    };
+    // Return the number of nodes and the depth of the parse tree, as "nodes,depth".
+    katexRs.complexity = function (input, opts) {
+        var tree = katex.__parse(input, prepareOpts(opts));
+        var nodes = 0;
+        var depth = 0;
+        function walk(value, level) {
+            if (Array.isArray(value)) {
+                value.forEach(function (v) {
+                    walk(v, level);
+                });
+                return;
+            }
+            if (!value || typeof value !== "object" || typeof value.type !== "string") {
+                return;
+            }
+            nodes++;
+            depth = Math.max(depth, level);
+            for (var key in value) {
+                if (key !== "loc" && Object.prototype.hasOwnProperty.call(value, key)) {
+                    walk(value[key], level + 1);
+                }
+            }
+        }
+        walk(tree, 1);
+        return nodes + "," + depth;
+    };
+    // Return the colors used by the parse tree, as "role\tcolor" lines in order of
+    // first use.
+    katexRs.colors = function (input, opts) {
+        opts = prepareOpts(opts);
+        var lines = [];
+        function add(role, color) {
+            var line = role + "\t" + color;
+            if (color && lines.indexOf(line) === -1) {
+                lines.push(line);
+            }
+        }
+        var tree;
+        try {
+            tree = katex.__parse(input, opts);
+        } catch (e) {
+            if (e instanceof katex.ParseError && opts && opts.throwOnError === false) {
+                add("error", opts.errorColor || "#cc0000");
+                return lines.join("\n");
+            }
+            throw e;
+        }
+        function walk(value) {
+            if (Array.isArray(value)) {
+                value.forEach(walk);
+                return;
+            }
+            if (!value || typeof value !== "object" || typeof value.type !== "string") {
+                return;
+            }
+            if (value.type === "color") {
+                add("text", value.color);
+            } else if (value.type === "enclose") {
+                add("background", value.backgroundColor);
+                add("border", value.borderColor);
+            }
+            for (var key in value) {
+                if (key !== "loc" && Object.prototype.hasOwnProperty.call(value, key)) {
+                    walk(value[key]);
+                }
+            }
+        }
+        walk(tree);
+        return lines.join("\n");
+    };
+    // Return the parse tree as JSON, without the source locations, which refer
+    // to the lexer.
+    katexRs.parse = function (input, opts) {
+        var tree = katex.__parse(input, prepareOpts(opts));
+        return JSON.stringify(tree, function (key, value) {
+            return key === "loc" ? undefined : value;
+        });
+    };
+    // Return which of the commands ("\name") and environments ("name"), given one
+    // per line, are defined neither by KaTeX nor by the macros of the options.
+    // Commands are also tried in text mode, e.g. "\textbackslash".
+    katexRs.undefinedCommands = function (names, opts) {
+        opts = prepareOpts(opts);
+        function isDefined(input) {
+            try {
+                katex.__parse(input, opts);
+            } catch (e) {
+                if (!(e instanceof katex.ParseError)) {
+                    throw e;
+                }
+                return !/Undefined control sequence|No such environment/.test(e.message);
+            }
+            return true;
+        }
+        return names
+            .split("\n")
+            .filter(function (name) {
+                if (name.charAt(0) !== "\\") {
+                    return !isDefined("\\begin{" + name + "}\\end{" + name + "}");
+                }
+                return !isDefined(name) && !isDefined("\\text{" + name + "}");
+            })
+            .join("\n");
+    };
+    // The Speech Rule Engine, which a script of the engine defines as the `SRE`
+    // global.
+    function speechRuleEngine() {
+        var sre = global.SRE;
+        if (!sre || typeof sre.toSpeech !== "function") {
+            throw new Error("the Speech Rule Engine is not loaded");
+        }
+        return sre;
+    }
+    // The rule set SRE is ready for, as "domain\tstyle".
+    var speechRules = null;
+    // Set up SRE for a rule set. SRE 4 sets up asynchronously: return a promise
+    // settled once its rules are loaded, which the backends settle by running
+    // the pending jobs.
+    katexRs.setupSpeech = function (domain, style) {
+        var sre = speechRuleEngine();
+        var rules = domain + "\t" + style;
+        if (speechRules === rules) {
+            return "";
+        }
+        speechRules = null;
+        var setup = sre.setupEngine({ domain: domain, style: style, modality: "speech" });
+        if (!setup || typeof setup.then !== "function") {
+            speechRules = rules;
+            return "";
+        }
+        return setup
+            .then(function () {
+                return typeof sre.engineReady === "function" ? sre.engineReady() : undefined;
+            })
+            .then(function () {
+                speechRules = rules;
+                return "";
+            });
+    };
+    // Return the speech of MathML, once SRE is set up for the rule set.
+    katexRs.speech = function (mathml, domain, style) {
+        var sre = speechRuleEngine();
+        if (speechRules !== domain + "\t" + style) {
+            throw new Error("the Speech Rule Engine is not ready for " + domain + " " + style);
+        }
+        return String(sre.toSpeech(mathml));
+    };
+    // Parse the input without building the output. Return "" if it is valid, and
+    // "position\tlength\tmessage" for a parse error, with -1 for an unknown
+    // position, in UTF-16 code units.
+    katexRs.check = function (input, opts) {
+        try {
+            katex.__parse(input, prepareOpts(opts));
+            return "";
+        } catch (e) {
+            if (!(e instanceof katex.ParseError)) {
+                throw e;
+            }
+            var position = typeof e.position === "number" ? e.position : -1;
+            // Older KaTeX versions have neither `length` nor `rawMessage`, so the
+            // length is the number of code units underlined in the context, and
+            // the prefix and the context are stripped from the message.
+            var length = e.length;
+            if (typeof length !== "number") {
+                length = (e.message.match(/\u0332/g) || []).length;
+            }
+            var message = e.rawMessage;
+            if (message === undefined) {
+                message = e.message.replace(/^KaTeX parse error: /, "");
+                var context = message.search(/ at (position \d+|end of input): /);
+                if (position !== -1 && context !== -1) {
+                    message = message.slice(0, context);
+                }
+            }
+            return position + "\t" + length + "\t" + message;
+        }
+    };
+    global.__katex_rs = katexRs;
+})();
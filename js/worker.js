@@ -0,0 +1,11 @@
+// Render the jobs posted by `katex::worker` in a dedicated Web Worker, with
+// the same entry points as the other backends. Each job is answered with
+// `{id, result}`, or `{id, error}` if the render throws.
+self.onmessage = function (event) {
+    var job = event.data;
+    try {
+        self.postMessage({ id: job.id, result: __katex_rs.renderToString(job.input, job.opts) });
+    } catch (e) {
+        self.postMessage({ id: job.id, error: String(e) });
+    }
+};
@@ -0,0 +1,555 @@
+//! Bindings to the C API of the system
+//! [JavaScriptCore](https://developer.apple.com/documentation/javascriptcore)
+//! framework on Apple platforms, for the `jsc` backend of the
+//! [katex](https://docs.rs/katex) crate.
+//!
+//! [`ffi`] declares the C functions, and [`Context`] and [`Value`] wrap them
+//! in a safe API, so that katex itself does not use `unsafe`. The crate is
+//! empty on other platforms.
+
+#![cfg(target_vendor = "apple")]
+#![deny(missing_docs, unsafe_op_in_unsafe_fn)]
+
+use core::{fmt, ptr};
+use std::panic;
+
+use self::ffi::*;
+
+/// A Rust function callable from JS.
+///
+/// It takes string arguments and returns a string. An error is thrown as a
+/// JS exception with the given message.
+pub type HostFunction = Box<dyn Fn(Vec<String>) -> Result<String, String>>;
+
+/// A JavaScriptCore global context.
+pub struct Context {
+    context: JSGlobalContextRef,
+    /// Class of the host functions, whose private data is the boxed
+    /// [`HostFunction`].
+    host_function_class: JSClassRef,
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        // SAFETY: both were created in `new`, and the values borrowing the
+        // context are dropped before it.
+        unsafe {
+            JSGlobalContextRelease(self.context);
+            JSClassRelease(self.host_function_class);
+        }
+    }
+}
+
+impl Context {
+    /// Create a context with the default global object, or `None` if
+    /// JavaScriptCore fails to.
+    pub fn new() -> Option<Self> {
+        let definition = JSClassDefinition {
+            className: c"HostFunction".as_ptr(),
+            finalize: Some(finalize_host_function),
+            callAsFunction: Some(call_host_function),
+            ..JSClassDefinition::EMPTY
+        };
+        // SAFETY: the definition and its class name outlive the call, and the
+        // callbacks match the private data set in `register_function`.
+        let host_function_class = unsafe { JSClassCreate(&definition) };
+        if host_function_class.is_null() {
+            return None;
+        }
+        // SAFETY: a null class creates the default global object.
+        let context = unsafe { JSGlobalContextCreate(ptr::null_mut()) };
+        if context.is_null() {
+            // SAFETY: the class was created above and is not used afterwards.
+            unsafe { JSClassRelease(host_function_class) };
+            return None;
+        }
+        Some(Self {
+            context,
+            host_function_class,
+        })
+    }
+
+    /// Protect `value` from the garbage collector while the returned value
+    /// is alive.
+    fn value(&self, value: JSValueRef) -> Value<'_> {
+        // SAFETY: the value belongs to the context, and is unprotected on
+        // drop.
+        unsafe { JSValueProtect(self.context, value) };
+        Value {
+            context: self,
+            value,
+        }
+    }
+
+    /// Run `f` with an exception slot, failing with the message of the
+    /// exception it sets.
+    fn check<T>(&self, f: impl FnOnce(*mut JSValueRef) -> T) -> Result<T, String> {
+        let mut exception = ptr::null();
+        let result = f(&mut exception);
+        if exception.is_null() {
+            Ok(result)
+        } else {
+            Err(to_string(self.context, exception)
+                .unwrap_or_else(|| "unknown exception".to_owned()))
+        }
+    }
+
+    /// Evaluate a script, returning its completion value.
+    pub fn eval(&self, code: &str) -> Result<Value<'_>, String> {
+        let script = JsString::new(code);
+        let source_url = JsString::new("katex");
+        let result = self.check(|exception| {
+            // SAFETY: the strings outlive the call, and `exception` is a
+            // valid slot.
+            unsafe {
+                JSEvaluateScript(
+                    self.context,
+                    script.0,
+                    ptr::null_mut(),
+                    source_url.0,
+                    1,
+                    exception,
+                )
+            }
+        })?;
+        Ok(self.value(result))
+    }
+
+    /// Return the global object.
+    pub fn global_object(&self) -> Value<'_> {
+        // SAFETY: the global object lives as long as the context.
+        self.value(unsafe { JSContextGetGlobalObject(self.context) })
+    }
+
+    /// Return the property `name` of `object`.
+    pub fn property(&self, object: &Value<'_>, name: &str) -> Result<Value<'_>, String> {
+        let object = self.to_object(object)?;
+        let name = JsString::new(name);
+        let property = self.check(|exception| {
+            // SAFETY: the object is protected by the caller, the name outlives
+            // the call, and `exception` is a valid slot.
+            unsafe { JSObjectGetProperty(self.context, object, name.0, exception) }
+        })?;
+        Ok(self.value(property))
+    }
+
+    /// Set the property `name` of `object` to `value`.
+    pub fn set_property(
+        &self,
+        object: &Value<'_>,
+        name: &str,
+        value: &Value<'_>,
+    ) -> Result<(), String> {
+        let object = self.to_object(object)?;
+        let name = JsString::new(name);
+        self.check(|exception| {
+            // SAFETY: the object and the value are protected by the caller,
+            // the name outlives the call, and `exception` is a valid slot.
+            unsafe { JSObjectSetProperty(self.context, object, name.0, value.value, 0, exception) }
+        })
+    }
+
+    /// Return whether `value` is a function.
+    pub fn is_function(&self, value: &Value<'_>) -> bool {
+        // SAFETY: the value is protected by the caller, and the object is
+        // checked not to be null.
+        self.to_object(value).is_ok_and(|object| {
+            !object.is_null() && unsafe { JSObjectIsFunction(self.context, object) }
+        })
+    }
+
+    /// Call `function` with `args` and an undefined `this`.
+    pub fn call(&self, function: &Value<'_>, args: &[Value<'_>]) -> Result<Value<'_>, String> {
+        if !self.is_function(function) {
+            return Err("not a function".to_owned());
+        }
+        let function = self.to_object(function)?;
+        let argv: Vec<_> = args.iter().map(|arg| arg.value).collect();
+        let result = self.check(|exception| {
+            // SAFETY: the caller keeps the arguments protected during the
+            // call, `argv` outlives it, and `exception` is a valid slot.
+            unsafe {
+                JSObjectCallAsFunction(
+                    self.context,
+                    function,
+                    ptr::null_mut(),
+                    argv.len(),
+                    argv.as_ptr(),
+                    exception,
+                )
+            }
+        })?;
+        Ok(self.value(result))
+    }
+
+    /// Set the global `name` to a JS function calling `function`.
+    pub fn register_function(&self, name: &str, function: HostFunction) -> Result<(), String> {
+        let data = Box::into_raw(Box::new(function));
+        // SAFETY: the class finalizer frees `data` when the JS object is
+        // collected, and `call_host_function` only borrows it.
+        let object = self
+            .value(unsafe { JSObjectMake(self.context, self.host_function_class, data.cast()) });
+        self.set_property(&self.global_object(), name, &object)
+    }
+
+    /// Create a boolean.
+    pub fn boolean(&self, input: bool) -> Value<'_> {
+        // SAFETY: primitive values need no other arguments.
+        self.value(unsafe { JSValueMakeBoolean(self.context, input) })
+    }
+
+    /// Create a number.
+    pub fn number(&self, input: f64) -> Value<'_> {
+        // SAFETY: primitive values need no other arguments.
+        self.value(unsafe { JSValueMakeNumber(self.context, input) })
+    }
+
+    /// Create a string.
+    pub fn string(&self, input: &str) -> Value<'_> {
+        self.value(make_string(self.context, input))
+    }
+
+    /// Create an array of `elements`.
+    pub fn array(&self, elements: &[Value<'_>]) -> Result<Value<'_>, String> {
+        let values: Vec<_> = elements.iter().map(|element| element.value).collect();
+        let array = self.check(|exception| {
+            // SAFETY: the caller keeps the elements protected during the call,
+            // `values` outlives it, and `exception` is a valid slot.
+            unsafe { JSObjectMakeArray(self.context, values.len(), values.as_ptr(), exception) }
+        })?;
+        Ok(self.value(array))
+    }
+
+    /// Create an empty plain object.
+    pub fn object(&self) -> Value<'_> {
+        // SAFETY: a null class creates a plain object without private data.
+        self.value(unsafe { JSObjectMake(self.context, ptr::null_mut(), ptr::null_mut()) })
+    }
+
+    /// Convert `value` to an object as `Object(value)` does.
+    fn to_object(&self, value: &Value<'_>) -> Result<JSObjectRef, String> {
+        self.check(|exception| {
+            // SAFETY: the value is protected by the caller, and `exception` is
+            // a valid slot.
+            unsafe { JSValueToObject(self.context, value.value, exception) }
+        })
+    }
+}
+
+/// A JavaScriptCore value.
+///
+/// It is protected from the garbage collector while alive.
+pub struct Value<'a> {
+    context: &'a Context,
+    value: JSValueRef,
+}
+
+impl Drop for Value<'_> {
+    fn drop(&mut self) {
+        // SAFETY: the value was protected when created, and the context
+        // outlives it.
+        unsafe { JSValueUnprotect(self.context.context, self.value) };
+    }
+}
+
+impl Value<'_> {
+    /// Return the UTF-16 code units of the value if it is a string.
+    pub fn as_utf16(&self) -> Option<Vec<u16>> {
+        let context = self.context.context;
+        // SAFETY: the value is alive.
+        if !unsafe { JSValueIsString(context, self.value) } {
+            return None;
+        }
+        let mut exception = ptr::null();
+        // SAFETY: the value is alive, and `exception` is a valid slot.
+        let string = unsafe { JSValueToStringCopy(context, self.value, &mut exception) };
+        if string.is_null() || !exception.is_null() {
+            return None;
+        }
+        Some(JsString(string).chars().to_vec())
+    }
+}
+
+impl fmt::Debug for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Value").field("value", &self.value).finish()
+    }
+}
+
+/// An owned `JSStringRef`.
+struct JsString(JSStringRef);
+
+impl JsString {
+    fn new(input: &str) -> Self {
+        // UTF-16 rather than a C string, which would end at a NUL character.
+        let chars: Vec<u16> = input.encode_utf16().collect();
+        // SAFETY: the characters are valid for their length, and copied.
+        Self(unsafe { JSStringCreateWithCharacters(chars.as_ptr(), chars.len()) })
+    }
+
+    /// Return the UTF-16 code units of the string.
+    fn chars(&self) -> &[u16] {
+        // SAFETY: the string is alive.
+        let len = unsafe { JSStringGetLength(self.0) };
+        if len == 0 {
+            return &[];
+        }
+        // SAFETY: the characters are valid for the length of the string,
+        // which is immutable and outlives the borrow.
+        unsafe { core::slice::from_raw_parts(JSStringGetCharactersPtr(self.0), len) }
+    }
+}
+
+impl Drop for JsString {
+    fn drop(&mut self) {
+        // SAFETY: the string is owned, created or copied by the caller of
+        // `JsString`.
+        unsafe { JSStringRelease(self.0) };
+    }
+}
+
+/// Create a string value, which retains its own copy of the characters.
+fn make_string(context: JSContextRef, input: &str) -> JSValueRef {
+    let string = JsString::new(input);
+    // SAFETY: the string outlives the call, and the value retains it.
+    unsafe { JSValueMakeString(context, string.0) }
+}
+
+/// Convert a value to a string as `String(value)` does, replacing unpaired
+/// surrogates.
+fn to_string(context: JSContextRef, value: JSValueRef) -> Option<String> {
+    let mut exception = ptr::null();
+    // SAFETY: the value is alive, and `exception` is a valid slot.
+    let string = unsafe { JSValueToStringCopy(context, value, &mut exception) };
+    if string.is_null() || !exception.is_null() {
+        return None;
+    }
+    Some(String::from_utf16_lossy(JsString(string).chars()))
+}
+
+unsafe extern "C" fn call_host_function(
+    context: JSContextRef,
+    function: JSObjectRef,
+    _this: JSObjectRef,
+    argc: usize,
+    argv: *const JSValueRef,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    // SAFETY: objects of the host function class are only made in
+    // `register_function`, with a boxed `HostFunction` as private data, which
+    // is freed after the object is collected.
+    let function = unsafe { &*(JSObjectGetPrivate(function) as *const HostFunction) };
+    let args = if argc == 0 {
+        &[][..]
+    } else {
+        // SAFETY: JavaScriptCore passes `argc` valid arguments.
+        unsafe { core::slice::from_raw_parts(argv, argc) }
+    };
+    let result = args
+        .iter()
+        .map(|&arg| {
+            // SAFETY: the argument is alive during the call.
+            if unsafe { JSValueIsString(context, arg) } {
+                to_string(context, arg).ok_or_else(|| "cannot convert value to string".to_owned())
+            } else {
+                Err("expected string argument".to_owned())
+            }
+        })
+        .collect::<Result<_, _>>()
+        .and_then(|args| {
+            // Unwinding into JavaScriptCore is undefined behavior.
+            panic::catch_unwind(panic::AssertUnwindSafe(|| function(args)))
+                .unwrap_or_else(|_| Err("host function panicked".to_owned()))
+        });
+    match result {
+        Ok(result) => make_string(context, &result),
+        Err(msg) => {
+            let msg = make_string(context, &msg);
+            // SAFETY: JavaScriptCore passes a valid exception slot, and the
+            // message is alive during the call.
+            unsafe {
+                *exception = JSObjectMakeError(context, 1, &msg, ptr::null_mut());
+                JSValueMakeUndefined(context)
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn finalize_host_function(object: JSObjectRef) {
+    // SAFETY: the object is of the host function class, whose private data is
+    // set in `register_function`.
+    let data = unsafe { JSObjectGetPrivate(object) } as *mut HostFunction;
+    if !data.is_null() {
+        // SAFETY: the data was leaked from a box in `register_function`, and
+        // the object is collected, so it is not used afterwards.
+        drop(unsafe { Box::from_raw(data) });
+    }
+}
+
+/// Declarations of the C API of JavaScriptCore, see `JavaScriptCore/JSBase.h`,
+/// `JSContextRef.h`, `JSObjectRef.h`, `JSStringRef.h` and `JSValueRef.h`.
+#[allow(
+    non_camel_case_types,
+    non_snake_case,
+    clippy::upper_case_acronyms,
+    missing_docs
+)]
+pub mod ffi {
+    use core::ffi::{c_char, c_int, c_uint, c_void};
+
+    #[repr(C)]
+    pub struct OpaqueJSContext([u8; 0]);
+    #[repr(C)]
+    pub struct OpaqueJSString([u8; 0]);
+    #[repr(C)]
+    pub struct OpaqueJSClass([u8; 0]);
+    #[repr(C)]
+    pub struct OpaqueJSValue([u8; 0]);
+
+    pub type JSContextRef = *const OpaqueJSContext;
+    pub type JSGlobalContextRef = *mut OpaqueJSContext;
+    pub type JSStringRef = *mut OpaqueJSString;
+    pub type JSClassRef = *mut OpaqueJSClass;
+    pub type JSValueRef = *const OpaqueJSValue;
+    pub type JSObjectRef = *mut OpaqueJSValue;
+    pub type JSChar = u16;
+    pub type JSPropertyAttributes = c_uint;
+    pub type JSClassAttributes = c_uint;
+
+    pub type JSObjectFinalizeCallback = unsafe extern "C" fn(JSObjectRef);
+    pub type JSObjectCallAsFunctionCallback = unsafe extern "C" fn(
+        JSContextRef,
+        JSObjectRef,
+        JSObjectRef,
+        usize,
+        *const JSValueRef,
+        *mut JSValueRef,
+    ) -> JSValueRef;
+
+    #[repr(C)]
+    pub struct JSClassDefinition {
+        pub version: c_int,
+        pub attributes: JSClassAttributes,
+        pub className: *const c_char,
+        pub parentClass: JSClassRef,
+        pub staticValues: *const c_void,
+        pub staticFunctions: *const c_void,
+        pub initialize: Option<unsafe extern "C" fn()>,
+        pub finalize: Option<JSObjectFinalizeCallback>,
+        pub hasProperty: Option<unsafe extern "C" fn()>,
+        pub getProperty: Option<unsafe extern "C" fn()>,
+        pub setProperty: Option<unsafe extern "C" fn()>,
+        pub deleteProperty: Option<unsafe extern "C" fn()>,
+        pub getPropertyNames: Option<unsafe extern "C" fn()>,
+        pub callAsFunction: Option<JSObjectCallAsFunctionCallback>,
+        pub callAsConstructor: Option<unsafe extern "C" fn()>,
+        pub hasInstance: Option<unsafe extern "C" fn()>,
+        pub convertToType: Option<unsafe extern "C" fn()>,
+    }
+
+    impl JSClassDefinition {
+        /// `kJSClassDefinitionEmpty`.
+        pub const EMPTY: Self = Self {
+            version: 0,
+            attributes: 0,
+            className: core::ptr::null(),
+            parentClass: core::ptr::null_mut(),
+            staticValues: core::ptr::null(),
+            staticFunctions: core::ptr::null(),
+            initialize: None,
+            finalize: None,
+            hasProperty: None,
+            getProperty: None,
+            setProperty: None,
+            deleteProperty: None,
+            getPropertyNames: None,
+            callAsFunction: None,
+            callAsConstructor: None,
+            hasInstance: None,
+            convertToType: None,
+        };
+    }
+
+    #[link(name = "JavaScriptCore", kind = "framework")]
+    extern "C" {
+        pub fn JSGlobalContextCreate(globalObjectClass: JSClassRef) -> JSGlobalContextRef;
+        pub fn JSGlobalContextRelease(ctx: JSGlobalContextRef);
+        pub fn JSContextGetGlobalObject(ctx: JSContextRef) -> JSObjectRef;
+        pub fn JSEvaluateScript(
+            ctx: JSContextRef,
+            script: JSStringRef,
+            thisObject: JSObjectRef,
+            sourceURL: JSStringRef,
+            startingLineNumber: c_int,
+            exception: *mut JSValueRef,
+        ) -> JSValueRef;
+
+        pub fn JSClassCreate(definition: *const JSClassDefinition) -> JSClassRef;
+        pub fn JSClassRelease(jsClass: JSClassRef);
+
+        pub fn JSObjectMake(
+            ctx: JSContextRef,
+            jsClass: JSClassRef,
+            data: *mut c_void,
+        ) -> JSObjectRef;
+        pub fn JSObjectMakeArray(
+            ctx: JSContextRef,
+            argumentCount: usize,
+            arguments: *const JSValueRef,
+            exception: *mut JSValueRef,
+        ) -> JSObjectRef;
+        pub fn JSObjectMakeError(
+            ctx: JSContextRef,
+            argumentCount: usize,
+            arguments: *const JSValueRef,
+            exception: *mut JSValueRef,
+        ) -> JSObjectRef;
+        pub fn JSObjectGetPrivate(object: JSObjectRef) -> *mut c_void;
+        pub fn JSObjectGetProperty(
+            ctx: JSContextRef,
+            object: JSObjectRef,
+            propertyName: JSStringRef,
+            exception: *mut JSValueRef,
+        ) -> JSValueRef;
+        pub fn JSObjectSetProperty(
+            ctx: JSContextRef,
+            object: JSObjectRef,
+            propertyName: JSStringRef,
+            value: JSValueRef,
+            attributes: JSPropertyAttributes,
+            exception: *mut JSValueRef,
+        );
+        pub fn JSObjectIsFunction(ctx: JSContextRef, object: JSObjectRef) -> bool;
+        pub fn JSObjectCallAsFunction(
+            ctx: JSContextRef,
+            object: JSObjectRef,
+            thisObject: JSObjectRef,
+            argumentCount: usize,
+            arguments: *const JSValueRef,
+            exception: *mut JSValueRef,
+        ) -> JSValueRef;
+
+        pub fn JSStringCreateWithCharacters(chars: *const JSChar, numChars: usize) -> JSStringRef;
+        pub fn JSStringRelease(string: JSStringRef);
+        pub fn JSStringGetLength(string: JSStringRef) -> usize;
+        pub fn JSStringGetCharactersPtr(string: JSStringRef) -> *const JSChar;
+
+        pub fn JSValueIsString(ctx: JSContextRef, value: JSValueRef) -> bool;
+        pub fn JSValueMakeUndefined(ctx: JSContextRef) -> JSValueRef;
+        pub fn JSValueMakeBoolean(ctx: JSContextRef, boolean: bool) -> JSValueRef;
+        pub fn JSValueMakeNumber(ctx: JSContextRef, number: f64) -> JSValueRef;
+        pub fn JSValueMakeString(ctx: JSContextRef, string: JSStringRef) -> JSValueRef;
+        pub fn JSValueToObject(
+            ctx: JSContextRef,
+            value: JSValueRef,
+            exception: *mut JSValueRef,
+        ) -> JSObjectRef;
+        pub fn JSValueToStringCopy(
+            ctx: JSContextRef,
+            value: JSValueRef,
+            exception: *mut JSValueRef,
+        ) -> JSStringRef;
+        pub fn JSValueProtect(ctx: JSContextRef, value: JSValueRef);
+        pub fn JSValueUnprotect(ctx: JSContextRef, value: JSValueRef);
+    }
+}
@@ -0,0 +1,70 @@
+//! Procedural macros of the [katex](https://docs.rs/katex) crate, which
+//! re-exports them with its `macros` feature.
+
+use proc_macro::TokenStream;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Ident, LitStr, Token,
+};
+
+/// Input of [`math!`]: an optional `display` mode followed by the equation.
+struct MathInput {
+    display_mode: bool,
+    equation: LitStr,
+}
+
+impl Parse for MathInput {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let display_mode = if input.peek(Ident) {
+            let mode: Ident = input.parse()?;
+            if mode != "display" {
+                return Err(syn::Error::new(
+                    mode.span(),
+                    "expected `display` or a string literal",
+                ));
+            }
+            input.parse::<Token![,]>()?;
+            true
+        } else {
+            false
+        };
+        let equation = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self {
+            display_mode,
+            equation,
+        })
+    }
+}
+
+/// Render a LaTeX equation to HTML at compile time, expanding to a
+/// `&'static str`.
+///
+/// Sites with a fixed set of equations then pay no rendering at runtime.
+/// Start the input with `display,` to render in display mode, otherwise the
+/// [default options](https://docs.rs/katex/latest/katex/opts/struct.Opts.html)
+/// apply. An equation which fails to render fails the build.
+///
+/// # Examples
+///
+/// ```ignore
+/// const EQUATION: &str = katex::math!("E = mc^2");
+/// let sum = katex::math!(display, r"\sum_{i=1}^n x_i");
+/// ```
+#[proc_macro]
+pub fn math(input: TokenStream) -> TokenStream {
+    let MathInput {
+        display_mode,
+        equation,
+    } = parse_macro_input!(input as MathInput);
+    let opts = katex::Opts::builder()
+        .display_mode(display_mode)
+        .build()
+        .expect("valid options");
+    match katex::render_with_opts(&equation.value(), opts) {
+        Ok(html) => quote::quote!(#html).into(),
+        Err(e) => syn::Error::new(equation.span(), format!("cannot render the equation: {e}"))
+            .to_compile_error()
+            .into(),
+    }
+}
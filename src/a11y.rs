@@ -0,0 +1,372 @@
+//! Spoken descriptions of equations, for accessibility.
+//!
+//! [`alt_text`] describes an equation in English words, e.g. "E equals m c
+//! squared" for `E = mc^2`, like the speech rules of MathJax but much
+//! simpler. The description is built from the MathML of the render, which
+//! mirrors KaTeX's parse tree, so macros are expanded. It suits the `alt`
+//! attribute of images or the `aria-label` of the output, which
+//! [`aria_label`](crate::OptsBuilder::aria_label) sets, for screen readers
+//! without MathML support.
+//!
+//! For the richer speech of the [Speech Rule Engine](https://speechruleengine.org),
+//! which MathJax uses, load it in a [renderer](crate::Renderer) and call
+//! [`Renderer::render_speech`](crate::Renderer::render_speech).
+//!
+//! # Examples
+//!
+//! ```
+//! let text = katex::a11y::alt_text(r"\frac{a}{b} \le \sqrt{x^2 + 1}").unwrap();
+//! assert_eq!(text, "a over b is less than or equal to the square root of x squared plus 1");
+//! ```
+
+use crate::{
+    error::Result,
+    html::{self, Node},
+    opts::{Opts, OutputType},
+};
+
+/// Spoken forms of operators.
+const OPERATORS: &[(&str, &str)] = &[
+    ("=", "equals"),
+    ("+", "plus"),
+    ("−", "minus"),
+    ("-", "minus"),
+    ("±", "plus or minus"),
+    ("∓", "minus or plus"),
+    ("×", "times"),
+    ("⋅", "times"),
+    ("÷", "divided by"),
+    ("/", "divided by"),
+    ("<", "is less than"),
+    (">", "is greater than"),
+    ("≤", "is less than or equal to"),
+    ("≥", "is greater than or equal to"),
+    ("≠", "is not equal to"),
+    ("≈", "is approximately equal to"),
+    ("≡", "is equivalent to"),
+    ("∝", "is proportional to"),
+    ("→", "to"),
+    ("↦", "maps to"),
+    ("⇒", "implies"),
+    ("⟹", "implies"),
+    ("⇔", "if and only if"),
+    ("⟺", "if and only if"),
+    ("∈", "is in"),
+    ("∉", "is not in"),
+    ("⊂", "is a subset of"),
+    ("⊆", "is a subset of or equal to"),
+    ("∪", "union"),
+    ("∩", "intersection"),
+    ("∀", "for all"),
+    ("∃", "there exists"),
+    ("¬", "not"),
+    ("∧", "and"),
+    ("∨", "or"),
+    ("∑", "sum"),
+    ("∏", "product"),
+    ("∫", "integral"),
+    ("∮", "contour integral"),
+    ("∂", "partial"),
+    ("∇", "nabla"),
+    ("!", "factorial"),
+    ("′", "prime"),
+    ("(", "open paren"),
+    (")", "close paren"),
+    ("[", "open bracket"),
+    ("]", "close bracket"),
+    ("{", "open brace"),
+    ("}", "close brace"),
+    ("∣", "divides"),
+    ("|", "vertical bar"),
+    ("…", "dot dot dot"),
+    ("⋯", "dot dot dot"),
+    // Function application, e.g. after `\sin`.
+    ("\u{2061}", "of"),
+];
+
+/// Spoken forms of identifiers and function names.
+const IDENTIFIERS: &[(&str, &str)] = &[
+    ("α", "alpha"),
+    ("β", "beta"),
+    ("γ", "gamma"),
+    ("δ", "delta"),
+    ("ϵ", "epsilon"),
+    ("ε", "epsilon"),
+    ("ζ", "zeta"),
+    ("η", "eta"),
+    ("θ", "theta"),
+    ("ι", "iota"),
+    ("κ", "kappa"),
+    ("λ", "lambda"),
+    ("μ", "mu"),
+    ("ν", "nu"),
+    ("ξ", "xi"),
+    ("π", "pi"),
+    ("ρ", "rho"),
+    ("σ", "sigma"),
+    ("τ", "tau"),
+    ("υ", "upsilon"),
+    ("ϕ", "phi"),
+    ("φ", "phi"),
+    ("χ", "chi"),
+    ("ψ", "psi"),
+    ("ω", "omega"),
+    ("Γ", "capital gamma"),
+    ("Δ", "capital delta"),
+    ("Θ", "capital theta"),
+    ("Λ", "capital lambda"),
+    ("Ξ", "capital xi"),
+    ("Π", "capital pi"),
+    ("Σ", "capital sigma"),
+    ("Φ", "capital phi"),
+    ("Ψ", "capital psi"),
+    ("Ω", "capital omega"),
+    ("∞", "infinity"),
+    ("∅", "the empty set"),
+    ("ℏ", "h bar"),
+    ("ℓ", "ell"),
+    ("∂", "partial"),
+    ("∇", "nabla"),
+    ("sin", "sine"),
+    ("cos", "cosine"),
+    ("tan", "tangent"),
+    ("ln", "natural log"),
+    ("exp", "exponential"),
+    ("max", "maximum"),
+    ("min", "minimum"),
+];
+
+/// Spoken forms of limits.
+const LIMITS: &[(&str, &str)] = &[
+    ("lim", "the limit as"),
+    ("max", "the maximum over"),
+    ("min", "the minimum over"),
+    ("sup", "the supremum over"),
+    ("inf", "the infimum over"),
+];
+
+/// Spoken forms of accents, read after their base.
+const ACCENTS: &[(&str, &str)] = &[
+    ("^", "hat"),
+    ("~", "tilde"),
+    ("ˉ", "bar"),
+    ("‾", "bar"),
+    ("→", "vector"),
+    ("\u{20d7}", "vector"),
+    ("˙", "dot"),
+    ("¨", "double dot"),
+];
+
+/// Rule set of the [Speech Rule Engine](https://speechruleengine.org), for
+/// [`Renderer::render_speech`](crate::Renderer::render_speech).
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SpeechStyle {
+    /// MathSpeak, reading every structure, e.g. "StartFraction a Over b
+    /// EndFraction".
+    #[default]
+    MathSpeak,
+    /// MathSpeak with shorter markers, e.g. "StartFrac a Over b EndFrac".
+    MathSpeakBrief,
+    /// MathSpeak with the shortest markers, e.g. "Frac a Over b EndFrac".
+    MathSpeakSuperbrief,
+    /// ClearSpeak, reading like a teacher, e.g. "a over b".
+    ClearSpeak,
+}
+
+impl SpeechStyle {
+    /// The domain and the style of the rule set, as SRE names them.
+    pub(crate) fn sre_names(self) -> (&'static str, &'static str) {
+        match self {
+            SpeechStyle::MathSpeak => ("mathspeak", "default"),
+            SpeechStyle::MathSpeakBrief => ("mathspeak", "brief"),
+            SpeechStyle::MathSpeakSuperbrief => ("mathspeak", "sbrief"),
+            SpeechStyle::ClearSpeak => ("clearspeak", "default"),
+        }
+    }
+}
+
+/// Describe a LaTeX equation in English words.
+#[inline]
+pub fn alt_text(input: &str) -> Result<String> {
+    alt_text_with_opts(input, Opts::default())
+}
+
+/// Describe a LaTeX equation in English words with additional
+/// [options](`Opts`), e.g. macros.
+///
+/// The [output type](`OutputType`) is ignored.
+pub fn alt_text_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    let mut opts = opts.as_ref().clone();
+    opts.set_output_type(OutputType::Mathml);
+    let mathml = crate::render_with_opts(input, opts)?;
+    Ok(describe(&mathml))
+}
+
+/// Describe the MathML of a render in English words.
+pub(crate) fn describe(mathml: &str) -> String {
+    let mut words = Vec::new();
+    for node in html::parse(mathml) {
+        speak(&node, &mut words);
+    }
+    words.join(" ")
+}
+
+/// Append the spoken form of a MathML node to `words`.
+fn speak(node: &Node, words: &mut Vec<String>) {
+    let (name, children) = match node {
+        Node::Text(_) => return,
+        Node::Element { name, children, .. } => (name.as_str(), children.as_slice()),
+    };
+    let say = |node: Option<&Node>| {
+        let mut words = Vec::new();
+        if let Some(node) = node {
+            speak(node, &mut words);
+        }
+        words.join(" ")
+    };
+    let spoken = match name {
+        "annotation" | "annotation-xml" | "mphantom" | "mspace" => return,
+        "mi" => {
+            let text = node.text();
+            lookup(IDENTIFIERS, &text).map_or(text, str::to_owned)
+        }
+        "mo" => {
+            let text = node.text();
+            match text.as_str() {
+                // Invisible times, separator and plus.
+                "\u{2062}" | "\u{2063}" | "\u{2064}" | "" => return,
+                text => lookup(OPERATORS, text).unwrap_or(text).to_owned(),
+            }
+        }
+        "mn" | "mtext" | "ms" => node.text().trim().to_owned(),
+        "msup" => format!("{} {}", say(children.first()), power(children.get(1))),
+        // Limits and bounds of large operators are scripts in inline mode.
+        "msub" => match limit(children.first()) {
+            Some(limit) => format!("{limit} {}", say(children.get(1))),
+            None if is_big_operator(children.first()) => {
+                format!("{} over {}", say(children.first()), say(children.get(1)))
+            }
+            None => format!("{} sub {}", say(children.first()), say(children.get(1))),
+        },
+        "msubsup" if is_big_operator(children.first()) => format!(
+            "{} from {} to {}",
+            say(children.first()),
+            say(children.get(1)),
+            say(children.get(2))
+        ),
+        "msubsup" => format!(
+            "{} sub {} {}",
+            say(children.first()),
+            say(children.get(1)),
+            power(children.get(2))
+        ),
+        "mfrac" => {
+            let is_simple = children.iter().all(is_token);
+            let (numerator, denominator) = (say(children.first()), say(children.get(1)));
+            if is_simple {
+                format!("{numerator} over {denominator}")
+            } else {
+                format!("the fraction {numerator} over {denominator}, end fraction")
+            }
+        }
+        "msqrt" => format!("the square root of {}", row(children)),
+        "mroot" => match say(children.get(1)).as_str() {
+            "3" => format!("the cube root of {}", say(children.first())),
+            index => format!("the root of index {index} of {}", say(children.first())),
+        },
+        "munder" | "mover" | "munderover" => {
+            let base = say(children.first());
+            let (under, over) = match name {
+                "munder" => (children.get(1), None),
+                "mover" => (None, children.get(1)),
+                _ => (children.get(1), children.get(2)),
+            };
+            if let Some(accent) = over
+                .filter(|_| under.is_none())
+                .and_then(|over| lookup(ACCENTS, over.text().trim()))
+            {
+                format!("{base} {accent}")
+            } else if let Some(limit) = limit(children.first()).filter(|_| over.is_none()) {
+                format!("{limit} {}", say(under))
+            } else {
+                let mut spoken = base;
+                if under.is_some() {
+                    spoken = format!("{spoken} from {}", say(under));
+                }
+                if over.is_some() {
+                    spoken = format!("{spoken} to {}", say(over));
+                }
+                spoken
+            }
+        }
+        "mtable" => children
+            .iter()
+            .map(|row| {
+                let cells: Vec<String> =
+                    row.children().iter().map(|cell| say(Some(cell))).collect();
+                cells.join(", ")
+            })
+            .collect::<Vec<_>>()
+            .join("; "),
+        _ => row(children),
+    };
+    let spoken = spoken.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !spoken.is_empty() {
+        words.push(spoken);
+    }
+}
+
+/// The spoken form of nodes read one after the other.
+fn row(children: &[Node]) -> String {
+    let mut words = Vec::new();
+    for child in children {
+        speak(child, &mut words);
+    }
+    words.join(" ")
+}
+
+/// The spoken form of a superscript.
+fn power(exponent: Option<&Node>) -> String {
+    let mut words = Vec::new();
+    if let Some(exponent) = exponent {
+        speak(exponent, &mut words);
+    }
+    match words.join(" ").as_str() {
+        "2" => "squared".to_owned(),
+        "3" => "cubed".to_owned(),
+        "prime" => "prime".to_owned(),
+        exponent => format!("to the power of {exponent}"),
+    }
+}
+
+/// Whether the node is a large operator, e.g. `\sum`, whose scripts are
+/// its bounds.
+fn is_big_operator(node: Option<&Node>) -> bool {
+    node.is_some_and(|node| matches!(node.text().trim(), "∑" | "∏" | "∫" | "∮"))
+}
+
+/// The spoken form of the node if it is a limit, e.g. `\lim` followed by the
+/// function application, read before its subscript.
+fn limit(node: Option<&Node>) -> Option<&'static str> {
+    let text = node?.text();
+    lookup(LIMITS, text.trim_end_matches('\u{2061}'))
+}
+
+/// Whether the node is a single identifier, number or operator.
+fn is_token(node: &Node) -> bool {
+    match node {
+        Node::Element { name, children, .. } => {
+            matches!(name.as_str(), "mi" | "mn" | "mo")
+                || (name == "mrow" && children.len() == 1 && is_token(&children[0]))
+        }
+        Node::Text(_) => false,
+    }
+}
+
+fn lookup<'a>(table: &[(&str, &'a str)], text: &str) -> Option<&'a str> {
+    table
+        .iter()
+        .find(|(symbol, _)| *symbol == text)
+        .map(|(_, spoken)| *spoken)
+}
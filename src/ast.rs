@@ -0,0 +1,407 @@
+//! Build equations as a tree in Rust rather than as LaTeX source.
+//!
+//! A [`Node`] is serialized to LaTeX with [`Node::to_latex`], which escapes
+//! symbols and text, adds the braces around arguments, and spaces commands
+//! from the following letters, e.g. for equations generated by a computer
+//! algebra system.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::ast::{render_ast, Node};
+//!
+//! // (a + b) / √2
+//! let node = Node::frac(
+//!     Node::row(["a", "+", "b"]),
+//!     Node::sqrt(Node::symbol("2")),
+//! );
+//! assert_eq!(node.to_latex().unwrap(), r"\frac{a+b}{\sqrt{2}}");
+//! let html = render_ast(&node).unwrap();
+//! assert!(html.contains("mfrac"));
+//! ```
+
+use crate::{
+    environments::check_braces,
+    error::{Error, Result},
+    latex::{append, escape_math, escape_text},
+    opts::Opts,
+};
+
+/// A node of an equation.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    /// Characters typeset as math symbols, e.g. `x`, `42` or `≤`.
+    ///
+    /// Characters with a special meaning in LaTeX are escaped.
+    Symbol(String),
+    /// A command without arguments, by name, e.g. `alpha` for `\alpha`.
+    ///
+    /// The name is either ASCII letters or a single other character.
+    Command(String),
+    /// Text typeset in text mode with `\text`.
+    Text(String),
+    /// An operator name typeset upright with `\operatorname`, e.g. `sinc`.
+    Operator(String),
+    /// Nodes typeset one after the other.
+    Row(Vec<Node>),
+    /// A fraction.
+    Fraction {
+        /// The numerator.
+        numerator: Box<Node>,
+        /// The denominator.
+        denominator: Box<Node>,
+    },
+    /// A square root, or an n-th root with an index.
+    Sqrt {
+        /// The node under the radical.
+        radicand: Box<Node>,
+        /// The index of the root, if any.
+        index: Option<Box<Node>>,
+    },
+    /// A node with a subscript, a superscript or both.
+    Scripts {
+        /// The node carrying the scripts.
+        base: Box<Node>,
+        /// The subscript, if any.
+        sub: Option<Box<Node>>,
+        /// The superscript, if any.
+        sup: Option<Box<Node>>,
+    },
+    /// A node between delimiters sized to fit it, with `\left` and
+    /// `\right`.
+    Fenced {
+        /// The opening delimiter, e.g. `(`, `\{` or `langle`, or an empty
+        /// string for none.
+        open: String,
+        /// The closing delimiter, like the opening one.
+        close: String,
+        /// The node between the delimiters.
+        body: Box<Node>,
+    },
+    /// A matrix, as rows of cells.
+    Matrix {
+        /// The delimiters around the matrix.
+        kind: MatrixKind,
+        /// The rows of cells.
+        rows: Vec<Vec<Node>>,
+    },
+    /// LaTeX source inserted as is, in a group, e.g. for what the other nodes
+    /// do not cover.
+    ///
+    /// Its braces must be balanced.
+    Raw(String),
+}
+
+/// Delimiters around a [matrix](Node::Matrix).
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MatrixKind {
+    /// No delimiters, `matrix`.
+    Plain,
+    /// Parentheses, `pmatrix`.
+    Paren,
+    /// Brackets, `bmatrix`.
+    Bracket,
+    /// Braces, `Bmatrix`.
+    Brace,
+    /// Vertical bars, e.g. for a determinant, `vmatrix`.
+    Bar,
+    /// Double vertical bars, e.g. for a norm, `Vmatrix`.
+    DoubleBar,
+}
+
+impl MatrixKind {
+    fn environment(self) -> &'static str {
+        match self {
+            MatrixKind::Plain => "matrix",
+            MatrixKind::Paren => "pmatrix",
+            MatrixKind::Bracket => "bmatrix",
+            MatrixKind::Brace => "Bmatrix",
+            MatrixKind::Bar => "vmatrix",
+            MatrixKind::DoubleBar => "Vmatrix",
+        }
+    }
+}
+
+impl Node {
+    /// Create a [symbol](Node::Symbol).
+    pub fn symbol(symbol: impl Into<String>) -> Self {
+        Node::Symbol(symbol.into())
+    }
+
+    /// Create a [command](Node::Command).
+    pub fn command(name: impl Into<String>) -> Self {
+        Node::Command(name.into())
+    }
+
+    /// Create a [text](Node::Text).
+    pub fn text(text: impl Into<String>) -> Self {
+        Node::Text(text.into())
+    }
+
+    /// Create a [row](Node::Row).
+    pub fn row<T: Into<Node>>(nodes: impl IntoIterator<Item = T>) -> Self {
+        Node::Row(nodes.into_iter().map(Into::into).collect())
+    }
+
+    /// Create a [fraction](Node::Fraction).
+    pub fn frac(numerator: impl Into<Node>, denominator: impl Into<Node>) -> Self {
+        Node::Fraction {
+            numerator: Box::new(numerator.into()),
+            denominator: Box::new(denominator.into()),
+        }
+    }
+
+    /// Create a [square root](Node::Sqrt).
+    pub fn sqrt(radicand: impl Into<Node>) -> Self {
+        Node::Sqrt {
+            radicand: Box::new(radicand.into()),
+            index: None,
+        }
+    }
+
+    /// Create an n-th [root](Node::Sqrt).
+    pub fn root(index: impl Into<Node>, radicand: impl Into<Node>) -> Self {
+        Node::Sqrt {
+            radicand: Box::new(radicand.into()),
+            index: Some(Box::new(index.into())),
+        }
+    }
+
+    /// Add a subscript, keeping the superscript of a node with
+    /// [scripts](Node::Scripts).
+    pub fn with_sub(self, sub: impl Into<Node>) -> Self {
+        let sub = Some(Box::new(sub.into()));
+        match self {
+            Node::Scripts {
+                base,
+                sub: None,
+                sup,
+            } => Node::Scripts { base, sub, sup },
+            base => Node::Scripts {
+                base: Box::new(base),
+                sub,
+                sup: None,
+            },
+        }
+    }
+
+    /// Add a superscript, keeping the subscript of a node with
+    /// [scripts](Node::Scripts).
+    pub fn with_sup(self, sup: impl Into<Node>) -> Self {
+        let sup = Some(Box::new(sup.into()));
+        match self {
+            Node::Scripts {
+                base,
+                sub,
+                sup: None,
+            } => Node::Scripts { base, sub, sup },
+            base => Node::Scripts {
+                base: Box::new(base),
+                sub: None,
+                sup,
+            },
+        }
+    }
+
+    /// Create a node between [delimiters](Node::Fenced).
+    pub fn fenced(
+        open: impl Into<String>,
+        body: impl Into<Node>,
+        close: impl Into<String>,
+    ) -> Self {
+        Node::Fenced {
+            open: open.into(),
+            close: close.into(),
+            body: Box::new(body.into()),
+        }
+    }
+
+    /// Create a [matrix](Node::Matrix).
+    pub fn matrix(kind: MatrixKind, rows: Vec<Vec<Node>>) -> Self {
+        Node::Matrix { kind, rows }
+    }
+
+    /// Serialize the node to LaTeX.
+    ///
+    /// Fail with [`Error::InvalidInput`] on an invalid command name or
+    /// delimiter, or on unbalanced braces in [raw](Node::Raw) LaTeX.
+    pub fn to_latex(&self) -> Result<String> {
+        let mut writer = Writer::default();
+        writer.node(self)?;
+        Ok(writer.output)
+    }
+
+    /// Whether scripts attach to the whole node without a group.
+    fn is_atom(&self) -> bool {
+        match self {
+            Node::Symbol(symbol) => symbol.chars().count() == 1,
+            Node::Row(_) | Node::Scripts { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+impl From<&str> for Node {
+    fn from(symbol: &str) -> Self {
+        Node::Symbol(symbol.to_owned())
+    }
+}
+
+impl From<String> for Node {
+    fn from(symbol: String) -> Self {
+        Node::Symbol(symbol)
+    }
+}
+
+/// Render a node.
+#[inline]
+pub fn render_ast(node: &Node) -> Result<String> {
+    render_ast_with_opts(node, Opts::default())
+}
+
+/// Render a node with additional [options](`Opts`).
+pub fn render_ast_with_opts(node: &Node, opts: impl AsRef<Opts>) -> Result<String> {
+    crate::render_with_opts(&node.to_latex()?, opts)
+}
+
+/// LaTeX output.
+#[derive(Default)]
+struct Writer {
+    output: String,
+}
+
+impl Writer {
+    fn push(&mut self, latex: &str) {
+        append(&mut self.output, latex);
+    }
+
+    fn command(&mut self, name: &str) -> Result<()> {
+        if !is_command_name(name) {
+            return Err(invalid(format!("invalid command name {name:?}")));
+        }
+        self.push(&format!("\\{name}"));
+        Ok(())
+    }
+
+    fn group(&mut self, node: &Node) -> Result<()> {
+        self.push("{");
+        self.node(node)?;
+        self.push("}");
+        Ok(())
+    }
+
+    fn node(&mut self, node: &Node) -> Result<()> {
+        match node {
+            Node::Symbol(symbol) => self.push(&escape_math(symbol)),
+            Node::Command(name) => self.command(name)?,
+            Node::Text(text) => {
+                self.command("text")?;
+                self.push(&format!("{{{}}}", escape_text(text)));
+            }
+            Node::Operator(name) => {
+                self.command("operatorname")?;
+                self.group(&Node::Symbol(name.clone()))?;
+            }
+            Node::Row(nodes) => {
+                for node in nodes {
+                    self.node(node)?;
+                }
+            }
+            Node::Fraction {
+                numerator,
+                denominator,
+            } => {
+                self.command("frac")?;
+                self.group(numerator)?;
+                self.group(denominator)?;
+            }
+            Node::Sqrt { radicand, index } => {
+                self.command("sqrt")?;
+                if let Some(index) = index {
+                    self.push("[");
+                    // A `]` in the index would end it early.
+                    self.group(index)?;
+                    self.push("]");
+                }
+                self.group(radicand)?;
+            }
+            Node::Scripts { base, sub, sup } => {
+                if base.is_atom() {
+                    self.node(base)?;
+                } else {
+                    self.group(base)?;
+                }
+                if let Some(sub) = sub {
+                    self.push("_");
+                    self.group(sub)?;
+                }
+                if let Some(sup) = sup {
+                    self.push("^");
+                    self.group(sup)?;
+                }
+            }
+            Node::Fenced { open, close, body } => {
+                self.command("left")?;
+                self.delimiter(open)?;
+                self.node(body)?;
+                self.command("right")?;
+                self.delimiter(close)?;
+            }
+            Node::Matrix { kind, rows } => {
+                let environment = kind.environment();
+                self.command("begin")?;
+                self.push(&format!("{{{environment}}}"));
+                for (i, row) in rows.iter().enumerate() {
+                    if i > 0 {
+                        self.push(r"\\");
+                    }
+                    for (j, cell) in row.iter().enumerate() {
+                        if j > 0 {
+                            self.push("&");
+                        }
+                        self.node(cell)?;
+                    }
+                }
+                self.command("end")?;
+                self.push(&format!("{{{environment}}}"));
+            }
+            Node::Raw(latex) => {
+                check_braces(latex).map_err(|reason| invalid(format!("raw LaTeX: {reason}")))?;
+                self.push(&format!("{{{latex}}}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a delimiter of `\left` or `\right`: a single character, `.` for
+    /// none, or a command name.
+    fn delimiter(&mut self, delimiter: &str) -> Result<()> {
+        let mut chars = delimiter.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => self.push("."),
+            (Some(c @ ('{' | '}')), None) => self.push(&format!("\\{c}")),
+            (Some(c), None) if !c.is_ascii_alphanumeric() && !"\\#$%&^_~".contains(c) => {
+                self.push(delimiter)
+            }
+            _ => self.command(delimiter.strip_prefix('\\').unwrap_or(delimiter))?,
+        }
+        Ok(())
+    }
+}
+
+/// Whether `name` is a valid command name: ASCII letters, or a single other
+/// character.
+fn is_command_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => !c.is_whitespace(),
+        (Some(_), Some(_)) => name.chars().all(|c| c.is_ascii_alphabetic()),
+        (None, _) => false,
+    }
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidInput(format!("ast: {reason}"))
+}
@@ -0,0 +1,132 @@
+//! Resumable rendering of large batches of equations.
+//!
+//! Results are persisted in a [store](`ResultStore`) under their
+//! [cache key](crate::cache_key) as soon as they are rendered. Rendering the
+//! same batch again, e.g. after a crash or a redeploy, loads the stored
+//! results instead of rendering them again.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::batch::{render_batch_resumable, DirStore};
+//!
+//! let dir = std::env::temp_dir().join("katex-batch-doctest");
+//! let mut store = DirStore::new(&dir).unwrap();
+//! let inputs = ["a^2 + b^2 = c^2", r"\frac{1}{2}"];
+//! let html = render_batch_resumable(&inputs, katex::Opts::default(), &mut store).unwrap();
+//! assert_eq!(html.len(), 2);
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+
+use crate::{
+    cache::cache_key,
+    error::{Error, Result},
+    opts::Opts,
+};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Storage of rendered results, keyed by [cache key](crate::cache_key).
+pub trait ResultStore {
+    /// Return the result stored under `key`, if any.
+    fn load(&mut self, key: &str) -> Result<Option<String>>;
+
+    /// Store the result rendered under `key`.
+    fn save(&mut self, key: &str, html: &str) -> Result<()>;
+}
+
+/// In-memory store, mostly useful for testing.
+impl ResultStore for HashMap<String, String> {
+    fn load(&mut self, key: &str) -> Result<Option<String>> {
+        Ok(self.get(key).cloned())
+    }
+
+    fn save(&mut self, key: &str, html: &str) -> Result<()> {
+        self.insert(key.to_owned(), html.to_owned());
+        Ok(())
+    }
+}
+
+/// Store keeping each result in a file of a directory.
+///
+/// Files are written to a temporary name first and then renamed, so a crash
+/// never leaves a truncated result behind.
+#[derive(Clone, Debug)]
+pub struct DirStore {
+    dir: PathBuf,
+}
+
+impl DirStore {
+    /// Create a store in `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| store_error(&dir, e))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.html"))
+    }
+}
+
+impl ResultStore for DirStore {
+    fn load(&mut self, key: &str) -> Result<Option<String>> {
+        let path = self.path(key);
+        match fs::read_to_string(&path) {
+            Ok(html) => Ok(Some(html)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(store_error(&path, e)),
+        }
+    }
+
+    fn save(&mut self, key: &str, html: &str) -> Result<()> {
+        let path = self.path(key);
+        let tmp = self.dir.join(format!("{key}.html.tmp"));
+        fs::write(&tmp, html).map_err(|e| store_error(&tmp, e))?;
+        fs::rename(&tmp, &path).map_err(|e| store_error(&path, e))
+    }
+}
+
+fn store_error(path: &Path, e: io::Error) -> Error {
+    Error::StoreError(format!("{}: {e}", path.display()))
+}
+
+/// Render LaTeX equations with additional [options](`Opts`), skipping the
+/// ones already in `store`.
+///
+/// Each result is saved as soon as it is rendered, unless a
+/// [`ResourceResolver`](crate::ResourceResolver) without id is set, whose
+/// results cannot be told apart. The first error stops
+/// the batch; the results rendered so far stay in the store, so calling this
+/// again resumes after them.
+pub fn render_batch_resumable<I, S>(
+    inputs: I,
+    opts: impl AsRef<Opts>,
+    store: &mut S,
+) -> Result<Vec<String>>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+    S: ResultStore + ?Sized,
+{
+    let opts = opts.as_ref();
+    inputs
+        .into_iter()
+        .map(|input| {
+            let input = input.as_ref();
+            if !opts.is_cacheable() {
+                return crate::render_with_opts(input, opts);
+            }
+            let key = cache_key(input, opts);
+            if let Some(html) = store.load(&key)? {
+                return Ok(html);
+            }
+            let html = crate::render_with_opts(input, opts)?;
+            store.save(&key, &html)?;
+            Ok(html)
+        })
+        .collect()
+}
@@ -0,0 +1,85 @@
+//! Representative formulas to measure rendering performance.
+//!
+//! The corpus is used by the benchmarks of this crate (`cargo bench --features bench`),
+//! and can be used to compare JS backends or other renderers on the same input.
+//!
+//! # Examples
+//!
+//! ```
+//! for formula in katex::bench::corpus() {
+//!     let opts = katex::Opts::builder()
+//!         .display_mode(formula.display_mode)
+//!         .build()
+//!         .unwrap();
+//!     katex::render_with_opts(formula.input, &opts).unwrap();
+//! }
+//! ```
+
+/// A formula of the corpus.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Formula {
+    /// Short name, used to label benchmarks.
+    pub name: &'static str,
+    /// LaTeX source.
+    pub input: &'static str,
+    /// Whether the formula is meant to be rendered in display mode.
+    pub display_mode: bool,
+}
+
+const fn formula(name: &'static str, input: &'static str, display_mode: bool) -> Formula {
+    Formula {
+        name,
+        input,
+        display_mode,
+    }
+}
+
+/// The corpus, from short inline symbols to large display environments.
+const CORPUS: &[Formula] = &[
+    formula("symbol", r"x", false),
+    formula("inline", r"E = mc^2", false),
+    formula("quadratic", r"x = \frac{-b \pm \sqrt{b^2 - 4ac}}{2a}", true),
+    formula(
+        "sum",
+        r"\sum_{n=1}^{\infty} \frac{1}{n^2} = \frac{\pi^2}{6}",
+        true,
+    ),
+    formula(
+        "integral",
+        r"\int_{-\infty}^{\infty} e^{-x^2} \, dx = \sqrt{\pi}",
+        true,
+    ),
+    formula(
+        "matrix",
+        r"A = \begin{pmatrix} a_{11} & a_{12} & \cdots & a_{1n} \\ a_{21} & a_{22} & \cdots & a_{2n} \\ \vdots & \vdots & \ddots & \vdots \\ a_{m1} & a_{m2} & \cdots & a_{mn} \end{pmatrix}",
+        true,
+    ),
+    formula(
+        "aligned",
+        r"\begin{aligned} \nabla \cdot \mathbf{E} &= \frac{\rho}{\varepsilon_0} \\ \nabla \cdot \mathbf{B} &= 0 \\ \nabla \times \mathbf{E} &= -\frac{\partial \mathbf{B}}{\partial t} \\ \nabla \times \mathbf{B} &= \mu_0 \mathbf{J} + \mu_0 \varepsilon_0 \frac{\partial \mathbf{E}}{\partial t} \end{aligned}",
+        true,
+    ),
+    formula(
+        "cases",
+        r"f(x) = \begin{cases} x^2 & \text{if } x \ge 0 \\ -x & \text{otherwise} \end{cases}",
+        true,
+    ),
+    formula(
+        "accents",
+        r"\hat{a} + \widetilde{xyz} + \overrightarrow{AB} + \underbrace{a + b}_{n}",
+        false,
+    ),
+    formula(
+        "nested",
+        r"\cfrac{1}{1 + \cfrac{1}{1 + \cfrac{1}{1 + \cfrac{1}{1 + x}}}}",
+        true,
+    ),
+    #[cfg(feature = "mhchem")]
+    formula("chemistry", r"\ce{2H2 + O2 -> 2H2O}", false),
+];
+
+/// Return the corpus.
+pub fn corpus() -> &'static [Formula] {
+    CORPUS
+}
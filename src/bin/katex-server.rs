@@ -0,0 +1,189 @@
+//! HTTP service rendering equations, for applications which cannot link this
+//! crate, e.g. written in PHP or Go.
+//!
+//! Build it with the `server` feature:
+//!
+//! ```sh
+//! cargo run --release --features server --bin katex-server -- 127.0.0.1:8080
+//! ```
+//!
+//! * `POST /render` takes `{"input": "E = mc^2", "options": {"displayMode": true}}`,
+//!   where the options are KaTeX's, and answers `{"html": "..."}`. Only the
+//!   options which cannot harm the server are accepted, not e.g. `trust`,
+//!   and `maxExpand` and `maxSize` are capped to 1000 and 100. Errors are
+//!   answered as `{"error": {"code": "KTX2002", "message": "..."}}`, with
+//!   status 400 for invalid input or options, including parse errors, and
+//!   500 otherwise.
+//! * `GET /healthz` answers `ok` once the JS engine renders.
+//!
+//! Requests are served by a pool of threads, each rendering with its own JS
+//! engine. The size of the pool defaults to the number of CPUs, and is set
+//! by `KATEX_SERVER_THREADS`.
+
+use katex::{JsonValue, Limit, Opts};
+use serde_json::{json, Value};
+use std::{env, io::Read, num::NonZeroUsize, process::ExitCode, sync::Arc, thread};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest accepted request body, in bytes.
+const MAX_BODY_BYTES: u64 = 1 << 20;
+/// KaTeX options accepted from clients.
+const ALLOWED_OPTIONS: &[&str] = &[
+    "displayMode",
+    "output",
+    "leqno",
+    "fleqn",
+    "throwOnError",
+    "errorColor",
+    "minRuleThickness",
+    "colorIsTextColor",
+    "strict",
+    "macros",
+    "maxSize",
+    "maxExpand",
+];
+/// Largest `maxExpand` accepted from clients, also used by default.
+const MAX_EXPAND: u32 = 1000;
+/// Largest `maxSize` accepted from clients, in ems, also used by default.
+const MAX_SIZE: f64 = 100.0;
+
+fn main() -> ExitCode {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8080".to_owned());
+    let threads = match env::var("KATEX_SERVER_THREADS") {
+        Ok(threads) => match threads.parse::<NonZeroUsize>() {
+            Ok(threads) => threads.get(),
+            Err(_) => {
+                eprintln!(
+                    "katex-server: KATEX_SERVER_THREADS must be a positive integer, got {threads:?}"
+                );
+                return ExitCode::FAILURE;
+            }
+        },
+        Err(_) => thread::available_parallelism().map_or(1, usize::from),
+    };
+    let server = match Server::http(&addr) {
+        Ok(server) => Arc::new(server),
+        Err(e) => {
+            eprintln!("katex-server: cannot listen on {addr}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("katex-server: listening on {addr} with {threads} threads");
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let server = server.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(request);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    ExitCode::SUCCESS
+}
+
+fn handle(mut request: Request) {
+    let (status, body, content_type) = match (request.method(), request.url()) {
+        (Method::Post, "/render") => {
+            let (status, body) = match read_body(&mut request).and_then(|body| render(&body)) {
+                Ok(html) => (200, json!({ "html": html })),
+                Err(error) => (error.status(), error.to_json()),
+            };
+            (status, body.to_string(), "application/json")
+        }
+        (Method::Get, "/healthz") => match katex::render("x") {
+            Ok(_) => (200, "ok".to_owned(), "text/plain"),
+            Err(e) => (503, e.to_string(), "text/plain"),
+        },
+        (_, "/render" | "/healthz") => (405, "method not allowed".to_owned(), "text/plain"),
+        _ => (404, "not found".to_owned(), "text/plain"),
+    };
+    let header = Header::from_bytes("Content-Type", content_type).expect("valid header");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+    // The client may be gone.
+    let _ = request.respond(response);
+}
+
+/// Error answered to a request.
+enum RequestError {
+    /// The request is malformed.
+    BadRequest(String),
+    /// The render failed.
+    Render(katex::Error),
+}
+
+impl RequestError {
+    fn status(&self) -> u16 {
+        match self {
+            RequestError::BadRequest(_) => 400,
+            // Errors caused by the input or the options, including KaTeX's
+            // parse errors with `throwOnError`.
+            RequestError::Render(e) if e.code().starts_with("KTX2") => 400,
+            RequestError::Render(_) => 500,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let (code, message) = match self {
+            RequestError::BadRequest(message) => ("bad_request", message.clone()),
+            RequestError::Render(e) => (e.code(), e.to_string()),
+        };
+        json!({ "error": { "code": code, "message": message } })
+    }
+}
+
+fn read_body(request: &mut Request) -> Result<Value, RequestError> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| RequestError::BadRequest(format!("cannot read the body: {e}")))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(RequestError::BadRequest(format!(
+            "the body exceeds {MAX_BODY_BYTES} bytes"
+        )));
+    }
+    serde_json::from_str(&body).map_err(|e| RequestError::BadRequest(format!("invalid JSON: {e}")))
+}
+
+fn render(body: &Value) -> Result<String, RequestError> {
+    let Some(input) = body.get("input").and_then(Value::as_str) else {
+        return Err(RequestError::BadRequest(
+            "expected the input as a string".to_owned(),
+        ));
+    };
+    let options = body.get("options").cloned().unwrap_or(Value::Null);
+    if let Some(key) = options.as_object().and_then(|options| {
+        options
+            .keys()
+            .find(|key| !ALLOWED_OPTIONS.contains(&key.as_str()))
+    }) {
+        return Err(RequestError::BadRequest(format!(
+            "the option {key:?} is not accepted"
+        )));
+    }
+    let mut opts = Opts::from_katex_json(JsonValue::from(options)).map_err(RequestError::Render)?;
+    let effective = opts.effective();
+    if !matches!(effective.max_expand, Limit::Finite(n) if n <= MAX_EXPAND) {
+        opts.set_max_expand(MAX_EXPAND);
+    }
+    if !matches!(effective.max_size, Limit::Finite(size) if size <= MAX_SIZE) {
+        opts.set_max_size(MAX_SIZE);
+    }
+    katex::render_with_opts(input, &opts).map_err(|e| {
+        // With `throwOnError`, KaTeX's parse errors are reported by the JS
+        // engine; check the input to report them as parse errors.
+        match katex::check_with_opts(input, &opts) {
+            Err(error @ katex::Error::ParseError(_)) => RequestError::Render(error),
+            _ => RequestError::Render(e),
+        }
+    })
+}
@@ -0,0 +1,376 @@
+//! Translate equations to Nemeth braille, the braille code for mathematics in
+//! North America.
+//!
+//! This module is only available with the experimental `braille` feature.
+//! The translation is built from the MathML of the render, which mirrors
+//! KaTeX's parse tree, and covers the constructs of school mathematics:
+//! numbers, Latin and Greek letters, function names, operators and
+//! comparisons, fractions, radicals, scripts, primes, and bar and vector
+//! accents. Scripts are always linear, as in inline math. Symbols without a
+//! translation, e.g. in matrices, are reported as [`Error::InvalidInput`], so
+//! callers can fall back to another translator.
+//!
+//! The braille is returned as Unicode braille patterns (U+2800 to U+283F),
+//! which braille displays and embossing software accept.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::braille::render_braille;
+//!
+//! assert_eq!(render_braille("E = mc^2").unwrap(), "⠠⠑⠀⠨⠅⠀⠍⠉⠘⠆");
+//! assert_eq!(render_braille(r"\frac{1}{2}").unwrap(), "⠹⠂⠌⠆⠼");
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    html::{self, Node},
+    opts::{Opts, OutputType},
+};
+
+/// Unicode braille patterns of the characters of North American braille
+/// ASCII, from space to `_`, in which the tables below are written.
+const BRAILLE_ASCII: &str = "⠀⠮⠐⠼⠫⠩⠯⠄⠷⠾⠡⠬⠠⠤⠨⠌⠴⠂⠆⠒⠲⠢⠖⠶⠦⠔⠱⠰⠣⠿⠜⠹⠈⠁⠃⠉⠙⠑⠋⠛⠓⠊⠚⠅⠇⠍⠝⠕⠏⠟⠗⠎⠞⠥⠧⠺⠭⠽⠵⠪⠳⠻⠘⠸";
+
+/// Comparison signs, which are spaced.
+const COMPARISONS: &[(&str, &str)] = &[
+    ("=", ".K"),
+    ("<", "\"K"),
+    (">", ".1"),
+    ("≤", "\"K:"),
+    ("≥", ".1:"),
+    ("≠", "/.K"),
+    ("≈", "@:@:"),
+    ("→", "$33O"),
+    ("∈", "@E"),
+];
+
+/// Operators and other symbols, unspaced.
+const SYMBOLS: &[(&str, &str)] = &[
+    ("+", "+"),
+    ("−", "-"),
+    ("-", "-"),
+    ("±", "+-"),
+    ("×", "@*"),
+    ("⋅", "*"),
+    ("÷", "./"),
+    ("/", "_/"),
+    ("(", "("),
+    (")", ")"),
+    ("[", "@("),
+    ("]", "@)"),
+    ("{", ".("),
+    ("}", ".)"),
+    ("|", "\\"),
+    ("∣", "\\"),
+    (",", ","),
+    ("!", "&"),
+    ("′", "'"),
+    ("∞", ",="),
+    ("∫", "!"),
+    ("∑", ".,S"),
+    ("∏", ".,P"),
+    ("∂", "@D"),
+    ("…", "'''"),
+    ("⋯", "'''"),
+];
+
+/// Greek letters, by the letter following the Greek indicator.
+const GREEK: &[(char, char)] = &[
+    ('α', 'A'),
+    ('β', 'B'),
+    ('γ', 'G'),
+    ('δ', 'D'),
+    ('ϵ', 'E'),
+    ('ε', 'E'),
+    ('ζ', 'Z'),
+    ('η', ':'),
+    ('θ', '?'),
+    ('ι', 'I'),
+    ('κ', 'K'),
+    ('λ', 'L'),
+    ('μ', 'M'),
+    ('ν', 'N'),
+    ('ξ', 'X'),
+    ('ο', 'O'),
+    ('π', 'P'),
+    ('ρ', 'R'),
+    ('σ', 'S'),
+    ('τ', 'T'),
+    ('υ', 'U'),
+    ('ϕ', 'F'),
+    ('φ', 'F'),
+    ('χ', '&'),
+    ('ψ', 'Y'),
+    ('ω', 'W'),
+];
+
+/// Accents over their base, as modifiers of a modified expression.
+const ACCENTS: &[(&str, &str)] = &[("ˉ", ":"), ("‾", ":"), ("→", "$33O"), ("\u{20d7}", "$33O")];
+
+/// Translate a LaTeX equation to Nemeth braille.
+#[inline]
+pub fn render_braille(input: &str) -> Result<String> {
+    render_braille_with_opts(input, Opts::default())
+}
+
+/// Translate a LaTeX equation to Nemeth braille with additional
+/// [options](`Opts`), e.g. macros.
+///
+/// The [output type](`OutputType`) is ignored.
+pub fn render_braille_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    let mut opts = opts.as_ref().clone();
+    opts.set_output_type(OutputType::Mathml);
+    let mathml = crate::render_with_opts(input, opts)?;
+    let mut writer = Writer::default();
+    for node in html::parse(&mathml) {
+        writer.node(&node)?;
+    }
+    Ok(writer.finish())
+}
+
+/// Nemeth braille being written, in braille ASCII.
+#[derive(Default)]
+struct Writer {
+    braille: String,
+    /// Level indicators of the current script level from the baseline,
+    /// e.g. `^;` in the subscript of a superscript.
+    level: String,
+    /// The level of the last written symbol.
+    written_level: String,
+    /// Number of radicals around the current symbol.
+    radicals: usize,
+}
+
+impl Writer {
+    fn finish(self) -> String {
+        self.braille
+            .trim_end()
+            .chars()
+            .map(|c| {
+                let index = (c as usize).wrapping_sub(' ' as usize);
+                BRAILLE_ASCII.chars().nth(index).unwrap_or(c)
+            })
+            .collect()
+    }
+
+    /// Write symbols at the current level.
+    fn write(&mut self, symbols: &str) {
+        if self.level != self.written_level {
+            if self.level.is_empty() {
+                // The baseline indicator.
+                self.braille.push('"');
+            } else {
+                self.braille.push_str(&self.level);
+            }
+            self.written_level.clone_from(&self.level);
+        } else if symbols.starts_with(|c: char| c.is_ascii_digit()) && self.starts_term() {
+            // The numeric indicator.
+            self.braille.push('#');
+        }
+        self.braille.push_str(symbols);
+    }
+
+    /// Whether the next symbol starts the expression or follows a space,
+    /// possibly after a minus sign.
+    fn starts_term(&self) -> bool {
+        let braille = self.braille.strip_suffix('-').unwrap_or(&self.braille);
+        braille.is_empty() || braille.ends_with(' ')
+    }
+
+    /// Write a space, which returns to the baseline.
+    fn space(&mut self) {
+        if !self.braille.is_empty() && !self.braille.ends_with(' ') {
+            self.braille.push(' ');
+        }
+        self.written_level.clear();
+    }
+
+    fn node(&mut self, node: &Node) -> Result<()> {
+        let (name, children) = match node {
+            Node::Text(_) => return Ok(()),
+            Node::Element { name, children, .. } => (name.as_str(), children.as_slice()),
+        };
+        match name {
+            "annotation" | "annotation-xml" | "mphantom" | "mspace" => {}
+            "mi" | "mn" | "mtext" => self.text(&node.text())?,
+            "mo" => self.operator(node.text().trim())?,
+            "mfrac" => {
+                let complex = ";".repeat(children.iter().map(fraction_depth).max().unwrap_or(0));
+                self.write(&format!("{complex}?"));
+                self.child(children.first())?;
+                self.write(&format!("{complex}/"));
+                self.child(children.get(1))?;
+                self.write(&format!("{complex}#"));
+            }
+            "msqrt" | "mroot" => {
+                let nested = ".".repeat(self.radicals);
+                let radicand = if name == "mroot" {
+                    // The index of the radical.
+                    self.write("<");
+                    self.child(children.get(1))?;
+                    &children[..1]
+                } else {
+                    children
+                };
+                self.write(&format!("{nested}>"));
+                self.radicals += 1;
+                let result = radicand.iter().try_for_each(|child| self.node(child));
+                self.radicals -= 1;
+                result?;
+                self.write(&format!("{nested}]"));
+            }
+            "msub" | "munder" => {
+                self.child(children.first())?;
+                self.script(';', children.first(), children.get(1))?;
+            }
+            "msup" | "mover" => {
+                let accent = children
+                    .get(1)
+                    .filter(|_| name == "mover")
+                    .and_then(|over| lookup(ACCENTS, over.text().trim()));
+                if let Some(accent) = accent {
+                    // A modified expression with the accent directly over.
+                    self.write("\"");
+                    self.child(children.first())?;
+                    self.write(&format!("<{accent}]"));
+                } else {
+                    self.child(children.first())?;
+                    self.script('^', children.first(), children.get(1))?;
+                }
+            }
+            "msubsup" | "munderover" => {
+                self.child(children.first())?;
+                self.script(';', children.first(), children.get(1))?;
+                self.script('^', children.first(), children.get(2))?;
+            }
+            "mtable" => return Err(unsupported(name)),
+            _ => {
+                for child in children {
+                    self.node(child)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn child(&mut self, node: Option<&Node>) -> Result<()> {
+        match node {
+            Some(node) => self.node(node),
+            None => Ok(()),
+        }
+    }
+
+    /// Write a subscript (`;`) or a superscript (`^`) of the base.
+    fn script(
+        &mut self,
+        indicator: char,
+        base: Option<&Node>,
+        script: Option<&Node>,
+    ) -> Result<()> {
+        let Some(script) = script else {
+            return Ok(());
+        };
+        let text = script.text();
+        if indicator == '^' && text.trim() == "′" {
+            return self.operator("′");
+        }
+        let is_letter = base.is_some_and(|base| {
+            let text = base.text();
+            let mut chars = text.chars();
+            chars.next().is_some_and(|c| c.is_alphabetic()) && chars.next().is_none()
+        });
+        if indicator == ';'
+            && self.level.is_empty()
+            && is_letter
+            && text.chars().all(|c| c.is_ascii_digit())
+        {
+            // Numeric subscripts of letters on the baseline have no indicator.
+            self.braille.push_str(&text);
+            return Ok(());
+        }
+        self.level.push(indicator);
+        let result = self.node(script);
+        self.level.pop();
+        result
+    }
+
+    /// Write letters, digits and words.
+    fn text(&mut self, text: &str) -> Result<()> {
+        let mut symbols = String::new();
+        for c in text.chars() {
+            if c.is_ascii_lowercase() {
+                symbols.push(c.to_ascii_uppercase());
+            } else if c.is_ascii_uppercase() {
+                // The capital indicator.
+                symbols.push(',');
+                symbols.push(c);
+            } else if c.is_ascii_digit() || c == '.' {
+                symbols.push(c);
+            } else if let Some(&(_, letter)) = GREEK.iter().find(|(greek, _)| *greek == c) {
+                symbols.push('.');
+                symbols.push(letter);
+            } else if let Some(&(_, letter)) = GREEK
+                .iter()
+                .find(|(greek, _)| c.to_lowercase().eq([*greek]))
+            {
+                symbols.push_str(".,");
+                symbols.push(letter);
+            } else if c == ' ' || c == '\u{a0}' {
+                if !symbols.is_empty() {
+                    self.write(&symbols);
+                    symbols.clear();
+                }
+                self.space();
+            } else if let Some(symbol) = lookup(SYMBOLS, c.encode_utf8(&mut [0; 4])) {
+                symbols.push_str(symbol);
+            } else {
+                return Err(unsupported(&c.to_string()));
+            }
+        }
+        if !symbols.is_empty() {
+            self.write(&symbols);
+        }
+        Ok(())
+    }
+
+    fn operator(&mut self, text: &str) -> Result<()> {
+        match text {
+            // Function application, e.g. after `\sin`, is a space.
+            "\u{2061}" => self.space(),
+            // Invisible times, separator and plus.
+            "\u{2062}" | "\u{2063}" | "\u{2064}" | "" => {}
+            text => {
+                if let Some(comparison) = lookup(COMPARISONS, text) {
+                    self.space();
+                    self.write(comparison);
+                    self.space();
+                } else if let Some(symbol) = lookup(SYMBOLS, text) {
+                    self.write(symbol);
+                } else {
+                    // Multi-letter operators, e.g. `\lim`.
+                    self.text(text)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Depth of the fractions nested in the node, including itself.
+fn fraction_depth(node: &Node) -> usize {
+    let inner = node.children().iter().map(fraction_depth).max();
+    let is_fraction = matches!(node, Node::Element { name, .. } if name == "mfrac");
+    inner.unwrap_or(0) + usize::from(is_fraction)
+}
+
+fn unsupported(symbol: &str) -> Error {
+    Error::InvalidInput(format!("no Nemeth braille translation for {symbol:?}"))
+}
+
+fn lookup<'a>(table: &[(&str, &'a str)], text: &str) -> Option<&'a str> {
+    table
+        .iter()
+        .find(|(symbol, _)| *symbol == text)
+        .map(|(_, braille)| *braille)
+}
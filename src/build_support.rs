@@ -0,0 +1,141 @@
+//! Ahead-of-time rendering in build scripts.
+//!
+//! [`render_dir`] renders the math found in a set of files, e.g. the
+//! Markdown pages of a site, and writes a Rust lookup table from each
+//! equation to its HTML. The crate serving the pages includes the table, so
+//! its binary does not render the math at runtime.
+//!
+//! # Examples
+//!
+//! In `build.rs`, with this crate as a build dependency:
+//!
+//! ```no_run
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! let rendered =
+//!     katex::build_support::render_dir("pages/**/*.md", &out_dir, katex::Opts::default())
+//!         .unwrap();
+//! for source in &rendered.sources {
+//!     println!("cargo:rerun-if-changed={}", source.display());
+//! }
+//! ```
+//!
+//! Then in the crate:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/katex_math.rs"));
+//!
+//! let html = katex_math(r"e^{i\pi} + 1 = 0", false).unwrap();
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    opts::Opts,
+    split::{split_math, DelimiterConfig},
+};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the table written by [`render_dir`].
+pub const TABLE_FILE_NAME: &str = "katex_math.rs";
+
+/// Result of [`render_dir`].
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct RenderedDir {
+    /// Path of the written table.
+    pub table: PathBuf,
+    /// Files scanned for math, e.g. to print `cargo:rerun-if-changed` for
+    /// them.
+    pub sources: Vec<PathBuf>,
+    /// Number of distinct equations in the table.
+    pub equations: usize,
+}
+
+/// Render the math in the files matching `input_glob` with additional
+/// [options](`Opts`), and write the table `katex_math.rs` to `output_dir`.
+///
+/// Math is found with the [default delimiters](DelimiterConfig::default),
+/// and rendered in display mode if its delimiters are for display math,
+/// overriding [`display_mode`](crate::OptsBuilder::display_mode). The table
+/// defines
+///
+/// ```ignore
+/// pub fn katex_math(input: &str, display: bool) -> Option<&'static str>
+/// ```
+///
+/// returning the HTML of the math `input`, as written between its
+/// delimiters. The table is only written if it changes, so that the crate
+/// including it is not rebuilt needlessly.
+///
+/// Fail with [`Error::InvalidInput`] naming the file if a file cannot be
+/// read or its math fails to render, and with [`Error::WriteError`] if the
+/// table cannot be written.
+pub fn render_dir(
+    input_glob: &str,
+    output_dir: impl AsRef<Path>,
+    opts: impl AsRef<Opts>,
+) -> Result<RenderedDir> {
+    let opts = opts.as_ref();
+    let paths = glob::glob(input_glob)
+        .map_err(|e| Error::InvalidInput(format!("invalid glob {input_glob:?}: {e}")))?;
+    let config = DelimiterConfig::default();
+    let mut sources = Vec::new();
+    let mut equations = BTreeMap::new();
+    for path in paths {
+        let path = path.map_err(|e| Error::InvalidInput(e.to_string()))?;
+        if !path.is_file() {
+            continue;
+        }
+        let text = fs::read_to_string(&path)
+            .map_err(|e| Error::InvalidInput(format!("cannot read {}: {e}", path.display())))?;
+        for (input, display) in split_math(&text, &config)
+            .iter()
+            .filter_map(|segment| segment.math())
+        {
+            let key = (input.to_owned(), display);
+            if equations.contains_key(&key) {
+                continue;
+            }
+            let mut opts = opts.clone();
+            opts.set_display_mode(display);
+            let html = crate::render_with_opts(input, &opts)
+                .map_err(|e| Error::InvalidInput(format!("{}: {input:?}: {e}", path.display())))?;
+            equations.insert(key, html);
+        }
+        sources.push(path);
+    }
+
+    let table = output_dir.as_ref().join(TABLE_FILE_NAME);
+    let code = table_code(input_glob, &equations);
+    if fs::read_to_string(&table).ok().as_deref() != Some(code.as_str()) {
+        fs::write(&table, code)
+            .map_err(|e| Error::WriteError(format!("{}: {e}", table.display())))?;
+    }
+    Ok(RenderedDir {
+        table,
+        sources,
+        equations: equations.len(),
+    })
+}
+
+/// Generate the code of the table. `Debug` formats strings as Rust
+/// literals.
+fn table_code(input_glob: &str, equations: &BTreeMap<(String, bool), String>) -> String {
+    let mut code = format!(
+        "// Generated by katex::build_support::render_dir from {input_glob:?}, do not edit.\n\n\
+         /// Return the HTML of the math `input` rendered ahead of time, in display\n\
+         /// mode if `display`.\n\
+         #[allow(dead_code)]\n\
+         pub fn katex_math(input: &str, display: bool) -> Option<&'static str> {{\n    \
+         match (input, display) {{\n"
+    );
+    for ((input, display), html) in equations {
+        let _ = writeln!(code, "        ({input:?}, {display}) => Some({html:?}),");
+    }
+    code.push_str("        _ => None,\n    }\n}\n");
+    code
+}
@@ -0,0 +1,176 @@
+//! Stable keys for caching rendered output.
+//!
+//! A key only depends on the KaTeX version, the [options](`Opts`) and the
+//! input, so it can be shared between processes, machines and JS backends.
+//! It is computed as follows, which makes it easy to reproduce in other
+//! languages:
+//!
+//! 1. Normalize the input by trimming it and replacing each run of
+//!    whitespace with a single `\n` if the run contains a line break (which
+//!    ends a `%` comment), or with a single space otherwise. Whitespace which
+//!    TeX does not ignore is kept as is: the character following a `\`, e.g.
+//!    in the control space `\ `, and the argument of `\verb`, from its
+//!    delimiter to the matching one.
+//! 2. Describe the options as a JSON object with sorted keys, no whitespace
+//!    and no unset options, e.g. `{"displayMode":true}`.
+//! 3. Hash the option description, a `\n` and the normalized input with
+//!    64-bit FNV-1a.
+//! 4. Format the key as `v1-<KaTeX version>-<hash as 16 lowercase hex digits>`.
+//!
+//! A [content hash](content_hash) is computed likewise, but from the exact
+//! input and only the options which change the output, e.g. to skip
+//! rendering unchanged equations in incremental builds.
+//!
+//! [`cache_key`] and [`content_hash`] use the version of the bundled KaTeX,
+//! so they are only valid for renders with it. Use
+//! [`Renderer::cache_key`](crate::Renderer::cache_key) and
+//! [`Renderer::content_hash`](crate::Renderer::content_hash) for renderers
+//! [loaded with another KaTeX](crate::Renderer::with_katex_source).
+
+use crate::{opts::Opts, KATEX_VERSION};
+
+/// Version of the key format.
+const KEY_FORMAT_VERSION: &str = "v1";
+
+/// Compute the cache key of rendering `input` with the given [options](`Opts`)
+/// and the bundled KaTeX.
+///
+/// A [`ResourceResolver`](crate::ResourceResolver) only counts by its id, so
+/// give it one with [`with_id`](crate::ResourceResolver::with_id) to cache
+/// the renders of several resolvers.
+///
+/// # Examples
+///
+/// ```
+/// let opts = katex::Opts::builder().display_mode(true).build().unwrap();
+/// let key = katex::cache_key("E = mc^2", &opts);
+/// assert_eq!(key, katex::cache_key("  E =  mc^2 ", &opts));
+/// assert_ne!(key, katex::cache_key("E = mc^2", katex::Opts::default()));
+/// ```
+pub fn cache_key(input: &str, opts: impl AsRef<Opts>) -> String {
+    cache_key_with_version(KATEX_VERSION, input, opts.as_ref())
+}
+
+/// Compute the cache key of rendering `input` with KaTeX `version`.
+pub(crate) fn cache_key_with_version(version: &str, input: &str, opts: &Opts) -> String {
+    let mut hash = Fnv1a::new();
+    hash.write(opts.fingerprint().as_bytes());
+    hash.write(b"\n");
+    hash.write(normalize_input(input).as_bytes());
+    format!("{KEY_FORMAT_VERSION}-{version}-{:016x}", hash.finish())
+}
+
+/// Hash `input` with the effective [options](`Opts`) and the version of the
+/// bundled KaTeX.
+///
+/// Options set to KaTeX's defaults hash like unset ones, e.g.
+/// `display_mode(false)`. The hash is the 64-bit FNV-1a of the KaTeX version,
+/// a `\n`, the [options described as JSON](self), a `\n` and the input, so it
+/// is stable across runs, platforms and JS backends, and it changes when
+/// this crate upgrades KaTeX. Unlike [`cache_key`], whitespace in the input
+/// is significant.
+///
+/// # Examples
+///
+/// ```
+/// let opts = katex::Opts::builder().display_mode(false).build().unwrap();
+/// let hash = katex::content_hash("E = mc^2", &opts);
+/// assert_eq!(hash, katex::content_hash("E = mc^2", katex::Opts::default()));
+/// assert_ne!(hash, katex::content_hash("E = mc^3", &opts));
+/// ```
+pub fn content_hash(input: &str, opts: impl AsRef<Opts>) -> u64 {
+    content_hash_with_version(KATEX_VERSION, input, opts.as_ref())
+}
+
+/// Hash `input` with the effective options and KaTeX `version`.
+pub(crate) fn content_hash_with_version(version: &str, input: &str, opts: &Opts) -> u64 {
+    let mut hash = Fnv1a::new();
+    hash.write(version.as_bytes());
+    hash.write(b"\n");
+    hash.write(opts.effective_fingerprint().as_bytes());
+    hash.write(b"\n");
+    hash.write(input.as_bytes());
+    hash.finish()
+}
+
+/// Trim the input and collapse whitespace, except where it is significant.
+fn normalize_input(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut whitespace: Option<char> = None;
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c.is_whitespace() {
+            if whitespace != Some('\n') {
+                whitespace = Some(if c == '\n' || c == '\r' { '\n' } else { ' ' });
+            }
+            continue;
+        }
+        if let Some(w) = whitespace.take().filter(|_| !output.is_empty()) {
+            output.push(w);
+        }
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        // Copy the command up to its first character after the backslash,
+        // even whitespace, or all of `\verb` and its argument.
+        let end = verb_end(&input[i..]).map_or_else(
+            || {
+                chars
+                    .clone()
+                    .next()
+                    .map_or(input.len(), |(j, next)| j + next.len_utf8())
+            },
+            |len| i + len,
+        );
+        output.push_str(&input[i..end]);
+        while chars.offset() < end {
+            chars.next();
+        }
+    }
+    output
+}
+
+/// Return the length of `\verb` and its argument, e.g. `\verb*|a  b|`, if
+/// the input starts with it.
+fn verb_end(input: &str) -> Option<usize> {
+    let rest = input.strip_prefix("\\verb")?;
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        // A longer control word.
+        return None;
+    }
+    let rest = rest.strip_prefix('*').unwrap_or(rest);
+    let mut argument = rest.chars();
+    let Some(delimiter) = argument.next() else {
+        return Some(input.len());
+    };
+    let body = argument.as_str();
+    let body_start = input.len() - body.len();
+    Some(
+        body.find(delimiter)
+            .map_or(input.len(), |end| body_start + end + delimiter.len_utf8()),
+    )
+}
+
+/// 64-bit FNV-1a hasher.
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    pub(crate) fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
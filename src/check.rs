@@ -0,0 +1,94 @@
+//! Validate LaTeX equations without rendering them.
+//!
+//! Checking parses the equation with KaTeX but skips building the output, so
+//! it is cheaper than rendering, e.g. to validate form input.
+
+use crate::{
+    error::{Error, ParseError, Result},
+    js_engine::{JsEngine, JsValue},
+    opts::Opts,
+    preprocess, with_engine,
+};
+use core::iter;
+
+/// Check that a LaTeX equation is valid.
+///
+/// Invalid LaTeX gives an [`Error::ParseError`].
+///
+/// # Examples
+///
+/// ```
+/// assert!(katex::check(r"\frac{1}{2}").is_ok());
+///
+/// let err = katex::check(r"x + \foo").unwrap_err();
+/// let katex::Error::ParseError(err) = err else { panic!() };
+/// assert_eq!(err.message, r"Undefined control sequence: \foo");
+/// assert_eq!(err.span, Some(4..8));
+/// assert_eq!(err.char_span, Some(4..8));
+/// ```
+#[inline]
+pub fn check(input: &str) -> Result<()> {
+    check_with_opts(input, Opts::default())
+}
+
+/// Check that a LaTeX equation is valid with additional [options](`Opts`),
+/// e.g. macros.
+///
+/// [`throw_on_error`](crate::OptsBuilder::throw_on_error) is ignored.
+pub fn check_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<()> {
+    with_engine(|engine| check_inner(engine, input, opts.as_ref()))
+}
+
+fn check_inner<E>(engine: &E, input: &str, opts: &Opts) -> Result<()>
+where
+    E: JsEngine,
+{
+    let original = input;
+    let (input, map) = preprocess::preprocess_with_map(input, opts)?;
+    let js_input = engine.create_string_value(input.clone())?;
+    let js_opts = opts.to_js_value(engine)?;
+    let args = iter::once(js_input).chain(iter::once(js_opts));
+    let result = engine
+        .call_function("__katex_rs.check", args)?
+        .into_string()?;
+    if result.is_empty() {
+        return Ok(());
+    }
+    let mut fields = result.splitn(3, '\t');
+    let (Some(Ok(position)), Some(Ok(length)), Some(message)) = (
+        fields.next().map(str::parse::<i64>),
+        fields.next().map(str::parse::<usize>),
+        fields.next(),
+    ) else {
+        return Err(Error::JsValueError(format!(
+            "unexpected parse error {result:?}"
+        )));
+    };
+    // KaTeX counts UTF-16 code units in the preprocessed input.
+    let span = usize::try_from(position).ok().map(|start| {
+        let end = byte_offset(&input, start + length);
+        let start = byte_offset(&input, start);
+        map.map(start, false)..map.map(end, true)
+    });
+    let char_span = span.as_ref().map(|span| {
+        let start = original[..span.start].chars().count();
+        start..start + original[span.clone()].chars().count()
+    });
+    Err(Error::ParseError(ParseError {
+        message: message.to_owned(),
+        span,
+        char_span,
+    }))
+}
+
+/// Convert an offset in UTF-16 code units, as used by JS, to a byte offset.
+fn byte_offset(input: &str, utf16_offset: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in input.char_indices() {
+        if units >= utf16_offset {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    input.len()
+}
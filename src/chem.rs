@@ -0,0 +1,77 @@
+//! Render chemical equations and physical units with the bundled mhchem
+//! extension.
+//!
+//! The helpers wrap the input in `\ce{...}` or `\pu{...}`, after checking
+//! its braces and escaping the characters which KaTeX would otherwise
+//! interpret before mhchem sees them, e.g. `%` starting a comment.
+
+use crate::{
+    environments::check_braces,
+    error::{Error, Result},
+    opts::Opts,
+};
+
+/// Render a chemical equation in mhchem's `\ce` syntax.
+///
+/// # Examples
+///
+/// ```
+/// let html = katex::chem::render_ce("CO2 + C -> 2 CO").unwrap();
+/// assert!(html.contains("CO"));
+/// ```
+#[inline]
+pub fn render_ce(input: &str) -> Result<String> {
+    render_ce_with_opts(input, Opts::default())
+}
+
+/// Render a chemical equation in mhchem's `\ce` syntax with additional
+/// [options](`Opts`).
+pub fn render_ce_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    render_mhchem("ce", input, opts.as_ref())
+}
+
+/// Render a physical quantity or unit in mhchem's `\pu` syntax.
+///
+/// # Examples
+///
+/// ```
+/// let html = katex::chem::render_pu("123 kJ/mol").unwrap();
+/// assert!(html.contains("kJ"));
+/// ```
+#[inline]
+pub fn render_pu(input: &str) -> Result<String> {
+    render_pu_with_opts(input, Opts::default())
+}
+
+/// Render a physical quantity or unit in mhchem's `\pu` syntax with
+/// additional [options](`Opts`).
+pub fn render_pu_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    render_mhchem("pu", input, opts.as_ref())
+}
+
+fn render_mhchem(command: &str, input: &str, opts: &Opts) -> Result<String> {
+    check_braces(input).map_err(|reason| Error::InvalidInput(format!("{command}: {reason}")))?;
+    let input = format!("\\{command}{{{}}}", escape(input));
+    crate::render_with_opts(&input, opts)
+}
+
+/// Escape the characters with a special meaning to KaTeX, unless they are
+/// already escaped.
+fn escape(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                escaped.push(c);
+                escaped.extend(chars.next());
+            }
+            '%' | '#' | '&' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
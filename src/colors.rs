@@ -0,0 +1,94 @@
+//! List the colors used by an equation.
+//!
+//! Theming systems can check the colors against the page background, e.g. for
+//! contrast ratios or dark mode. The colors are collected from the parse tree
+//! of the equation, so they include the ones set by macros, and not the ones
+//! hard-coded in the KaTeX stylesheet.
+
+use crate::{
+    error::{Error, Result},
+    js_engine::{JsEngine, JsValue},
+    opts::Opts,
+    preprocess, with_engine,
+};
+use core::iter;
+
+/// How a color is used.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorRole {
+    /// Text color, set by `\color` or `\textcolor`.
+    Text,
+    /// Background color, set by `\colorbox` or `\fcolorbox`.
+    Background,
+    /// Border color, set by `\fcolorbox`.
+    Border,
+    /// The [error color](crate::OptsBuilder::error_color), used when the
+    /// equation fails to parse and [`throw_on_error`](crate::OptsBuilder::throw_on_error)
+    /// is unset.
+    Error,
+}
+
+/// A color used by an equation.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ColorUse {
+    /// The color as written in the input, e.g. `red` or `#ff0000`, or as
+    /// normalized by KaTeX, which adds the `#` to six hex digits.
+    pub color: String,
+    /// How the color is used.
+    pub role: ColorRole,
+}
+
+/// List the colors used by a LaTeX equation, in order of first use and
+/// without duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use katex::{colors, ColorRole};
+///
+/// let colors = colors(r"\textcolor{red}{x} + \colorbox{#eee}{y}").unwrap();
+/// assert_eq!(colors[0].color, "red");
+/// assert_eq!(colors[0].role, ColorRole::Text);
+/// assert_eq!(colors[1].color, "#eee");
+/// assert_eq!(colors[1].role, ColorRole::Background);
+/// ```
+#[inline]
+pub fn colors(input: &str) -> Result<Vec<ColorUse>> {
+    colors_with_opts(input, Opts::default())
+}
+
+/// List the colors used by a LaTeX equation with additional [options](`Opts`).
+pub fn colors_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<Vec<ColorUse>> {
+    with_engine(|engine| colors_inner(engine, input, opts.as_ref()))
+}
+
+fn colors_inner<E>(engine: &E, input: &str, opts: &Opts) -> Result<Vec<ColorUse>>
+where
+    E: JsEngine,
+{
+    let input = preprocess::preprocess(input, opts)?;
+    let js_input = engine.create_string_value(input)?;
+    let js_opts = opts.to_js_value(engine)?;
+    let args = iter::once(js_input).chain(iter::once(js_opts));
+    let result = engine
+        .call_function("__katex_rs.colors", args)?
+        .into_string()?;
+    result
+        .lines()
+        .map(|line| {
+            let (role, color) = match line.split_once('\t') {
+                Some(("text", color)) => (ColorRole::Text, color),
+                Some(("background", color)) => (ColorRole::Background, color),
+                Some(("border", color)) => (ColorRole::Border, color),
+                Some(("error", color)) => (ColorRole::Error, color),
+                _ => return Err(Error::JsValueError(format!("unexpected color {line:?}"))),
+            };
+            Ok(ColorUse {
+                color: color.to_owned(),
+                role,
+            })
+        })
+        .collect()
+}
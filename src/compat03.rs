@@ -0,0 +1,24 @@
+//! Thin wrappers replicating the katex 0.3 API.
+//!
+//! This module is only available with the `compat03` feature. It allows large
+//! code bases to upgrade this crate without migrating all call sites at once.
+//! Every item is deprecated in favor of its replacement in the current API.
+
+use crate::{error::Result, opts::Opts};
+
+/// Render LaTeX equation to HTML.
+#[deprecated(since = "0.4.0", note = "use `katex::render` instead")]
+#[inline]
+pub fn render(input: &str) -> Result<String> {
+    crate::render(input)
+}
+
+/// Render LaTeX equation to HTML with additional [options](`Opts`) passed by value.
+#[deprecated(
+    since = "0.4.0",
+    note = "use `katex::render_with_opts`, which accepts `Opts` by value or by reference"
+)]
+#[inline]
+pub fn render_with_opts(input: &str, opts: Opts) -> Result<String> {
+    crate::render_with_opts(input, opts)
+}
@@ -0,0 +1,86 @@
+//! Estimate how expensive an equation is to render.
+//!
+//! The score is meant for admission control, e.g. to reject or queue heavy
+//! equations before rendering them, and for analytics. Computing it parses
+//! the equation with KaTeX but skips building the output.
+
+use crate::{
+    error::Result,
+    js_engine::{JsEngine, JsValue},
+    opts::Opts,
+    preprocess, with_engine,
+};
+use core::iter;
+
+/// Complexity of an equation.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ComplexityScore {
+    /// Number of nodes of the parse tree.
+    pub nodes: usize,
+    /// Depth of the parse tree.
+    pub depth: usize,
+    /// Estimated number of macro expansions, counting the macros from the
+    /// [options](`Opts`) and the ones defined in the input.
+    pub macro_expansions: usize,
+}
+
+impl ComplexityScore {
+    /// Combine the measures into a single score.
+    ///
+    /// Deep trees weigh more than their node count suggests, as every level
+    /// adds nested boxes to the output.
+    pub fn total(&self) -> usize {
+        self.nodes
+            .saturating_add(self.depth.saturating_mul(10))
+            .saturating_add(self.macro_expansions)
+    }
+}
+
+/// Compute the complexity of a LaTeX equation.
+///
+/// # Examples
+///
+/// ```
+/// let simple = katex::complexity("x").unwrap();
+/// let nested = katex::complexity(r"\frac{1}{1 + \frac{1}{1 + x}}").unwrap();
+/// assert!(nested.total() > simple.total());
+/// ```
+#[inline]
+pub fn complexity(input: &str) -> Result<ComplexityScore> {
+    complexity_with_opts(input, Opts::default())
+}
+
+/// Compute the complexity of a LaTeX equation with additional [options](`Opts`).
+///
+/// Errors are the same as the ones of rendering with
+/// [`throw_on_error`](crate::OptsBuilder::throw_on_error) set, regardless of the
+/// options.
+pub fn complexity_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<ComplexityScore> {
+    with_engine(|engine| complexity_inner(engine, input, opts.as_ref()))
+}
+
+fn complexity_inner<E>(engine: &E, input: &str, opts: &Opts) -> Result<ComplexityScore>
+where
+    E: JsEngine,
+{
+    let input = preprocess::preprocess(input, opts)?;
+    let macro_expansions = preprocess::macro_expansions(&input, opts);
+    let js_input = engine.create_string_value(input)?;
+    let js_opts = opts.to_js_value(engine)?;
+    let args = iter::once(js_input).chain(iter::once(js_opts));
+    let result = engine
+        .call_function("__katex_rs.complexity", args)?
+        .into_string()?;
+    let mut measures = result.split(',').map(str::parse::<usize>);
+    match (measures.next(), measures.next()) {
+        (Some(Ok(nodes)), Some(Ok(depth))) => Ok(ComplexityScore {
+            nodes,
+            depth,
+            macro_expansions,
+        }),
+        _ => Err(crate::Error::JsValueError(format!(
+            "unexpected complexity {result:?}"
+        ))),
+    }
+}
@@ -0,0 +1,250 @@
+//! Convert KaTeX's output to other math formats.
+//!
+//! This module is only available with the `omml` feature. It converts
+//! KaTeX's MathML output to Office MathML (OMML), the native equation format
+//! of Microsoft Word, so `.docx` pipelines can embed equations generated from
+//! the same LaTeX source as the web pages.
+//!
+//! # Examples
+//!
+//! ```
+//! let omml = katex::convert::render_omml(r"\frac{a}{b}", katex::Opts::default()).unwrap();
+//! assert!(omml.starts_with("<m:oMath"));
+//! assert!(omml.contains("<m:f><m:num>"));
+//! ```
+
+use crate::{
+    error::Result,
+    html::{self, Node},
+    opts::{Opts, OutputType},
+};
+
+/// Namespace of Office MathML.
+const OMML_NAMESPACE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/math";
+
+/// Render LaTeX equation to an OMML fragment with additional [options](`Opts`).
+///
+/// The [output type](`OutputType`) is ignored.
+pub fn render_omml(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    let mut opts = opts.as_ref().clone();
+    opts.set_output_type(OutputType::Mathml);
+    let mathml = crate::render_with_opts(input, opts)?;
+    Ok(mathml_to_omml(&mathml))
+}
+
+/// Convert MathML, e.g. rendered by KaTeX with the
+/// [MathML output type](`OutputType::Mathml`), to OMML fragments.
+///
+/// Each `<math>` element becomes an `<m:oMath>` element, wrapped in an
+/// `<m:oMathPara>` for display math. The `m` prefix is declared on each
+/// fragment, so they can be pasted in a `<w:p>` of a Word document as is.
+pub fn mathml_to_omml(mathml: &str) -> String {
+    let nodes = html::parse(mathml);
+    let mut stack: Vec<&Node> = nodes.iter().rev().collect();
+    let mut output = String::new();
+    while let Some(node) = stack.pop() {
+        match node {
+            Node::Element { name, children, .. } if name == "math" => {
+                let mut content = String::new();
+                convert_children(children, &mut content);
+                if node.attr("display") == Some("block") {
+                    output.push_str(&format!(
+                        r#"<m:oMathPara xmlns:m="{OMML_NAMESPACE}"><m:oMath>{content}</m:oMath></m:oMathPara>"#
+                    ));
+                } else {
+                    output.push_str(&format!(
+                        r#"<m:oMath xmlns:m="{OMML_NAMESPACE}">{content}</m:oMath>"#
+                    ));
+                }
+            }
+            Node::Element { children, .. } => stack.extend(children.iter().rev()),
+            Node::Text(_) => {}
+        }
+    }
+    output
+}
+
+fn convert_children(nodes: &[Node], output: &mut String) {
+    for node in nodes {
+        convert(node, output);
+    }
+}
+
+/// Convert a child as the content of an OMML argument element, e.g. `m:e`.
+fn argument(tag: &str, node: Option<&Node>, output: &mut String) {
+    output.push_str(&format!("<m:{tag}>"));
+    if let Some(node) = node {
+        convert(node, output);
+    }
+    output.push_str(&format!("</m:{tag}>"));
+}
+
+/// Write a run of text, with the style given by an OMML `m:sty` value.
+fn run(text: &str, style: Option<&str>, output: &mut String) {
+    if text.is_empty() {
+        return;
+    }
+    output.push_str("<m:r>");
+    if let Some(style) = style {
+        output.push_str(&format!(r#"<m:rPr><m:sty m:val="{style}"/></m:rPr>"#));
+    }
+    let space = if text.starts_with(' ') || text.ends_with(' ') {
+        r#" xml:space="preserve""#
+    } else {
+        ""
+    };
+    output.push_str(&format!("<m:t{space}>{}</m:t></m:r>", html::escape(text)));
+}
+
+/// Return the OMML style of a `mathvariant`, where `italic` is the default.
+fn style(variant: Option<&str>, italic: bool) -> Option<&'static str> {
+    match variant {
+        Some("bold") => Some("b"),
+        Some("bold-italic") => Some("bi"),
+        Some("italic") => (!italic).then_some("i"),
+        Some(_) => Some("p"),
+        None if italic => None,
+        None => Some("p"),
+    }
+}
+
+fn convert(node: &Node, output: &mut String) {
+    let (name, children) = match node {
+        Node::Text(text) => return run(text, Some("p"), output),
+        Node::Element { name, children, .. } => (name.as_str(), children.as_slice()),
+    };
+    let child = |i: usize| children.get(i);
+    let variant = node.attr("mathvariant");
+    match name {
+        "annotation" | "annotation-xml" | "mspace" => {}
+        "semantics" => {
+            if let Some(first) = children.first() {
+                convert(first, output);
+            }
+        }
+        "mi" => {
+            let text = node.text();
+            let italic = text.chars().count() == 1;
+            run(&text, style(variant, italic), output);
+        }
+        "mn" | "mo" | "mtext" | "ms" => run(&node.text(), style(variant, false), output),
+        "mfrac" => {
+            output.push_str("<m:f>");
+            if node.attr("linethickness").is_some_and(is_zero) {
+                output.push_str(r#"<m:fPr><m:type m:val="noBar"/></m:fPr>"#);
+            }
+            argument("num", child(0), output);
+            argument("den", child(1), output);
+            output.push_str("</m:f>");
+        }
+        "msup" => {
+            output.push_str("<m:sSup>");
+            argument("e", child(0), output);
+            argument("sup", child(1), output);
+            output.push_str("</m:sSup>");
+        }
+        "msub" => {
+            output.push_str("<m:sSub>");
+            argument("e", child(0), output);
+            argument("sub", child(1), output);
+            output.push_str("</m:sSub>");
+        }
+        "msubsup" => {
+            output.push_str("<m:sSubSup>");
+            argument("e", child(0), output);
+            argument("sub", child(1), output);
+            argument("sup", child(2), output);
+            output.push_str("</m:sSubSup>");
+        }
+        "mover" if node.attr("accent") == Some("true") => {
+            let accent = child(1).map(Node::text).unwrap_or_default();
+            output.push_str(&format!(
+                r#"<m:acc><m:accPr><m:chr m:val="{}"/></m:accPr>"#,
+                html::escape(&accent)
+            ));
+            argument("e", child(0), output);
+            output.push_str("</m:acc>");
+        }
+        "mover" => limit("limUpp", child(0), child(1), output),
+        "munder" => limit("limLow", child(0), child(1), output),
+        "munderover" => {
+            output.push_str("<m:limUpp><m:e>");
+            limit("limLow", child(0), child(1), output);
+            output.push_str("</m:e>");
+            argument("lim", child(2), output);
+            output.push_str("</m:limUpp>");
+        }
+        "msqrt" => {
+            output.push_str(r#"<m:rad><m:radPr><m:degHide m:val="1"/></m:radPr><m:deg/><m:e>"#);
+            convert_children(children, output);
+            output.push_str("</m:e></m:rad>");
+        }
+        "mroot" => {
+            output.push_str("<m:rad>");
+            argument("deg", child(1), output);
+            argument("e", child(0), output);
+            output.push_str("</m:rad>");
+        }
+        "mphantom" => {
+            output.push_str("<m:phant><m:e>");
+            convert_children(children, output);
+            output.push_str("</m:e></m:phant>");
+        }
+        "mtable" => {
+            output.push_str("<m:m>");
+            for row in children {
+                output.push_str("<m:mr>");
+                for cell in row.children() {
+                    output.push_str("<m:e>");
+                    convert_children(cell.children(), output);
+                    output.push_str("</m:e>");
+                }
+                output.push_str("</m:mr>");
+            }
+            output.push_str("</m:m>");
+        }
+        "mrow" => match fenced(children) {
+            Some((open, inner, close)) => {
+                output.push_str(&format!(
+                    r#"<m:d><m:dPr><m:begChr m:val="{}"/><m:endChr m:val="{}"/></m:dPr><m:e>"#,
+                    html::escape(&open),
+                    html::escape(&close)
+                ));
+                convert_children(inner, output);
+                output.push_str("</m:e></m:d>");
+            }
+            None => convert_children(children, output),
+        },
+        _ => convert_children(children, output),
+    }
+}
+
+/// Write a base with a limit above (`limUpp`) or below (`limLow`) it.
+fn limit(tag: &str, base: Option<&Node>, lim: Option<&Node>, output: &mut String) {
+    output.push_str(&format!("<m:{tag}>"));
+    argument("e", base, output);
+    argument("lim", lim, output);
+    output.push_str(&format!("</m:{tag}>"));
+}
+
+/// Split the children of an `mrow` delimited by fences, e.g. from `\left(`
+/// and `\right)`, into the opening fence, the content and the closing fence.
+fn fenced(children: &[Node]) -> Option<(String, &[Node], String)> {
+    let is_fence = |node: &Node| {
+        matches!(node, Node::Element { name, .. } if name == "mo")
+            && node.attr("fence") == Some("true")
+    };
+    match children {
+        [open, inner @ .., close] if is_fence(open) && is_fence(close) => {
+            Some((open.text(), inner, close.text()))
+        }
+        _ => None,
+    }
+}
+
+fn is_zero(length: &str) -> bool {
+    length
+        .trim_end_matches(char::is_alphabetic)
+        .parse::<f64>()
+        .is_ok_and(|value| value == 0.0)
+}
@@ -0,0 +1,462 @@
+//! Minimal readers of the JSON and TOML subsets used by data files, e.g.
+//! [macro files](crate::MacroFormat) and [test suites](crate::testing).
+//!
+//! Only strings, numbers, booleans, arrays and tables are supported, not
+//! e.g. TOML dates or dotted keys.
+
+use core::fmt;
+
+/// A parsed JSON or TOML value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Describe the type of the value for error messages.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Integer(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::Bool(_) => "a boolean",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+}
+
+/// Syntax error with its line number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SyntaxError {
+    pub(crate) line: usize,
+    pub(crate) reason: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+type Result<T> = core::result::Result<T, SyntaxError>;
+
+/// Parse a JSON object into its fields.
+pub(crate) fn parse_json(input: &str) -> Result<Vec<(String, Value)>> {
+    let mut cursor = Cursor::new(input);
+    cursor.skip(true, false);
+    if cursor.peek() != Some('{') {
+        return Err(cursor.error("expected an object"));
+    }
+    let Value::Table(fields) = cursor.json_value()? else {
+        unreachable!("objects are parsed into tables");
+    };
+    cursor.skip(true, false);
+    if cursor.peek().is_some() {
+        return Err(cursor.error("unexpected content after the object"));
+    }
+    Ok(fields)
+}
+
+/// Parse a TOML document into its top-level fields.
+///
+/// Tables (`[name]`) and arrays of tables (`[[name]]`) are supported at the
+/// top level only.
+pub(crate) fn parse_toml(input: &str) -> Result<Vec<(String, Value)>> {
+    let mut cursor = Cursor::new(input);
+    let mut root: Vec<(String, Value)> = Vec::new();
+    // The current table: its name in `root`, and whether it is an array.
+    let mut current: Option<(String, bool)> = None;
+    loop {
+        cursor.skip(true, true);
+        if cursor.peek().is_none() {
+            break;
+        }
+        if cursor.eat('[') {
+            let array = cursor.eat('[');
+            cursor.skip(false, false);
+            let name = cursor.toml_key()?;
+            cursor.skip(false, false);
+            cursor.expect(']')?;
+            if array {
+                cursor.expect(']')?;
+            }
+            match root.iter_mut().find(|(key, _)| *key == name) {
+                Some((_, Value::Array(tables))) if array => tables.push(Value::Table(Vec::new())),
+                Some(_) => return Err(cursor.error(format!("duplicate key {name:?}"))),
+                None if array => {
+                    root.push((name.clone(), Value::Array(vec![Value::Table(Vec::new())])))
+                }
+                None => root.push((name.clone(), Value::Table(Vec::new()))),
+            }
+            current = Some((name, array));
+        } else {
+            let key = cursor.toml_key()?;
+            cursor.skip(false, false);
+            cursor.expect('=')?;
+            cursor.skip(false, false);
+            let value = cursor.toml_value()?;
+            let table = match &current {
+                None => &mut root,
+                Some((name, array)) => {
+                    let (_, value) = root.iter_mut().find(|(key, _)| key == name).unwrap();
+                    let value = match value {
+                        Value::Array(tables) if *array => tables.last_mut().unwrap(),
+                        value => value,
+                    };
+                    let Value::Table(fields) = value else {
+                        unreachable!("table headers create tables");
+                    };
+                    fields
+                }
+            };
+            if table.iter().any(|(k, _)| *k == key) {
+                return Err(cursor.error(format!("duplicate key {key:?}")));
+            }
+            table.push((key, value));
+        }
+        cursor.skip(false, true);
+        if !(cursor.eat('\n') || cursor.eat('\r') || cursor.peek().is_none()) {
+            return Err(cursor.error("expected a new line"));
+        }
+    }
+    Ok(root)
+}
+
+/// A cursor over JSON or TOML input.
+struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{c}`")))
+        }
+    }
+
+    /// Skip whitespace, including newlines if `newlines` is set, and TOML
+    /// comments if `comments` is set.
+    fn skip(&mut self, newlines: bool, comments: bool) {
+        loop {
+            let rest = self.rest();
+            let trimmed = if newlines {
+                rest.trim_start()
+            } else {
+                rest.trim_start_matches([' ', '\t'])
+            };
+            self.pos += rest.len() - trimmed.len();
+            if comments && trimmed.starts_with('#') {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+                if newlines {
+                    continue;
+                }
+            }
+            break;
+        }
+    }
+
+    fn error(&self, reason: impl fmt::Display) -> SyntaxError {
+        SyntaxError {
+            line: self.input[..self.pos].matches('\n').count() + 1,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Read a string in double quotes with JSON and TOML escapes.
+    fn basic_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut output = String::new();
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(output),
+                '\\' => output.push(self.escape()?),
+                '\n' => return Err(self.error("unterminated string")),
+                c => output.push(c),
+            }
+        }
+    }
+
+    /// Read the character escaped after a backslash.
+    fn escape(&mut self) -> Result<char> {
+        let escaped = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.pos += escaped.len_utf8();
+        Ok(match escaped {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let high = self.hex4()?;
+                // Characters outside the BMP are escaped as surrogate pairs.
+                let code = if (0xd800..0xdc00).contains(&high) && self.rest().starts_with("\\u") {
+                    self.pos += 2;
+                    let low = self.hex4()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error("invalid unicode escape"));
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            _ => return Err(self.error(format!("invalid escape `\\{escaped}`"))),
+        })
+    }
+
+    /// Read the 4 hexadecimal digits of a unicode escape.
+    fn hex4(&mut self) -> Result<u32> {
+        let hex = self
+            .rest()
+            .get(..4)
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        let code = u32::from_str_radix(hex, 16).unwrap();
+        self.pos += 4;
+        Ok(code)
+    }
+
+    /// Read a TOML multi-line string, in triple double quotes with escapes or
+    /// in triple single quotes without.
+    fn multiline_string(&mut self, quote: char) -> Result<String> {
+        let delimiter = if quote == '"' { "\"\"\"" } else { "'''" };
+        self.pos += 3;
+        // A newline right after the delimiter is trimmed.
+        if !self.eat('\n') && self.rest().starts_with("\r\n") {
+            self.pos += 2;
+        }
+        let mut output = String::new();
+        loop {
+            if self.rest().starts_with(delimiter) {
+                // Up to two quotes may precede the closing delimiter.
+                let quotes = self.rest().len() - self.rest().trim_start_matches(quote).len();
+                let quotes = quotes.min(5);
+                output.extend(core::iter::repeat_n(quote, quotes - 3));
+                self.pos += quotes;
+                return Ok(output);
+            }
+            let c = self
+                .peek()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += c.len_utf8();
+            if c == '\\' && quote == '"' {
+                // A backslash at the end of a line trims the following
+                // whitespace, including newlines.
+                let rest = self.rest().trim_start_matches([' ', '\t']);
+                if rest.starts_with('\n') || rest.starts_with("\r\n") {
+                    let rest = self.rest();
+                    self.pos += rest.len() - rest.trim_start().len();
+                } else {
+                    output.push(self.escape()?);
+                }
+            } else {
+                output.push(c);
+            }
+        }
+    }
+
+    /// Read a TOML literal string in single quotes, without escapes.
+    fn literal_string(&mut self) -> Result<String> {
+        self.expect('\'')?;
+        let end = self
+            .rest()
+            .find(['\'', '\n'])
+            .filter(|&end| self.rest()[end..].starts_with('\''))
+            .ok_or_else(|| self.error("unterminated string"))?;
+        let value = self.rest()[..end].to_owned();
+        self.pos += end + 1;
+        Ok(value)
+    }
+
+    /// Read an integer or a float. TOML numbers may also have underscores
+    /// between digits and be `inf` or `nan`.
+    fn number(&mut self, toml: bool) -> Result<Value> {
+        let rest = self.rest();
+        let unsigned = rest.trim_start_matches(['-', '+']);
+        if toml && (unsigned.starts_with("inf") || unsigned.starts_with("nan")) {
+            let len = rest.len() - unsigned.len() + 3;
+            let value = rest[..len]
+                .parse()
+                .map_err(|_| self.error("invalid float"))?;
+            self.pos += len;
+            return Ok(Value::Float(value));
+        }
+        let len = rest
+            .find(|c: char| {
+                !(c.is_ascii_digit()
+                    || matches!(c, '-' | '+' | '.' | 'e' | 'E')
+                    || toml && c == '_')
+            })
+            .unwrap_or(rest.len());
+        let number = &rest[..len];
+        if number.contains("__") || number.starts_with('_') || number.ends_with('_') {
+            return Err(self.error("invalid number"));
+        }
+        let number = number.replace('_', "");
+        let value = if number.contains(['.', 'e', 'E']) {
+            number
+                .parse()
+                .map(Value::Float)
+                .map_err(|_| self.error("invalid float"))?
+        } else {
+            number
+                .parse()
+                .map(Value::Integer)
+                .map_err(|_| self.error("invalid integer"))?
+        };
+        self.pos += len;
+        Ok(value)
+    }
+
+    fn keyword(&mut self) -> Result<bool> {
+        for (keyword, value) in [("true", true), ("false", false)] {
+            if self.rest().starts_with(keyword) {
+                self.pos += keyword.len();
+                return Ok(value);
+            }
+        }
+        Err(self.error("invalid value"))
+    }
+
+    fn json_value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('{') => {
+                let fields = self.sequence('{', '}', true, false, |cursor| {
+                    let key = cursor.basic_string()?;
+                    cursor.skip(true, false);
+                    cursor.expect(':')?;
+                    cursor.skip(true, false);
+                    Ok((key, cursor.json_value()?))
+                })?;
+                Ok(Value::Table(fields))
+            }
+            Some('[') => Ok(Value::Array(self.sequence(
+                '[',
+                ']',
+                true,
+                false,
+                Self::json_value,
+            )?)),
+            Some('t' | 'f') => Ok(Value::Bool(self.keyword()?)),
+            Some('-' | '0'..='9') => self.number(false),
+            _ => Err(self.error("invalid value")),
+        }
+    }
+
+    fn toml_key(&mut self) -> Result<String> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let len = self
+                    .rest()
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+                    .unwrap_or(self.rest().len());
+                if len == 0 {
+                    return Err(self.error("expected a key"));
+                }
+                let key = self.rest()[..len].to_owned();
+                self.pos += len;
+                Ok(key)
+            }
+        }
+    }
+
+    fn toml_value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) if self.rest()[1..].starts_with([quote; 2]) => {
+                Ok(Value::String(self.multiline_string(quote)?))
+            }
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('\'') => Ok(Value::String(self.literal_string()?)),
+            Some('{') => {
+                let fields = self.sequence('{', '}', false, false, |cursor| {
+                    let key = cursor.toml_key()?;
+                    cursor.skip(false, false);
+                    cursor.expect('=')?;
+                    cursor.skip(false, false);
+                    Ok((key, cursor.toml_value()?))
+                })?;
+                Ok(Value::Table(fields))
+            }
+            Some('[') => Ok(Value::Array(self.sequence(
+                '[',
+                ']',
+                true,
+                true,
+                Self::toml_value,
+            )?)),
+            Some('t' | 'f') => Ok(Value::Bool(self.keyword()?)),
+            Some('-' | '+' | 'i' | 'n' | '0'..='9') => self.number(true),
+            _ => Err(self.error("invalid value")),
+        }
+    }
+
+    /// Read comma-separated items between `open` and `close`, which may span
+    /// lines if `multiline` is set, with TOML comments if `comments` is set. A
+    /// trailing comma is accepted.
+    fn sequence<T>(
+        &mut self,
+        open: char,
+        close: char,
+        multiline: bool,
+        comments: bool,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        self.expect(open)?;
+        let mut items = Vec::new();
+        loop {
+            self.skip(multiline, comments);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            items.push(item(self)?);
+            self.skip(multiline, comments);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(',')?;
+        }
+    }
+}
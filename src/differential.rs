@@ -0,0 +1,235 @@
+//! Differential testing of the JS backends.
+//!
+//! Formulas and options are generated from a seed, rendered on every enabled
+//! backend, and the outputs compared, to catch marshaling bugs specific to a
+//! backend, e.g. in the formatting of numbers or the encoding of strings.
+
+use crate::{
+    error::Result,
+    init_katex_with_sources,
+    js_engine::{Engine, JsEngine},
+    opts::{Limit, Opts, OutputType},
+    render_inner, BUNDLED_SRC,
+};
+use cfg_if::cfg_if;
+
+/// A JS backend rendering with its own engine.
+pub(crate) struct Backend {
+    pub(crate) name: &'static str,
+    render: Box<RenderFn>,
+}
+
+type RenderFn = dyn Fn(&str, &Opts) -> Result<String>;
+
+impl Backend {
+    fn new<E: JsEngine + 'static>(name: &'static str) -> Result<Self> {
+        let engine: E = init_katex_with_sources(None, &[], BUNDLED_SRC)?;
+        Ok(Self {
+            name,
+            render: Box::new(move |input, opts| Ok(render_inner(&engine, input, opts)?.html)),
+        })
+    }
+
+    pub(crate) fn render(&self, input: &str, opts: &Opts) -> Result<String> {
+        (self.render)(input, opts)
+    }
+}
+
+/// Create every enabled backend, the selected one first.
+pub(crate) fn backends() -> Result<Vec<Backend>> {
+    cfg_if! {
+        if #[cfg(all(feature = "android", target_os = "android"))] {
+            let selected = "duktape";
+        } else if #[cfg(feature = "quick-js")] {
+            let selected = "quick-js";
+        } else if #[cfg(any(feature = "duktape", feature = "android"))] {
+            let selected = "duktape";
+        } else if #[cfg(feature = "wasm-js")] {
+            let selected = "wasm-js";
+        } else if #[cfg(feature = "rquickjs")] {
+            let selected = "rquickjs";
+        } else {
+            let selected = "jsc";
+        }
+    }
+    #[allow(unused_mut)]
+    let mut backends = vec![Backend::new::<Engine>(selected)?];
+    #[cfg(all(
+        feature = "duktape",
+        feature = "quick-js",
+        any(unix, windows),
+        not(target_os = "android")
+    ))]
+    backends.push(Backend::new::<crate::js_engine::duktape::Engine>(
+        "duktape",
+    )?);
+    #[cfg(all(
+        feature = "rquickjs",
+        feature = "duktape",
+        not(feature = "quick-js"),
+        any(unix, windows)
+    ))]
+    backends.push(Backend::new::<crate::js_engine::rquickjs::Engine>(
+        "rquickjs",
+    )?);
+    Ok(backends)
+}
+
+/// A generated formula with its options.
+#[derive(Debug)]
+pub(crate) struct Case {
+    pub(crate) input: String,
+    pub(crate) opts: Opts,
+}
+
+/// Generate the case of `seed`.
+pub(crate) fn generate(seed: u64) -> Case {
+    let mut rng = Rng(seed);
+    let mut input = formula(&mut rng, 3);
+    let mut opts = Opts::default();
+    opts.set_display_mode(rng.chance(2));
+    opts.set_output_type(match rng.below(3) {
+        0 => OutputType::Html,
+        1 => OutputType::Mathml,
+        _ => OutputType::HtmlAndMathml,
+    });
+    opts.set_throw_on_error(false);
+    if rng.chance(3) {
+        opts.set_min_rule_thickness(rng.float());
+    }
+    if rng.chance(3) {
+        opts.set_max_size(Limit::Finite(rng.float() * 20.0));
+    }
+    if rng.chance(3) {
+        // Limits at the edges of the integer types.
+        opts.set_max_expand(match rng.below(3) {
+            0 => Limit::Finite(i32::MAX as u32),
+            1 => Limit::Finite(u32::MAX),
+            _ => Limit::Unlimited,
+        });
+    }
+    if rng.chance(3) {
+        opts.add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned());
+        input.push_str(r" \in \RR");
+    }
+    Case { input, opts }
+}
+
+const ATOMS: &[&str] = &[
+    "x",
+    "y",
+    "a",
+    "b",
+    r"\alpha",
+    r"\omega",
+    r"\infty",
+    r"\partial",
+    "é",
+    "∑",
+    r"\{",
+    "<",
+    ">",
+    r"\&",
+];
+
+const TEXTS: &[&str] = &[
+    "naïve",
+    "中文",
+    "😀",
+    "👍🏽",
+    "e\u{301}",
+    "\"quoted\"",
+    "<b>",
+    r"a \& b",
+    "\u{a0}",
+    "tab\there",
+];
+
+const OPERATORS: &[&str] = &["+", "-", "=", r"\le", r"\cdot", r"\to", ","];
+
+fn formula(rng: &mut Rng, depth: u32) -> String {
+    if depth == 0 {
+        return rng.pick(ATOMS).to_owned();
+    }
+    let depth = depth - 1;
+    match rng.below(12) {
+        0 => rng.pick(ATOMS).to_owned(),
+        1 => number(rng),
+        2 => format!(
+            r"\frac{{{}}}{{{}}}",
+            formula(rng, depth),
+            formula(rng, depth)
+        ),
+        3 => format!(r"\sqrt[{}]{{{}}}", rng.below(10), formula(rng, depth)),
+        4 => format!(
+            "{{{}}}^{{{}}}_{{{}}}",
+            formula(rng, depth),
+            formula(rng, depth),
+            formula(rng, depth)
+        ),
+        5 => format!(r"\left( {} \right)", formula(rng, depth)),
+        6 => format!(
+            r"\{}{{{}}}",
+            rng.pick(&["mathbf", "mathrm", "mathcal", "mathbb", "boldsymbol"]),
+            formula(rng, depth)
+        ),
+        7 => format!(r"\text{{{}}}", rng.pick(TEXTS)),
+        8 => format!(
+            r"\kern{{{:.3}em}}\rule{{{:.2}em}}{{{}pt}}",
+            rng.float() - 0.5,
+            rng.float(),
+            rng.below(4)
+        ),
+        9 => format!(
+            r"\color{{#{:06x}}}{{{}}}",
+            rng.below(0x0100_0000),
+            formula(rng, depth)
+        ),
+        10 => format!(
+            r"\ce{{{}}}",
+            rng.pick(&["H2O", "CO2 + C -> 2 CO", "^{227}_{90}Th+"])
+        ),
+        _ => (0..2 + rng.below(2))
+            .map(|_| formula(rng, depth))
+            .collect::<Vec<_>>()
+            .join(rng.pick(OPERATORS)),
+    }
+}
+
+fn number(rng: &mut Rng) -> String {
+    match rng.below(3) {
+        0 => rng.below(1_000_000).to_string(),
+        1 => format!("{}", rng.float() * 1000.0),
+        _ => format!("{}.{:03}", rng.below(100), rng.below(1000)),
+    }
+}
+
+/// SplitMix64, enough to generate formulas deterministically.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn chance(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+
+    /// A float in `[0, 1)`.
+    fn float(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
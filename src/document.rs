@@ -0,0 +1,188 @@
+//! Render a full HTML article mixing prose and equations.
+//!
+//! # Examples
+//!
+//! ```
+//! let html = katex::document::Builder::new()
+//!     .title("Relativity")
+//!     .paragraph("The famous equation is")
+//!     .numbered_equation("E = mc^2")
+//!     .build()
+//!     .unwrap();
+//! assert!(html.contains("<title>Relativity</title>"));
+//! ```
+
+use crate::{error::Result, html, opts::Opts, KATEX_VERSION};
+use core::fmt::Write;
+
+/// Color theme of the generated article.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Theme {
+    /// Dark text on a light background.
+    #[default]
+    Light,
+    /// Light text on a dark background.
+    Dark,
+}
+
+/// A block in the document.
+#[derive(Clone, Debug)]
+enum Block {
+    /// Raw HTML.
+    Html(String),
+    /// Plain text paragraph, escaped on output.
+    Paragraph(String),
+    /// Display equation.
+    Equation { input: String, numbered: bool },
+}
+
+/// Builder of a full HTML article.
+///
+/// All equations share the same [options](`Opts`), so that custom macros are
+/// defined once for the whole document. Numbered equations are tagged with
+/// consecutive numbers in the order they are added.
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    title: Option<String>,
+    opts: Opts,
+    theme: Theme,
+    stylesheet: Option<String>,
+    blocks: Vec<Block>,
+}
+
+impl Builder {
+    /// Create an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the title of the document.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the [options](`Opts`) used for all equations.
+    ///
+    /// The display mode is always enabled for equation blocks.
+    pub fn opts(mut self, opts: Opts) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    /// Set the color [theme](`Theme`).
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Set the URL of the KaTeX stylesheet.
+    ///
+    /// Default to the stylesheet matching [`KATEX_VERSION`] on jsDelivr.
+    pub fn stylesheet(mut self, url: impl Into<String>) -> Self {
+        self.stylesheet = Some(url.into());
+        self
+    }
+
+    /// Append a block of raw HTML.
+    pub fn html(mut self, html: impl Into<String>) -> Self {
+        self.blocks.push(Block::Html(html.into()));
+        self
+    }
+
+    /// Append a paragraph of plain text.
+    pub fn paragraph(mut self, text: impl Into<String>) -> Self {
+        self.blocks.push(Block::Paragraph(text.into()));
+        self
+    }
+
+    /// Append an unnumbered display equation.
+    pub fn equation(mut self, input: impl Into<String>) -> Self {
+        self.blocks.push(Block::Equation {
+            input: input.into(),
+            numbered: false,
+        });
+        self
+    }
+
+    /// Append a numbered display equation.
+    pub fn numbered_equation(mut self, input: impl Into<String>) -> Self {
+        self.blocks.push(Block::Equation {
+            input: input.into(),
+            numbered: true,
+        });
+        self
+    }
+
+    /// Render all equations and return the HTML article.
+    pub fn build(&self) -> Result<String> {
+        let mut opts = self.opts.clone();
+        opts.set_display_mode(true);
+
+        let mut body = String::new();
+        let mut number = 0;
+        for block in &self.blocks {
+            match block {
+                Block::Html(content) => body.push_str(content),
+                Block::Paragraph(text) => {
+                    let _ = write!(body, "<p>{}</p>", html::escape(text));
+                }
+                Block::Equation { input, numbered } => {
+                    let rendered = if *numbered {
+                        number += 1;
+                        crate::render_with_opts(&format!("{input}\\tag{{{number}}}"), &opts)?
+                    } else {
+                        crate::render_with_opts(input, &opts)?
+                    };
+                    let _ = write!(body, r#"<div class="equation">{rendered}</div>"#);
+                }
+            }
+            body.push('\n');
+        }
+
+        let stylesheet = self.stylesheet.clone().unwrap_or_else(|| {
+            format!("https://cdn.jsdelivr.net/npm/katex@{KATEX_VERSION}/dist/katex.min.css")
+        });
+        let mut output =
+            String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        if let Some(title) = &self.title {
+            let _ = writeln!(output, "<title>{}</title>", html::escape(title));
+        }
+        let _ = writeln!(
+            output,
+            r#"<link rel="stylesheet" href="{}">"#,
+            html::escape(&stylesheet)
+        );
+        let _ = writeln!(output, "<style>{}</style>", self.css(number > 0));
+        output.push_str("</head>\n<body>\n<article>\n");
+        if let Some(title) = &self.title {
+            let _ = writeln!(output, "<h1>{}</h1>", html::escape(title));
+        }
+        output.push_str(&body);
+        output.push_str("</article>\n</body>\n</html>\n");
+        Ok(output)
+    }
+
+    /// Return the document CSS, pruned to the rules used by the document.
+    fn css(&self, has_numbered_equations: bool) -> String {
+        let (foreground, background) = match self.theme {
+            Theme::Light => ("#222", "#fff"),
+            Theme::Dark => ("#ddd", "#1e1e1e"),
+        };
+        let mut css = format!(
+            "body{{color:{foreground};background:{background};}}\
+             article{{max-width:48em;margin:0 auto;}}"
+        );
+        if self
+            .blocks
+            .iter()
+            .any(|b| matches!(b, Block::Equation { .. }))
+        {
+            css.push_str(".equation{overflow-x:auto;overflow-y:hidden;}");
+        }
+        if has_numbered_equations {
+            css.push_str(".equation .tag{padding-left:1em;}");
+        }
+        css
+    }
+}
@@ -0,0 +1,173 @@
+//! Render several equations in one aligned environment, or a numbered
+//! equation.
+//!
+//! The helpers build the LaTeX source, so the rows do not need to spell out
+//! the row separators and the environment syntax, and equation numbers need
+//! no escaping.
+
+use crate::{
+    error::{Error, Result},
+    latex::escape_text,
+    opts::Opts,
+    preprocess::matching_brace,
+};
+
+/// Render lines as an `aligned` environment, in display mode.
+///
+/// Each line is a row, whose `&` mark the alignment points.
+///
+/// # Examples
+///
+/// ```
+/// let html = katex::render_align(&["a &= b + c", "&= d"]).unwrap();
+/// assert!(html.contains("katex-display"));
+/// ```
+#[inline]
+pub fn render_align<S: AsRef<str>>(lines: &[S]) -> Result<String> {
+    render_align_with_opts(lines, Opts::default())
+}
+
+/// Render lines as an `aligned` environment with additional [options](`Opts`).
+///
+/// The display mode is enabled unless the options set it.
+pub fn render_align_with_opts<S: AsRef<str>>(
+    lines: &[S],
+    opts: impl AsRef<Opts>,
+) -> Result<String> {
+    let rows = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| row(i, line.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    render_environment("aligned", &rows, opts.as_ref())
+}
+
+/// Render `(value, condition)` pairs as a `cases` environment, in display
+/// mode.
+///
+/// The condition is typeset in math mode, so text needs `\text`.
+///
+/// # Examples
+///
+/// ```
+/// let html = katex::render_cases(&[("x", r"x \ge 0"), ("-x", r"\text{otherwise}")]).unwrap();
+/// assert!(html.contains("otherwise"));
+/// ```
+#[inline]
+pub fn render_cases<S: AsRef<str>, T: AsRef<str>>(cases: &[(S, T)]) -> Result<String> {
+    render_cases_with_opts(cases, Opts::default())
+}
+
+/// Render `(value, condition)` pairs as a `cases` environment with additional
+/// [options](`Opts`).
+///
+/// The display mode is enabled unless the options set it.
+pub fn render_cases_with_opts<S: AsRef<str>, T: AsRef<str>>(
+    cases: &[(S, T)],
+    opts: impl AsRef<Opts>,
+) -> Result<String> {
+    let rows = cases
+        .iter()
+        .enumerate()
+        .map(|(i, (value, condition))| {
+            let value = row(i, value.as_ref())?;
+            let condition = row(i, condition.as_ref())?;
+            if has_alignment(&value) || has_alignment(&condition) {
+                return Err(invalid(i, "unexpected `&`, the columns are added"));
+            }
+            Ok(format!("{value} & {condition}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    render_environment("cases", &rows, opts.as_ref())
+}
+
+/// Render an equation numbered with `tag`, in display mode.
+///
+/// The tag is typeset as text, e.g. `1.2` is shown as `(1.2)`, so it may
+/// contain any character, including `{`, `}` and `\`.
+///
+/// # Examples
+///
+/// ```
+/// let html = katex::render_numbered("E = mc^2", "1.2").unwrap();
+/// assert!(html.contains("(1.2)"));
+/// ```
+#[inline]
+pub fn render_numbered(input: &str, tag: &str) -> Result<String> {
+    render_numbered_with_opts(input, tag, Opts::default())
+}
+
+/// Render an equation numbered with `tag` with additional [options](`Opts`),
+/// e.g. [`leqno`](crate::OptsBuilder::leqno) to show the number on the left.
+///
+/// The display mode is enabled unless the options set it. KaTeX rejects tags
+/// in inline mode.
+pub fn render_numbered_with_opts(input: &str, tag: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    check_braces(input).map_err(|reason| Error::InvalidInput(format!("equation: {reason}")))?;
+    let input = format!("{input}\n\\tag{{{}}}", escape_text(tag));
+    render_display(&input, opts.as_ref())
+}
+
+fn render_environment(name: &str, rows: &[String], opts: &Opts) -> Result<String> {
+    let input = format!(
+        "\\begin{{{name}}}\n{}\n\\end{{{name}}}",
+        rows.join(" \\\\\n")
+    );
+    render_display(&input, opts)
+}
+
+fn render_display(input: &str, opts: &Opts) -> Result<String> {
+    let mut opts = opts.clone();
+    if opts.display_mode.is_none() {
+        opts.set_display_mode(true);
+    }
+    crate::render_with_opts(input, opts)
+}
+
+/// Check a row, and remove a trailing row separator.
+///
+/// A row with unbalanced braces would swallow the end of the environment, so
+/// it is rejected with the index of the row.
+fn row(index: usize, line: &str) -> Result<String> {
+    let line = line.trim();
+    let line = line.strip_suffix(r"\\").unwrap_or(line).trim_end();
+    check_braces(line).map_err(|reason| invalid(index, reason))?;
+    Ok(line.to_owned())
+}
+
+pub(crate) fn check_braces(input: &str) -> core::result::Result<(), &'static str> {
+    let bytes = input.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => match matching_brace(bytes, i) {
+                Some(close) => i = close,
+                None => return Err("unbalanced `{`"),
+            },
+            b'}' => return Err("unbalanced `}`"),
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Whether the row has an alignment point, i.e. an unescaped `&`.
+fn has_alignment(row: &str) -> bool {
+    let bytes = row.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'&' => return true,
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+fn invalid(index: usize, reason: &str) -> Error {
+    Error::InvalidInput(format!("row {}: {reason}", index + 1))
+}
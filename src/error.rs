@@ -13,6 +13,190 @@ pub enum Error {
     /// Error on JS value conversion.
     #[error("failed to convert js value (detail: {0})")]
     JsValueError(String),
+    /// Error on a string returned by the JS engine which is not valid
+    /// Unicode, e.g. with a lone UTF-16 surrogate, instead of a lossy copy.
+    #[error("js engine returned invalid unicode (detail: {0})")]
+    InvalidUtf8(String),
+    /// Error on JS execution which left the per thread JS engine broken.
+    ///
+    /// The engine is re-created by the next render on the thread, which
+    /// then succeeds or fails on its own.
+    #[error("js engine restarted after an error (detail: {0})")]
+    EngineRestarted(String),
+    /// Error on the JS engine running out of a resource, e.g. on input too
+    /// large for the [memory limit](crate::EngineBuilder::memory_limit) or
+    /// too deeply nested for the stack.
+    ///
+    /// Unlike other JS errors, it is caused by the input, so rendering it
+    /// again fails the same way.
+    #[error("js engine ran out of {kind} (detail: {detail})")]
+    ResourceExhausted {
+        /// The exhausted resource.
+        kind: ResourceKind,
+        /// The error reported by the JS engine.
+        detail: String,
+    },
+    /// Error on invalid LaTeX, reported by [`check`](crate::check).
+    #[error("failed to parse (detail: {0})")]
+    ParseError(ParseError),
+    /// Error on input rejected before being passed to KaTeX.
+    #[error("invalid input (detail: {0})")]
+    InvalidInput(String),
+    /// Error on an invalid [option](crate::Opts).
+    #[error("invalid option {field} (detail: {reason})")]
+    InvalidOption {
+        /// Name of the option, as in [`OptsBuilder`](crate::OptsBuilder).
+        field: &'static str,
+        /// Why the value is rejected.
+        reason: String,
+    },
+    /// Error on rendered HTML larger than the
+    /// [limit](crate::OptsBuilder::max_output_bytes).
+    #[error("output of {size} bytes exceeds the limit of {limit} bytes")]
+    OutputTooLarge {
+        /// Size of the output in bytes, before post-processing if it
+        /// exceeds the limit already.
+        size: usize,
+        /// The limit in bytes.
+        limit: usize,
+    },
+    /// Error on macro definitions expanding to themselves, used by the input.
+    ///
+    /// The cycle is listed in expansion order, starting and ending with the same macro.
+    #[error("recursive macro definition ({})", .0.join(" -> "))]
+    MacroRecursion(Vec<String>),
+    /// Error on reading or writing a [result store](crate::batch::ResultStore).
+    #[error("failed to access result store (detail: {0})")]
+    StoreError(String),
+    /// Error on writing the output to a [writer](crate::render_to_writer).
+    #[error("failed to write output (detail: {0})")]
+    WriteError(String),
+    /// Error on rasterization.
+    #[cfg(feature = "raster")]
+    #[error("failed to rasterize (detail: {0})")]
+    RasterError(String),
+}
+
+impl Error {
+    /// Return a stable code identifying the kind of error, e.g. `KTX2003`
+    /// for [`Error::ParseError`].
+    ///
+    /// Codes never change across versions, unlike messages, so logs and
+    /// client APIs can rely on them. The first digit groups related errors:
+    ///
+    /// * `KTX1xxx`: the JS engine, e.g. [`Error::JsExecError`].
+    /// * `KTX2xxx`: the input or the options, e.g. [`Error::ParseError`].
+    /// * `KTX4xxx`: the storage or conversion of the output, e.g.
+    ///   [`Error::StoreError`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let err = katex::check(r"\frac{1}").unwrap_err();
+    /// assert_eq!(err.code(), "KTX2003");
+    /// ```
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::JsInitError(_) => "KTX1001",
+            Error::JsExecError(_) => "KTX1002",
+            Error::JsValueError(_) => "KTX1003",
+            Error::EngineRestarted(_) => "KTX1004",
+            Error::ResourceExhausted { .. } => "KTX1005",
+            Error::InvalidUtf8(_) => "KTX1006",
+            Error::InvalidInput(_) => "KTX2001",
+            Error::InvalidOption { .. } => "KTX2002",
+            Error::ParseError(_) => "KTX2003",
+            Error::MacroRecursion(_) => "KTX2004",
+            Error::OutputTooLarge { .. } => "KTX2005",
+            Error::StoreError(_) => "KTX4001",
+            Error::WriteError(_) => "KTX4003",
+            #[cfg(feature = "raster")]
+            Error::RasterError(_) => "KTX4002",
+        }
+    }
+
+    /// Create an [`Error::JsExecError`], or an [`Error::ResourceExhausted`]
+    /// if the JS engine reports one.
+    pub(crate) fn js_exec(detail: String) -> Self {
+        match ResourceKind::from_message(&detail) {
+            Some(kind) => Error::ResourceExhausted { kind, detail },
+            None => Error::JsExecError(detail),
+        }
+    }
+}
+
+/// A resource of the JS engine, reported by [`Error::ResourceExhausted`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    /// The heap.
+    Memory,
+    /// The call stack, or the recursion limit of the JS engine.
+    Stack,
+    /// The execution was interrupted, e.g. by a timeout.
+    Interrupt,
+}
+
+impl ResourceKind {
+    /// Recognize the resource from an error message, as worded by the JS
+    /// backends and the major browsers.
+    fn from_message(message: &str) -> Option<Self> {
+        const PATTERNS: &[(&str, ResourceKind)] = &[
+            ("out of memory", ResourceKind::Memory),
+            ("alloc failed", ResourceKind::Memory),
+            ("allocation failed", ResourceKind::Memory),
+            ("stack overflow", ResourceKind::Stack),
+            ("maximum call stack size exceeded", ResourceKind::Stack),
+            ("too much recursion", ResourceKind::Stack),
+            ("callstack limit", ResourceKind::Stack),
+            ("c stack depth limit", ResourceKind::Stack),
+            ("interrupted", ResourceKind::Interrupt),
+        ];
+        // KaTeX parse errors quote the input, which may contain any text.
+        if message.contains("KaTeX parse error") {
+            return None;
+        }
+        let message = message.to_ascii_lowercase();
+        PATTERNS
+            .iter()
+            .find(|(pattern, _)| message.contains(pattern))
+            .map(|&(_, kind)| kind)
+    }
+}
+
+impl core::fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ResourceKind::Memory => "memory",
+            ResourceKind::Stack => "stack",
+            ResourceKind::Interrupt => "time",
+        })
+    }
+}
+
+/// A KaTeX parse error.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The message, without KaTeX's prefix and position.
+    pub message: String,
+    /// Byte range of the offending input, if known.
+    ///
+    /// It is relative to the input as given, even if a crate-side
+    /// [option](crate::Opts) rewrote it before passing it to KaTeX.
+    pub span: Option<core::ops::Range<usize>>,
+    /// The same range as [`span`](Self::span), in characters, e.g. for
+    /// editors counting columns in characters.
+    pub char_span: Option<core::ops::Range<usize>>,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{} at bytes {}..{}", self.message, span.start, span.end),
+            None => f.write_str(&self.message),
+        }
+    }
 }
 
 /// Alias to `core::result::Result<T, katex::Error>`
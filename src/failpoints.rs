@@ -0,0 +1,95 @@
+//! Failure injection to test error handling of downstream crates.
+//!
+//! With the `failpoints` feature, rendering an input which starts with the
+//! [trigger](`Failpoint::input`) of a failpoint fails deterministically. The
+//! errors go through the JS engine where possible, so they are formatted
+//! like real ones. This feature is meant for tests only.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::failpoints::Failpoint;
+//!
+//! let err = katex::render(Failpoint::Exec.input()).unwrap_err();
+//! assert!(matches!(err, katex::Error::JsExecError(_)));
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    js_engine::{JsEngine, JsValue},
+};
+
+/// A failure which can be injected.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Failpoint {
+    /// Fail to initialize the JS engine, with [`Error::JsInitError`].
+    Init,
+    /// Throw a JS exception, with [`Error::JsExecError`].
+    Exec,
+    /// Fail to convert the result, with [`Error::JsValueError`].
+    Value,
+    /// Interrupt a long running execution, with [`Error::ResourceExhausted`].
+    Timeout,
+    /// Run out of memory, with [`Error::ResourceExhausted`].
+    OutOfMemory,
+    /// Leave the JS engine broken, with [`Error::EngineRestarted`] from the
+    /// per thread engine and [`Error::JsExecError`] from a
+    /// [`Renderer`](crate::Renderer).
+    Corrupt,
+}
+
+impl Failpoint {
+    /// All failpoints.
+    pub const ALL: &'static [Failpoint] = &[
+        Failpoint::Init,
+        Failpoint::Exec,
+        Failpoint::Value,
+        Failpoint::Timeout,
+        Failpoint::OutOfMemory,
+        Failpoint::Corrupt,
+    ];
+
+    /// Input triggering the failpoint.
+    ///
+    /// Any input starting with it also triggers the failpoint.
+    pub fn input(self) -> &'static str {
+        match self {
+            Failpoint::Init => r"\katexrsFailpoint{init}",
+            Failpoint::Exec => r"\katexrsFailpoint{exec}",
+            Failpoint::Value => r"\katexrsFailpoint{value}",
+            Failpoint::Timeout => r"\katexrsFailpoint{timeout}",
+            Failpoint::OutOfMemory => r"\katexrsFailpoint{oom}",
+            Failpoint::Corrupt => r"\katexrsFailpoint{corrupt}",
+        }
+    }
+}
+
+/// Fail if the input triggers a failpoint.
+pub(crate) fn trigger<E>(engine: &E, input: &str) -> Result<()>
+where
+    E: JsEngine,
+{
+    let Some(failpoint) = Failpoint::ALL.iter().find(|f| input.starts_with(f.input())) else {
+        return Ok(());
+    };
+    match failpoint {
+        Failpoint::Init => Err(Error::JsInitError(
+            "failpoint: failed to create js runtime".to_owned(),
+        )),
+        Failpoint::Exec => engine
+            .eval("throw new Error('failpoint: js exception')")
+            .map(drop),
+        // A number cannot be converted to a string.
+        Failpoint::Value => engine.create_int_value(0)?.into_string().map(drop),
+        Failpoint::Timeout => engine
+            .eval("throw new Error('failpoint: interrupted')")
+            .map(drop),
+        Failpoint::OutOfMemory => engine
+            .eval("throw new RangeError('failpoint: out of memory')")
+            .map(drop),
+        Failpoint::Corrupt => engine
+            .eval("katex = undefined; throw new Error('failpoint: corrupted engine')")
+            .map(drop),
+    }
+}
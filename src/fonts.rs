@@ -0,0 +1,113 @@
+//! Check that the KaTeX fonts cover the characters of an equation.
+//!
+//! Characters missing from the KaTeX fonts are displayed with system fonts,
+//! which breaks the visual consistency of the equation and may not be
+//! available at all when printing. The check uses the font metrics bundled
+//! with KaTeX, so it does not need the font files.
+
+use crate::{
+    error::{Error, Result},
+    js_engine::{JsEngine, JsValue},
+    opts::Opts,
+    preprocess, with_engine,
+};
+use core::iter;
+
+/// A character without a glyph in the KaTeX fonts.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MissingGlyph {
+    /// The character.
+    pub character: char,
+    /// How KaTeX renders the character.
+    pub fallback: Fallback,
+}
+
+/// How KaTeX renders a character missing from its fonts.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Fallback {
+    /// The character is unknown to KaTeX and laid out as an empty box, e.g. a
+    /// symbol in math mode.
+    Unsupported {
+        /// The KaTeX font looked up, e.g. `Main-Regular`.
+        font: String,
+    },
+    /// The character belongs to a script supported in text mode, e.g. `cjk`,
+    /// and is laid out with approximated metrics.
+    Script {
+        /// The name of the script, as in KaTeX's `*_fallback` CSS classes.
+        script: String,
+    },
+}
+
+/// List the characters of a LaTeX equation missing from the KaTeX fonts, in
+/// order of first use and without duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use katex::fonts::{check_coverage, Fallback};
+///
+/// assert!(check_coverage(r"\int_0^1 x^2 \, dx").unwrap().is_empty());
+///
+/// let missing = check_coverage(r"\text{数}").unwrap();
+/// assert_eq!(missing[0].character, '数');
+/// assert_eq!(missing[0].fallback, Fallback::Script { script: "cjk".to_owned() });
+/// ```
+#[inline]
+pub fn check_coverage(input: &str) -> Result<Vec<MissingGlyph>> {
+    check_coverage_with_opts(input, Opts::default())
+}
+
+/// List the characters of a LaTeX equation missing from the KaTeX fonts with
+/// additional [options](`Opts`).
+pub fn check_coverage_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<Vec<MissingGlyph>> {
+    with_engine(|engine| check_coverage_inner(engine, input, opts.as_ref()))
+}
+
+fn check_coverage_inner<E>(engine: &E, input: &str, opts: &Opts) -> Result<Vec<MissingGlyph>>
+where
+    E: JsEngine,
+{
+    let input = preprocess::preprocess(input, opts)?;
+    let js_input = engine.create_string_value(input)?;
+    let js_opts = opts.to_js_value(engine)?;
+    let args = iter::once(js_input).chain(iter::once(js_opts));
+    let result = engine
+        .call_function("__katex_rs.missingGlyphs", args)?
+        .into_string()?;
+    let mut missing: Vec<MissingGlyph> = Vec::new();
+    for line in result.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (text, fallback) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(text), Some("font"), Some(font)) => (
+                text,
+                Fallback::Unsupported {
+                    font: font.to_owned(),
+                },
+            ),
+            (Some(text), Some("script"), Some(script)) => (
+                text,
+                Fallback::Script {
+                    script: script.to_owned(),
+                },
+            ),
+            _ => {
+                return Err(Error::JsValueError(format!(
+                    "unexpected missing glyph {line:?}"
+                )))
+            }
+        };
+        for character in text.chars() {
+            let glyph = MissingGlyph {
+                character,
+                fallback: fallback.clone(),
+            };
+            if !missing.contains(&glyph) {
+                missing.push(glyph);
+            }
+        }
+    }
+    Ok(missing)
+}
@@ -0,0 +1,36 @@
+//! Components rendering equations in [Leptos](https://leptos.dev) and
+//! [Yew](https://yew.rs) apps.
+//!
+//! `MathView` renders its LaTeX with [`render_with_opts`](crate::render_with_opts)
+//! and sets the HTML as the inner HTML of the component. The options default
+//! to [`Opts::hardened`], so untrusted commands are disabled and invalid
+//! input is shown as its escaped source in a `katex-error` span, whose title
+//! is the error, instead of breaking the page.
+//!
+//! In the browser, the crate needs the `wasm-js` backend, e.g.
+//! `default-features = false, features = ["wasm-js", "mhchem", "leptos"]`.
+//! Rendering on the server works with any backend.
+
+use crate::{html::escape, opts::Opts};
+
+#[cfg(feature = "leptos")]
+pub mod leptos;
+#[cfg(feature = "yew")]
+pub mod yew;
+
+/// Render the LaTeX for a component, falling back to its escaped source with
+/// the error as its title.
+pub(crate) fn render_or_fallback(latex: &str, display_mode: bool, opts: Option<&Opts>) -> String {
+    let mut opts = opts.cloned().unwrap_or_else(Opts::hardened);
+    if display_mode {
+        opts.set_display_mode(true);
+    }
+    match crate::render_with_opts(latex, &opts) {
+        Ok(html) => html,
+        Err(e) => format!(
+            r#"<span class="katex-error" title="{}">{}</span>"#,
+            escape(&e.to_string()),
+            escape(latex)
+        ),
+    }
+}
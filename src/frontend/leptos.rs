@@ -0,0 +1,35 @@
+//! Leptos component.
+//!
+//! ```no_run
+//! use katex::frontend::leptos::MathView;
+//! use leptos::prelude::*;
+//!
+//! #[component]
+//! fn Formula() -> impl IntoView {
+//!     view! {
+//!         <p>"Einstein: " <MathView latex="E = mc^2" /></p>
+//!         <MathView latex=r"\sum_{i=1}^n i" display_mode=true />
+//!     }
+//! }
+//! ```
+
+use super::render_or_fallback;
+use crate::opts::Opts;
+use leptos::prelude::*;
+
+/// Render an equation, updating it when the LaTeX changes.
+#[component]
+pub fn MathView(
+    /// LaTeX source of the equation.
+    #[prop(into)]
+    latex: Signal<String>,
+    /// Whether to render the equation in the display mode.
+    #[prop(optional)]
+    display_mode: bool,
+    /// Options, [`Opts::hardened`] by default.
+    #[prop(optional)]
+    opts: Option<Opts>,
+) -> impl IntoView {
+    let html = move || latex.with(|latex| render_or_fallback(latex, display_mode, opts.as_ref()));
+    view! { <span class="katex-math-view" inner_html=html></span> }
+}
@@ -0,0 +1,60 @@
+//! Yew component.
+//!
+//! ```no_run
+//! use katex::frontend::yew::MathView;
+//! use yew::prelude::*;
+//!
+//! #[function_component]
+//! fn Formula() -> Html {
+//!     html! {
+//!         <>
+//!             <p>{"Einstein: "}<MathView latex="E = mc^2" /></p>
+//!             <MathView latex={r"\sum_{i=1}^n i"} display_mode=true />
+//!         </>
+//!     }
+//! }
+//! ```
+
+use super::render_or_fallback;
+use crate::opts::Opts;
+use std::rc::Rc;
+use yew::{function_component, html, use_memo, AttrValue, Html, Properties};
+
+/// Properties of [`MathView`].
+#[derive(Properties)]
+pub struct MathViewProps {
+    /// LaTeX source of the equation.
+    pub latex: AttrValue,
+    /// Whether to render the equation in the display mode.
+    #[prop_or_default]
+    pub display_mode: bool,
+    /// Options, [`Opts::hardened`] by default.
+    #[prop_or_default]
+    pub opts: Option<Rc<Opts>>,
+}
+
+impl PartialEq for MathViewProps {
+    fn eq(&self, other: &Self) -> bool {
+        self.latex == other.latex
+            && self.display_mode == other.display_mode
+            && self.opts.as_ref().map(|opts| opts.fingerprint())
+                == other.opts.as_ref().map(|opts| opts.fingerprint())
+    }
+}
+
+/// Render an equation, rendering it again only when the properties change.
+#[function_component]
+pub fn MathView(props: &MathViewProps) -> Html {
+    let opts = props.opts.clone();
+    let html = use_memo(
+        (
+            props.latex.clone(),
+            props.display_mode,
+            props.opts.as_ref().map(|opts| opts.fingerprint()),
+        ),
+        move |(latex, display_mode, _)| render_or_fallback(latex, *display_mode, opts.as_deref()),
+    );
+    html! {
+        <span class="katex-math-view">{ Html::from_html_unchecked(AttrValue::from((*html).clone())) }</span>
+    }
+}
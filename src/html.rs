@@ -0,0 +1,435 @@
+//! HTML helpers.
+
+/// Escape special HTML characters in text or attribute values.
+pub(crate) fn escape(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#x27;"),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Rewrite the values of the attribute `name` in all tags.
+///
+/// `f` receives the raw (still escaped) attribute value and returns the
+/// replacement, or `None` to keep it unchanged.
+pub(crate) fn map_attribute(
+    html: &str,
+    name: &str,
+    mut f: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut last = 0;
+    for (start, end) in attribute_values(html, name) {
+        if let Some(value) = f(&html[start..end]) {
+            output.push_str(&html[last..start]);
+            output.push_str(&value);
+            last = end;
+        }
+    }
+    output.push_str(&html[last..]);
+    output
+}
+
+/// Find byte ranges of the quoted values of the attribute `name` in all tags.
+pub(crate) fn attribute_values(html: &str, name: &str) -> Vec<(usize, usize)> {
+    let bytes = html.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'<' {
+            i += 1;
+            continue;
+        }
+        // Skip the tag name.
+        i += 1;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+            i += 1;
+        }
+        // Scan the attributes until the end of the tag.
+        while i < bytes.len() && bytes[i] != b'>' {
+            if bytes[i].is_ascii_whitespace() || bytes[i] == b'/' {
+                i += 1;
+                continue;
+            }
+            let attr_start = i;
+            while i < bytes.len() && !matches!(bytes[i], b'=' | b'>' | b' ' | b'/') {
+                i += 1;
+            }
+            let attr_name = &html[attr_start..i];
+            if i < bytes.len() && bytes[i] == b'=' {
+                i += 1;
+                let quote = bytes.get(i).copied();
+                if let Some(quote @ (b'"' | b'\'')) = quote {
+                    let value_start = i + 1;
+                    let value_end = html[value_start..]
+                        .find(quote as char)
+                        .map_or(html.len(), |p| value_start + p);
+                    if attr_name.eq_ignore_ascii_case(name) {
+                        ranges.push((value_start, value_end));
+                    }
+                    i = value_end + 1;
+                } else {
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+    ranges
+}
+
+/// A node of a parsed HTML/XML fragment.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Node {
+    /// Element with its name, attributes and children.
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+    /// Unescaped text.
+    Text(String),
+}
+
+impl Node {
+    /// Return the value of the attribute `name` of an element.
+    pub(crate) fn attr(&self, name: &str) -> Option<&str> {
+        match self {
+            Node::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+
+    /// Return the children of an element.
+    pub(crate) fn children(&self) -> &[Node] {
+        match self {
+            Node::Element { children, .. } => children,
+            Node::Text(_) => &[],
+        }
+    }
+
+    /// Return the concatenated text of the node and its descendants.
+    pub(crate) fn text(&self) -> String {
+        match self {
+            Node::Element { children, .. } => children.iter().map(Node::text).collect(),
+            Node::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// Elements without content in HTML.
+const VOID_ELEMENTS: &[&str] = &["br", "hr", "img", "input", "link", "meta", "col", "wbr"];
+
+/// Parse a well-formed HTML/XML fragment, such as the output of KaTeX.
+pub(crate) fn parse(input: &str) -> Vec<Node> {
+    // Stack of open elements: (name, attrs, children).
+    let mut stack: Vec<OpenElement> = vec![(String::new(), Vec::new(), Vec::new())];
+    let mut rest = input;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_text(&mut stack, rest);
+            break;
+        };
+        push_text(&mut stack, &rest[..lt]);
+        rest = &rest[lt..];
+        let Some(gt) = tag_end(rest) else {
+            push_text(&mut stack, rest);
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            if let Some(pos) = stack.iter().rposition(|(n, _, _)| n == name) {
+                if pos > 0 {
+                    while stack.len() > pos {
+                        let (name, attrs, children) = stack.pop().unwrap();
+                        let node = Node::Element {
+                            name,
+                            attrs,
+                            children,
+                        };
+                        stack.last_mut().unwrap().2.push(node);
+                    }
+                }
+            }
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_owned();
+        let attrs = parse_attrs(&tag[name_end..]);
+        if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+            let node = Node::Element {
+                name,
+                attrs,
+                children: Vec::new(),
+            };
+            stack.last_mut().unwrap().2.push(node);
+        } else {
+            stack.push((name, attrs, Vec::new()));
+        }
+    }
+    while stack.len() > 1 {
+        let (name, attrs, children) = stack.pop().unwrap();
+        let node = Node::Element {
+            name,
+            attrs,
+            children,
+        };
+        stack.last_mut().unwrap().2.push(node);
+    }
+    stack.pop().unwrap().2
+}
+
+/// An element being parsed: name, attributes and children.
+type OpenElement = (String, Vec<(String, String)>, Vec<Node>);
+
+fn push_text(stack: &mut [OpenElement], text: &str) {
+    if !text.is_empty() {
+        stack.last_mut().unwrap().2.push(Node::Text(unescape(text)));
+    }
+}
+
+/// Find the position of the `>` closing the tag at the start of `input`,
+/// skipping quoted attribute values.
+///
+/// Like browsers, quotes only start a value after `=`, which matters for the
+/// stray quote in KaTeX's `<img>` markup.
+fn tag_end(input: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut after_equals = false;
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if after_equals => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+        if !c.is_ascii_whitespace() {
+            after_equals = quote.is_none() && c == '=';
+        }
+    }
+    None
+}
+
+/// Sort the attributes of all tags by name, keeping their raw values.
+///
+/// The order of attributes does not matter in HTML, but it does when comparing
+/// outputs as text.
+pub(crate) fn sort_attributes(html: &str) -> String {
+    rewrite_attributes(html, |_| {})
+}
+
+/// Rewrite the attributes of all tags with `f`, which receives their names and
+/// raw `name="value"` text, then sort them by name.
+pub(crate) fn rewrite_attributes(html: &str, mut f: impl FnMut(&mut Vec<(&str, &str)>)) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        output.push_str(&rest[..lt]);
+        rest = &rest[lt..];
+        let Some(gt) = tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+        if tag.starts_with(['/', '!', '?']) {
+            output.push('<');
+            output.push_str(tag);
+            output.push('>');
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(tag.len());
+        // Stray quotes are not attributes, and stay at the end.
+        let (mut attrs, stray): (Vec<_>, Vec<_>) = raw_attrs(&tag[name_end..])
+            .into_iter()
+            .partition(|(name, _)| !name.starts_with(['"', '\'']));
+        f(&mut attrs);
+        attrs.sort_by_key(|(name, _)| *name);
+        output.push('<');
+        output.push_str(&tag[..name_end]);
+        for (_, raw) in attrs {
+            output.push(' ');
+            output.push_str(raw);
+        }
+        for (_, raw) in stray {
+            output.push_str(raw);
+        }
+        if self_closing {
+            output.push('/');
+        }
+        output.push('>');
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Split attributes into their names and raw `name="value"` text.
+fn raw_attrs(input: &str) -> Vec<(&str, &str)> {
+    let mut attrs = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        let after_name = rest[name_end..].trim_start();
+        let end = if let Some(r) = after_name.strip_prefix('=') {
+            let r = r.trim_start();
+            let value_end = match r.chars().next() {
+                Some(q @ ('"' | '\'')) => r[1..].find(q).map_or(r.len(), |p| p + 2),
+                _ => r.find(|c: char| c.is_ascii_whitespace()).unwrap_or(r.len()),
+            };
+            rest.len() - r.len() + value_end
+        } else {
+            name_end
+        };
+        attrs.push((name, &rest[..end]));
+        rest = rest[end..].trim_start();
+    }
+    attrs
+}
+
+fn parse_attrs(input: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_owned();
+        rest = rest[name_end..].trim_start();
+        let value = if let Some(r) = rest.strip_prefix('=') {
+            let r = r.trim_start();
+            match r.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = r[1..].find(q).map_or(r.len(), |p| p + 1);
+                    rest = r.get(end + 1..).unwrap_or("");
+                    unescape(&r[1..end])
+                }
+                _ => {
+                    let end = r.find(|c: char| c.is_ascii_whitespace()).unwrap_or(r.len());
+                    rest = &r[end..];
+                    unescape(&r[..end])
+                }
+            }
+        } else {
+            String::new()
+        };
+        attrs.push((name, value));
+        rest = rest.trim_start();
+    }
+    attrs
+}
+
+/// Unescape HTML character references.
+pub(crate) fn unescape(input: &str) -> String {
+    if !input.contains('&') {
+        return input.to_owned();
+    }
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(amp) = rest.find('&') {
+        output.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(|n| n.ok())
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                output.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Rewrite the text content between tags, except inside the elements named in
+/// `skip`.
+///
+/// `f` receives the raw (still escaped) text and returns the replacement, or
+/// `None` to keep it unchanged.
+pub(crate) fn map_text(
+    html: &str,
+    skip: &[&str],
+    mut f: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut skipping: Option<&str> = None;
+    while !rest.is_empty() {
+        let lt = rest.find('<').unwrap_or(rest.len());
+        let text = &rest[..lt];
+        match f(text) {
+            Some(replacement) if skipping.is_none() && !text.is_empty() => {
+                output.push_str(&replacement)
+            }
+            _ => output.push_str(text),
+        }
+        rest = &rest[lt..];
+        if rest.is_empty() {
+            break;
+        }
+        let gt = tag_end(rest).map_or(rest.len(), |p| p + 1);
+        let tag = &rest[..gt];
+        let name = tag[1..]
+            .split(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+            .find(|s| !s.is_empty())
+            .unwrap_or("");
+        match skipping {
+            Some(skipped) if tag.starts_with("</") && name == skipped => skipping = None,
+            None if !tag.starts_with("</") && !tag.ends_with("/>") => {
+                skipping = skip.iter().find(|s| **s == name).copied();
+            }
+            _ => {}
+        }
+        output.push_str(tag);
+        rest = &rest[gt..];
+    }
+    output
+}
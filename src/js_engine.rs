@@ -2,6 +2,73 @@
 
 use crate::error::Result;
 use cfg_if::cfg_if;
+use core::fmt;
+use std::borrow::Cow;
+
+/// JS backend selected by the crate features.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    /// [QuickJS](https://crates.io/crates/quick-js), the `quick-js` feature.
+    QuickJs,
+    /// [Duktape](https://crates.io/crates/ducc), the `duktape` feature.
+    Duktape,
+    /// The JS engine of the host of a WebAssembly module, the `wasm-js`
+    /// feature.
+    WasmJs,
+    /// [rquickjs](https://crates.io/crates/rquickjs), the `rquickjs` feature.
+    Rquickjs,
+    /// The system JavaScriptCore framework on Apple platforms, the `jsc`
+    /// feature.
+    Jsc,
+}
+
+impl BackendKind {
+    /// Name of the feature selecting the backend.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BackendKind::QuickJs => "quick-js",
+            BackendKind::Duktape => "duktape",
+            BackendKind::WasmJs => "wasm-js",
+            BackendKind::Rquickjs => "rquickjs",
+            BackendKind::Jsc => "jsc",
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Capabilities of the JS backend, returned by [`engine_info`](crate::engine_info).
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EngineInfo {
+    /// The backend.
+    pub backend: BackendKind,
+    /// Whether the engine can interrupt a running script, e.g. to enforce a
+    /// timeout.
+    pub supports_interrupt: bool,
+    /// Whether the engine can compile scripts to bytecode and load them
+    /// back, e.g. to speed up initialization.
+    pub supports_bytecode_cache: bool,
+    /// Version of the JS engine.
+    pub version: String,
+}
+
+/// Enter a `katex::js_call` span for a call to `func_name`.
+#[cfg(feature = "tracing")]
+pub(crate) fn js_call_span(func_name: &str) -> tracing::span::EnteredSpan {
+    tracing::debug_span!("katex::js_call", function = func_name).entered()
+}
+
+/// A Rust function callable from JS.
+///
+/// It takes string arguments and returns a string. An error is thrown as a
+/// JS exception with the given message.
+pub(crate) type HostFunction = Box<dyn Fn(Vec<String>) -> core::result::Result<String, String>>;
 
 /// A trait to represent a JS engine.
 pub(crate) trait JsEngine: Sized {
@@ -10,30 +77,72 @@ pub(crate) trait JsEngine: Sized {
     where
         Self: 'a;
 
+    /// Describe the JS engine.
+    fn info() -> EngineInfo;
+
     /// Create a JS engine.
     fn new() -> Result<Self>;
 
+    /// Create a JS engine whose heap is limited to `max_bytes`.
+    fn with_memory_limit(max_bytes: usize) -> Result<Self> {
+        let _ = max_bytes;
+        Err(crate::Error::JsInitError(
+            "memory limit is not supported by the JS backend".to_owned(),
+        ))
+    }
+
     /// Evaluate arbitrary code in the JS engine.
     fn eval<'a>(&'a self, code: &str) -> Result<Self::JsValue<'a>>;
 
     /// Call a JS function in the JS engine.
+    ///
+    /// `func_name` is either the name of a global function, or
+    /// `namespace.name` for a function of a global object, such as the entry
+    /// points in `__katex_rs`. Engines which only call global functions call
+    /// the namespace with the name as first argument instead.
+    ///
+    /// If the function returns a promise, engines which can run the pending
+    /// jobs run them until it settles, and return its value.
     fn call_function<'a>(
         &'a self,
         func_name: &str,
         args: impl Iterator<Item = Self::JsValue<'a>>,
     ) -> Result<Self::JsValue<'a>>;
 
+    /// Register a [host function](`HostFunction`) as a global JS function.
+    ///
+    /// Calling it from JS with non-string arguments throws an exception.
+    fn register_function(&self, name: &str, function: HostFunction) -> Result<()>;
+
     /// Create a JS value from [`bool`].
     fn create_bool_value(&self, input: bool) -> Result<Self::JsValue<'_>>;
 
     /// Create a JS value from [`i32`].
+    ///
+    /// It is the JS number of the same value, as for
+    /// [`create_float_value`](Self::create_float_value).
     fn create_int_value(&self, input: i32) -> Result<Self::JsValue<'_>>;
 
     /// Create a JS value from [`f64`].
+    ///
+    /// Every backend creates the JS number of exactly the same value,
+    /// including `-0`, the subnormals, the infinities and NaN, so that KaTeX
+    /// behaves the same for edge-case option values. `test_number_marshaling`
+    /// checks it for each backend.
     fn create_float_value(&self, input: f64) -> Result<Self::JsValue<'_>>;
 
-    /// Create a JS value from [`String`].
-    fn create_string_value(&self, input: String) -> Result<Self::JsValue<'_>>;
+    /// Create a JS string value.
+    ///
+    /// The JS engine copies the string into its heap. Backends taking a
+    /// `&str` read borrowed input in place, but quick-js only takes an owned
+    /// [`String`], so borrowed input is copied once more on that backend.
+    fn create_string_value(&self, input: Cow<'_, str>) -> Result<Self::JsValue<'_>>;
+
+    /// Create a JS array value from an iterator for `Self::JsValue`.
+    fn create_array_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>>;
 
     /// Create a JS object value from an iterator for `(String, Self::JsValue)`.
     fn create_object_value<'a>(
@@ -49,9 +158,17 @@ pub(crate) trait JsValue<'a>: Sized {
 }
 
 cfg_if! {
-    if #[cfg(feature = "quick-js")] {
+    if #[cfg(all(feature = "android", target_os = "android"))] {
+        // quick-js is not a dependency on Android, even with the default
+        // features, so that they only need to be disabled on other targets.
+        mod duktape;
+
+        pub(crate) type Engine = self::duktape::Engine;
+    } else if #[cfg(feature = "quick-js")] {
         cfg_if! {
-            if #[cfg(any(unix, all(windows, target_env = "gnu")))] {
+            if #[cfg(target_os = "android")] {
+                compile_error!("quick-js backend does not build for Android, enable the `android` feature instead.");
+            } else if #[cfg(any(unix, all(windows, target_env = "gnu")))] {
                 mod quick_js;
 
                 pub(crate) type Engine = self::quick_js::Engine;
@@ -59,7 +176,7 @@ cfg_if! {
                 compile_error!("quick-js backend is not support in the current build target.");
             }
         }
-    } else if #[cfg(feature = "duktape")] {
+    } else if #[cfg(any(feature = "duktape", feature = "android"))] {
         cfg_if! {
             if #[cfg(any(unix, windows))] {
                 mod duktape;
@@ -79,7 +196,55 @@ cfg_if! {
                 compile_error!("wasm-js backend is not support in the current build target.");
             }
         }
+    } else if #[cfg(feature = "rquickjs")] {
+        cfg_if! {
+            if #[cfg(any(unix, windows, all(target_arch = "wasm32", target_os = "wasi")))] {
+                mod rquickjs;
+
+                pub(crate) type Engine = self::rquickjs::Engine;
+            } else {
+                compile_error!("rquickjs backend is not support in the current build target.");
+            }
+        }
+    } else if #[cfg(feature = "jsc")] {
+        cfg_if! {
+            if #[cfg(target_vendor = "apple")] {
+                mod jsc;
+
+                pub(crate) type Engine = self::jsc::Engine;
+            } else {
+                compile_error!("jsc backend is not support in the current build target.");
+            }
+        }
     } else {
         compile_error!("Must enable one of the JS engines.");
     }
 }
+
+// The differential tests also compile the other enabled backends.
+#[cfg(all(
+    test,
+    feature = "differential",
+    feature = "duktape",
+    feature = "quick-js",
+    any(unix, windows),
+    not(target_os = "android")
+))]
+pub(crate) mod duktape;
+// quick-js and rquickjs both bundle QuickJS, so they cannot be linked together.
+#[cfg(all(
+    test,
+    feature = "differential",
+    feature = "rquickjs",
+    feature = "duktape",
+    not(feature = "quick-js"),
+    any(unix, windows)
+))]
+pub(crate) mod rquickjs;
+#[cfg(all(
+    test,
+    feature = "differential",
+    feature = "quick-js",
+    feature = "rquickjs"
+))]
+compile_error!("quick-js and rquickjs cannot be compared, they both bundle QuickJS.");
@@ -2,10 +2,11 @@
 
 use crate::{
     error::{Error, Result},
-    js_engine::{JsEngine, JsValue},
+    js_engine::{BackendKind, EngineInfo, HostFunction, JsEngine, JsValue},
 };
 use core::fmt;
 use ducc::{FromValue, ToValue};
+use std::borrow::Cow;
 
 /// Duktape Engine.
 pub struct Engine(ducc::Ducc);
@@ -13,6 +14,15 @@ pub struct Engine(ducc::Ducc);
 impl JsEngine for Engine {
     type JsValue<'a> = Value<'a>;
 
+    fn info() -> EngineInfo {
+        EngineInfo {
+            backend: BackendKind::Duktape,
+            supports_interrupt: false,
+            supports_bytecode_cache: false,
+            version: "Duktape (ducc 0.1)".to_owned(),
+        }
+    }
+
     fn new() -> Result<Self> {
         Ok(Self(ducc::Ducc::new()))
     }
@@ -32,10 +42,15 @@ impl JsEngine for Engine {
         func_name: &str,
         args: impl Iterator<Item = Self::JsValue<'a>>,
     ) -> Result<Self::JsValue<'a>> {
-        let function = self
-            .0
-            .globals()
-            .get::<String, ducc::Function>(func_name.to_owned())?;
+        #[cfg(feature = "tracing")]
+        let _span = super::js_call_span(func_name);
+        let globals = self.0.globals();
+        let function = match func_name.split_once('.') {
+            Some((namespace, name)) => globals
+                .get::<String, ducc::Object>(namespace.to_owned())?
+                .get::<String, ducc::Function>(name.to_owned())?,
+            None => globals.get::<String, ducc::Function>(func_name.to_owned())?,
+        };
         let args: ducc::Values = args.map(|v| v.value).collect();
         let result = function.call(args)?;
         Ok(Value {
@@ -44,6 +59,21 @@ impl JsEngine for Engine {
         })
     }
 
+    fn register_function(&self, name: &str, function: HostFunction) -> Result<()> {
+        let function = self.0.create_function(move |invocation| {
+            let args = invocation
+                .args
+                .into_vec()
+                .into_iter()
+                .map(|arg| String::from_value(arg, invocation.ducc))
+                .collect::<ducc::Result<_>>()?;
+            let result = function(args).map_err(ducc::Error::external)?;
+            result.to_value(invocation.ducc)
+        });
+        self.0.globals().set(name.to_owned(), function)?;
+        Ok(())
+    }
+
     fn create_bool_value(&self, input: bool) -> Result<Self::JsValue<'_>> {
         Ok(Value {
             value: input.to_value(&self.0)?,
@@ -52,22 +82,36 @@ impl JsEngine for Engine {
     }
 
     fn create_int_value(&self, input: i32) -> Result<Self::JsValue<'_>> {
+        // Duktape has a single number type, so integers take the same path as
+        // the other numbers.
+        self.create_float_value(input.into())
+    }
+
+    fn create_float_value(&self, input: f64) -> Result<Self::JsValue<'_>> {
         Ok(Value {
             value: input.to_value(&self.0)?,
             engine: &self.0,
         })
     }
 
-    fn create_float_value(&self, input: f64) -> Result<Self::JsValue<'_>> {
+    fn create_string_value(&self, input: Cow<'_, str>) -> Result<Self::JsValue<'_>> {
+        let input: &str = &input;
         Ok(Value {
             value: input.to_value(&self.0)?,
             engine: &self.0,
         })
     }
 
-    fn create_string_value(&self, input: String) -> Result<Self::JsValue<'_>> {
+    fn create_array_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>> {
+        let arr = self.0.create_array();
+        for v in input {
+            arr.push(v.value)?;
+        }
         Ok(Value {
-            value: input.to_value(&self.0)?,
+            value: ducc::Value::Array(arr),
             engine: &self.0,
         })
     }
@@ -95,7 +139,53 @@ pub struct Value<'a> {
 
 impl<'a> JsValue<'a> for Value<'a> {
     fn into_string(self) -> Result<String> {
-        Ok(String::from_value(self.value, self.engine)?)
+        let string = ducc::String::from_value(self.value, self.engine)?;
+        decode_cesu8(string.as_bytes())
+    }
+}
+
+/// Decode a string as stored by Duktape, which is UTF-8 except that the
+/// surrogate pairs created in JS, e.g. by `"\uD83D\uDE00"`, are encoded
+/// separately, as in CESU-8.
+///
+/// Fail with [`Error::InvalidUtf8`] on a lone surrogate or invalid bytes.
+fn decode_cesu8(bytes: &[u8]) -> Result<String> {
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    loop {
+        let e = match core::str::from_utf8(rest) {
+            Ok(valid) => {
+                decoded.push_str(valid);
+                return Ok(decoded);
+            }
+            Err(e) => e,
+        };
+        let (valid, after) = rest.split_at(e.valid_up_to());
+        decoded.push_str(core::str::from_utf8(valid).expect("checked above"));
+        let high = surrogate(after).filter(|unit| (0xD800..0xDC00).contains(unit));
+        let low = after
+            .get(3..)
+            .and_then(surrogate)
+            .filter(|unit| (0xDC00..0xE000).contains(unit));
+        let (Some(high), Some(low)) = (high, low) else {
+            return Err(Error::InvalidUtf8(format!(
+                "invalid sequence at byte {}",
+                bytes.len() - after.len()
+            )));
+        };
+        let c = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        decoded.push(char::from_u32(c).expect("surrogate pairs encode valid characters"));
+        rest = &after[6..];
+    }
+}
+
+/// Decode a UTF-16 surrogate encoded as a 3-byte UTF-8 sequence.
+fn surrogate(bytes: &[u8]) -> Option<u32> {
+    match *bytes.get(..3)? {
+        [0xED, b1 @ 0xA0..=0xBF, b2 @ 0x80..=0xBF] => {
+            Some(0xD000 | (u32::from(b1 & 0x3F) << 6) | u32::from(b2 & 0x3F))
+        }
+        _ => None,
     }
 }
 
@@ -113,7 +203,7 @@ impl From<ducc::Error> for Error {
             ErrorKind::ToJsConversionError { .. } | ErrorKind::FromJsConversionError { .. } => {
                 Self::JsValueError(format!("{e}"))
             }
-            _ => Self::JsExecError(format!("{e}")),
+            _ => Self::js_exec(format!("{e}")),
         }
     }
 }
@@ -0,0 +1,115 @@
+//! JS Engine implemented by the system
+//! [JavaScriptCore](https://developer.apple.com/documentation/javascriptcore)
+//! framework on Apple platforms, through the safe API of
+//! [katex-jsc-sys](https://docs.rs/katex-jsc-sys).
+
+use crate::{
+    error::{Error, Result},
+    js_engine::{BackendKind, EngineInfo, HostFunction, JsEngine, JsValue},
+};
+use katex_jsc_sys::Context;
+use std::borrow::Cow;
+
+/// JavaScriptCore Engine.
+pub struct Engine(Context);
+
+impl JsEngine for Engine {
+    type JsValue<'a> = Value<'a>;
+
+    fn info() -> EngineInfo {
+        EngineInfo {
+            backend: BackendKind::Jsc,
+            supports_interrupt: false,
+            supports_bytecode_cache: false,
+            // The framework of the OS, which has no version of its own.
+            version: "JavaScriptCore (system)".to_owned(),
+        }
+    }
+
+    fn new() -> Result<Self> {
+        Context::new().map(Self).ok_or_else(|| {
+            Error::JsInitError("failed to create a JavaScriptCore context".to_owned())
+        })
+    }
+
+    fn eval<'a>(&'a self, code: &str) -> Result<Self::JsValue<'a>> {
+        self.0.eval(code).map(Value).map_err(Error::js_exec)
+    }
+
+    fn call_function<'a>(
+        &'a self,
+        func_name: &str,
+        args: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>> {
+        #[cfg(feature = "tracing")]
+        let _span = super::js_call_span(func_name);
+        // Look up the namespace, if any, then the function.
+        let mut function = self.0.global_object();
+        for segment in func_name.split('.') {
+            function = self.0.property(&function, segment).map_err(Error::js_exec)?;
+        }
+        if !self.0.is_function(&function) {
+            return Err(Error::JsExecError(format!("{func_name} is not a function")));
+        }
+        let args: Vec<_> = args.map(|arg| arg.0).collect();
+        self.0.call(&function, &args).map(Value).map_err(Error::js_exec)
+    }
+
+    fn register_function(&self, name: &str, function: HostFunction) -> Result<()> {
+        self.0
+            .register_function(name, function)
+            .map_err(Error::js_exec)
+    }
+
+    fn create_bool_value(&self, input: bool) -> Result<Self::JsValue<'_>> {
+        Ok(Value(self.0.boolean(input)))
+    }
+
+    fn create_int_value(&self, input: i32) -> Result<Self::JsValue<'_>> {
+        Ok(Value(self.0.number(input.into())))
+    }
+
+    fn create_float_value(&self, input: f64) -> Result<Self::JsValue<'_>> {
+        Ok(Value(self.0.number(input)))
+    }
+
+    fn create_string_value(&self, input: Cow<'_, str>) -> Result<Self::JsValue<'_>> {
+        Ok(Value(self.0.string(&input)))
+    }
+
+    fn create_array_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>> {
+        let elements: Vec<_> = input.map(|v| v.0).collect();
+        self.0.array(&elements).map(Value).map_err(Error::js_exec)
+    }
+
+    fn create_object_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = (String, Self::JsValue<'a>)>,
+    ) -> Result<Self::JsValue<'a>> {
+        let object = self.0.object();
+        for (k, v) in input {
+            self.0
+                .set_property(&object, &k, &v.0)
+                .map_err(Error::js_exec)?;
+        }
+        Ok(Value(object))
+    }
+}
+
+/// JavaScriptCore Value.
+#[derive(Debug)]
+pub struct Value<'a>(katex_jsc_sys::Value<'a>);
+
+impl<'a> JsValue<'a> for Value<'a> {
+    fn into_string(self) -> Result<String> {
+        let chars = self
+            .0
+            .as_utf16()
+            .ok_or_else(|| Error::JsValueError("cannot convert value to string".to_owned()))?;
+        // Unlike the messages of exceptions, results are not copied lossily.
+        String::from_utf16(&chars).map_err(|e| Error::InvalidUtf8(e.to_string()))
+    }
+}
@@ -1,10 +1,11 @@
 //! JS Engine implemented by [QuickJs](https://crates.io/crates/quick-js).
 
 use crate::{
-    error::{Error, Result},
-    js_engine::{JsEngine, JsValue},
+    error::{Error, ResourceKind, Result},
+    js_engine::{BackendKind, EngineInfo, HostFunction, JsEngine, JsValue},
 };
 use core::convert::TryInto;
+use std::{borrow::Cow, panic::AssertUnwindSafe};
 
 /// QuickJS Engine.
 pub struct Engine(quick_js::Context);
@@ -12,10 +13,28 @@ pub struct Engine(quick_js::Context);
 impl JsEngine for Engine {
     type JsValue<'a> = Value;
 
+    fn info() -> EngineInfo {
+        EngineInfo {
+            backend: BackendKind::QuickJs,
+            supports_interrupt: false,
+            supports_bytecode_cache: false,
+            // Bundled by libquickjs-sys.
+            version: "QuickJS 2020-11-08".to_owned(),
+        }
+    }
+
     fn new() -> Result<Self> {
         Ok(Self(quick_js::Context::new()?))
     }
 
+    fn with_memory_limit(max_bytes: usize) -> Result<Self> {
+        Ok(Self(
+            quick_js::Context::builder()
+                .memory_limit(max_bytes)
+                .build()?,
+        ))
+    }
+
     fn eval<'a>(&'a self, code: &str) -> Result<Self::JsValue<'a>> {
         Ok(Value(self.0.eval(code)?))
     }
@@ -25,7 +44,35 @@ impl JsEngine for Engine {
         func_name: &str,
         args: impl Iterator<Item = Self::JsValue<'a>>,
     ) -> Result<Self::JsValue<'a>> {
-        Ok(Value(self.0.call_function(func_name, args.map(|v| v.0))?))
+        #[cfg(feature = "tracing")]
+        let _span = super::js_call_span(func_name);
+        // quick-js only calls global functions.
+        let result = match func_name.split_once('.') {
+            Some((namespace, name)) => self.0.call_function(
+                namespace,
+                core::iter::once(quick_js::JsValue::String(name.to_owned()))
+                    .chain(args.map(|v| v.0)),
+            )?,
+            None => self.0.call_function(func_name, args.map(|v| v.0))?,
+        };
+        Ok(Value(result))
+    }
+
+    fn register_function(&self, name: &str, function: HostFunction) -> Result<()> {
+        let function = AssertUnwindSafe(function);
+        self.0
+            .add_callback(name, move |args: quick_js::Arguments| {
+                let args = args
+                    .into_vec()
+                    .into_iter()
+                    .map(|arg| match arg {
+                        quick_js::JsValue::String(s) => Ok(s),
+                        arg => Err(format!("expected string argument, got {arg:?}")),
+                    })
+                    .collect::<core::result::Result<_, _>>()?;
+                (*function)(args)
+            })
+            .map_err(Error::from)
     }
 
     fn create_bool_value(&self, input: bool) -> Result<Self::JsValue<'_>> {
@@ -40,8 +87,17 @@ impl JsEngine for Engine {
         Ok(Value(quick_js::JsValue::Float(input)))
     }
 
-    fn create_string_value(&self, input: String) -> Result<Self::JsValue<'_>> {
-        Ok(Value(quick_js::JsValue::String(input)))
+    fn create_string_value(&self, input: Cow<'_, str>) -> Result<Self::JsValue<'_>> {
+        // quick-js only converts owned strings, so borrowed input is copied.
+        Ok(Value(quick_js::JsValue::String(input.into_owned())))
+    }
+
+    fn create_array_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>> {
+        let arr = input.map(|v| v.0).collect();
+        Ok(Value(quick_js::JsValue::Array(arr)))
     }
 
     fn create_object_value<'a>(
@@ -71,12 +127,25 @@ impl From<quick_js::ContextError> for Error {
 
 impl From<quick_js::ExecutionError> for Error {
     fn from(e: quick_js::ExecutionError) -> Self {
-        Self::JsExecError(format!("{e}"))
+        match e {
+            quick_js::ExecutionError::OutOfMemory => Self::ResourceExhausted {
+                kind: ResourceKind::Memory,
+                detail: format!("{e}"),
+            },
+            quick_js::ExecutionError::Conversion(quick_js::ValueError::InvalidString(_)) => {
+                Self::InvalidUtf8(format!("{e}"))
+            }
+            e => Self::js_exec(format!("{e}")),
+        }
     }
 }
 
 impl From<quick_js::ValueError> for Error {
     fn from(e: quick_js::ValueError) -> Self {
-        Self::JsValueError(format!("{e}"))
+        match e {
+            // QuickJS encodes lone surrogates as is, which is not UTF-8.
+            quick_js::ValueError::InvalidString(_) => Self::InvalidUtf8(format!("{e}")),
+            e => Self::JsValueError(format!("{e}")),
+        }
     }
 }
@@ -0,0 +1,208 @@
+//! JS Engine implemented by [rquickjs](https://crates.io/crates/rquickjs).
+
+use crate::{
+    error::{Error, Result},
+    js_engine::{BackendKind, EngineInfo, HostFunction, JsEngine, JsValue},
+};
+use core::fmt;
+use rquickjs::{
+    convert::Coerced,
+    function::{Args, Rest},
+    Array, CaughtError, Ctx, Exception, Function, Object, Persistent,
+};
+use std::borrow::Cow;
+
+/// rquickjs Engine.
+pub struct Engine {
+    // The runtime must outlive the context.
+    _runtime: rquickjs::Runtime,
+    context: rquickjs::Context,
+}
+
+impl Engine {
+    fn with_runtime(runtime: rquickjs::Runtime) -> Result<Self> {
+        let context = rquickjs::Context::full(&runtime)?;
+        Ok(Self {
+            _runtime: runtime,
+            context,
+        })
+    }
+
+    /// Run `f` in the context and convert any JS exception into [`Error::JsExecError`],
+    /// or [`Error::ResourceExhausted`].
+    fn with<R>(&self, f: impl for<'js> FnOnce(&Ctx<'js>) -> rquickjs::Result<R>) -> Result<R> {
+        self.context.with(|ctx| {
+            f(&ctx).map_err(|e| match CaughtError::from_error(&ctx, e) {
+                CaughtError::Error(e) => Error::from(e),
+                CaughtError::Exception(e) => {
+                    let msg: Result<Coerced<String>, _> = e.into_value().get();
+                    Error::js_exec(msg.map(|m| m.0).unwrap_or_default())
+                }
+                CaughtError::Value(v) => Error::js_exec(format!("{v:?}")),
+            })
+        })
+    }
+
+    fn value<'js>(&self, ctx: &Ctx<'js>, value: rquickjs::Value<'js>) -> Value {
+        Value {
+            value: Persistent::save(ctx, value),
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl JsEngine for Engine {
+    type JsValue<'a> = Value;
+
+    fn info() -> EngineInfo {
+        EngineInfo {
+            backend: BackendKind::Rquickjs,
+            supports_interrupt: true,
+            supports_bytecode_cache: true,
+            // Bundled by rquickjs-sys.
+            version: "QuickJS-ng 0.11.0".to_owned(),
+        }
+    }
+
+    fn new() -> Result<Self> {
+        Self::with_runtime(rquickjs::Runtime::new()?)
+    }
+
+    fn with_memory_limit(max_bytes: usize) -> Result<Self> {
+        let runtime = rquickjs::Runtime::new()?;
+        runtime.set_memory_limit(max_bytes);
+        Self::with_runtime(runtime)
+    }
+
+    fn eval<'a>(&'a self, code: &str) -> Result<Self::JsValue<'a>> {
+        self.with(|ctx| {
+            let value = ctx.eval::<rquickjs::Value, _>(code)?;
+            Ok(self.value(ctx, value))
+        })
+    }
+
+    fn call_function<'a>(
+        &'a self,
+        func_name: &str,
+        args: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>> {
+        #[cfg(feature = "tracing")]
+        let _span = super::js_call_span(func_name);
+        let args: Vec<_> = args.map(|v| v.value).collect();
+        self.with(|ctx| {
+            let function: Function = match func_name.split_once('.') {
+                Some((namespace, name)) => ctx.globals().get::<_, Object>(namespace)?.get(name)?,
+                None => ctx.globals().get(func_name)?,
+            };
+            let mut js_args = Args::new(ctx.clone(), args.len());
+            for arg in args {
+                js_args.push_arg(arg.restore(ctx)?)?;
+            }
+            let mut result = function.call_arg::<rquickjs::Value>(js_args)?;
+            if let Some(promise) = result.as_promise() {
+                result = promise.finish()?;
+            }
+            Ok(self.value(ctx, result))
+        })
+    }
+
+    fn register_function(&self, name: &str, function: HostFunction) -> Result<()> {
+        self.with(|ctx| {
+            let function = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'_>, args: Rest<String>| -> rquickjs::Result<String> {
+                    function(args.into_inner()).map_err(|msg| Exception::throw_message(&ctx, &msg))
+                },
+            )?;
+            ctx.globals().set(name, function)
+        })
+    }
+
+    fn create_bool_value(&self, input: bool) -> Result<Self::JsValue<'_>> {
+        self.with(|ctx| Ok(self.value(ctx, rquickjs::Value::new_bool(ctx.clone(), input))))
+    }
+
+    fn create_int_value(&self, input: i32) -> Result<Self::JsValue<'_>> {
+        self.with(|ctx| Ok(self.value(ctx, rquickjs::Value::new_int(ctx.clone(), input))))
+    }
+
+    fn create_float_value(&self, input: f64) -> Result<Self::JsValue<'_>> {
+        self.with(|ctx| Ok(self.value(ctx, rquickjs::Value::new_float(ctx.clone(), input))))
+    }
+
+    fn create_string_value(&self, input: Cow<'_, str>) -> Result<Self::JsValue<'_>> {
+        self.with(|ctx| {
+            let value = rquickjs::String::from_str(ctx.clone(), &input)?;
+            Ok(self.value(ctx, value.into_value()))
+        })
+    }
+
+    fn create_array_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>> {
+        let input: Vec<_> = input.map(|v| v.value).collect();
+        self.with(|ctx| {
+            let arr = Array::new(ctx.clone())?;
+            for (i, v) in input.into_iter().enumerate() {
+                arr.set(i, v.restore(ctx)?)?;
+            }
+            Ok(self.value(ctx, arr.into_value()))
+        })
+    }
+
+    fn create_object_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = (String, Self::JsValue<'a>)>,
+    ) -> Result<Self::JsValue<'a>> {
+        let input: Vec<_> = input.map(|(k, v)| (k, v.value)).collect();
+        self.with(|ctx| {
+            let obj = Object::new(ctx.clone())?;
+            for (k, v) in input {
+                obj.set(k, v.restore(ctx)?)?;
+            }
+            Ok(self.value(ctx, obj.into_value()))
+        })
+    }
+}
+
+/// rquickjs Value.
+pub struct Value {
+    value: Persistent<rquickjs::Value<'static>>,
+    context: rquickjs::Context,
+}
+
+impl<'a> JsValue<'a> for Value {
+    fn into_string(self) -> Result<String> {
+        let Self { value, context } = self;
+        context.with(|ctx| {
+            value
+                .restore(&ctx)?
+                .into_string()
+                .ok_or_else(|| Error::JsValueError("cannot convert value to string".to_owned()))?
+                .to_string()
+                .map_err(Error::from)
+        })
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Value").finish_non_exhaustive()
+    }
+}
+
+impl From<rquickjs::Error> for Error {
+    fn from(e: rquickjs::Error) -> Self {
+        match e {
+            rquickjs::Error::Allocation | rquickjs::Error::InvalidString(_) => {
+                Self::JsInitError(format!("{e}"))
+            }
+            rquickjs::Error::FromJs { .. } | rquickjs::Error::IntoJs { .. } => {
+                Self::JsValueError(format!("{e}"))
+            }
+            rquickjs::Error::Utf8(_) => Self::InvalidUtf8(format!("{e}")),
+            _ => Self::js_exec(format!("{e}")),
+        }
+    }
+}
@@ -3,8 +3,10 @@
 
 use crate::{
     error::{Error, Result},
-    js_engine::{JsEngine, JsValue},
+    js_engine::{BackendKind, EngineInfo, HostFunction, JsEngine, JsValue},
 };
+use std::borrow::Cow;
+use wasm_bindgen::{closure::Closure, JsCast};
 
 /// Wasm JS Engine.
 pub struct Engine;
@@ -12,6 +14,16 @@ pub struct Engine;
 impl JsEngine for Engine {
     type JsValue<'a> = Value;
 
+    fn info() -> EngineInfo {
+        EngineInfo {
+            backend: BackendKind::WasmJs,
+            supports_interrupt: false,
+            supports_bytecode_cache: false,
+            // Whatever runs the module, e.g. a browser.
+            version: "host".to_owned(),
+        }
+    }
+
     fn new() -> Result<Self> {
         Ok(Self)
     }
@@ -19,7 +31,7 @@ impl JsEngine for Engine {
     fn eval<'a>(&'a self, code: &str) -> Result<Self::JsValue<'a>> {
         js_sys::eval(code)
             .map(Value)
-            .map_err(|e| Error::JsExecError(format!("{e:?}")))
+            .map_err(|e| Error::js_exec(format!("{e:?}")))
     }
 
     fn call_function<'a>(
@@ -27,17 +39,53 @@ impl JsEngine for Engine {
         func_name: &str,
         args: impl Iterator<Item = Self::JsValue<'a>>,
     ) -> Result<Self::JsValue<'a>> {
-        let function: js_sys::Function = js_sys::Reflect::get(&js_sys::global(), &func_name.into())
-            .map_err(|e| Error::JsExecError(format!("{e:?}")))?
-            .into();
+        #[cfg(feature = "tracing")]
+        let _span = super::js_call_span(func_name);
+        let get = |target: &wasm_bindgen::JsValue, key: &str| {
+            js_sys::Reflect::get(target, &key.into())
+                .map_err(|e| Error::JsExecError(format!("{e:?}")))
+        };
+        let function: js_sys::Function = match func_name.split_once('.') {
+            Some((namespace, name)) => get(&get(&js_sys::global(), namespace)?, name)?,
+            None => get(&js_sys::global(), func_name)?,
+        }
+        .into();
 
         let args: js_sys::Array = args.map(|v| v.0).collect();
         let result = function
             .apply(&wasm_bindgen::JsValue::NULL, &args)
-            .map_err(|e| Error::JsExecError(format!("{e:?}")))?;
+            .map_err(|e| Error::js_exec(format!("{e:?}")))?;
         Ok(Value(result))
     }
 
+    fn register_function(&self, name: &str, function: HostFunction) -> Result<()> {
+        let closure = Closure::<dyn Fn(js_sys::Array) -> core::result::Result<String, wasm_bindgen::JsValue>>::new(
+            move |args: js_sys::Array| {
+                args.iter()
+                    .map(|arg| {
+                        arg.as_string()
+                            .ok_or_else(|| format!("expected string argument, got {arg:?}"))
+                    })
+                    .collect::<core::result::Result<_, _>>()
+                    .and_then(|args| function(args))
+                    .map_err(|msg| js_sys::Error::new(&msg).into())
+            },
+        );
+        // Closures only receive a fixed number of arguments, so collect them into an array.
+        let wrapper = js_sys::Function::new_with_args(
+            "f",
+            "return function () { return f(Array.prototype.slice.call(arguments)); };",
+        )
+        .call1(&wasm_bindgen::JsValue::NULL, closure.as_ref())
+        .map_err(|e| Error::JsExecError(format!("{e:?}")))?
+        .unchecked_into::<js_sys::Function>();
+        // The function lives as long as the JS global object.
+        closure.forget();
+        js_sys::Reflect::set(&js_sys::global(), &name.into(), &wrapper)
+            .map_err(|e| Error::JsValueError(format!("{e:?}")))?;
+        Ok(())
+    }
+
     fn create_bool_value(&self, input: bool) -> Result<Self::JsValue<'_>> {
         Ok(Value(input.into()))
     }
@@ -50,8 +98,16 @@ impl JsEngine for Engine {
         Ok(Value(input.into()))
     }
 
-    fn create_string_value(&self, input: String) -> Result<Self::JsValue<'_>> {
-        Ok(Value(input.into()))
+    fn create_string_value(&self, input: Cow<'_, str>) -> Result<Self::JsValue<'_>> {
+        Ok(Value(wasm_bindgen::JsValue::from_str(&input)))
+    }
+
+    fn create_array_value<'a>(
+        &'a self,
+        input: impl Iterator<Item = Self::JsValue<'a>>,
+    ) -> Result<Self::JsValue<'a>> {
+        let arr: js_sys::Array = input.map(|v| v.0).collect();
+        Ok(Value(arr.into()))
     }
 
     fn create_object_value<'a>(
@@ -71,8 +127,30 @@ impl JsEngine for Engine {
 #[derive(Debug)]
 pub struct Value(wasm_bindgen::JsValue);
 
+impl Value {
+    /// Return the JS value, e.g. to post it to a worker.
+    #[cfg(feature = "wasm-worker")]
+    pub(crate) fn into_inner(self) -> wasm_bindgen::JsValue {
+        self.0
+    }
+}
+
+impl From<wasm_bindgen::JsValue> for Value {
+    fn from(value: wasm_bindgen::JsValue) -> Self {
+        Self(value)
+    }
+}
+
 impl<'a> JsValue<'a> for Value {
     fn into_string(self) -> Result<String> {
+        // `as_string` replaces lone surrogates rather than failing.
+        if let Some(string) = self.0.dyn_ref::<js_sys::JsString>() {
+            if !string.is_valid_utf16() {
+                return Err(Error::InvalidUtf8(
+                    "string with a lone surrogate".to_owned(),
+                ));
+            }
+        }
         self.0
             .as_string()
             .ok_or_else(|| Error::JsValueError("cannot convert value to string".to_owned()))
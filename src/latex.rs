@@ -0,0 +1,182 @@
+//! Escape untrusted fragments and compose them into LaTeX.
+//!
+//! Interpolating a value such as a user name into LaTeX source with
+//! `format!` lets a `}` or a `\` in it end the argument early and inject
+//! commands. [`escape_text`] and [`escape_math`] make values safe to
+//! interpolate, and [`Latex`] composes formulas from escaped fragments.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::latex::Latex;
+//!
+//! let name = r"} \href{javascript:alert(1)}{x";
+//! let latex = Latex::new()
+//!     .text(name)
+//!     .raw("=")
+//!     .frac("a", Latex::new().sub("x", "i"));
+//! assert_eq!(
+//!     latex.as_str(),
+//!     r"\text{\} \textbackslash{}href\{javascript:alert(1)\}\{x}=\frac{a}{{x}_{i}}"
+//! );
+//! ```
+
+use core::fmt;
+
+/// Escape text so that it is typeset as is in text mode, e.g. in `\text{}`.
+///
+/// # Examples
+///
+/// ```
+/// let escaped = katex::latex::escape_text(r"50% of {x} \o/");
+/// assert_eq!(escaped, r"50\% of \{x\} \textbackslash{}o/");
+/// ```
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape characters so that they are typeset as math symbols, e.g. in a
+/// subscript.
+///
+/// # Examples
+///
+/// ```
+/// let escaped = katex::latex::escape_math("a_b^c");
+/// assert_eq!(escaped, r#"a\_b{\char"5E}c"#);
+/// ```
+pub fn escape_math(symbols: &str) -> String {
+    let mut escaped = String::with_capacity(symbols.len());
+    for c in symbols.chars() {
+        match c {
+            // Grouped, so that the following letters or digits do not extend
+            // the command.
+            '\\' => escaped.push_str(r"{\backslash}"),
+            '^' => escaped.push_str(r#"{\char"5E}"#),
+            '~' => escaped.push_str(r#"{\char"7E}"#),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append LaTeX, with a space if it would otherwise extend a control word
+/// ending the output, e.g. `\alpha` followed by `x`.
+pub(crate) fn append(output: &mut String, latex: &str) {
+    if latex.starts_with(|c: char| c.is_ascii_alphabetic()) && ends_with_control_word(output) {
+        output.push(' ');
+    }
+    output.push_str(latex);
+}
+
+fn ends_with_control_word(latex: &str) -> bool {
+    let name_start = latex.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    if name_start.len() == latex.len() {
+        return false;
+    }
+    // `\\alpha` is a line break followed by letters.
+    let backslashes = name_start.len() - name_start.trim_end_matches('\\').len();
+    backslashes % 2 == 1
+}
+
+/// A LaTeX formula composed from fragments.
+///
+/// Strings converted into fragments, e.g. the arguments of
+/// [`frac`](Self::frac), are escaped like [`symbol`](Self::symbol); only
+/// [`raw`](Self::raw) takes LaTeX source.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Latex(String);
+
+impl Latex {
+    /// Create an empty formula.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append trusted LaTeX source as is.
+    pub fn raw(mut self, latex: impl AsRef<str>) -> Self {
+        append(&mut self.0, latex.as_ref());
+        self
+    }
+
+    /// Append characters typeset as math symbols, [escaped](escape_math).
+    pub fn symbol(self, symbols: impl AsRef<str>) -> Self {
+        self.raw(escape_math(symbols.as_ref()))
+    }
+
+    /// Append text typeset in text mode, [escaped](escape_text).
+    pub fn text(self, text: impl AsRef<str>) -> Self {
+        self.raw(format!(r"\text{{{}}}", escape_text(text.as_ref())))
+    }
+
+    /// Append a fraction.
+    pub fn frac(self, numerator: impl Into<Latex>, denominator: impl Into<Latex>) -> Self {
+        let (numerator, denominator) = (numerator.into(), denominator.into());
+        self.raw(format!(r"\frac{{{numerator}}}{{{denominator}}}"))
+    }
+
+    /// Append a square root.
+    pub fn sqrt(self, radicand: impl Into<Latex>) -> Self {
+        self.raw(format!(r"\sqrt{{{}}}", radicand.into()))
+    }
+
+    /// Append a node with a subscript.
+    pub fn sub(self, base: impl Into<Latex>, sub: impl Into<Latex>) -> Self {
+        self.raw(format!("{{{}}}_{{{}}}", base.into(), sub.into()))
+    }
+
+    /// Append a node with a superscript.
+    pub fn sup(self, base: impl Into<Latex>, sup: impl Into<Latex>) -> Self {
+        self.raw(format!("{{{}}}^{{{}}}", base.into(), sup.into()))
+    }
+
+    /// Return the LaTeX source.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Return the LaTeX source.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl From<&str> for Latex {
+    fn from(symbols: &str) -> Self {
+        Latex::new().symbol(symbols)
+    }
+}
+
+impl From<String> for Latex {
+    fn from(symbols: String) -> Self {
+        Latex::new().symbol(symbols)
+    }
+}
+
+impl AsRef<str> for Latex {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Latex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,352 @@
+//! Approximate box layout of KaTeX's MathML output.
+//!
+//! The layout uses the bundled metrics of the KaTeX fonts, generated by
+//! `gen_font_metrics.py`, which is good enough to produce standalone vector
+//! output without a browser. Dimensions are in ems, the y axis points
+//! downwards, and the baseline of the equation is at `y = 0`.
+
+mod metrics;
+
+use self::metrics::{
+    Metrics, AMS_REGULAR, MAIN_BOLD, MAIN_REGULAR, MATH_BOLD_ITALIC, MATH_ITALIC, SIZE1_REGULAR,
+};
+use crate::html::Node;
+
+/// Scale of scripts relative to their base.
+const SCRIPT_SCALE: f64 = 0.7;
+/// Height of the math axis above the baseline.
+const AXIS_HEIGHT: f64 = 0.25;
+/// Thickness of fraction bars and radical overlines.
+const RULE_THICKNESS: f64 = 0.04;
+
+/// A positioned drawing primitive.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Primitive {
+    /// A run of text whose baseline starts at `(x, y)`.
+    Text {
+        text: String,
+        x: f64,
+        y: f64,
+        size: f64,
+        italic: bool,
+        bold: bool,
+    },
+    /// A filled rectangle with its top left corner at `(x, y)`.
+    Rule {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+}
+
+impl Primitive {
+    fn translate(&mut self, dx: f64, dy: f64) {
+        match self {
+            Primitive::Text { x, y, .. } | Primitive::Rule { x, y, .. } => {
+                *x += dx;
+                *y += dy;
+            }
+        }
+    }
+}
+
+/// A laid out box.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LayoutBox {
+    /// Width of the box.
+    pub(crate) width: f64,
+    /// Height above the baseline.
+    pub(crate) ascent: f64,
+    /// Depth below the baseline.
+    pub(crate) descent: f64,
+    /// Primitives relative to the origin of the box on the baseline.
+    pub(crate) items: Vec<Primitive>,
+}
+
+impl LayoutBox {
+    fn text(text: &str, size: f64, italic: bool, bold: bool) -> Self {
+        if text.is_empty() {
+            return Self::default();
+        }
+        let (mut width, mut ascent, mut descent) = (0.0, 0.0, 0.0);
+        for c in text.chars() {
+            let (_, depth, height, advance) = char_metrics(c, italic, bold);
+            width += advance;
+            ascent = f64::max(ascent, height);
+            descent = f64::max(descent, depth);
+        }
+        Self {
+            width: width * size,
+            ascent: ascent * size,
+            descent: descent * size,
+            items: vec![Primitive::Text {
+                text: text.to_owned(),
+                x: 0.0,
+                y: 0.0,
+                size,
+                italic,
+                bold,
+            }],
+        }
+    }
+
+    fn space(width: f64) -> Self {
+        Self {
+            width,
+            ..Self::default()
+        }
+    }
+
+    fn rule(width: f64, y: f64) -> Self {
+        Self {
+            width,
+            ascent: (-y).max(0.0),
+            descent: (y + RULE_THICKNESS).max(0.0),
+            items: vec![Primitive::Rule {
+                x: 0.0,
+                y,
+                width,
+                height: RULE_THICKNESS,
+            }],
+        }
+    }
+
+    /// Append `other` at horizontal offset `dx` and vertical offset `dy`.
+    fn place(&mut self, mut other: LayoutBox, dx: f64, dy: f64) {
+        for item in &mut other.items {
+            item.translate(dx, dy);
+        }
+        self.items.append(&mut other.items);
+        self.width = self.width.max(dx + other.width);
+        self.ascent = self.ascent.max(other.ascent - dy);
+        self.descent = self.descent.max(other.descent + dy);
+    }
+
+    fn row(boxes: impl Iterator<Item = LayoutBox>) -> Self {
+        let mut row = Self::default();
+        for b in boxes {
+            let x = row.width;
+            row.place(b, x, 0.0);
+        }
+        row
+    }
+}
+
+/// Lay out the nodes of KaTeX's MathML output.
+pub(crate) fn layout(nodes: &[Node]) -> LayoutBox {
+    LayoutBox::row(nodes.iter().map(|n| layout_node(n, 1.0)))
+}
+
+fn layout_node(node: &Node, size: f64) -> LayoutBox {
+    let (name, children) = match node {
+        Node::Text(text) => return LayoutBox::text(text.trim(), size, false, false),
+        Node::Element { name, children, .. } => (name.as_str(), children.as_slice()),
+    };
+    let child = |i: usize, size: f64| {
+        children
+            .get(i)
+            .map(|c| layout_node(c, size))
+            .unwrap_or_default()
+    };
+    let variant = node.attr("mathvariant");
+    match name {
+        "annotation" | "annotation-xml" => LayoutBox::default(),
+        "mphantom" => {
+            let mut b = LayoutBox::row(children.iter().map(|c| layout_node(c, size)));
+            b.items.clear();
+            b
+        }
+        "mi" => {
+            let text = node.text();
+            let italic = match variant {
+                Some(v) => v.contains("italic"),
+                None => text.chars().count() == 1,
+            };
+            let bold = variant.is_some_and(|v| v.contains("bold"));
+            LayoutBox::text(&text, size, italic, bold)
+        }
+        "mn" | "mtext" | "ms" => {
+            let bold = variant.is_some_and(|v| v.contains("bold"));
+            LayoutBox::text(&node.text(), size, false, bold)
+        }
+        "mo" => {
+            let text = node.text();
+            // Like TeX, operators in scripts are not spaced.
+            let space = match text.as_str() {
+                _ if size < 1.0 => 0.0,
+                "=" | "<" | ">" | "≤" | "≥" | "≠" | "≈" | "→" | "∈" | "⇒" | "↦" => {
+                    0.2778
+                }
+                "+" | "−" | "×" | "÷" | "±" | "∓" | "⋅" => 0.2222,
+                _ => 0.0,
+            } * size;
+            let glyph = LayoutBox::text(&text, size, false, false);
+            LayoutBox::row([LayoutBox::space(space), glyph, LayoutBox::space(space)].into_iter())
+        }
+        "mspace" => LayoutBox::space(node.attr("width").map_or(0.0, parse_em) * size),
+        "msup" => script(child(0, size), children.get(1), None, size),
+        "msub" => script(child(0, size), None, children.get(1), size),
+        "msubsup" => script(child(0, size), children.get(2), children.get(1), size),
+        "mover" => stack(child(0, size), children.get(1), None, size),
+        "munder" => stack(child(0, size), None, children.get(1), size),
+        "munderover" => stack(child(0, size), children.get(2), children.get(1), size),
+        "mfrac" => fraction(child(0, size), child(1, size), size),
+        "msqrt" => radical(
+            LayoutBox::row(children.iter().map(|c| layout_node(c, size))),
+            None,
+            size,
+        ),
+        "mroot" => radical(child(0, size), Some(child(1, size * SCRIPT_SCALE)), size),
+        "mtable" => table(children, size),
+        _ => LayoutBox::row(children.iter().map(|c| layout_node(c, size))),
+    }
+}
+
+fn script(base: LayoutBox, sup: Option<&Node>, sub: Option<&Node>, size: f64) -> LayoutBox {
+    let x = base.width;
+    let base_ascent = base.ascent;
+    let mut b = LayoutBox::default();
+    b.place(base, 0.0, 0.0);
+    if let Some(sup) = sup {
+        let sup = layout_node(sup, size * SCRIPT_SCALE);
+        let shift = (base_ascent - 0.35 * size).max(0.4 * size);
+        b.place(sup, x, -shift);
+    }
+    if let Some(sub) = sub {
+        let sub = layout_node(sub, size * SCRIPT_SCALE);
+        b.place(sub, x, 0.2 * size);
+    }
+    b
+}
+
+fn stack(base: LayoutBox, over: Option<&Node>, under: Option<&Node>, size: f64) -> LayoutBox {
+    let over = over.map(|n| layout_node(n, size * SCRIPT_SCALE));
+    let under = under.map(|n| layout_node(n, size * SCRIPT_SCALE));
+    let width = [Some(&base), over.as_ref(), under.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|b| b.width)
+        .fold(0.0, f64::max);
+    let gap = 0.1 * size;
+    let (base_ascent, base_descent) = (base.ascent, base.descent);
+    let mut b = LayoutBox::default();
+    let base_width = base.width;
+    b.place(base, (width - base_width) / 2.0, 0.0);
+    if let Some(over) = over {
+        let dy = -(base_ascent + gap + over.descent);
+        let dx = (width - over.width) / 2.0;
+        b.place(over, dx, dy);
+    }
+    if let Some(under) = under {
+        let dy = base_descent + gap + under.ascent;
+        let dx = (width - under.width) / 2.0;
+        b.place(under, dx, dy);
+    }
+    b
+}
+
+fn fraction(num: LayoutBox, den: LayoutBox, size: f64) -> LayoutBox {
+    let width = num.width.max(den.width) + 0.2 * size;
+    let axis = -AXIS_HEIGHT * size;
+    let gap = 0.15 * size;
+    let mut b = LayoutBox::rule(width, axis - RULE_THICKNESS / 2.0);
+    let dy = axis - RULE_THICKNESS / 2.0 - gap - num.descent;
+    let dx = (width - num.width) / 2.0;
+    b.place(num, dx, dy);
+    let dy = axis + RULE_THICKNESS / 2.0 + gap + den.ascent;
+    let dx = (width - den.width) / 2.0;
+    b.place(den, dx, dy);
+    b
+}
+
+fn radical(radicand: LayoutBox, index: Option<LayoutBox>, size: f64) -> LayoutBox {
+    let mut b = LayoutBox::default();
+    let mut x = 0.0;
+    if let Some(index) = index {
+        x = index.width;
+        b.place(index, 0.0, -0.45 * size);
+    }
+    let sign = LayoutBox::text("√", size, false, false);
+    let sign_width = sign.width;
+    b.place(sign, x, 0.0);
+    x += sign_width;
+    let top = -(radicand.ascent + 0.1 * size);
+    let width = radicand.width;
+    b.place(radicand, x, 0.0);
+    b.place(LayoutBox::rule(width, top), x, 0.0);
+    b
+}
+
+fn table(rows: &[Node], size: f64) -> LayoutBox {
+    let cells: Vec<Vec<LayoutBox>> = rows
+        .iter()
+        .map(|row| {
+            row.children()
+                .iter()
+                .map(|c| layout_node(c, size))
+                .collect()
+        })
+        .collect();
+    let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<f64> = (0..columns)
+        .map(|c| {
+            cells
+                .iter()
+                .filter_map(|row| row.get(c))
+                .map(|b| b.width)
+                .fold(0.0, f64::max)
+        })
+        .collect();
+    let col_gap = 1.0 * size;
+    let row_gap = 0.3 * size;
+    let mut b = LayoutBox::default();
+    let mut y = 0.0;
+    for (r, row) in cells.into_iter().enumerate() {
+        let ascent = row.iter().map(|c| c.ascent).fold(0.0, f64::max);
+        let descent = row.iter().map(|c| c.descent).fold(0.0, f64::max);
+        if r > 0 {
+            y += ascent + row_gap;
+        }
+        let mut x = 0.0;
+        for (c, cell) in row.into_iter().enumerate() {
+            let dx = x + (widths[c] - cell.width) / 2.0;
+            b.place(cell, dx, y);
+            x += widths[c] + col_gap;
+        }
+        y += descent;
+    }
+    // Center the table on the math axis.
+    let center = (b.descent - b.ascent) / 2.0 + AXIS_HEIGHT * size;
+    let mut centered = LayoutBox::default();
+    centered.place(b, 0.0, -center);
+    centered
+}
+
+/// Parse a MathML length in ems.
+fn parse_em(value: &str) -> f64 {
+    value
+        .strip_suffix("em")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.0)
+}
+
+/// Return the metrics of a character in the KaTeX font of its style.
+///
+/// Characters missing from the font fall back to the upright fonts, then,
+/// like KaTeX does for unsupported scripts, to the metrics of `M`.
+fn char_metrics(c: char, italic: bool, bold: bool) -> Metrics {
+    let font = match (italic, bold) {
+        (true, true) => MATH_BOLD_ITALIC,
+        (true, false) => MATH_ITALIC,
+        (false, true) => MAIN_BOLD,
+        (false, false) => MAIN_REGULAR,
+    };
+    [font, MAIN_REGULAR, AMS_REGULAR, SIZE1_REGULAR]
+        .into_iter()
+        .find_map(|font| {
+            let i = font.binary_search_by_key(&c, |m| m.0).ok()?;
+            Some(font[i])
+        })
+        .unwrap_or_else(|| char_metrics('M', false, bold))
+}
@@ -0,0 +1,386 @@
+//! Font metrics of KaTeX 0.16.7.
+//!
+//! Generated by `gen_font_metrics.py`, do not edit.
+
+/// A character, with its depth, height and width in ems, sorted by
+/// character.
+pub(super) type Metrics = (char, f64, f64, f64);
+
+/// Metrics of `KaTeX_Main-Regular`.
+#[rustfmt::skip]
+pub(super) const MAIN_REGULAR: &[Metrics] = &[
+    ('\u{0020}', 0.0, 0.0, 0.25), ('\u{0021}', 0.0, 0.69444, 0.27778), ('\u{0022}', 0.0, 0.69444, 0.5),
+    ('\u{0023}', 0.19444, 0.69444, 0.83334), ('\u{0024}', 0.05556, 0.75, 0.5), ('\u{0025}', 0.05556, 0.75, 0.83334),
+    ('\u{0026}', 0.0, 0.69444, 0.77778), ('\u{0027}', 0.0, 0.69444, 0.27778), ('\u{0028}', 0.25, 0.75, 0.38889),
+    ('\u{0029}', 0.25, 0.75, 0.38889), ('\u{002A}', 0.0, 0.75, 0.5), ('\u{002B}', 0.08333, 0.58333, 0.77778),
+    ('\u{002C}', 0.19444, 0.10556, 0.27778), ('\u{002D}', 0.0, 0.43056, 0.33333), ('\u{002E}', 0.0, 0.10556, 0.27778),
+    ('\u{002F}', 0.25, 0.75, 0.5), ('\u{0030}', 0.0, 0.64444, 0.5), ('\u{0031}', 0.0, 0.64444, 0.5),
+    ('\u{0032}', 0.0, 0.64444, 0.5), ('\u{0033}', 0.0, 0.64444, 0.5), ('\u{0034}', 0.0, 0.64444, 0.5),
+    ('\u{0035}', 0.0, 0.64444, 0.5), ('\u{0036}', 0.0, 0.64444, 0.5), ('\u{0037}', 0.0, 0.64444, 0.5),
+    ('\u{0038}', 0.0, 0.64444, 0.5), ('\u{0039}', 0.0, 0.64444, 0.5), ('\u{003A}', 0.0, 0.43056, 0.27778),
+    ('\u{003B}', 0.19444, 0.43056, 0.27778), ('\u{003C}', 0.0391, 0.5391, 0.77778), ('\u{003D}', -0.13313, 0.36687, 0.77778),
+    ('\u{003E}', 0.0391, 0.5391, 0.77778), ('\u{003F}', 0.0, 0.69444, 0.47222), ('\u{0040}', 0.0, 0.69444, 0.77778),
+    ('\u{0041}', 0.0, 0.68333, 0.75), ('\u{0042}', 0.0, 0.68333, 0.70834), ('\u{0043}', 0.0, 0.68333, 0.72222),
+    ('\u{0044}', 0.0, 0.68333, 0.76389), ('\u{0045}', 0.0, 0.68333, 0.68056), ('\u{0046}', 0.0, 0.68333, 0.65278),
+    ('\u{0047}', 0.0, 0.68333, 0.78472), ('\u{0048}', 0.0, 0.68333, 0.75), ('\u{0049}', 0.0, 0.68333, 0.36111),
+    ('\u{004A}', 0.0, 0.68333, 0.51389), ('\u{004B}', 0.0, 0.68333, 0.77778), ('\u{004C}', 0.0, 0.68333, 0.625),
+    ('\u{004D}', 0.0, 0.68333, 0.91667), ('\u{004E}', 0.0, 0.68333, 0.75), ('\u{004F}', 0.0, 0.68333, 0.77778),
+    ('\u{0050}', 0.0, 0.68333, 0.68056), ('\u{0051}', 0.19444, 0.68333, 0.77778), ('\u{0052}', 0.0, 0.68333, 0.73611),
+    ('\u{0053}', 0.0, 0.68333, 0.55556), ('\u{0054}', 0.0, 0.68333, 0.72222), ('\u{0055}', 0.0, 0.68333, 0.75),
+    ('\u{0056}', 0.0, 0.68333, 0.75), ('\u{0057}', 0.0, 0.68333, 1.02778), ('\u{0058}', 0.0, 0.68333, 0.75),
+    ('\u{0059}', 0.0, 0.68333, 0.75), ('\u{005A}', 0.0, 0.68333, 0.61111), ('\u{005B}', 0.25, 0.75, 0.27778),
+    ('\u{005C}', 0.25, 0.75, 0.5), ('\u{005D}', 0.25, 0.75, 0.27778), ('\u{005E}', 0.0, 0.69444, 0.5),
+    ('\u{005F}', 0.31, 0.12056, 0.5), ('\u{0061}', 0.0, 0.43056, 0.5), ('\u{0062}', 0.0, 0.69444, 0.55556),
+    ('\u{0063}', 0.0, 0.43056, 0.44445), ('\u{0064}', 0.0, 0.69444, 0.55556), ('\u{0065}', 0.0, 0.43056, 0.44445),
+    ('\u{0066}', 0.0, 0.69444, 0.30556), ('\u{0067}', 0.19444, 0.43056, 0.5), ('\u{0068}', 0.0, 0.69444, 0.55556),
+    ('\u{0069}', 0.0, 0.66786, 0.27778), ('\u{006A}', 0.19444, 0.66786, 0.30556), ('\u{006B}', 0.0, 0.69444, 0.52778),
+    ('\u{006C}', 0.0, 0.69444, 0.27778), ('\u{006D}', 0.0, 0.43056, 0.83334), ('\u{006E}', 0.0, 0.43056, 0.55556),
+    ('\u{006F}', 0.0, 0.43056, 0.5), ('\u{0070}', 0.19444, 0.43056, 0.55556), ('\u{0071}', 0.19444, 0.43056, 0.52778),
+    ('\u{0072}', 0.0, 0.43056, 0.39167), ('\u{0073}', 0.0, 0.43056, 0.39445), ('\u{0074}', 0.0, 0.61508, 0.38889),
+    ('\u{0075}', 0.0, 0.43056, 0.55556), ('\u{0076}', 0.0, 0.43056, 0.52778), ('\u{0077}', 0.0, 0.43056, 0.72222),
+    ('\u{0078}', 0.0, 0.43056, 0.52778), ('\u{0079}', 0.19444, 0.43056, 0.52778), ('\u{007A}', 0.0, 0.43056, 0.44445),
+    ('\u{007B}', 0.25, 0.75, 0.5), ('\u{007C}', 0.25, 0.75, 0.27778), ('\u{007D}', 0.25, 0.75, 0.5),
+    ('\u{007E}', 0.35, 0.31786, 0.5), ('\u{00A0}', 0.0, 0.0, 0.25), ('\u{00A3}', 0.0, 0.69444, 0.76909),
+    ('\u{00A7}', 0.19444, 0.69444, 0.44445), ('\u{00A8}', 0.0, 0.66786, 0.5), ('\u{00AC}', 0.0, 0.43056, 0.66667),
+    ('\u{00B0}', 0.0, 0.69444, 0.75), ('\u{00B1}', 0.08333, 0.58333, 0.77778), ('\u{00B6}', 0.19444, 0.69444, 0.61111),
+    ('\u{00B8}', 0.17014, 0.0, 0.44445), ('\u{00C6}', 0.0, 0.68333, 0.90278), ('\u{00D7}', 0.08333, 0.58333, 0.77778),
+    ('\u{00D8}', 0.04861, 0.73194, 0.77778), ('\u{00DF}', 0.0, 0.69444, 0.5), ('\u{00E6}', 0.0, 0.43056, 0.72222),
+    ('\u{00F7}', 0.08333, 0.58333, 0.77778), ('\u{00F8}', 0.09722, 0.52778, 0.5), ('\u{0131}', 0.0, 0.43056, 0.27778),
+    ('\u{0152}', 0.0, 0.68333, 1.01389), ('\u{0153}', 0.0, 0.43056, 0.77778), ('\u{0237}', 0.19444, 0.43056, 0.30556),
+    ('\u{02C6}', 0.0, 0.69444, 0.5), ('\u{02C7}', 0.0, 0.62847, 0.5), ('\u{02C9}', 0.0, 0.56778, 0.5),
+    ('\u{02CA}', 0.0, 0.69444, 0.5), ('\u{02CB}', 0.0, 0.69444, 0.5), ('\u{02D8}', 0.0, 0.69444, 0.5),
+    ('\u{02D9}', 0.0, 0.66786, 0.27778), ('\u{02DA}', 0.0, 0.69444, 0.75), ('\u{02DC}', 0.0, 0.66786, 0.5),
+    ('\u{02DD}', 0.0, 0.69444, 0.5), ('\u{0393}', 0.0, 0.68333, 0.625), ('\u{0394}', 0.0, 0.68333, 0.83334),
+    ('\u{0398}', 0.0, 0.68333, 0.77778), ('\u{039B}', 0.0, 0.68333, 0.69445), ('\u{039E}', 0.0, 0.68333, 0.66667),
+    ('\u{03A0}', 0.0, 0.68333, 0.75), ('\u{03A3}', 0.0, 0.68333, 0.72222), ('\u{03A5}', 0.0, 0.68333, 0.77778),
+    ('\u{03A6}', 0.0, 0.68333, 0.72222), ('\u{03A8}', 0.0, 0.68333, 0.77778), ('\u{03A9}', 0.0, 0.68333, 0.72222),
+    ('\u{2013}', 0.0, 0.43056, 0.5), ('\u{2014}', 0.0, 0.43056, 1.0), ('\u{2018}', 0.0, 0.69444, 0.27778),
+    ('\u{2019}', 0.0, 0.69444, 0.27778), ('\u{201C}', 0.0, 0.69444, 0.5), ('\u{201D}', 0.0, 0.69444, 0.5),
+    ('\u{2020}', 0.19444, 0.69444, 0.44445), ('\u{2021}', 0.19444, 0.69444, 0.44445), ('\u{2026}', 0.0, 0.123, 1.172),
+    ('\u{2032}', 0.0, 0.55556, 0.275), ('\u{20D7}', 0.0, 0.71444, 0.5), ('\u{210F}', 0.0, 0.68889, 0.54028),
+    ('\u{2111}', 0.0, 0.69444, 0.72222), ('\u{2113}', 0.0, 0.69444, 0.41667), ('\u{2118}', 0.19444, 0.43056, 0.63646),
+    ('\u{211C}', 0.0, 0.69444, 0.72222), ('\u{2135}', 0.0, 0.69444, 0.61111), ('\u{2190}', -0.13313, 0.36687, 1.0),
+    ('\u{2191}', 0.19444, 0.69444, 0.5), ('\u{2192}', -0.13313, 0.36687, 1.0), ('\u{2193}', 0.19444, 0.69444, 0.5),
+    ('\u{2194}', -0.13313, 0.36687, 1.0), ('\u{2195}', 0.25, 0.75, 0.5), ('\u{2196}', 0.19444, 0.69444, 1.0),
+    ('\u{2197}', 0.19444, 0.69444, 1.0), ('\u{2198}', 0.19444, 0.69444, 1.0), ('\u{2199}', 0.19444, 0.69444, 1.0),
+    ('\u{21A6}', 0.011, 0.511, 1.0), ('\u{21A9}', 0.011, 0.511, 1.126), ('\u{21AA}', 0.011, 0.511, 1.126),
+    ('\u{21BC}', -0.13313, 0.36687, 1.0), ('\u{21BD}', -0.13313, 0.36687, 1.0), ('\u{21C0}', -0.13313, 0.36687, 1.0),
+    ('\u{21C1}', -0.13313, 0.36687, 1.0), ('\u{21CC}', 0.011, 0.671, 1.0), ('\u{21D0}', -0.13313, 0.36687, 1.0),
+    ('\u{21D1}', 0.19444, 0.69444, 0.61111), ('\u{21D2}', -0.13313, 0.36687, 1.0), ('\u{21D3}', 0.19444, 0.69444, 0.61111),
+    ('\u{21D4}', -0.13313, 0.36687, 1.0), ('\u{21D5}', 0.25, 0.75, 0.61111), ('\u{2200}', 0.0, 0.69444, 0.55556),
+    ('\u{2202}', 0.0, 0.69444, 0.5309), ('\u{2203}', 0.0, 0.69444, 0.55556), ('\u{2205}', 0.05556, 0.75, 0.5),
+    ('\u{2207}', 0.0, 0.68333, 0.83334), ('\u{2208}', 0.0391, 0.5391, 0.66667), ('\u{220B}', 0.0391, 0.5391, 0.66667),
+    ('\u{2212}', 0.08333, 0.58333, 0.77778), ('\u{2213}', 0.08333, 0.58333, 0.77778), ('\u{2215}', 0.25, 0.75, 0.5),
+    ('\u{2216}', 0.25, 0.75, 0.5), ('\u{2217}', -0.03472, 0.46528, 0.5), ('\u{2218}', -0.05555, 0.44445, 0.5),
+    ('\u{2219}', -0.05555, 0.44445, 0.5), ('\u{221A}', 0.2, 0.8, 0.83334), ('\u{221D}', 0.0, 0.43056, 0.77778),
+    ('\u{221E}', 0.0, 0.43056, 1.0), ('\u{2220}', 0.0, 0.69224, 0.72222), ('\u{2223}', 0.25, 0.75, 0.27778),
+    ('\u{2225}', 0.25, 0.75, 0.5), ('\u{2227}', 0.0, 0.55556, 0.66667), ('\u{2228}', 0.0, 0.55556, 0.66667),
+    ('\u{2229}', 0.0, 0.55556, 0.66667), ('\u{222A}', 0.0, 0.55556, 0.66667), ('\u{222B}', 0.19444, 0.69444, 0.41667),
+    ('\u{223C}', -0.13313, 0.36687, 0.77778), ('\u{2240}', 0.19444, 0.69444, 0.27778), ('\u{2243}', -0.03625, 0.46375, 0.77778),
+    ('\u{2245}', -0.022, 0.589, 0.778), ('\u{2248}', -0.01688, 0.48312, 0.77778), ('\u{224D}', -0.03625, 0.46375, 0.77778),
+    ('\u{2250}', -0.133, 0.673, 0.778), ('\u{2261}', -0.03625, 0.46375, 0.77778), ('\u{2264}', 0.13597, 0.63597, 0.77778),
+    ('\u{2265}', 0.13597, 0.63597, 0.77778), ('\u{226A}', 0.0391, 0.5391, 1.0), ('\u{226B}', 0.0391, 0.5391, 1.0),
+    ('\u{227A}', 0.0391, 0.5391, 0.77778), ('\u{227B}', 0.0391, 0.5391, 0.77778), ('\u{2282}', 0.0391, 0.5391, 0.77778),
+    ('\u{2283}', 0.0391, 0.5391, 0.77778), ('\u{2286}', 0.13597, 0.63597, 0.77778), ('\u{2287}', 0.13597, 0.63597, 0.77778),
+    ('\u{228E}', 0.0, 0.55556, 0.66667), ('\u{2291}', 0.13597, 0.63597, 0.77778), ('\u{2292}', 0.13597, 0.63597, 0.77778),
+    ('\u{2293}', 0.0, 0.55556, 0.66667), ('\u{2294}', 0.0, 0.55556, 0.66667), ('\u{2295}', 0.08333, 0.58333, 0.77778),
+    ('\u{2296}', 0.08333, 0.58333, 0.77778), ('\u{2297}', 0.08333, 0.58333, 0.77778), ('\u{2298}', 0.08333, 0.58333, 0.77778),
+    ('\u{2299}', 0.08333, 0.58333, 0.77778), ('\u{22A2}', 0.0, 0.69444, 0.61111), ('\u{22A3}', 0.0, 0.69444, 0.61111),
+    ('\u{22A4}', 0.0, 0.69444, 0.77778), ('\u{22A5}', 0.0, 0.69444, 0.77778), ('\u{22A8}', 0.249, 0.75, 0.867),
+    ('\u{22C4}', -0.05555, 0.44445, 0.5), ('\u{22C5}', -0.05555, 0.44445, 0.27778), ('\u{22C6}', -0.03472, 0.46528, 0.5),
+    ('\u{22C8}', 0.005, 0.505, 0.9), ('\u{22EE}', 0.03, 0.903, 0.278), ('\u{22EF}', -0.19, 0.313, 1.172),
+    ('\u{22F1}', -0.1, 0.823, 1.282), ('\u{2308}', 0.25, 0.75, 0.44445), ('\u{2309}', 0.25, 0.75, 0.44445),
+    ('\u{230A}', 0.25, 0.75, 0.44445), ('\u{230B}', 0.25, 0.75, 0.44445), ('\u{2322}', -0.14236, 0.35764, 1.0),
+    ('\u{2323}', -0.14236, 0.35764, 1.0), ('\u{23B0}', 0.244, 0.744, 0.412), ('\u{23B1}', 0.244, 0.745, 0.412),
+    ('\u{25B3}', 0.19444, 0.69444, 0.88889), ('\u{25B9}', -0.03472, 0.46528, 0.5), ('\u{25BD}', 0.19444, 0.69444, 0.88889),
+    ('\u{25C3}', -0.03472, 0.46528, 0.5), ('\u{25EF}', 0.19444, 0.69444, 1.0), ('\u{2660}', 0.12963, 0.69444, 0.77778),
+    ('\u{2661}', 0.12963, 0.69444, 0.77778), ('\u{2662}', 0.12963, 0.69444, 0.77778), ('\u{2663}', 0.12963, 0.69444, 0.77778),
+    ('\u{266D}', 0.0, 0.75, 0.38889), ('\u{266E}', 0.19444, 0.69444, 0.38889), ('\u{266F}', 0.19444, 0.69444, 0.38889),
+    ('\u{27E8}', 0.25, 0.75, 0.38889), ('\u{27E9}', 0.25, 0.75, 0.38889), ('\u{27EE}', 0.244, 0.744, 0.412),
+    ('\u{27EF}', 0.244, 0.745, 0.412), ('\u{27F5}', 0.011, 0.511, 1.609), ('\u{27F6}', 0.011, 0.511, 1.638),
+    ('\u{27F7}', 0.011, 0.511, 1.859), ('\u{27F8}', 0.024, 0.525, 1.609), ('\u{27F9}', 0.024, 0.525, 1.638),
+    ('\u{27FA}', 0.024, 0.525, 1.858), ('\u{27FC}', 0.011, 0.511, 1.638), ('\u{2A3F}', 0.0, 0.68333, 0.75),
+    ('\u{2AAF}', 0.13597, 0.63597, 0.77778), ('\u{2AB0}', 0.13597, 0.63597, 0.77778), ('\u{E020}', 0.19444, 0.69444, 0.0),
+];
+
+/// Metrics of `KaTeX_Main-Bold`.
+#[rustfmt::skip]
+pub(super) const MAIN_BOLD: &[Metrics] = &[
+    ('\u{0020}', 0.0, 0.0, 0.25), ('\u{0021}', 0.0, 0.69444, 0.35), ('\u{0022}', 0.0, 0.69444, 0.60278),
+    ('\u{0023}', 0.19444, 0.69444, 0.95833), ('\u{0024}', 0.05556, 0.75, 0.575), ('\u{0025}', 0.05556, 0.75, 0.95833),
+    ('\u{0026}', 0.0, 0.69444, 0.89444), ('\u{0027}', 0.0, 0.69444, 0.31944), ('\u{0028}', 0.25, 0.75, 0.44722),
+    ('\u{0029}', 0.25, 0.75, 0.44722), ('\u{002A}', 0.0, 0.75, 0.575), ('\u{002B}', 0.13333, 0.63333, 0.89444),
+    ('\u{002C}', 0.19444, 0.15556, 0.31944), ('\u{002D}', 0.0, 0.44444, 0.38333), ('\u{002E}', 0.0, 0.15556, 0.31944),
+    ('\u{002F}', 0.25, 0.75, 0.575), ('\u{0030}', 0.0, 0.64444, 0.575), ('\u{0031}', 0.0, 0.64444, 0.575),
+    ('\u{0032}', 0.0, 0.64444, 0.575), ('\u{0033}', 0.0, 0.64444, 0.575), ('\u{0034}', 0.0, 0.64444, 0.575),
+    ('\u{0035}', 0.0, 0.64444, 0.575), ('\u{0036}', 0.0, 0.64444, 0.575), ('\u{0037}', 0.0, 0.64444, 0.575),
+    ('\u{0038}', 0.0, 0.64444, 0.575), ('\u{0039}', 0.0, 0.64444, 0.575), ('\u{003A}', 0.0, 0.44444, 0.31944),
+    ('\u{003B}', 0.19444, 0.44444, 0.31944), ('\u{003C}', 0.08556, 0.58556, 0.89444), ('\u{003D}', -0.10889, 0.39111, 0.89444),
+    ('\u{003E}', 0.08556, 0.58556, 0.89444), ('\u{003F}', 0.0, 0.69444, 0.54305), ('\u{0040}', 0.0, 0.69444, 0.89444),
+    ('\u{0041}', 0.0, 0.68611, 0.86944), ('\u{0042}', 0.0, 0.68611, 0.81805), ('\u{0043}', 0.0, 0.68611, 0.83055),
+    ('\u{0044}', 0.0, 0.68611, 0.88194), ('\u{0045}', 0.0, 0.68611, 0.75555), ('\u{0046}', 0.0, 0.68611, 0.72361),
+    ('\u{0047}', 0.0, 0.68611, 0.90416), ('\u{0048}', 0.0, 0.68611, 0.9), ('\u{0049}', 0.0, 0.68611, 0.43611),
+    ('\u{004A}', 0.0, 0.68611, 0.59444), ('\u{004B}', 0.0, 0.68611, 0.90138), ('\u{004C}', 0.0, 0.68611, 0.69166),
+    ('\u{004D}', 0.0, 0.68611, 1.09166), ('\u{004E}', 0.0, 0.68611, 0.9), ('\u{004F}', 0.0, 0.68611, 0.86388),
+    ('\u{0050}', 0.0, 0.68611, 0.78611), ('\u{0051}', 0.19444, 0.68611, 0.86388), ('\u{0052}', 0.0, 0.68611, 0.8625),
+    ('\u{0053}', 0.0, 0.68611, 0.63889), ('\u{0054}', 0.0, 0.68611, 0.8), ('\u{0055}', 0.0, 0.68611, 0.88472),
+    ('\u{0056}', 0.0, 0.68611, 0.86944), ('\u{0057}', 0.0, 0.68611, 1.18888), ('\u{0058}', 0.0, 0.68611, 0.86944),
+    ('\u{0059}', 0.0, 0.68611, 0.86944), ('\u{005A}', 0.0, 0.68611, 0.70277), ('\u{005B}', 0.25, 0.75, 0.31944),
+    ('\u{005C}', 0.25, 0.75, 0.575), ('\u{005D}', 0.25, 0.75, 0.31944), ('\u{005E}', 0.0, 0.69444, 0.575),
+    ('\u{005F}', 0.31, 0.13444, 0.575), ('\u{0061}', 0.0, 0.44444, 0.55902), ('\u{0062}', 0.0, 0.69444, 0.63889),
+    ('\u{0063}', 0.0, 0.44444, 0.51111), ('\u{0064}', 0.0, 0.69444, 0.63889), ('\u{0065}', 0.0, 0.44444, 0.52708),
+    ('\u{0066}', 0.0, 0.69444, 0.35139), ('\u{0067}', 0.19444, 0.44444, 0.575), ('\u{0068}', 0.0, 0.69444, 0.63889),
+    ('\u{0069}', 0.0, 0.69444, 0.31944), ('\u{006A}', 0.19444, 0.69444, 0.35139), ('\u{006B}', 0.0, 0.69444, 0.60694),
+    ('\u{006C}', 0.0, 0.69444, 0.31944), ('\u{006D}', 0.0, 0.44444, 0.95833), ('\u{006E}', 0.0, 0.44444, 0.63889),
+    ('\u{006F}', 0.0, 0.44444, 0.575), ('\u{0070}', 0.19444, 0.44444, 0.63889), ('\u{0071}', 0.19444, 0.44444, 0.60694),
+    ('\u{0072}', 0.0, 0.44444, 0.47361), ('\u{0073}', 0.0, 0.44444, 0.45361), ('\u{0074}', 0.0, 0.63492, 0.44722),
+    ('\u{0075}', 0.0, 0.44444, 0.63889), ('\u{0076}', 0.0, 0.44444, 0.60694), ('\u{0077}', 0.0, 0.44444, 0.83055),
+    ('\u{0078}', 0.0, 0.44444, 0.60694), ('\u{0079}', 0.19444, 0.44444, 0.60694), ('\u{007A}', 0.0, 0.44444, 0.51111),
+    ('\u{007B}', 0.25, 0.75, 0.575), ('\u{007C}', 0.25, 0.75, 0.31944), ('\u{007D}', 0.25, 0.75, 0.575),
+    ('\u{007E}', 0.35, 0.34444, 0.575), ('\u{00A0}', 0.0, 0.0, 0.25), ('\u{00A3}', 0.0, 0.69444, 0.86853),
+    ('\u{00A8}', 0.0, 0.69444, 0.575), ('\u{00AC}', 0.0, 0.44444, 0.76666), ('\u{00B0}', 0.0, 0.69444, 0.86944),
+    ('\u{00B1}', 0.13333, 0.63333, 0.89444), ('\u{00B8}', 0.17014, 0.0, 0.51111), ('\u{00C6}', 0.0, 0.68611, 1.04166),
+    ('\u{00D7}', 0.13333, 0.63333, 0.89444), ('\u{00D8}', 0.04861, 0.73472, 0.89444), ('\u{00DF}', 0.0, 0.69444, 0.59722),
+    ('\u{00E6}', 0.0, 0.44444, 0.83055), ('\u{00F7}', 0.13333, 0.63333, 0.89444), ('\u{00F8}', 0.09722, 0.54167, 0.575),
+    ('\u{0131}', 0.0, 0.44444, 0.31944), ('\u{0152}', 0.0, 0.68611, 1.16944), ('\u{0153}', 0.0, 0.44444, 0.89444),
+    ('\u{0237}', 0.19444, 0.44444, 0.35139), ('\u{02C6}', 0.0, 0.69444, 0.575), ('\u{02C7}', 0.0, 0.63194, 0.575),
+    ('\u{02C9}', 0.0, 0.59611, 0.575), ('\u{02CA}', 0.0, 0.69444, 0.575), ('\u{02CB}', 0.0, 0.69444, 0.575),
+    ('\u{02D8}', 0.0, 0.69444, 0.575), ('\u{02D9}', 0.0, 0.69444, 0.31944), ('\u{02DA}', 0.0, 0.69444, 0.86944),
+    ('\u{02DC}', 0.0, 0.69444, 0.575), ('\u{02DD}', 0.0, 0.69444, 0.575), ('\u{0393}', 0.0, 0.68611, 0.69166),
+    ('\u{0394}', 0.0, 0.68611, 0.95833), ('\u{0398}', 0.0, 0.68611, 0.89444), ('\u{039B}', 0.0, 0.68611, 0.80555),
+    ('\u{039E}', 0.0, 0.68611, 0.76666), ('\u{03A0}', 0.0, 0.68611, 0.9), ('\u{03A3}', 0.0, 0.68611, 0.83055),
+    ('\u{03A5}', 0.0, 0.68611, 0.89444), ('\u{03A6}', 0.0, 0.68611, 0.83055), ('\u{03A8}', 0.0, 0.68611, 0.89444),
+    ('\u{03A9}', 0.0, 0.68611, 0.83055), ('\u{2013}', 0.0, 0.44444, 0.575), ('\u{2014}', 0.0, 0.44444, 1.14999),
+    ('\u{2018}', 0.0, 0.69444, 0.31944), ('\u{2019}', 0.0, 0.69444, 0.31944), ('\u{201C}', 0.0, 0.69444, 0.60278),
+    ('\u{201D}', 0.0, 0.69444, 0.60278), ('\u{2020}', 0.19444, 0.69444, 0.51111), ('\u{2021}', 0.19444, 0.69444, 0.51111),
+    ('\u{2032}', 0.0, 0.55556, 0.34444), ('\u{20D7}', 0.0, 0.72444, 0.575), ('\u{210F}', 0.0, 0.69444, 0.66759),
+    ('\u{2111}', 0.0, 0.69444, 0.83055), ('\u{2113}', 0.0, 0.69444, 0.47361), ('\u{2118}', 0.19444, 0.44444, 0.74027),
+    ('\u{211C}', 0.0, 0.69444, 0.83055), ('\u{2135}', 0.0, 0.69444, 0.70277), ('\u{2190}', -0.10889, 0.39111, 1.14999),
+    ('\u{2191}', 0.19444, 0.69444, 0.575), ('\u{2192}', -0.10889, 0.39111, 1.14999), ('\u{2193}', 0.19444, 0.69444, 0.575),
+    ('\u{2194}', -0.10889, 0.39111, 1.14999), ('\u{2195}', 0.25, 0.75, 0.575), ('\u{2196}', 0.19444, 0.69444, 1.14999),
+    ('\u{2197}', 0.19444, 0.69444, 1.14999), ('\u{2198}', 0.19444, 0.69444, 1.14999), ('\u{2199}', 0.19444, 0.69444, 1.14999),
+    ('\u{21BC}', -0.10889, 0.39111, 1.14999), ('\u{21BD}', -0.10889, 0.39111, 1.14999), ('\u{21C0}', -0.10889, 0.39111, 1.14999),
+    ('\u{21C1}', -0.10889, 0.39111, 1.14999), ('\u{21D0}', -0.10889, 0.39111, 1.14999), ('\u{21D1}', 0.19444, 0.69444, 0.70277),
+    ('\u{21D2}', -0.10889, 0.39111, 1.14999), ('\u{21D3}', 0.19444, 0.69444, 0.70277), ('\u{21D4}', -0.10889, 0.39111, 1.14999),
+    ('\u{21D5}', 0.25, 0.75, 0.70277), ('\u{2200}', 0.0, 0.69444, 0.63889), ('\u{2202}', 0.0, 0.69444, 0.62847),
+    ('\u{2203}', 0.0, 0.69444, 0.63889), ('\u{2205}', 0.05556, 0.75, 0.575), ('\u{2207}', 0.0, 0.68611, 0.95833),
+    ('\u{2208}', 0.08556, 0.58556, 0.76666), ('\u{220B}', 0.08556, 0.58556, 0.76666), ('\u{2212}', 0.13333, 0.63333, 0.89444),
+    ('\u{2213}', 0.13333, 0.63333, 0.89444), ('\u{2215}', 0.25, 0.75, 0.575), ('\u{2216}', 0.25, 0.75, 0.575),
+    ('\u{2217}', -0.02778, 0.47222, 0.575), ('\u{2218}', -0.02639, 0.47361, 0.575), ('\u{2219}', -0.02639, 0.47361, 0.575),
+    ('\u{221A}', 0.18, 0.82, 0.95833), ('\u{221D}', 0.0, 0.44444, 0.89444), ('\u{221E}', 0.0, 0.44444, 1.14999),
+    ('\u{2220}', 0.0, 0.69224, 0.72222), ('\u{2223}', 0.25, 0.75, 0.31944), ('\u{2225}', 0.25, 0.75, 0.575),
+    ('\u{2227}', 0.0, 0.55556, 0.76666), ('\u{2228}', 0.0, 0.55556, 0.76666), ('\u{2229}', 0.0, 0.55556, 0.76666),
+    ('\u{222A}', 0.0, 0.55556, 0.76666), ('\u{222B}', 0.19444, 0.69444, 0.56875), ('\u{223C}', -0.10889, 0.39111, 0.89444),
+    ('\u{2240}', 0.19444, 0.69444, 0.31944), ('\u{2243}', 0.00222, 0.50222, 0.89444), ('\u{2245}', 0.027, 0.638, 0.894),
+    ('\u{2248}', 0.02444, 0.52444, 0.89444), ('\u{224D}', 0.00222, 0.50222, 0.89444), ('\u{2261}', 0.00222, 0.50222, 0.89444),
+    ('\u{2264}', 0.19667, 0.69667, 0.89444), ('\u{2265}', 0.19667, 0.69667, 0.89444), ('\u{226A}', 0.08556, 0.58556, 1.14999),
+    ('\u{226B}', 0.08556, 0.58556, 1.14999), ('\u{227A}', 0.08556, 0.58556, 0.89444), ('\u{227B}', 0.08556, 0.58556, 0.89444),
+    ('\u{2282}', 0.08556, 0.58556, 0.89444), ('\u{2283}', 0.08556, 0.58556, 0.89444), ('\u{2286}', 0.19667, 0.69667, 0.89444),
+    ('\u{2287}', 0.19667, 0.69667, 0.89444), ('\u{228E}', 0.0, 0.55556, 0.76666), ('\u{2291}', 0.19667, 0.69667, 0.89444),
+    ('\u{2292}', 0.19667, 0.69667, 0.89444), ('\u{2293}', 0.0, 0.55556, 0.76666), ('\u{2294}', 0.0, 0.55556, 0.76666),
+    ('\u{2295}', 0.13333, 0.63333, 0.89444), ('\u{2296}', 0.13333, 0.63333, 0.89444), ('\u{2297}', 0.13333, 0.63333, 0.89444),
+    ('\u{2298}', 0.13333, 0.63333, 0.89444), ('\u{2299}', 0.13333, 0.63333, 0.89444), ('\u{22A2}', 0.0, 0.69444, 0.70277),
+    ('\u{22A3}', 0.0, 0.69444, 0.70277), ('\u{22A4}', 0.0, 0.69444, 0.89444), ('\u{22A5}', 0.0, 0.69444, 0.89444),
+    ('\u{22C4}', -0.02639, 0.47361, 0.575), ('\u{22C5}', -0.02639, 0.47361, 0.31944), ('\u{22C6}', -0.02778, 0.47222, 0.575),
+    ('\u{2308}', 0.25, 0.75, 0.51111), ('\u{2309}', 0.25, 0.75, 0.51111), ('\u{230A}', 0.25, 0.75, 0.51111),
+    ('\u{230B}', 0.25, 0.75, 0.51111), ('\u{2322}', -0.13889, 0.36111, 1.14999), ('\u{2323}', -0.13889, 0.36111, 1.14999),
+    ('\u{25B3}', 0.19444, 0.69444, 1.02222), ('\u{25B9}', -0.02778, 0.47222, 0.575), ('\u{25BD}', 0.19444, 0.69444, 1.02222),
+    ('\u{25C3}', -0.02778, 0.47222, 0.575), ('\u{25EF}', 0.19444, 0.69444, 1.14999), ('\u{2660}', 0.12963, 0.69444, 0.89444),
+    ('\u{2661}', 0.12963, 0.69444, 0.89444), ('\u{2662}', 0.12963, 0.69444, 0.89444), ('\u{2663}', 0.12963, 0.69444, 0.89444),
+    ('\u{266D}', 0.0, 0.75, 0.44722), ('\u{266E}', 0.19444, 0.69444, 0.44722), ('\u{266F}', 0.19444, 0.69444, 0.44722),
+    ('\u{27E8}', 0.25, 0.75, 0.44722), ('\u{27E9}', 0.25, 0.75, 0.44722), ('\u{2A3F}', 0.0, 0.68611, 0.9),
+    ('\u{2AAF}', 0.19667, 0.69667, 0.89444), ('\u{2AB0}', 0.19667, 0.69667, 0.89444), ('\u{E020}', 0.19444, 0.69444, 0.0),
+];
+
+/// Metrics of `KaTeX_Math-Italic`.
+#[rustfmt::skip]
+pub(super) const MATH_ITALIC: &[Metrics] = &[
+    ('\u{0020}', 0.0, 0.0, 0.25), ('\u{0030}', 0.0, 0.43056, 0.5), ('\u{0031}', 0.0, 0.43056, 0.5),
+    ('\u{0032}', 0.0, 0.43056, 0.5), ('\u{0033}', 0.19444, 0.43056, 0.5), ('\u{0034}', 0.19444, 0.43056, 0.5),
+    ('\u{0035}', 0.19444, 0.43056, 0.5), ('\u{0036}', 0.0, 0.64444, 0.5), ('\u{0037}', 0.19444, 0.43056, 0.5),
+    ('\u{0038}', 0.0, 0.64444, 0.5), ('\u{0039}', 0.19444, 0.43056, 0.5), ('\u{0041}', 0.0, 0.68333, 0.75),
+    ('\u{0042}', 0.0, 0.68333, 0.75851), ('\u{0043}', 0.0, 0.68333, 0.71472), ('\u{0044}', 0.0, 0.68333, 0.82792),
+    ('\u{0045}', 0.0, 0.68333, 0.7382), ('\u{0046}', 0.0, 0.68333, 0.64306), ('\u{0047}', 0.0, 0.68333, 0.78625),
+    ('\u{0048}', 0.0, 0.68333, 0.83125), ('\u{0049}', 0.0, 0.68333, 0.43958), ('\u{004A}', 0.0, 0.68333, 0.55451),
+    ('\u{004B}', 0.0, 0.68333, 0.84931), ('\u{004C}', 0.0, 0.68333, 0.68056), ('\u{004D}', 0.0, 0.68333, 0.97014),
+    ('\u{004E}', 0.0, 0.68333, 0.80347), ('\u{004F}', 0.0, 0.68333, 0.76278), ('\u{0050}', 0.0, 0.68333, 0.64201),
+    ('\u{0051}', 0.19444, 0.68333, 0.79056), ('\u{0052}', 0.0, 0.68333, 0.75929), ('\u{0053}', 0.0, 0.68333, 0.6132),
+    ('\u{0054}', 0.0, 0.68333, 0.58438), ('\u{0055}', 0.0, 0.68333, 0.68278), ('\u{0056}', 0.0, 0.68333, 0.58333),
+    ('\u{0057}', 0.0, 0.68333, 0.94445), ('\u{0058}', 0.0, 0.68333, 0.82847), ('\u{0059}', 0.0, 0.68333, 0.58056),
+    ('\u{005A}', 0.0, 0.68333, 0.68264), ('\u{0061}', 0.0, 0.43056, 0.52859), ('\u{0062}', 0.0, 0.69444, 0.42917),
+    ('\u{0063}', 0.0, 0.43056, 0.43276), ('\u{0064}', 0.0, 0.69444, 0.52049), ('\u{0065}', 0.0, 0.43056, 0.46563),
+    ('\u{0066}', 0.19444, 0.69444, 0.48959), ('\u{0067}', 0.19444, 0.43056, 0.47697), ('\u{0068}', 0.0, 0.69444, 0.57616),
+    ('\u{0069}', 0.0, 0.65952, 0.34451), ('\u{006A}', 0.19444, 0.65952, 0.41181), ('\u{006B}', 0.0, 0.69444, 0.5206),
+    ('\u{006C}', 0.0, 0.69444, 0.29838), ('\u{006D}', 0.0, 0.43056, 0.87801), ('\u{006E}', 0.0, 0.43056, 0.60023),
+    ('\u{006F}', 0.0, 0.43056, 0.48472), ('\u{0070}', 0.19444, 0.43056, 0.50313), ('\u{0071}', 0.19444, 0.43056, 0.44641),
+    ('\u{0072}', 0.0, 0.43056, 0.45116), ('\u{0073}', 0.0, 0.43056, 0.46875), ('\u{0074}', 0.0, 0.61508, 0.36111),
+    ('\u{0075}', 0.0, 0.43056, 0.57246), ('\u{0076}', 0.0, 0.43056, 0.48472), ('\u{0077}', 0.0, 0.43056, 0.71592),
+    ('\u{0078}', 0.0, 0.43056, 0.57153), ('\u{0079}', 0.19444, 0.43056, 0.49028), ('\u{007A}', 0.0, 0.43056, 0.46505),
+    ('\u{00A0}', 0.0, 0.0, 0.25), ('\u{0393}', 0.0, 0.68333, 0.61528), ('\u{0394}', 0.0, 0.68333, 0.83334),
+    ('\u{0398}', 0.0, 0.68333, 0.76278), ('\u{039B}', 0.0, 0.68333, 0.69445), ('\u{039E}', 0.0, 0.68333, 0.74236),
+    ('\u{03A0}', 0.0, 0.68333, 0.83125), ('\u{03A3}', 0.0, 0.68333, 0.77986), ('\u{03A5}', 0.0, 0.68333, 0.58333),
+    ('\u{03A6}', 0.0, 0.68333, 0.66667), ('\u{03A8}', 0.0, 0.68333, 0.61222), ('\u{03A9}', 0.0, 0.68333, 0.7724),
+    ('\u{03B1}', 0.0, 0.43056, 0.6397), ('\u{03B2}', 0.19444, 0.69444, 0.56563), ('\u{03B3}', 0.19444, 0.43056, 0.51773),
+    ('\u{03B4}', 0.0, 0.69444, 0.44444), ('\u{03B5}', 0.0, 0.43056, 0.46632), ('\u{03B6}', 0.19444, 0.69444, 0.4375),
+    ('\u{03B7}', 0.19444, 0.43056, 0.49653), ('\u{03B8}', 0.0, 0.69444, 0.46944), ('\u{03B9}', 0.0, 0.43056, 0.35394),
+    ('\u{03BA}', 0.0, 0.43056, 0.57616), ('\u{03BB}', 0.0, 0.69444, 0.58334), ('\u{03BC}', 0.19444, 0.43056, 0.60255),
+    ('\u{03BD}', 0.0, 0.43056, 0.49398), ('\u{03BE}', 0.19444, 0.69444, 0.4375), ('\u{03BF}', 0.0, 0.43056, 0.48472),
+    ('\u{03C0}', 0.0, 0.43056, 0.57003), ('\u{03C1}', 0.19444, 0.43056, 0.51702), ('\u{03C2}', 0.09722, 0.43056, 0.36285),
+    ('\u{03C3}', 0.0, 0.43056, 0.57141), ('\u{03C4}', 0.0, 0.43056, 0.43715), ('\u{03C5}', 0.0, 0.43056, 0.54028),
+    ('\u{03C6}', 0.19444, 0.43056, 0.65417), ('\u{03C7}', 0.19444, 0.43056, 0.62569), ('\u{03C8}', 0.19444, 0.69444, 0.65139),
+    ('\u{03C9}', 0.0, 0.43056, 0.62245), ('\u{03D1}', 0.0, 0.69444, 0.59144), ('\u{03D5}', 0.19444, 0.69444, 0.59583),
+    ('\u{03D6}', 0.0, 0.43056, 0.82813), ('\u{03F1}', 0.19444, 0.43056, 0.51702), ('\u{03F5}', 0.0, 0.43056, 0.4059),
+    ('\u{E131}', 0.0, 0.43056, 0.32246), ('\u{E237}', 0.19444, 0.43056, 0.38403),
+];
+
+/// Metrics of `KaTeX_Math-BoldItalic`.
+#[rustfmt::skip]
+pub(super) const MATH_BOLD_ITALIC: &[Metrics] = &[
+    ('\u{0020}', 0.0, 0.0, 0.25), ('\u{0030}', 0.0, 0.44444, 0.575), ('\u{0031}', 0.0, 0.44444, 0.575),
+    ('\u{0032}', 0.0, 0.44444, 0.575), ('\u{0033}', 0.19444, 0.44444, 0.575), ('\u{0034}', 0.19444, 0.44444, 0.575),
+    ('\u{0035}', 0.19444, 0.44444, 0.575), ('\u{0036}', 0.0, 0.64444, 0.575), ('\u{0037}', 0.19444, 0.44444, 0.575),
+    ('\u{0038}', 0.0, 0.64444, 0.575), ('\u{0039}', 0.19444, 0.44444, 0.575), ('\u{0041}', 0.0, 0.68611, 0.86944),
+    ('\u{0042}', 0.0, 0.68611, 0.8664), ('\u{0043}', 0.0, 0.68611, 0.81694), ('\u{0044}', 0.0, 0.68611, 0.93812),
+    ('\u{0045}', 0.0, 0.68611, 0.81007), ('\u{0046}', 0.0, 0.68611, 0.68889), ('\u{0047}', 0.0, 0.68611, 0.88673),
+    ('\u{0048}', 0.0, 0.68611, 0.98229), ('\u{0049}', 0.0, 0.68611, 0.51111), ('\u{004A}', 0.0, 0.68611, 0.63125),
+    ('\u{004B}', 0.0, 0.68611, 0.97118), ('\u{004C}', 0.0, 0.68611, 0.75555), ('\u{004D}', 0.0, 0.68611, 1.14201),
+    ('\u{004E}', 0.0, 0.68611, 0.95034), ('\u{004F}', 0.0, 0.68611, 0.83666), ('\u{0050}', 0.0, 0.68611, 0.72309),
+    ('\u{0051}', 0.19444, 0.68611, 0.86861), ('\u{0052}', 0.0, 0.68611, 0.87235), ('\u{0053}', 0.0, 0.68611, 0.69271),
+    ('\u{0054}', 0.0, 0.68611, 0.63663), ('\u{0055}', 0.0, 0.68611, 0.80027), ('\u{0056}', 0.0, 0.68611, 0.67778),
+    ('\u{0057}', 0.0, 0.68611, 1.09305), ('\u{0058}', 0.0, 0.68611, 0.94722), ('\u{0059}', 0.0, 0.68611, 0.67458),
+    ('\u{005A}', 0.0, 0.68611, 0.77257), ('\u{0061}', 0.0, 0.44444, 0.63287), ('\u{0062}', 0.0, 0.69444, 0.52083),
+    ('\u{0063}', 0.0, 0.44444, 0.51342), ('\u{0064}', 0.0, 0.69444, 0.60972), ('\u{0065}', 0.0, 0.44444, 0.55361),
+    ('\u{0066}', 0.19444, 0.69444, 0.56806), ('\u{0067}', 0.19444, 0.44444, 0.5449), ('\u{0068}', 0.0, 0.69444, 0.66759),
+    ('\u{0069}', 0.0, 0.69326, 0.4048), ('\u{006A}', 0.19444, 0.69326, 0.47083), ('\u{006B}', 0.0, 0.69444, 0.6037),
+    ('\u{006C}', 0.0, 0.69444, 0.34815), ('\u{006D}', 0.0, 0.44444, 1.0324), ('\u{006E}', 0.0, 0.44444, 0.71296),
+    ('\u{006F}', 0.0, 0.44444, 0.58472), ('\u{0070}', 0.19444, 0.44444, 0.60092), ('\u{0071}', 0.19444, 0.44444, 0.54213),
+    ('\u{0072}', 0.0, 0.44444, 0.5287), ('\u{0073}', 0.0, 0.44444, 0.53125), ('\u{0074}', 0.0, 0.63492, 0.41528),
+    ('\u{0075}', 0.0, 0.44444, 0.68102), ('\u{0076}', 0.0, 0.44444, 0.56666), ('\u{0077}', 0.0, 0.44444, 0.83148),
+    ('\u{0078}', 0.0, 0.44444, 0.65903), ('\u{0079}', 0.19444, 0.44444, 0.59028), ('\u{007A}', 0.0, 0.44444, 0.55509),
+    ('\u{00A0}', 0.0, 0.0, 0.25), ('\u{0393}', 0.0, 0.68611, 0.65694), ('\u{0394}', 0.0, 0.68611, 0.95833),
+    ('\u{0398}', 0.0, 0.68611, 0.86722), ('\u{039B}', 0.0, 0.68611, 0.80555), ('\u{039E}', 0.0, 0.68611, 0.84125),
+    ('\u{03A0}', 0.0, 0.68611, 0.98229), ('\u{03A3}', 0.0, 0.68611, 0.88507), ('\u{03A5}', 0.0, 0.68611, 0.67083),
+    ('\u{03A6}', 0.0, 0.68611, 0.76666), ('\u{03A8}', 0.0, 0.68611, 0.71402), ('\u{03A9}', 0.0, 0.68611, 0.8789),
+    ('\u{03B1}', 0.0, 0.44444, 0.76064), ('\u{03B2}', 0.19444, 0.69444, 0.65972), ('\u{03B3}', 0.19444, 0.44444, 0.59003),
+    ('\u{03B4}', 0.0, 0.69444, 0.52222), ('\u{03B5}', 0.0, 0.44444, 0.52882), ('\u{03B6}', 0.19444, 0.69444, 0.50833),
+    ('\u{03B7}', 0.19444, 0.44444, 0.6), ('\u{03B8}', 0.0, 0.69444, 0.5618), ('\u{03B9}', 0.0, 0.44444, 0.41204),
+    ('\u{03BA}', 0.0, 0.44444, 0.66759), ('\u{03BB}', 0.0, 0.69444, 0.67083), ('\u{03BC}', 0.19444, 0.44444, 0.70787),
+    ('\u{03BD}', 0.0, 0.44444, 0.57685), ('\u{03BE}', 0.19444, 0.69444, 0.50833), ('\u{03BF}', 0.0, 0.44444, 0.58472),
+    ('\u{03C0}', 0.0, 0.44444, 0.68241), ('\u{03C1}', 0.19444, 0.44444, 0.6118), ('\u{03C2}', 0.09722, 0.44444, 0.42361),
+    ('\u{03C3}', 0.0, 0.44444, 0.68588), ('\u{03C4}', 0.0, 0.44444, 0.52083), ('\u{03C5}', 0.0, 0.44444, 0.63055),
+    ('\u{03C6}', 0.19444, 0.44444, 0.74722), ('\u{03C7}', 0.19444, 0.44444, 0.71805), ('\u{03C8}', 0.19444, 0.69444, 0.75833),
+    ('\u{03C9}', 0.0, 0.44444, 0.71782), ('\u{03D1}', 0.0, 0.69444, 0.69155), ('\u{03D5}', 0.19444, 0.69444, 0.7125),
+    ('\u{03D6}', 0.0, 0.44444, 0.975), ('\u{03F1}', 0.19444, 0.44444, 0.6118), ('\u{03F5}', 0.0, 0.44444, 0.48333),
+    ('\u{E131}', 0.0, 0.44444, 0.39352), ('\u{E237}', 0.19444, 0.44444, 0.43889),
+];
+
+/// Metrics of `KaTeX_AMS-Regular`.
+#[rustfmt::skip]
+pub(super) const AMS_REGULAR: &[Metrics] = &[
+    ('\u{0020}', 0.0, 0.0, 0.25), ('\u{0041}', 0.0, 0.68889, 0.72222), ('\u{0042}', 0.0, 0.68889, 0.66667),
+    ('\u{0043}', 0.0, 0.68889, 0.72222), ('\u{0044}', 0.0, 0.68889, 0.72222), ('\u{0045}', 0.0, 0.68889, 0.66667),
+    ('\u{0046}', 0.0, 0.68889, 0.61111), ('\u{0047}', 0.0, 0.68889, 0.77778), ('\u{0048}', 0.0, 0.68889, 0.77778),
+    ('\u{0049}', 0.0, 0.68889, 0.38889), ('\u{004A}', 0.16667, 0.68889, 0.5), ('\u{004B}', 0.0, 0.68889, 0.77778),
+    ('\u{004C}', 0.0, 0.68889, 0.66667), ('\u{004D}', 0.0, 0.68889, 0.94445), ('\u{004E}', 0.0, 0.68889, 0.72222),
+    ('\u{004F}', 0.16667, 0.68889, 0.77778), ('\u{0050}', 0.0, 0.68889, 0.61111), ('\u{0051}', 0.16667, 0.68889, 0.77778),
+    ('\u{0052}', 0.0, 0.68889, 0.72222), ('\u{0053}', 0.0, 0.68889, 0.55556), ('\u{0054}', 0.0, 0.68889, 0.66667),
+    ('\u{0055}', 0.0, 0.68889, 0.72222), ('\u{0056}', 0.0, 0.68889, 0.72222), ('\u{0057}', 0.0, 0.68889, 1.0),
+    ('\u{0058}', 0.0, 0.68889, 0.72222), ('\u{0059}', 0.0, 0.68889, 0.72222), ('\u{005A}', 0.0, 0.68889, 0.66667),
+    ('\u{006B}', 0.0, 0.68889, 0.55556), ('\u{00A0}', 0.0, 0.0, 0.25), ('\u{00A5}', 0.0, 0.675, 0.75),
+    ('\u{00AE}', 0.15559, 0.69224, 0.94666), ('\u{00F0}', 0.0, 0.68889, 0.55556), ('\u{0127}', 0.0, 0.68889, 0.54028),
+    ('\u{02C6}', 0.0, 0.825, 2.33334), ('\u{02DC}', 0.0, 0.9, 2.33334), ('\u{0302}', 0.0, 0.825, 2.33334),
+    ('\u{0303}', 0.0, 0.9, 2.33334), ('\u{03DD}', 0.08167, 0.58167, 0.77778), ('\u{03F0}', 0.0, 0.43056, 0.66667),
+    ('\u{2035}', 0.0, 0.54986, 0.275), ('\u{210F}', 0.0, 0.68889, 0.54028), ('\u{2127}', 0.0, 0.68889, 0.72222),
+    ('\u{2132}', 0.0, 0.68889, 0.55556), ('\u{2136}', 0.0, 0.68889, 0.66667), ('\u{2137}', 0.0, 0.68889, 0.44445),
+    ('\u{2138}', 0.0, 0.68889, 0.66667), ('\u{2141}', 0.0, 0.68889, 0.63889), ('\u{2190}', -0.03598, 0.46402, 0.5),
+    ('\u{2192}', -0.03598, 0.46402, 0.5), ('\u{219A}', -0.13313, 0.36687, 1.0), ('\u{219B}', -0.13313, 0.36687, 1.0),
+    ('\u{219E}', 0.01354, 0.52239, 1.0), ('\u{21A0}', 0.01354, 0.52239, 1.0), ('\u{21A2}', 0.01354, 0.52239, 1.11111),
+    ('\u{21A3}', 0.01354, 0.52239, 1.11111), ('\u{21AB}', 0.0, 0.54986, 1.0), ('\u{21AC}', 0.0, 0.54986, 1.0),
+    ('\u{21AD}', -0.13313, 0.37788, 1.38889), ('\u{21AE}', -0.13313, 0.36687, 1.0), ('\u{21B0}', 0.0, 0.69224, 0.5),
+    ('\u{21B1}', 0.0, 0.69224, 0.5), ('\u{21B6}', 0.0, 0.43056, 1.0), ('\u{21B7}', 0.0, 0.43056, 1.0),
+    ('\u{21BA}', 0.08198, 0.58198, 0.77778), ('\u{21BB}', 0.08198, 0.58198, 0.77778), ('\u{21BE}', 0.19444, 0.69224, 0.41667),
+    ('\u{21BF}', 0.19444, 0.69224, 0.41667), ('\u{21C2}', 0.19444, 0.69224, 0.41667), ('\u{21C3}', 0.19444, 0.69224, 0.41667),
+    ('\u{21C4}', 0.1808, 0.675, 1.0), ('\u{21C6}', 0.1808, 0.675, 1.0), ('\u{21C7}', 0.1808, 0.675, 1.0),
+    ('\u{21C8}', 0.19444, 0.69224, 0.83334), ('\u{21C9}', 0.1808, 0.675, 1.0), ('\u{21CA}', 0.19444, 0.69224, 0.83334),
+    ('\u{21CB}', 0.01354, 0.52239, 1.0), ('\u{21CC}', 0.01354, 0.52239, 1.0), ('\u{21CD}', -0.13313, 0.36687, 1.0),
+    ('\u{21CE}', -0.13313, 0.36687, 1.0), ('\u{21CF}', -0.13313, 0.36687, 1.0), ('\u{21DA}', 0.13667, 0.63667, 1.0),
+    ('\u{21DB}', 0.13667, 0.63667, 1.0), ('\u{21DD}', -0.13313, 0.37788, 1.0), ('\u{21E0}', -0.064, 0.437, 1.334),
+    ('\u{21E2}', -0.064, 0.437, 1.334), ('\u{2201}', 0.0, 0.825, 0.5), ('\u{2204}', 0.0, 0.68889, 0.55556),
+    ('\u{2205}', 0.08167, 0.58167, 0.77778), ('\u{220D}', 0.0, 0.43056, 0.42917), ('\u{2212}', -0.03598, 0.46402, 0.5),
+    ('\u{2214}', 0.08198, 0.69224, 0.77778), ('\u{2216}', 0.08167, 0.58167, 0.77778), ('\u{221D}', 0.0, 0.69224, 0.77778),
+    ('\u{2220}', 0.0, 0.69224, 0.72222), ('\u{2221}', 0.0, 0.69224, 0.72222), ('\u{2222}', 0.03517, 0.52239, 0.72222),
+    ('\u{2223}', 0.08167, 0.58167, 0.22222), ('\u{2224}', 0.25142, 0.74111, 0.27778), ('\u{2225}', 0.08167, 0.58167, 0.38889),
+    ('\u{2226}', 0.25142, 0.74111, 0.5), ('\u{2234}', 0.0, 0.69224, 0.66667), ('\u{2235}', 0.0, 0.69224, 0.66667),
+    ('\u{223C}', -0.13313, 0.36687, 0.77778), ('\u{223D}', -0.13313, 0.37788, 0.77778), ('\u{2241}', -0.13313, 0.36687, 0.77778),
+    ('\u{2242}', -0.03625, 0.46375, 0.77778), ('\u{2246}', 0.30274, 0.79383, 0.77778), ('\u{2248}', -0.01688, 0.48312, 0.77778),
+    ('\u{224A}', 0.08167, 0.58167, 0.77778), ('\u{224E}', 0.06062, 0.54986, 0.77778), ('\u{224F}', 0.06062, 0.54986, 0.77778),
+    ('\u{2251}', 0.08198, 0.58198, 0.77778), ('\u{2252}', 0.08198, 0.58198, 0.77778), ('\u{2253}', 0.08198, 0.58198, 0.77778),
+    ('\u{2256}', 0.0, 0.69224, 0.77778), ('\u{2257}', 0.22958, 0.72958, 0.77778), ('\u{225C}', 0.08198, 0.91667, 0.77778),
+    ('\u{2266}', 0.25583, 0.75583, 0.77778), ('\u{2267}', 0.25583, 0.75583, 0.77778), ('\u{2268}', 0.25142, 0.75726, 0.77778),
+    ('\u{2269}', 0.25142, 0.75726, 0.77778), ('\u{226C}', 0.25583, 0.75583, 0.5), ('\u{226E}', 0.20576, 0.70576, 0.77778),
+    ('\u{226F}', 0.20576, 0.70576, 0.77778), ('\u{2270}', 0.30274, 0.79383, 0.77778), ('\u{2271}', 0.30274, 0.79383, 0.77778),
+    ('\u{2272}', 0.22958, 0.72958, 0.77778), ('\u{2273}', 0.22958, 0.72958, 0.77778), ('\u{2276}', 0.1808, 0.675, 0.77778),
+    ('\u{2277}', 0.1808, 0.675, 0.77778), ('\u{227C}', 0.13667, 0.63667, 0.77778), ('\u{227D}', 0.13667, 0.63667, 0.77778),
+    ('\u{227E}', 0.22958, 0.72958, 0.77778), ('\u{227F}', 0.22958, 0.72958, 0.77778), ('\u{2280}', 0.20576, 0.70576, 0.77778),
+    ('\u{2281}', 0.20576, 0.70576, 0.77778), ('\u{2288}', 0.30274, 0.79383, 0.77778), ('\u{2289}', 0.30274, 0.79383, 0.77778),
+    ('\u{228A}', 0.13597, 0.63597, 0.77778), ('\u{228B}', 0.13597, 0.63597, 0.77778), ('\u{228F}', 0.03517, 0.54986, 0.77778),
+    ('\u{2290}', 0.03517, 0.54986, 0.77778), ('\u{229A}', 0.08198, 0.58198, 0.77778), ('\u{229B}', 0.08198, 0.58198, 0.77778),
+    ('\u{229D}', 0.08198, 0.58198, 0.77778), ('\u{229E}', 0.0, 0.675, 0.77778), ('\u{229F}', 0.0, 0.675, 0.77778),
+    ('\u{22A0}', 0.0, 0.675, 0.77778), ('\u{22A1}', 0.0, 0.675, 0.77778), ('\u{22A8}', 0.0, 0.69224, 0.61111),
+    ('\u{22A9}', 0.0, 0.69224, 0.72222), ('\u{22AA}', 0.0, 0.69224, 0.88889), ('\u{22AC}', 0.0, 0.68889, 0.61111),
+    ('\u{22AD}', 0.0, 0.68889, 0.61111), ('\u{22AE}', 0.0, 0.68889, 0.72222), ('\u{22AF}', 0.0, 0.68889, 0.72222),
+    ('\u{22B2}', 0.03517, 0.54986, 0.77778), ('\u{22B3}', 0.03517, 0.54986, 0.77778), ('\u{22B4}', 0.13667, 0.63667, 0.77778),
+    ('\u{22B5}', 0.13667, 0.63667, 0.77778), ('\u{22B8}', 0.0, 0.54986, 1.11111), ('\u{22BA}', 0.19444, 0.43056, 0.55556),
+    ('\u{22BB}', 0.19444, 0.69224, 0.61111), ('\u{22BC}', 0.19444, 0.69224, 0.61111), ('\u{22C5}', 0.0, 0.54986, 0.27778),
+    ('\u{22C7}', 0.08167, 0.58167, 0.77778), ('\u{22C9}', 0.08167, 0.58167, 0.77778), ('\u{22CA}', 0.08167, 0.58167, 0.77778),
+    ('\u{22CB}', 0.0, 0.69224, 0.77778), ('\u{22CC}', 0.0, 0.69224, 0.77778), ('\u{22CD}', -0.03598, 0.46402, 0.77778),
+    ('\u{22CE}', 0.0, 0.54986, 0.76042), ('\u{22CF}', 0.0, 0.54986, 0.76042), ('\u{22D0}', 0.03517, 0.54986, 0.77778),
+    ('\u{22D1}', 0.03517, 0.54986, 0.77778), ('\u{22D2}', 0.0, 0.54986, 0.66667), ('\u{22D3}', 0.0, 0.54986, 0.66667),
+    ('\u{22D4}', 0.0, 0.69224, 0.66667), ('\u{22D6}', 0.0391, 0.5391, 0.77778), ('\u{22D7}', 0.0391, 0.5391, 0.77778),
+    ('\u{22D8}', 0.03517, 0.54986, 1.33334), ('\u{22D9}', 0.03517, 0.54986, 1.33334), ('\u{22DA}', 0.38569, 0.88569, 0.77778),
+    ('\u{22DB}', 0.38569, 0.88569, 0.77778), ('\u{22DE}', 0.13667, 0.63667, 0.77778), ('\u{22DF}', 0.13667, 0.63667, 0.77778),
+    ('\u{22E0}', 0.30274, 0.79383, 0.77778), ('\u{22E1}', 0.30274, 0.79383, 0.77778), ('\u{22E6}', 0.23222, 0.74111, 0.77778),
+    ('\u{22E7}', 0.23222, 0.74111, 0.77778), ('\u{22E8}', 0.23222, 0.74111, 0.77778), ('\u{22E9}', 0.23222, 0.74111, 0.77778),
+    ('\u{22EA}', 0.20576, 0.70576, 0.77778), ('\u{22EB}', 0.20576, 0.70576, 0.77778), ('\u{22EC}', 0.30274, 0.79383, 0.77778),
+    ('\u{22ED}', 0.30274, 0.79383, 0.77778), ('\u{2322}', 0.19444, 0.69224, 0.77778), ('\u{2323}', 0.19444, 0.69224, 0.77778),
+    ('\u{24C8}', 0.15559, 0.69224, 0.90222), ('\u{250C}', 0.0, 0.69224, 0.5), ('\u{2510}', 0.0, 0.69224, 0.5),
+    ('\u{2514}', 0.0, 0.37788, 0.5), ('\u{2518}', 0.0, 0.37788, 0.5), ('\u{2571}', 0.19444, 0.68889, 0.88889),
+    ('\u{2572}', 0.19444, 0.74111, 0.88889), ('\u{25A0}', 0.0, 0.675, 0.77778), ('\u{25A1}', 0.0, 0.675, 0.77778),
+    ('\u{25B2}', 0.0, 0.54986, 0.72222), ('\u{25B3}', 0.0, 0.54986, 0.72222), ('\u{25B6}', 0.03517, 0.54986, 0.77778),
+    ('\u{25BC}', 0.0, 0.54986, 0.72222), ('\u{25BD}', 0.0, 0.54986, 0.72222), ('\u{25C0}', 0.03517, 0.54986, 0.77778),
+    ('\u{25CA}', 0.11111, 0.69224, 0.66667), ('\u{2605}', 0.19444, 0.69224, 0.94445), ('\u{2713}', 0.0, 0.69224, 0.83334),
+    ('\u{2720}', 0.0, 0.69224, 0.83334), ('\u{29EB}', 0.11111, 0.69224, 0.66667), ('\u{2A5E}', 0.19444, 0.75583, 0.61111),
+    ('\u{2A7D}', 0.13667, 0.63667, 0.77778), ('\u{2A7E}', 0.13667, 0.63667, 0.77778), ('\u{2A85}', 0.25583, 0.75583, 0.77778),
+    ('\u{2A86}', 0.25583, 0.75583, 0.77778), ('\u{2A87}', 0.13597, 0.63597, 0.77778), ('\u{2A88}', 0.13597, 0.63597, 0.77778),
+    ('\u{2A89}', 0.26167, 0.75726, 0.77778), ('\u{2A8A}', 0.26167, 0.75726, 0.77778), ('\u{2A8B}', 0.48256, 0.98256, 0.77778),
+    ('\u{2A8C}', 0.48256, 0.98256, 0.77778), ('\u{2A95}', 0.13667, 0.63667, 0.77778), ('\u{2A96}', 0.13667, 0.63667, 0.77778),
+    ('\u{2AB5}', 0.25142, 0.75726, 0.77778), ('\u{2AB6}', 0.25142, 0.75726, 0.77778), ('\u{2AB7}', 0.26167, 0.75726, 0.77778),
+    ('\u{2AB8}', 0.26167, 0.75726, 0.77778), ('\u{2AB9}', 0.26167, 0.75726, 0.77778), ('\u{2ABA}', 0.26167, 0.75726, 0.77778),
+    ('\u{2AC5}', 0.25583, 0.75583, 0.77778), ('\u{2AC6}', 0.25583, 0.75583, 0.77778), ('\u{2ACB}', 0.28481, 0.79383, 0.77778),
+    ('\u{2ACC}', 0.28481, 0.79383, 0.77778), ('\u{E006}', 0.08167, 0.58167, 0.22222), ('\u{E007}', 0.08167, 0.58167, 0.38889),
+    ('\u{E008}', 0.08167, 0.58167, 0.77778), ('\u{E009}', 0.0, 0.43056, 0.66667), ('\u{E00C}', 0.25142, 0.75726, 0.77778),
+    ('\u{E00D}', 0.25142, 0.75726, 0.77778), ('\u{E00E}', 0.41951, 0.91951, 0.77778), ('\u{E00F}', 0.30274, 0.79383, 0.77778),
+    ('\u{E010}', 0.30274, 0.79383, 0.77778), ('\u{E011}', 0.41951, 0.91951, 0.77778), ('\u{E016}', 0.25142, 0.75726, 0.77778),
+    ('\u{E017}', 0.25142, 0.75726, 0.77778), ('\u{E018}', 0.25142, 0.75726, 0.77778), ('\u{E019}', 0.25142, 0.75726, 0.77778),
+    ('\u{E01A}', 0.13597, 0.63597, 0.77778), ('\u{E01B}', 0.13597, 0.63597, 0.77778),
+];
+
+/// Metrics of `KaTeX_Size1-Regular`.
+#[rustfmt::skip]
+pub(super) const SIZE1_REGULAR: &[Metrics] = &[
+    ('\u{0020}', 0.0, 0.0, 0.25), ('\u{0028}', 0.35001, 0.85, 0.45834), ('\u{0029}', 0.35001, 0.85, 0.45834),
+    ('\u{002F}', 0.35001, 0.85, 0.57778), ('\u{005B}', 0.35001, 0.85, 0.41667), ('\u{005C}', 0.35001, 0.85, 0.57778),
+    ('\u{005D}', 0.35001, 0.85, 0.41667), ('\u{007B}', 0.35001, 0.85, 0.58334), ('\u{007D}', 0.35001, 0.85, 0.58334),
+    ('\u{00A0}', 0.0, 0.0, 0.25), ('\u{02C6}', 0.0, 0.72222, 0.55556), ('\u{02DC}', 0.0, 0.72222, 0.55556),
+    ('\u{0302}', 0.0, 0.72222, 0.55556), ('\u{0303}', 0.0, 0.72222, 0.55556), ('\u{2016}', -0.00099, 0.601, 0.77778),
+    ('\u{2191}', 0.00001, 0.6, 0.66667), ('\u{2193}', 0.00001, 0.6, 0.66667), ('\u{21D1}', 0.00001, 0.6, 0.77778),
+    ('\u{21D3}', 0.00001, 0.6, 0.77778), ('\u{220F}', 0.25001, 0.75, 0.94445), ('\u{2210}', 0.25001, 0.75, 0.94445),
+    ('\u{2211}', 0.25001, 0.75, 1.05556), ('\u{221A}', 0.35001, 0.85, 1.0), ('\u{2223}', -0.00599, 0.606, 0.33333),
+    ('\u{2225}', -0.00599, 0.606, 0.55556), ('\u{222B}', 0.30612, 0.805, 0.47222), ('\u{222C}', 0.306, 0.805, 0.47222),
+    ('\u{222D}', 0.306, 0.805, 0.47222), ('\u{222E}', 0.30612, 0.805, 0.47222), ('\u{22C0}', 0.25001, 0.75, 0.83334),
+    ('\u{22C1}', 0.25001, 0.75, 0.83334), ('\u{22C2}', 0.25001, 0.75, 0.83334), ('\u{22C3}', 0.25001, 0.75, 0.83334),
+    ('\u{2308}', 0.35001, 0.85, 0.47222), ('\u{2309}', 0.35001, 0.85, 0.47222), ('\u{230A}', 0.35001, 0.85, 0.47222),
+    ('\u{230B}', 0.35001, 0.85, 0.47222), ('\u{23D0}', -0.00099, 0.601, 0.66667), ('\u{27E8}', 0.35001, 0.85, 0.47222),
+    ('\u{27E9}', 0.35001, 0.85, 0.47222), ('\u{2A00}', 0.25001, 0.75, 1.11111), ('\u{2A01}', 0.25001, 0.75, 1.11111),
+    ('\u{2A02}', 0.25001, 0.75, 1.11111), ('\u{2A04}', 0.25001, 0.75, 0.83334), ('\u{2A06}', 0.25001, 0.75, 0.83334),
+];
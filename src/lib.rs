@@ -12,12 +12,89 @@
 //! This crate offers the following features:
 //!
 //! * `quick-js`: Enable by default. Use [quick-js](https://crates.io/crates/quick-js)
-//!    as the JS backend.
+//!   as the JS backend.
 //! * `duktape`: Use [duktape](https://crates.io/crates/ducc) as the JS backend.
-//!    You need to disable the default features to enable this backend.
+//!   You need to disable the default features to enable this backend.
 //! * `wasm-js`: Use [wasm-bindgen](https://crates.io/crates/wasm-bindgen) and
-//!    [js-sys](https://crates.io/crates/js-sys) as the JS backend.
-//!    You need to disable the default features to enable this backend.
+//!   [js-sys](https://crates.io/crates/js-sys) as the JS backend.
+//!   You need to disable the default features to enable this backend.
+//!   `katex::wasm::render_to_element` renders into a DOM element with it.
+//! * `wasm-bindings`: Enable `wasm-js` and export `renderKatex(input, opts)`
+//!   to JS with [wasm-bindgen](https://crates.io/crates/wasm-bindgen), taking
+//!   KaTeX's options as an object, for modules built with wasm-pack.
+//! * `wasm-worker`: Enable `wasm-js` and `katex::render_in_worker`, rendering
+//!   in a dedicated Web Worker so that the main thread of the browser stays
+//!   responsive.
+//! * `rquickjs`: Use [rquickjs](https://crates.io/crates/rquickjs) as the JS backend.
+//!   Unlike `wasm-js`, it also works on `wasm32-wasip1`, e.g. inside WASI plugins.
+//!   You need to disable the default features to enable this backend.
+//! * `jsc`: Use the system [JavaScriptCore](https://developer.apple.com/documentation/javascriptcore)
+//!   framework as the JS backend on Apple platforms, e.g. on iOS, without
+//!   bundling a JS engine. You need to disable the default features to enable
+//!   this backend.
+//! * `android`: Use [duktape](https://crates.io/crates/ducc) as the JS backend
+//!   when building for Android, see [below](#android). It keeps the other
+//!   backends on the other targets.
+//! * `mhchem`: Enable by default. Bundle the [mhchem](https://mhchem.github.io/MathJax-mhchem/)
+//!   extension for `\ce` and `\pu`, and the helpers of `katex::chem`.
+//!   Enable it again when disabling the default features to keep it.
+//! * `raster`: Enable PNG output in `katex::raster`, using [resvg](https://crates.io/crates/resvg).
+//! * `print`: Lay out equations as positioned text runs and rules for PDF
+//!   writers in `katex::print`.
+//! * `omml`: Convert equations to Office MathML for Word documents in
+//!   `katex::convert`.
+//! * `braille`: Translate equations to Nemeth braille in `katex::braille`.
+//!   This is experimental.
+//! * `metrics`: Report counters and histograms of renders to a sink in
+//!   `katex::metrics`.
+//! * `leptos`, `yew`: Add a `MathView` component for [Leptos](https://leptos.dev)
+//!   or [Yew](https://yew.rs) apps in `katex::frontend`.
+//! * `serde_json`: Convert [serde_json](https://crates.io/crates/serde_json)
+//!   values to [`JsonValue`], e.g. for [raw options](OptsBuilder::raw_option),
+//!   and render to a JSON object with [`render_to_json`].
+//! * `macros`: Add [`math!`], rendering equations at compile time to
+//!   `&'static str`.
+//! * `build-support`: Render the math of files ahead of time in build
+//!   scripts with `katex::build_support`.
+//! * `server`: Build the `katex-server` binary, an HTTP service rendering
+//!   equations posted as JSON with KaTeX's options.
+//! * `tracing`: Instrument renders, JS engine initializations and JS calls
+//!   with [tracing](https://crates.io/crates/tracing) spans.
+//! * `markdown`: Render math in [pulldown-cmark](https://crates.io/crates/pulldown-cmark)
+//!   event streams in `katex::markdown`.
+//! * `sanitize`: Allow sanitizing the rendered HTML with an allowlist of KaTeX
+//!   elements, attributes and styles, see [`OptsBuilder::sanitize`].
+//! * `bench`: Expose the formulas used by the benchmarks in `katex::bench`.
+//! * `failpoints`: Inject failures from specific inputs in `katex::failpoints`,
+//!   to test error handling. Do not enable it in production.
+//! * `compat03`: Expose the katex 0.3 API as deprecated wrappers in `katex::compat03`.
+//! * `test-util`: Expose assertions and a canonical form of the output for
+//!   the tests of downstream crates in `katex::testing`.
+//! * `differential`: Compare the output of two JS backends on generated
+//!   formulas in the tests: duktape with the default quick-js, with
+//!   `--features duktape,differential`, or duktape with rquickjs, with
+//!   `--no-default-features --features rquickjs,duktape,differential`.
+//!   `quick-js` and `rquickjs` both bundle QuickJS, so they cannot be
+//!   compared. It has no effect outside the tests, nor without duktape.
+//!
+//! # Android
+//!
+//! quick-js does not build with the Android NDK, so it is never compiled for
+//! `target_os = "android"`. Enable the `android` feature to render with
+//! duktape there instead, while keeping the default features for the other
+//! targets:
+//!
+//! ```toml
+//! [dependencies]
+//! katex = { version = "0.4", features = ["android"] }
+//! ```
+//!
+//! Then build with the compilers of the NDK, e.g. with
+//! [cargo-ndk](https://crates.io/crates/cargo-ndk):
+//!
+//! ```sh
+//! cargo ndk -t arm64-v8a build --release
+//! ```
 //!
 //! # Examples
 //!
@@ -28,34 +105,197 @@
 //! let html_in_display_mode = katex::render_with_opts("E = mc^2", &opts).unwrap();
 //! ```
 
-#![forbid(unsafe_code)]
+// The exports of `wasm-bindings` and the imports of `wasm-worker` expand to
+// `unsafe` shims. They are the only modules allowed to use `unsafe`.
+#![cfg_attr(
+    not(any(feature = "wasm-bindings", feature = "wasm-worker")),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(
+    any(feature = "wasm-bindings", feature = "wasm-worker"),
+    deny(unsafe_code)
+)]
 #![deny(missing_docs)]
 
 pub mod error;
-pub use error::{Error, Result};
+pub use error::{Error, ParseError, ResourceKind, Result};
 
 pub mod opts;
-pub use opts::{Opts, OptsBuilder, OutputType};
+pub use opts::{
+    DigitGrouping, EffectiveOpts, EscapedInputPolicy, ExpansionMode, JsonValue, Limit, Macro, Opts,
+    OptsBuilder, OutputType, ResourceResolver, StrictErrorCode, StrictMode, TextUnicodePolicy,
+};
+
+mod mathjax;
+mod preprocess;
+mod unicode;
+
+mod data;
+mod macro_parser;
+pub use macro_parser::MacroFormat;
+
+pub mod document;
+
+#[cfg(feature = "mhchem")]
+pub mod chem;
+
+pub mod environments;
+
+pub mod ast;
+
+pub mod latex;
+
+pub mod num;
+
+pub mod vars;
+pub use environments::{
+    render_align, render_align_with_opts, render_cases, render_cases_with_opts, render_numbered,
+    render_numbered_with_opts,
+};
+pub use vars::{render_with_vars, MathValue};
+
+pub mod split;
+pub use split::{split_math, Delimiter, DelimiterConfig, Segment};
+
+pub mod cache;
+pub use cache::{cache_key, content_hash};
+
+pub mod batch;
+
+#[cfg(feature = "build-support")]
+pub mod build_support;
+
+pub mod queue;
+
+pub mod testing;
+
+pub mod complexity;
+pub use complexity::{complexity, complexity_with_opts, ComplexityScore};
+
+pub mod check;
+pub use check::{check, check_with_opts};
+
+pub mod preview;
+
+pub mod colors;
+pub use colors::{colors, colors_with_opts, ColorRole, ColorUse};
+
+pub mod a11y;
+
+pub mod lint;
+pub use lint::{lint, lint_with_opts, LintIssue, LintSeverity};
+
+pub mod fonts;
+
+mod html;
+
+#[cfg(feature = "sanitize")]
+mod sanitize;
+
+pub mod output;
+pub use output::{LogMessage, RenderOutput};
+
+pub mod postprocess;
+
+pub mod term;
+
+mod layout;
+pub mod svg;
+
+#[cfg(feature = "raster")]
+pub mod raster;
+
+#[cfg(feature = "print")]
+pub mod print;
+
+#[cfg(feature = "omml")]
+pub mod convert;
+
+#[cfg(feature = "braille")]
+pub mod braille;
+pub use postprocess::PostProcessor;
+
+#[cfg(feature = "markdown")]
+pub mod markdown;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+
+#[cfg(feature = "failpoints")]
+pub mod failpoints;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "compat03")]
+pub mod compat03;
+
+#[cfg(feature = "macros")]
+pub use katex_macros::math;
+
+#[cfg(any(feature = "leptos", feature = "yew"))]
+pub mod frontend;
+
+#[cfg(all(
+    feature = "wasm-bindings",
+    target_arch = "wasm32",
+    target_os = "unknown"
+))]
+#[allow(unsafe_code)]
+mod wasm_bindings;
+
+#[cfg(all(feature = "wasm-js", target_arch = "wasm32", target_os = "unknown"))]
+pub mod wasm;
+
+#[cfg(all(feature = "wasm-worker", target_arch = "wasm32", target_os = "unknown"))]
+#[allow(unsafe_code)]
+pub mod worker;
+#[cfg(all(feature = "wasm-worker", target_arch = "wasm32", target_os = "unknown"))]
+pub use worker::render_in_worker;
 
 mod js_engine;
+pub use js_engine::{BackendKind, EngineInfo};
+
+pub mod renderer;
 use js_engine::{Engine, JsEngine, JsValue};
+pub use renderer::{EngineBuilder, Renderer, SharedRenderer, Source};
+
+use core::{
+    cell::{Cell, RefCell},
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use std::{io, time::Instant};
 
 /// KaTeX version.
 pub const KATEX_VERSION: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/KATEX-VERSION"));
 
-/// JS source code.
-const JS_SRC: &str = concat!(
-    // HACK to load KaTeX code in Node.js
-    // By setting `module` and `exports` as undefined, we prevent KaTeX to
-    // be loaded like normal Node.js module.
-    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/js/node-hack.js")),
-    // KaTeX JS source code
-    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/vendor/katex.min.js")),
-    // mhchem JS source code
-    include_str!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/vendor/contrib/mhchem.min.js"
-    )),
+/// JS source code loaded before KaTeX.
+// HACK to load KaTeX code in Node.js
+// By setting `module` and `exports` as undefined, we prevent KaTeX to
+// be loaded like normal Node.js module.
+const JS_PRELUDE_SRC: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/js/node-hack.js"));
+
+/// KaTeX JS source code.
+const KATEX_SRC: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/vendor/katex.min.js"));
+
+/// mhchem JS source code.
+#[cfg(feature = "mhchem")]
+const MHCHEM_SRC: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/vendor/contrib/mhchem.min.js"
+));
+
+/// Sources of the bundled KaTeX, with the extensions enabled by features.
+const BUNDLED_SRC: &[&str] = &[
+    KATEX_SRC,
+    #[cfg(feature = "mhchem")]
+    MHCHEM_SRC,
+];
+
+/// JS source code loaded after KaTeX.
+const JS_EPILOGUE_SRC: &str = concat!(
     // restore HACK done in node-hack.js
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/js/post-node-hack.js")),
     // entry function
@@ -63,8 +303,107 @@ const JS_SRC: &str = concat!(
 );
 
 thread_local! {
-    /// Per thread JS Engine used to render KaTeX.
-    static KATEX: Result<Engine> = init_katex();
+    /// Per thread JS Engine used to render KaTeX, initialized on first use.
+    static KATEX: RefCell<Option<ThreadEngine>> = const { RefCell::new(None) };
+}
+
+/// Idle time after which [`release_idle_engine`] drops the engine of a
+/// thread, in milliseconds, or `u64::MAX` if unset.
+static ENGINE_IDLE_TTL: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// State of the per thread JS engine.
+struct ThreadEngine {
+    engine: Result<Engine>,
+    /// Last use of the engine, only tracked with an idle TTL.
+    last_used: Cell<Option<Instant>>,
+    /// Whether a panic unwound through a use of the engine, which may have
+    /// left it in a broken state.
+    poisoned: Cell<bool>,
+}
+
+/// Mark the engine as poisoned if dropped while panicking.
+struct PanicGuard<'a>(&'a Cell<bool>);
+
+impl Drop for PanicGuard<'_> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.set(true);
+        }
+    }
+}
+
+impl ThreadEngine {
+    fn touch(&self) {
+        if engine_idle_ttl().is_some() {
+            self.last_used.set(Some(Instant::now()));
+        }
+    }
+}
+
+/// Run `f` with the JS engine of the current thread, initializing it if needed.
+///
+/// If `f` fails with a JS error and the engine no longer passes a self-test,
+/// the engine is dropped, to be re-created by the next call, and
+/// [`Error::EngineRestarted`] is returned instead. An engine used by a
+/// panicking call is re-created too.
+fn with_engine<T>(f: impl FnOnce(&Engine) -> Result<T>) -> Result<T> {
+    KATEX.with(|state| {
+        let poisoned = state
+            .borrow()
+            .as_ref()
+            .is_some_and(|state| state.poisoned.get());
+        if poisoned {
+            // Nested calls keep using the engine until the outer one ends.
+            if let Ok(mut state) = state.try_borrow_mut() {
+                *state = None;
+            }
+        }
+        if state.borrow().is_none() {
+            *state.borrow_mut() = Some(ThreadEngine {
+                engine: init_katex(),
+                last_used: Cell::new(None),
+                poisoned: Cell::new(false),
+            });
+        }
+        let result = {
+            let state = state.borrow();
+            let state = state.as_ref().expect("engine is initialized above");
+            state.touch();
+            let engine = state.engine.as_ref().map_err(|e| e.clone())?;
+            let _guard = PanicGuard(&state.poisoned);
+            match f(engine) {
+                Err(e) if is_engine_error(&e) && !is_healthy(engine) => {
+                    state.poisoned.set(true);
+                    Err(Error::EngineRestarted(e.to_string()))
+                }
+                result => result,
+            }
+        };
+        if let (Err(Error::EngineRestarted(_)), Ok(mut state)) = (&result, state.try_borrow_mut()) {
+            *state = None;
+        }
+        result
+    })
+}
+
+/// Whether an error may come from a broken JS engine.
+fn is_engine_error(error: &Error) -> bool {
+    match error {
+        Error::JsExecError(message) => !message.contains("KaTeX parse error"),
+        Error::JsValueError(_) | Error::ResourceExhausted { .. } => true,
+        _ => false,
+    }
+}
+
+/// Run a self-test of the JS engine, rendering a trivial formula.
+fn is_healthy<E>(engine: &E) -> bool
+where
+    E: JsEngine,
+{
+    engine
+        .call_function("__katex_rs.healthCheck", core::iter::empty())
+        .and_then(|value| value.into_string())
+        .is_ok_and(|value| value == "ok")
 }
 
 /// Initialize KaTeX js environment.
@@ -72,33 +411,425 @@ fn init_katex<E>() -> Result<E>
 where
     E: JsEngine,
 {
-    let engine = E::new()?;
-    engine.eval(JS_SRC)?;
+    init_katex_with_sources(None, &[], BUNDLED_SRC)
+}
+
+/// Initialize KaTeX js environment with custom KaTeX sources.
+///
+/// `polyfills` are evaluated first, in a plain JS environment, and `sources`
+/// are evaluated as KaTeX and its extensions, i.e. with the Node.js hack.
+fn init_katex_with_sources<E>(
+    memory_limit: Option<usize>,
+    polyfills: &[&str],
+    sources: &[&str],
+) -> Result<E>
+where
+    E: JsEngine,
+{
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("katex::engine_init", sources = sources.len()).entered();
+    #[cfg(feature = "metrics")]
+    return metrics::measure_engine_init(|| init_engine(memory_limit, polyfills, sources));
+    #[cfg(not(feature = "metrics"))]
+    init_engine(memory_limit, polyfills, sources)
+}
+
+fn init_engine<E>(memory_limit: Option<usize>, polyfills: &[&str], sources: &[&str]) -> Result<E>
+where
+    E: JsEngine,
+{
+    let engine = match memory_limit {
+        Some(max_bytes) => E::with_memory_limit(max_bytes)?,
+        None => E::new()?,
+    };
+    for src in polyfills {
+        engine.eval(src)?;
+    }
+    engine.eval(JS_PRELUDE_SRC)?;
+    for src in sources {
+        engine.eval(src)?;
+    }
+    engine.eval(JS_EPILOGUE_SRC)?;
     Ok(engine)
 }
 
 /// Render LaTeX equation to HTML using specified [engine](`JsEngine`) and [options](`Opts`).
 #[inline]
-fn render_inner<E>(engine: &E, input: &str, opts: impl AsRef<Opts>) -> Result<String>
+fn render_inner<E>(engine: &E, input: &str, opts: impl AsRef<Opts>) -> Result<Rendered>
+where
+    E: JsEngine,
+{
+    #[cfg(feature = "tracing")]
+    let _span = {
+        let opts = opts.as_ref();
+        tracing::info_span!(
+            "katex::render",
+            input_len = input.len(),
+            display_mode = opts.display_mode.unwrap_or(false),
+            output = %opts.output_type.as_ref().unwrap_or(&OutputType::HtmlAndMathml),
+        )
+        .entered()
+    };
+    #[cfg(feature = "metrics")]
+    return metrics::measure_render(|| render_js(engine, input, opts.as_ref()));
+    #[cfg(not(feature = "metrics"))]
+    render_js(engine, input, opts.as_ref())
+}
+
+fn render_js<E>(engine: &E, input: &str, opts: &Opts) -> Result<Rendered>
 where
     E: JsEngine,
 {
     use core::iter;
 
-    let input = engine.create_string_value(input.to_owned())?;
-    let opts = opts.as_ref().to_js_value(engine)?;
-    let args = iter::once(input).chain(iter::once(opts));
-    let result = engine.call_function("katexRenderToString", args)?;
-    result.into_string()
+    #[cfg(feature = "failpoints")]
+    failpoints::trigger(engine, input)?;
+    let (preprocessed, warnings) = preprocess::preprocess_with_warnings(input, opts)?;
+    let js_input = engine.create_string_value(preprocessed)?;
+    let js_opts = opts.to_js_value(engine)?;
+    let args = iter::once(js_input).chain(iter::once(js_opts));
+    let result = engine
+        .call_function("__katex_rs.renderToString", args)?
+        .into_string()?;
+    let mut rendered = finish_render(input, &result, opts)?;
+    rendered.warnings = warnings;
+    Ok(rendered)
+}
+
+/// Parse the result of `__katex_rs.renderToString` and post-process the HTML.
+fn finish_render(input: &str, result: &str, opts: &Opts) -> Result<Rendered> {
+    let too_large = |size| Error::OutputTooLarge {
+        size,
+        limit: opts.max_output_bytes.unwrap_or_default(),
+    };
+    if let Some(size) = result.strip_prefix("tooLarge\t") {
+        return Err(too_large(size.parse().unwrap_or_default()));
+    }
+    let mut fields = result.splitn(4, '\t');
+    let mut metric = || fields.next().and_then(|s| s.parse().ok());
+    let (height, depth) = (metric(), metric());
+    let (Some(used_macros), Some(html)) = (fields.next(), fields.next()) else {
+        return Err(Error::JsValueError(
+            "expected the metrics, the macros and the HTML of the render".to_owned(),
+        ));
+    };
+    let html = postprocess::apply(input, html.to_owned(), opts)?;
+    if opts
+        .max_output_bytes
+        .is_some_and(|limit| html.len() > limit)
+    {
+        return Err(too_large(html.len()));
+    }
+    Ok(Rendered {
+        html,
+        height,
+        depth,
+        used_macros: used_macros
+            .lines()
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        warnings: Vec::new(),
+    })
+}
+
+/// HTML rendered by KaTeX, with the vertical metrics of the formula.
+struct Rendered {
+    html: String,
+    /// Height above the baseline, in em, if there is HTML output.
+    height: Option<f64>,
+    /// Depth below the baseline, in em, if there is HTML output.
+    depth: Option<f64>,
+    /// User macros expanded by the render, if tracked.
+    used_macros: Vec<String>,
+    /// Warnings about the changes of preprocessing to the rendered text.
+    warnings: Vec<String>,
+}
+
+/// Take the messages written to the JS console since the last render.
+fn take_logs<E>(engine: &E) -> Result<Vec<LogMessage>>
+where
+    E: JsEngine,
+{
+    let logs = engine
+        .call_function("__katex_rs.takeLogs", core::iter::empty())?
+        .into_string()?;
+    Ok(LogMessage::parse_all(&logs))
+}
+
+/// Initialize the JS engine of the current thread.
+///
+/// The engine is otherwise initialized by the first render on each thread,
+/// which then takes noticeably longer. Call this, e.g. on server startup, to
+/// pay the cost upfront and to detect initialization errors early.
+///
+/// # Examples
+///
+/// ```
+/// katex::warm_up().unwrap();
+/// let html = katex::render("E = mc^2").unwrap();
+/// ```
+pub fn warm_up() -> Result<()> {
+    with_engine(|_| Ok(()))
+}
+
+/// Describe the JS backend the crate is built with, e.g. to check whether it
+/// supports a feature, or for bug reports.
+///
+/// It does not initialize a JS engine.
+///
+/// # Examples
+///
+/// ```
+/// let info = katex::engine_info();
+/// println!("katex {} on {} ({})", katex::KATEX_VERSION, info.backend, info.version);
+/// ```
+pub fn engine_info() -> EngineInfo {
+    Engine::info()
+}
+
+/// Drop the JS engine of the current thread, if any, to reclaim its memory.
+///
+/// The next render on this thread initializes a new engine, like the first
+/// one. It also retries a failed initialization. Return whether an engine
+/// was dropped, which is not the case while it is in use, e.g. when called
+/// from a [`ResourceResolver`].
+///
+/// # Examples
+///
+/// ```
+/// katex::warm_up().unwrap();
+/// assert!(katex::shutdown_thread_engine());
+/// assert!(!katex::shutdown_thread_engine());
+/// let html = katex::render("E = mc^2").unwrap();
+/// ```
+pub fn shutdown_thread_engine() -> bool {
+    KATEX.with(|state| match state.try_borrow_mut() {
+        Ok(mut state) => state.take().is_some(),
+        Err(_) => false,
+    })
+}
+
+/// Set how long the JS engine of a thread may stay unused before
+/// [`release_idle_engine`] drops it, for all threads. `None`, the default,
+/// keeps engines until the thread exits or [`shutdown_thread_engine`].
+///
+/// Idle times are measured with [`Instant`], which is not available on
+/// `wasm32-unknown-unknown`.
+pub fn set_engine_idle_ttl(ttl: Option<Duration>) {
+    let millis = ttl.map_or(u64::MAX, |ttl| {
+        u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX - 1)
+    });
+    ENGINE_IDLE_TTL.store(millis, Ordering::Relaxed);
+}
+
+/// Return the idle TTL set by [`set_engine_idle_ttl`].
+pub fn engine_idle_ttl() -> Option<Duration> {
+    match ENGINE_IDLE_TTL.load(Ordering::Relaxed) {
+        u64::MAX => None,
+        millis => Some(Duration::from_millis(millis)),
+    }
+}
+
+/// Drop the JS engine of the current thread if it has been unused for longer
+/// than the [idle TTL](set_engine_idle_ttl).
+///
+/// Threads cannot drop each other's engines, so call this from the thread
+/// itself when it becomes idle, e.g. in the `on_thread_park` hook of a tokio
+/// runtime. Return whether an engine was dropped.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// katex::set_engine_idle_ttl(Some(Duration::ZERO));
+/// katex::warm_up().unwrap();
+/// std::thread::sleep(Duration::from_millis(1));
+/// assert!(katex::release_idle_engine());
+/// katex::set_engine_idle_ttl(None);
+/// ```
+pub fn release_idle_engine() -> bool {
+    let Some(ttl) = engine_idle_ttl() else {
+        return false;
+    };
+    let idle = KATEX.with(|state| {
+        state.try_borrow().is_ok_and(|state| {
+            state
+                .as_ref()
+                .and_then(|state| state.last_used.get())
+                .is_some_and(|last_used| last_used.elapsed() > ttl)
+        })
+    });
+    idle && shutdown_thread_engine()
 }
 
 /// Render LaTeX equation to HTML with additional [options](`Opts`).
 pub fn render_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
-    KATEX.with(|engine| {
-        engine
-            .as_ref()
-            .map_err(|e| e.clone())
-            .and_then(|engine| render_inner(engine, input, opts))
+    with_engine(|engine| render_inner(engine, input, opts)).map(|rendered| rendered.html)
+}
+
+/// Render LaTeX equation to a structured [output](`RenderOutput`) with additional [options](`Opts`).
+pub fn render_to_output(input: &str, opts: impl AsRef<Opts>) -> Result<RenderOutput> {
+    with_engine(|engine| {
+        let rendered = render_inner(engine, input, opts)?;
+        Ok(RenderOutput::new(rendered, take_logs(engine)?))
+    })
+}
+
+/// Render LaTeX equation with additional [options](`Opts`) to a JSON object,
+/// e.g. for bindings and services which need a single machine-readable
+/// result:
+///
+/// ```json
+/// {
+///   "html": "<span class=\"katex\">...</span>",
+///   "mathml": "<math ...>...</math>",
+///   "errors": [{ "code": "KTX2003", "message": "...", "span": [4, 8] }],
+///   "warnings": ["..."],
+///   "metrics": { "height_em": 0.68, "baseline_em": 0.0 }
+/// }
+/// ```
+///
+/// Errors caused by the input or the options, e.g. a parse error, are
+/// listed in `errors`, with `null` output, or with the error rendered in
+/// place of the equation with
+/// [`throw_on_error(false)`](OptsBuilder::throw_on_error). Parse errors have
+/// a `span` of bytes, which is `null` if unknown. Other errors, e.g. of the
+/// JS engine, are returned as `Err`. `warnings` are the messages of KaTeX's
+/// [strict mode](OptsBuilder::strict).
+///
+/// # Examples
+///
+/// ```
+/// let json = katex::render_to_json(r"x + \foo", katex::Opts::default()).unwrap();
+/// assert!(json["html"].is_null());
+/// assert_eq!(json["errors"][0]["code"], "KTX2003");
+/// assert_eq!(json["errors"][0]["span"], serde_json::json!([4, 8]));
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn render_to_json(input: &str, opts: impl AsRef<Opts>) -> Result<serde_json::Value> {
+    use serde_json::{json, Value};
+
+    let opts = opts.as_ref();
+    let describe = |error: &Error| match error {
+        Error::ParseError(e) => json!({
+            "code": error.code(),
+            "message": e.message,
+            "span": e.span.as_ref().map(|span| [span.start, span.end]),
+        }),
+        error => json!({ "code": error.code(), "message": error.to_string() }),
+    };
+    let output = match render_to_output(input, opts) {
+        Ok(output) => output,
+        Err(e) => {
+            let error = match check_with_opts(input, opts) {
+                Err(error @ Error::ParseError(_)) => error,
+                _ if e.code().starts_with("KTX2") => e,
+                _ => return Err(e),
+            };
+            return Ok(json!({
+                "html": Value::Null,
+                "mathml": Value::Null,
+                "errors": [describe(&error)],
+                "warnings": [],
+                "metrics": { "height_em": Value::Null, "baseline_em": Value::Null },
+            }));
+        }
+    };
+    // An error rendered in place of the equation.
+    let errors: Vec<Value> = if output.html.contains("katex-error") {
+        check_with_opts(input, opts)
+            .err()
+            .iter()
+            .map(describe)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let warnings: Vec<&str> = output
+        .logs
+        .iter()
+        .filter(|log| log.level == renderer::ConsoleLevel::Warn)
+        .map(|log| log.message.as_str())
+        .collect();
+    Ok(json!({
+        "html": output.html,
+        "mathml": output.mathml(),
+        "errors": errors,
+        "warnings": warnings,
+        "metrics": { "height_em": output.height_em, "baseline_em": output.baseline_em },
+    }))
+}
+
+/// Render LaTeX equation to HTML with additional [options](`Opts`), writing
+/// it to `output`, e.g. the buffer of a large document.
+///
+/// Fail with [`Error::WriteError`] if the output cannot be written.
+///
+/// # Examples
+///
+/// ```
+/// let mut html = String::from("<p>");
+/// katex::render_to_writer("E = mc^2", katex::Opts::default(), &mut html).unwrap();
+/// html.push_str("</p>");
+/// assert!(html.starts_with(r#"<p><span class="katex">"#));
+/// ```
+pub fn render_to_writer(
+    input: &str,
+    opts: impl AsRef<Opts>,
+    output: &mut impl fmt::Write,
+) -> Result<()> {
+    render_all_to_writer([input], opts, output)
+}
+
+/// Render LaTeX equation to HTML with additional [options](`Opts`), writing
+/// it to an [`io::Write`], e.g. a file.
+///
+/// Fail with [`Error::WriteError`] if the output cannot be written.
+pub fn render_to_io_writer(
+    input: &str,
+    opts: impl AsRef<Opts>,
+    output: &mut impl io::Write,
+) -> Result<()> {
+    let html = render_with_opts(input, opts)?;
+    output
+        .write_all(html.as_bytes())
+        .map_err(|e| Error::WriteError(e.to_string()))
+}
+
+/// Render LaTeX equations to HTML with additional [options](`Opts`), writing
+/// them one after another to `output`.
+///
+/// Each HTML is written as soon as it is rendered, so only one is held in
+/// memory at a time. Stop at the first error, after writing the equations
+/// before it.
+///
+/// # Examples
+///
+/// ```
+/// let mut html = String::new();
+/// katex::render_all_to_writer(["a^2", "b^2"], katex::Opts::default(), &mut html).unwrap();
+/// assert_eq!(html.matches(r#"<span class="katex">"#).count(), 2);
+/// ```
+pub fn render_all_to_writer<I, S>(
+    inputs: I,
+    opts: impl AsRef<Opts>,
+    output: &mut impl fmt::Write,
+) -> Result<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let opts = opts.as_ref();
+    with_engine(|engine| {
+        for input in inputs {
+            let rendered = render_inner(engine, input.as_ref(), opts)?;
+            output
+                .write_str(&rendered.html)
+                .map_err(|e| Error::WriteError(e.to_string()))?;
+        }
+        Ok(())
     })
 }
 
@@ -110,3 +841,15 @@ pub fn render(input: &str) -> Result<String> {
 
 #[cfg(test)]
 mod tests;
+
+// Only the backend pairs which the differential tests compile, see
+// `js_engine`: duktape with quick-js or with rquickjs.
+#[cfg(all(
+    test,
+    feature = "differential",
+    feature = "duktape",
+    any(feature = "quick-js", feature = "rquickjs"),
+    any(unix, windows),
+    not(target_os = "android")
+))]
+mod differential;
@@ -0,0 +1,258 @@
+//! Report the commands and environments of an equation which KaTeX does not
+//! support, with alternatives.
+//!
+//! Equations written for LaTeX or MathJax often use commands that KaTeX
+//! lacks, e.g. `\DeclareMathOperator` or `\begin{eqnarray}`. [`lint`] lists
+//! all of them at once, where rendering stops at the first one with a
+//! generic parse error. KaTeX itself tells which commands it defines, and
+//! hints for the common ones suggest what to write instead. It also warns
+//! about plain TeX commands which KaTeX supports but which are easily
+//! misread, such as `\over`.
+
+use crate::{
+    error::Result,
+    js_engine::{JsEngine, JsValue},
+    mathjax,
+    opts::Opts,
+    preprocess, with_engine,
+};
+use core::{iter, ops::Range};
+use itertools::Itertools;
+use std::collections::BTreeSet;
+
+/// Hints for commands and environments, the latter without a backslash,
+/// met when migrating from LaTeX or MathJax. They only apply when KaTeX does
+/// not define them.
+const HINTS: &[(&str, &str)] = &[
+    (
+        r"\newenvironment",
+        r"environments cannot be defined, define a macro with `\newcommand` instead",
+    ),
+    (
+        r"\renewenvironment",
+        r"environments cannot be defined, define a macro with `\newcommand` instead",
+    ),
+    (
+        r"\DeclareMathOperator",
+        r"use `\operatorname{name}`, or a macro defined with `\newcommand`",
+    ),
+    (
+        r"\require",
+        "remove it, KaTeX does not load extensions at runtime",
+    ),
+    (r"\bbox", r"use `\colorbox` or `\fcolorbox`"),
+    (r"\class", r"use `\htmlClass`, with the command trusted"),
+    (r"\cssId", r"use `\htmlId`, with the command trusted"),
+    (r"\style", r"use `\htmlStyle`, with the command trusted"),
+    (r"\unicode", "write the character itself"),
+    (
+        r"\label",
+        r"cross-references are not supported, number the equation with `\tag`",
+    ),
+    (
+        r"\ref",
+        r"cross-references are not supported, write the number of the equation",
+    ),
+    (
+        r"\eqref",
+        r"cross-references are not supported, write the number of the equation",
+    ),
+    (r"\usepackage", "remove the preamble, only math is rendered"),
+    (
+        r"\documentclass",
+        "remove the preamble, only math is rendered",
+    ),
+    (
+        r"\tikz",
+        "TikZ is not supported, render the figure to SVG separately",
+    ),
+    (
+        r"\draw",
+        "TikZ is not supported, render the figure to SVG separately",
+    ),
+    (r"\cancelto", r"use `\cancel`"),
+    (r"\hfill", r"use `\hspace` or `\quad`"),
+    (r"\mspace", r"use `\mskip`"),
+    (r"\vspace", r"use `\\[<size>]` to space the rows"),
+    (r"\mbox", r"use `\text`"),
+    (r"\emph", r"use `\textit`"),
+    (r"\displaylines", r"use `\begin{gathered}`"),
+    (r"\eqalign", r"use `\begin{aligned}`"),
+    (r"\root", r"use `\sqrt[n]{x}`"),
+    (r"\buildrel", r"use `\overset`"),
+    (r"\lefteqn", r"use `\mathrlap`"),
+    (r"\cr", r"use `\\`"),
+    (
+        "eqnarray",
+        r"use `\begin{align}`, or `\begin{aligned}` inline",
+    ),
+    (
+        "eqnarray*",
+        r"use `\begin{align*}`, or `\begin{aligned}` inline",
+    ),
+    ("multline", r"use `\begin{gathered}` or `\begin{split}`"),
+    ("flalign", r"use `\begin{align}`"),
+    ("xalignat", r"use `\begin{alignat}`"),
+    ("tabular", r"use `\begin{array}` with `\text` cells"),
+    ("tikzcd", r"use `\begin{CD}`"),
+    (
+        "tikzpicture",
+        "TikZ is not supported, render the figure to SVG separately",
+    ),
+];
+
+/// Plain TeX commands which KaTeX supports, but which act on their whole
+/// enclosing group and are easily misread, with what to use instead.
+const PLAIN_TEX: &[(&str, &str)] = &[
+    (r"\over", r"use `\frac{a}{b}`"),
+    (r"\atop", r"use `\genfrac{}{}{0pt}{}{a}{b}`"),
+    (r"\choose", r"use `\binom{a}{b}`"),
+    (r"\rm", r"use `\mathrm{...}`"),
+    (r"\bf", r"use `\mathbf{...}`"),
+    (r"\it", r"use `\mathit{...}`"),
+    (r"\sf", r"use `\mathsf{...}`"),
+    (r"\tt", r"use `\mathtt{...}`"),
+    (r"\cal", r"use `\mathcal{...}`"),
+];
+
+/// How serious a [`LintIssue`] is.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LintSeverity {
+    /// KaTeX fails to render the equation.
+    Error,
+    /// KaTeX renders the equation, but the input is better written otherwise.
+    Warning,
+}
+
+/// A command or environment reported by [`lint`].
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintIssue {
+    /// The command, e.g. `\bbox`, or the environment, e.g. `eqnarray`.
+    pub name: String,
+    /// Byte range of the command, or of `\begin{...}` for an environment, in
+    /// the input.
+    pub span: Range<usize>,
+    /// How serious the issue is.
+    pub severity: LintSeverity,
+    /// What is wrong, e.g. "KaTeX does not support \bbox".
+    pub message: String,
+    /// What to write instead, if known.
+    pub suggestion: Option<String>,
+}
+
+/// List the commands and environments of a LaTeX equation which KaTeX does
+/// not support, and the plain TeX commands which are better written
+/// otherwise, in order of position.
+///
+/// Unlike [`check`](crate::check), every occurrence is reported, not only the
+/// first error. Commands defined in the input, e.g. with `\newcommand`, are
+/// not reported.
+///
+/// # Examples
+///
+/// ```
+/// use katex::LintSeverity;
+///
+/// let issues = katex::lint(r"\bbox[yellow]{a \over b} + \foo").unwrap();
+/// assert_eq!(issues[0].name, r"\bbox");
+/// assert_eq!(issues[0].span, 0..5);
+/// assert_eq!(issues[0].severity, LintSeverity::Error);
+/// assert_eq!(issues[0].suggestion.as_deref(), Some(r"use `\colorbox` or `\fcolorbox`"));
+/// assert_eq!(issues[1].name, r"\over");
+/// assert_eq!(issues[1].severity, LintSeverity::Warning);
+/// assert_eq!(issues[2].message, r"KaTeX does not support \foo");
+/// assert_eq!(issues[2].suggestion, None);
+/// ```
+#[inline]
+pub fn lint(input: &str) -> Result<Vec<LintIssue>> {
+    lint_with_opts(input, Opts::default())
+}
+
+/// List the unsupported commands and environments of a LaTeX equation with
+/// additional [options](`Opts`), so that the commands defined by their
+/// [macros](crate::OptsBuilder::add_macro) are not reported.
+pub fn lint_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<Vec<LintIssue>> {
+    with_engine(|engine| lint_inner(engine, input, opts.as_ref()))
+}
+
+fn lint_inner<E>(engine: &E, input: &str, opts: &Opts) -> Result<Vec<LintIssue>>
+where
+    E: JsEngine,
+{
+    let defined: Vec<String> = preprocess::inline_definitions(input)
+        .into_iter()
+        .map(|(name, _)| format!("\\{name}"))
+        .collect();
+    // Commands and environments, as (name, span).
+    let mut uses = Vec::new();
+    for (name, start, end) in preprocess::control_words(input) {
+        if name == "begin" {
+            if let Some((environment, end)) = environment_name(input, end) {
+                uses.push((environment.to_owned(), start..end));
+            }
+        } else if name != "end"
+            && !defined.iter().any(|defined| &defined[1..] == name)
+            && !(opts.mathjax_compat.unwrap_or_default()
+                && mathjax::REWRITTEN.contains(&&input[start..end]))
+        {
+            uses.push((input[start..end].to_owned(), start..end));
+        }
+    }
+    let names: BTreeSet<&str> = uses.iter().map(|(name, _)| name.as_str()).collect();
+    let undefined: Vec<String> = if names.is_empty() {
+        Vec::new()
+    } else {
+        let js_names = engine.create_string_value(names.iter().join("\n").into())?;
+        let js_opts = opts.to_js_value(engine)?;
+        let args = iter::once(js_names).chain(iter::once(js_opts));
+        let result = engine
+            .call_function("__katex_rs.undefinedCommands", args)?
+            .into_string()?;
+        result.lines().map(str::to_owned).collect()
+    };
+
+    let hint = |table: &[(&str, &str)], name: &str| {
+        table
+            .iter()
+            .find(|(hinted, _)| *hinted == name)
+            .map(|(_, hint)| (*hint).to_owned())
+    };
+    Ok(uses
+        .into_iter()
+        .filter_map(|(name, span)| {
+            let is_environment = !name.starts_with('\\');
+            let (severity, message, suggestion) = if undefined.contains(&name) {
+                let message = if is_environment {
+                    format!("KaTeX does not support the {name} environment")
+                } else {
+                    format!("KaTeX does not support {name}")
+                };
+                (LintSeverity::Error, message, hint(HINTS, &name))
+            } else {
+                let suggestion = hint(PLAIN_TEX, &name)?;
+                let message =
+                    format!("{name} is a plain TeX command acting on its whole enclosing group");
+                (LintSeverity::Warning, message, Some(suggestion))
+            };
+            Some(LintIssue {
+                name,
+                span,
+                severity,
+                message,
+                suggestion,
+            })
+        })
+        .collect())
+}
+
+/// Read the `{name}` of an environment following `\begin` at `i`, returning
+/// the name and the end of the closing brace.
+fn environment_name(input: &str, i: usize) -> Option<(&str, usize)> {
+    let rest = input[i..].trim_start();
+    let start = input.len() - rest.len() + 1;
+    let len = rest.strip_prefix('{')?.find('}')?;
+    let name = &input[start..start + len];
+    (!name.is_empty()).then_some((name, start + len + 1))
+}
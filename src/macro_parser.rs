@@ -0,0 +1,322 @@
+//! Parsers of macro files, to share macros between LaTeX documents and
+//! rendering.
+//!
+//! Three formats are supported:
+//!
+//! * LaTeX, e.g. a `.sty` file: `\newcommand`, `\renewcommand`,
+//!   `\providecommand`, `\def`, `\gdef` and `\DeclareMathOperator`
+//!   definitions are read, anything else is ignored.
+//! * JSON: an object mapping macro names to either a body, or an object with
+//!   a `body`, and optionally `num_args` and `unexpandable`.
+//! * TOML: the same schema as JSON, with tables or inline tables for macros
+//!   with parameters.
+//!
+//! ```toml
+//! '\RR' = '\mathbb{R}'
+//!
+//! ['\first']
+//! body = '#1'
+//! num_args = 2
+//! ```
+
+use crate::{
+    data::{self, SyntaxError, Value},
+    error::{Error, Result},
+    opts::{ExpansionMode, Macro},
+    preprocess::matching_brace,
+};
+use std::path::Path;
+
+/// Format of a macro file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MacroFormat {
+    /// LaTeX definitions, e.g. a `.sty` file.
+    Latex,
+    /// JSON object.
+    Json,
+    /// TOML document.
+    Toml,
+}
+
+impl MacroFormat {
+    /// Guess the format from the extension of a path: `.json`, `.toml`, or
+    /// `.sty`, `.tex` and `.cls` for LaTeX.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "sty" | "tex" | "cls" => Some(MacroFormat::Latex),
+            "json" => Some(MacroFormat::Json),
+            "toml" => Some(MacroFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// A macro read from a file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Definition {
+    pub(crate) name: String,
+    pub(crate) definition: Macro,
+    /// Whether the definition replaces an existing macro of the same name,
+    /// which is not the case for `\providecommand`.
+    pub(crate) replace: bool,
+}
+
+/// Parse the macros of a file, in definition order.
+pub(crate) fn parse(format: MacroFormat, input: &str) -> Result<Vec<Definition>> {
+    match format {
+        MacroFormat::Latex => parse_latex(input),
+        MacroFormat::Json => parse_json(input),
+        MacroFormat::Toml => parse_toml(input),
+    }
+}
+
+/// Build a macro from the value associated with its name.
+fn to_definition(name: String, value: Value) -> core::result::Result<Definition, String> {
+    let definition = match value {
+        Value::String(body) => Macro::new(body),
+        Value::Table(fields) => {
+            let mut definition = None;
+            let mut num_args = None;
+            let mut expansion_mode = ExpansionMode::Expandable;
+            for (key, value) in fields {
+                match (key.as_str(), value) {
+                    ("body", Value::String(body)) => definition = Some(body),
+                    ("num_args", Value::Integer(n)) => {
+                        let n = u8::try_from(n)
+                            .ok()
+                            .filter(|n| *n <= 9)
+                            .ok_or_else(|| format!("invalid num_args {n} for {name}"))?;
+                        num_args = Some(n);
+                    }
+                    ("unexpandable", Value::Bool(unexpandable)) => {
+                        if unexpandable {
+                            expansion_mode = ExpansionMode::Unexpandable;
+                        }
+                    }
+                    (key, value) => {
+                        return Err(format!(
+                            "unexpected field {key:?} with {} for {name}",
+                            value.kind()
+                        ))
+                    }
+                }
+            }
+            let body = definition.ok_or_else(|| format!("missing body for {name}"))?;
+            Macro {
+                body,
+                num_args,
+                expansion_mode,
+            }
+        }
+        value => {
+            return Err(format!(
+                "expected a body or a table for {name}, got {}",
+                value.kind()
+            ))
+        }
+    };
+    Ok(Definition {
+        name,
+        definition,
+        replace: true,
+    })
+}
+
+/// Error at byte offset `pos` of the input.
+fn error(input: &str, pos: usize, reason: impl core::fmt::Display) -> Error {
+    let line = input[..pos.min(input.len())].matches('\n').count() + 1;
+    Error::InvalidOption {
+        field: "macros",
+        reason: format!("line {line}: {reason}"),
+    }
+}
+
+fn parse_latex(input: &str) -> Result<Vec<Definition>> {
+    let bytes = input.as_bytes();
+    let mut definitions = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'\\' => {}
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        let start = i;
+        let (command, end) = control_sequence(input, i);
+        i = end;
+        let starred = bytes.get(i) == Some(&b'*');
+        if starred {
+            i += 1;
+        }
+        let definition = match command {
+            r"\newcommand" | r"\renewcommand" | r"\providecommand" => {
+                let (name, next) = defined_name(input, i)?;
+                i = skip_latex_whitespace(input, next);
+                let mut num_args = None;
+                if bytes.get(i) == Some(&b'[') {
+                    let close = input[i..]
+                        .find(']')
+                        .map(|p| i + p)
+                        .ok_or_else(|| error(input, i, "unterminated number of arguments"))?;
+                    let n = input[i + 1..close]
+                        .trim()
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|n| *n <= 9)
+                        .ok_or_else(|| error(input, i, "invalid number of arguments"))?;
+                    num_args = Some(n);
+                    i = skip_latex_whitespace(input, close + 1);
+                    if bytes.get(i) == Some(&b'[') {
+                        return Err(error(input, i, "optional arguments are not supported"));
+                    }
+                }
+                let (body, next) = group(input, i)?;
+                i = next;
+                Definition {
+                    name,
+                    definition: Macro {
+                        num_args,
+                        ..Macro::new(body)
+                    },
+                    replace: command != r"\providecommand",
+                }
+            }
+            r"\def" | r"\gdef" => {
+                let (name, next) = control_sequence(input, skip_latex_whitespace(input, i));
+                if name.len() < 2 {
+                    return Err(error(input, start, "expected a macro name"));
+                }
+                i = next;
+                // Only undelimited parameters, i.e. `#1#2...`, are supported.
+                let mut num_args = 0;
+                while bytes.get(i) == Some(&b'#') {
+                    if bytes.get(i + 1) != Some(&(b'1' + num_args)) {
+                        return Err(error(input, i, "invalid parameter text"));
+                    }
+                    num_args += 1;
+                    i += 2;
+                }
+                if bytes.get(i) != Some(&b'{') {
+                    return Err(error(input, i, "delimited parameters are not supported"));
+                }
+                let (body, next) = group(input, i)?;
+                i = next;
+                Definition {
+                    name: name.to_owned(),
+                    definition: Macro {
+                        num_args: (num_args > 0).then_some(num_args),
+                        ..Macro::new(body)
+                    },
+                    replace: true,
+                }
+            }
+            r"\DeclareMathOperator" => {
+                let (name, next) = defined_name(input, i)?;
+                let (text, next) = group(input, skip_latex_whitespace(input, next))?;
+                i = next;
+                let operator = if starred {
+                    r"\operatorname*"
+                } else {
+                    r"\operatorname"
+                };
+                Definition {
+                    name,
+                    definition: Macro::new(format!("{operator}{{{text}}}")),
+                    replace: true,
+                }
+            }
+            _ => continue,
+        };
+        definitions.push(definition);
+    }
+    Ok(definitions)
+}
+
+/// Read the control sequence at `i`, i.e. a backslash followed by letters or
+/// by a single other character.
+fn control_sequence(input: &str, i: usize) -> (&str, usize) {
+    let bytes = input.as_bytes();
+    if bytes.get(i) != Some(&b'\\') {
+        return ("", i);
+    }
+    let mut end = i + 1;
+    while end < bytes.len() && bytes[end].is_ascii_alphabetic() {
+        end += 1;
+    }
+    if end == i + 1 {
+        end += input[end..].chars().next().map_or(0, char::len_utf8);
+    }
+    (&input[i..end], end)
+}
+
+/// Read the name defined by `\newcommand` and alike, either `\name` or
+/// `{\name}`.
+fn defined_name(input: &str, i: usize) -> Result<(String, usize)> {
+    let bytes = input.as_bytes();
+    let mut i = skip_latex_whitespace(input, i);
+    let braced = bytes.get(i) == Some(&b'{');
+    if braced {
+        i = skip_latex_whitespace(input, i + 1);
+    }
+    let (name, mut end) = control_sequence(input, i);
+    if name.len() < 2 {
+        return Err(error(input, i, "expected a macro name"));
+    }
+    if braced {
+        end = skip_latex_whitespace(input, end);
+        if bytes.get(end) != Some(&b'}') {
+            return Err(error(input, end, "expected `}` after the macro name"));
+        }
+        end += 1;
+    }
+    Ok((name.to_owned(), end))
+}
+
+/// Read the content of the brace group at `i`.
+fn group(input: &str, i: usize) -> Result<(&str, usize)> {
+    if input.as_bytes().get(i) != Some(&b'{') {
+        return Err(error(input, i, "expected `{`"));
+    }
+    let close =
+        matching_brace(input.as_bytes(), i).ok_or_else(|| error(input, i, "unbalanced braces"))?;
+    Ok((&input[i + 1..close], close + 1))
+}
+
+fn skip_latex_whitespace(input: &str, mut i: usize) -> usize {
+    let bytes = input.as_bytes();
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn parse_json(input: &str) -> Result<Vec<Definition>> {
+    to_definitions(data::parse_json(input))
+}
+
+fn parse_toml(input: &str) -> Result<Vec<Definition>> {
+    to_definitions(data::parse_toml(input))
+}
+
+fn to_definitions(
+    fields: core::result::Result<Vec<(String, Value)>, SyntaxError>,
+) -> Result<Vec<Definition>> {
+    let invalid = |reason| Error::InvalidOption {
+        field: "macros",
+        reason,
+    };
+    fields
+        .map_err(|e| invalid(e.to_string()))?
+        .into_iter()
+        .map(|(name, value)| to_definition(name, value).map_err(invalid))
+        .collect()
+}
@@ -0,0 +1,74 @@
+//! Render math inside a [pulldown-cmark](https://crates.io/crates/pulldown-cmark) event stream.
+//!
+//! Both math events, produced when [`ENABLE_MATH`](pulldown_cmark::Options::ENABLE_MATH)
+//! is set, and math delimited in text events are rendered, so the same code
+//! works whether or not the parser knows about math.
+//!
+//! # Examples
+//!
+//! ```
+//! use pulldown_cmark::{Event, Options, Parser};
+//!
+//! let parser = Parser::new_ext("Let $x^2$ be", Options::ENABLE_MATH);
+//! let events = katex::markdown::process_events(parser, katex::Opts::default()).unwrap();
+//! assert!(matches!(&events[2], Event::InlineHtml(html) if html.contains(r#"class="katex""#)));
+//! ```
+
+use crate::{
+    error::Result,
+    opts::Opts,
+    split::{split_math, DelimiterConfig, Segment},
+};
+use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+
+/// Replace math in the events with rendered HTML.
+///
+/// Inline and display math events, as well as math found by
+/// [`split_math`] with the default delimiters in text events, become inline
+/// HTML events. Text in code blocks is left as is. The display mode of
+/// `opts` is overridden for each span.
+pub fn process_events<'a, I>(events: I, opts: impl AsRef<Opts>) -> Result<Vec<Event<'a>>>
+where
+    I: IntoIterator<Item = Event<'a>>,
+{
+    let mut opts = opts.as_ref().clone();
+    let config = DelimiterConfig::default();
+    let mut render = |content: &str, display: bool| -> Result<Event<'a>> {
+        opts.set_display_mode(display);
+        let html = crate::render_with_opts(content, &opts)?;
+        Ok(Event::InlineHtml(CowStr::from(html)))
+    };
+
+    let mut output = Vec::new();
+    let mut in_code_block = false;
+    for event in events {
+        match event {
+            Event::InlineMath(content) => output.push(render(&content, false)?),
+            Event::DisplayMath(content) => output.push(render(&content, true)?),
+            Event::Text(text) if !in_code_block => {
+                let segments = split_math(&text, &config);
+                if segments.iter().all(|s| matches!(s, Segment::Text(_))) {
+                    output.push(Event::Text(text));
+                    continue;
+                }
+                for segment in segments {
+                    output.push(match segment {
+                        Segment::Text(text) => Event::Text(CowStr::from(text.to_owned())),
+                        Segment::Math {
+                            content, display, ..
+                        } => render(content, display)?,
+                    });
+                }
+            }
+            event => {
+                match &event {
+                    Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+                    Event::End(TagEnd::CodeBlock) => in_code_block = false,
+                    _ => {}
+                }
+                output.push(event);
+            }
+        }
+    }
+    Ok(output)
+}
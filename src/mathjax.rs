@@ -0,0 +1,197 @@
+//! Emulation of MathJax, see
+//! [`mathjax_compat`](crate::OptsBuilder::mathjax_compat).
+
+use crate::{
+    opts::Macro,
+    preprocess::{control_words, matching_brace, skip_whitespace, Edit},
+};
+use std::borrow::Cow;
+
+/// Macros emulating MathJax commands, as (name, body, number of parameters).
+const MACROS: &[(&str, &str, u8)] = &[
+    // Extensions are loaded on demand by MathJax, KaTeX bundles its own.
+    (r"\require", "", 1),
+    // Cross-references are not supported, so labels are dropped.
+    (r"\label", "", 1),
+    (r"\mbox", r"\text{#1}", 1),
+    (r"\emph", r"\textit{#1}", 1),
+    (r"\displaylines", r"\begin{gathered}#1\end{gathered}", 1),
+    (r"\mspace", r"\mskip{#1}", 1),
+    (r"\idotsint", r"\int\cdots\int", 0),
+    (r"\class", r"\htmlClass{#1}{#2}", 2),
+    (r"\cssId", r"\htmlId{#1}{#2}", 2),
+    (r"\style", r"\htmlStyle{#1}{#2}", 2),
+];
+
+/// Commands which [`rewrite`] handles, rather than macros.
+pub(crate) const REWRITTEN: &[&str] = &[r"\unicode", r"\DeclareMathOperator"];
+
+/// Commands taking colors, which MathJax also accepts in a color model,
+/// e.g. `\color[rgb]{1,0,0}`, with their number of colors.
+const COLOR_COMMANDS: &[(&str, usize)] = &[
+    ("color", 1),
+    ("textcolor", 1),
+    ("colorbox", 1),
+    ("fcolorbox", 2),
+];
+
+/// The macros emulating MathJax commands.
+pub(crate) fn macros() -> impl Iterator<Item = (String, Macro)> {
+    MACROS.iter().map(|&(name, body, num_args)| {
+        let definition = Macro {
+            num_args: Some(num_args),
+            ..Macro::new(body)
+        };
+        (name.to_owned(), definition)
+    })
+}
+
+/// Rewrite the MathJax constructs which macros cannot emulate into their
+/// KaTeX equivalents, recording the rewrites in `edits`:
+///
+/// * `\unicode{x2192}` or `\unicode{8594}` into the character,
+/// * `\DeclareMathOperator{\name}{text}` into `\def` and `\operatorname`,
+///   or `\operatorname*` for operators with limits. Unlike `\newcommand`,
+///   `\def` replaces the operators which KaTeX already defines, e.g.
+///   `\argmax`,
+/// * colors in the `rgb`, `RGB`, `gray` and `HTML` models into hex colors.
+pub(crate) fn rewrite<'a>(input: &'a str, edits: &mut Vec<Edit>) -> Cow<'a, str> {
+    let rewrites = edits.len();
+    let mut output = String::new();
+    let mut last = 0;
+    for (name, start, end) in control_words(input) {
+        if start < last {
+            continue;
+        }
+        let rewritten = match name {
+            "unicode" => unicode(input, end),
+            "DeclareMathOperator" => declare_math_operator(input, end),
+            _ => COLOR_COMMANDS
+                .iter()
+                .find(|(command, _)| *command == name)
+                .and_then(|&(command, colors)| color_model(input, command, colors, end)),
+        };
+        let Some((replacement, replaced_end)) = rewritten else {
+            continue;
+        };
+        output.push_str(&input[last..start]);
+        let output_start = output.len();
+        output.push_str(&replacement);
+        edits.push((start..replaced_end, output_start..output.len()));
+        last = replaced_end;
+    }
+    if edits.len() == rewrites {
+        return Cow::Borrowed(input);
+    }
+    output.push_str(&input[last..]);
+    Cow::Owned(output)
+}
+
+/// Rewrite `\unicode[font]{code}`, whose font is ignored, given the end of
+/// `\unicode`.
+fn unicode(input: &str, i: usize) -> Option<(String, usize)> {
+    let i = optional(input, i).map_or(i, |(_, end)| end);
+    let (code, end) = group(input, i)?;
+    let code = code.trim();
+    let value = match code
+        .strip_prefix("0x")
+        .or_else(|| code.strip_prefix(['x', 'X']))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => code.parse().ok()?,
+    };
+    let c = char::from_u32(value).filter(|c| !c.is_control())?;
+    // Characters with a special meaning in LaTeX are typeset by their code.
+    let replacement = if r"\{}#$%&^_~".contains(c) {
+        format!("{{\\char\"{value:X}}}")
+    } else {
+        c.to_string()
+    };
+    Some((replacement, end))
+}
+
+/// Rewrite `\DeclareMathOperator{\name}{text}`, given the end of
+/// `\DeclareMathOperator`.
+fn declare_math_operator(input: &str, i: usize) -> Option<(String, usize)> {
+    let mut i = skip_whitespace(input.as_bytes(), i);
+    let star = input[i..].starts_with('*');
+    if star {
+        i += 1;
+    }
+    let (name, i) = match group(input, i) {
+        Some((name, end)) => (name.trim(), end),
+        None => {
+            let i = skip_whitespace(input.as_bytes(), i);
+            let (_, start, end) = control_words(&input[i..]).next()?;
+            (&input[i + start..i + end], i + end)
+        }
+    };
+    let is_command = name
+        .strip_prefix('\\')
+        .is_some_and(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic()));
+    if !is_command {
+        return None;
+    }
+    let (text, end) = group(input, i)?;
+    let star = if star { "*" } else { "" };
+    Some((format!(r"\def{name}{{\operatorname{star}{{{text}}}}}"), end))
+}
+
+/// Rewrite the colors of `\command[model]{color}`, given the end of the
+/// command.
+fn color_model(input: &str, command: &str, colors: usize, i: usize) -> Option<(String, usize)> {
+    let (model, mut i) = optional(input, i)?;
+    let mut replacement = format!("\\{command}");
+    for _ in 0..colors {
+        let (spec, end) = group(input, i)?;
+        replacement.push('{');
+        replacement.push_str(&hex_color(model.trim(), spec)?);
+        replacement.push('}');
+        i = end;
+    }
+    Some((replacement, i))
+}
+
+/// Convert a color in a model of the LaTeX `xcolor` package to `#rrggbb`.
+fn hex_color(model: &str, spec: &str) -> Option<String> {
+    fn unit(component: &str) -> Option<u8> {
+        let value: f64 = component.parse().ok()?;
+        // In range, so that the conversion does not saturate.
+        (0.0..=1.0)
+            .contains(&value)
+            .then(|| (value * 255.0).round() as u8)
+    }
+
+    let components: Vec<&str> = spec.split(',').map(str::trim).collect();
+    let [r, g, b] = match (model, components.as_slice()) {
+        ("rgb", [r, g, b]) => [unit(r)?, unit(g)?, unit(b)?],
+        ("RGB", [r, g, b]) => [r.parse().ok()?, g.parse().ok()?, b.parse().ok()?],
+        ("gray", [gray]) => [unit(gray)?; 3],
+        ("HTML", [hex]) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            return Some(format!("#{hex}"));
+        }
+        _ => return None,
+    };
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+/// Read the `{...}` group at `i`, after whitespace, returning its content
+/// and the end of the closing brace.
+fn group(input: &str, i: usize) -> Option<(&str, usize)> {
+    let bytes = input.as_bytes();
+    let open = skip_whitespace(bytes, i);
+    if bytes.get(open) != Some(&b'{') {
+        return None;
+    }
+    let close = matching_brace(bytes, open)?;
+    Some((&input[open + 1..close], close + 1))
+}
+
+/// Read the optional `[...]` argument at `i`, after whitespace, returning
+/// its content and the end of the closing bracket.
+fn optional(input: &str, i: usize) -> Option<(&str, usize)> {
+    let open = skip_whitespace(input.as_bytes(), i);
+    let rest = input[open..].strip_prefix('[')?;
+    let len = rest.find(']')?;
+    Some((&rest[..len], open + len + 2))
+}
@@ -0,0 +1,117 @@
+//! Counters and histograms of renders, for monitoring.
+//!
+//! This module is only available with the `metrics` feature. Once a
+//! [sink](`MetricsSink`) is [installed](set_sink), every render, whether by
+//! [`render`](crate::render) or by a [`Renderer`](crate::Renderer), and every
+//! JS engine initialization is reported to it. The sink can forward them to
+//! any monitoring system, e.g. the `metrics` crate facade:
+//!
+//! ```ignore
+//! struct Facade;
+//!
+//! impl katex::metrics::MetricsSink for Facade {
+//!     fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)], value: u64) {
+//!         let labels: Vec<_> = labels.iter().map(|&(k, v)| (k, v.to_owned())).collect();
+//!         metrics::counter!(name, &labels).increment(value);
+//!     }
+//!
+//!     fn record_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+//!         let labels: Vec<_> = labels.iter().map(|&(k, v)| (k, v.to_owned())).collect();
+//!         metrics::histogram!(name, &labels).record(value);
+//!     }
+//! }
+//!
+//! katex::metrics::set_sink(Facade);
+//! ```
+//!
+//! Durations are measured with [`Instant`], which is not available on
+//! `wasm32-unknown-unknown`.
+
+use crate::error::Result;
+use std::{
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+/// Counter of renders, labeled with `result`, `ok` or `error`.
+pub const RENDERS: &str = "katex_renders_total";
+
+/// Counter of failed renders, labeled with the error `code`, see
+/// [`Error::code`](crate::Error::code).
+pub const RENDER_ERRORS: &str = "katex_render_errors_total";
+
+/// Histogram of render durations, in seconds.
+pub const RENDER_DURATION: &str = "katex_render_duration_seconds";
+
+/// Counter of JS engine initializations, labeled with `result`, `ok` or
+/// `error`.
+pub const ENGINE_INITS: &str = "katex_engine_inits_total";
+
+/// Histogram of JS engine initialization durations, in seconds.
+pub const ENGINE_INIT_DURATION: &str = "katex_engine_init_duration_seconds";
+
+/// Receiver of metrics.
+///
+/// Metrics are reported from the rendering threads, so implementations
+/// should be cheap, e.g. updating atomics.
+pub trait MetricsSink: Send + Sync {
+    /// Increment the counter `name` by `value`.
+    fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)], value: u64);
+
+    /// Record `value` in the histogram `name`.
+    fn record_histogram(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64);
+}
+
+static SINK: RwLock<Option<Arc<dyn MetricsSink>>> = RwLock::new(None);
+
+/// Install the sink receiving the metrics of all threads, replacing the
+/// previous one.
+pub fn set_sink(sink: impl MetricsSink + 'static) {
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(sink));
+}
+
+/// Remove the sink, if any. Metrics are then no longer measured.
+pub fn clear_sink() {
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn sink() -> Option<Arc<dyn MetricsSink>> {
+    SINK.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn result_label<T>(result: &Result<T>) -> &'static str {
+    if result.is_ok() {
+        "ok"
+    } else {
+        "error"
+    }
+}
+
+/// Run `f`, reporting it as a render.
+pub(crate) fn measure_render<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let Some(sink) = sink() else {
+        return f();
+    };
+    let start = Instant::now();
+    let result = f();
+    let duration = start.elapsed().as_secs_f64();
+    sink.increment_counter(RENDERS, &[("result", result_label(&result))], 1);
+    if let Err(e) = &result {
+        sink.increment_counter(RENDER_ERRORS, &[("code", e.code())], 1);
+    }
+    sink.record_histogram(RENDER_DURATION, &[], duration);
+    result
+}
+
+/// Run `f`, reporting it as a JS engine initialization.
+pub(crate) fn measure_engine_init<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let Some(sink) = sink() else {
+        return f();
+    };
+    let start = Instant::now();
+    let result = f();
+    let duration = start.elapsed().as_secs_f64();
+    sink.increment_counter(ENGINE_INITS, &[("result", result_label(&result))], 1);
+    sink.record_histogram(ENGINE_INIT_DURATION, &[], duration);
+    result
+}
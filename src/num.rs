@@ -0,0 +1,188 @@
+//! Format numbers as LaTeX.
+//!
+//! Reports typesetting computed values need the same conventions everywhere,
+//! e.g. `1.23\times10^{-4}` rather than `1.23e-4`, or `12{,}345` rather than
+//! `12,345`, whose comma is spaced as punctuation in math mode. The numbers
+//! can be [substituted](crate::vars) into formulas with
+//! [`MathValue`](crate::MathValue).
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::num::NumberFormat;
+//!
+//! let format = NumberFormat::significant(3);
+//! assert_eq!(format.format(0.000123456).unwrap(), r"0.000123");
+//! let format = NumberFormat::significant(3).with_scientific(true);
+//! assert_eq!(format.format(0.000123456).unwrap(), r"1.23\times10^{-4}");
+//! let format = NumberFormat::fixed(2).with_grouping("{,}");
+//! assert_eq!(format.format(1234567.891).unwrap(), r"1{,}234{,}567.89");
+//! ```
+
+use crate::error::{Error, Result};
+
+/// How to write a number.
+///
+/// Infinite numbers are written as `\infty`, and NaN cannot be formatted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NumberFormat {
+    /// Number of digits after the decimal point, or after the decimal point
+    /// of the mantissa in scientific notation. The shortest representation
+    /// which reads back as the same number if neither this nor the
+    /// significant digits are set.
+    pub precision: Option<usize>,
+    /// Number of significant digits, overriding the precision.
+    pub significant_digits: Option<usize>,
+    /// Whether to write the number in scientific notation, e.g.
+    /// `1.5\times10^{3}`.
+    pub scientific: bool,
+    /// LaTeX inserted between groups of three digits of the integer part,
+    /// e.g. `{,}` or `\,`.
+    pub grouping: Option<String>,
+}
+
+impl NumberFormat {
+    /// Write numbers with `precision` digits after the decimal point.
+    pub fn fixed(precision: usize) -> Self {
+        Self {
+            precision: Some(precision),
+            ..Self::default()
+        }
+    }
+
+    /// Write numbers in scientific notation, with `precision` digits after
+    /// the decimal point of the mantissa.
+    pub fn scientific(precision: usize) -> Self {
+        Self {
+            precision: Some(precision),
+            scientific: true,
+            ..Self::default()
+        }
+    }
+
+    /// Write numbers with `digits` significant digits.
+    pub fn significant(digits: usize) -> Self {
+        Self {
+            significant_digits: Some(digits),
+            ..Self::default()
+        }
+    }
+
+    /// Set whether to write numbers in scientific notation.
+    pub fn with_scientific(mut self, scientific: bool) -> Self {
+        self.scientific = scientific;
+        self
+    }
+
+    /// Group the digits of the integer part with a separator, e.g. `{,}`.
+    pub fn with_grouping(mut self, separator: impl Into<String>) -> Self {
+        self.grouping = Some(separator.into());
+        self
+    }
+
+    /// Write a number as LaTeX.
+    ///
+    /// Fail with [`Error::InvalidInput`] for NaN.
+    pub fn format(&self, value: f64) -> Result<String> {
+        if value.is_nan() {
+            return Err(Error::InvalidInput(
+                "num: NaN cannot be formatted".to_owned(),
+            ));
+        }
+        let sign = if value.is_sign_negative() && value != 0.0 {
+            "-"
+        } else {
+            ""
+        };
+        let value = value.abs();
+        if value.is_infinite() {
+            return Ok(format!(r"{sign}\infty"));
+        }
+        let precision = match self.significant_digits {
+            Some(digits) => Some(digits.max(1) - 1),
+            None => self.precision,
+        };
+        if self.scientific {
+            let (mantissa, exponent) = scientific_parts(value, precision);
+            return Ok(match exponent {
+                0 => format!("{sign}{mantissa}"),
+                exponent => format!(r"{sign}{mantissa}\times10^{{{exponent}}}"),
+            });
+        }
+        let fixed = match (self.significant_digits, precision) {
+            (Some(_), precision) => {
+                let (mantissa, exponent) = scientific_parts(value, precision);
+                from_scientific(&mantissa, exponent)
+            }
+            (None, Some(precision)) => format!("{value:.precision$}"),
+            (None, None) => value.to_string(),
+        };
+        Ok(format!("{sign}{}", self.group(&fixed)))
+    }
+
+    /// Write an integer as LaTeX, exactly unless a precision, significant
+    /// digits or the scientific notation are set.
+    pub fn format_int(&self, value: i64) -> String {
+        if self.precision.is_some() || self.significant_digits.is_some() || self.scientific {
+            // Finite, so it cannot fail.
+            return self.format(value as f64).unwrap_or_default();
+        }
+        let sign = if value < 0 { "-" } else { "" };
+        format!("{sign}{}", self.group(&value.unsigned_abs().to_string()))
+    }
+
+    /// Insert the separator between groups of three digits of the integer
+    /// part of an unsigned number.
+    fn group(&self, number: &str) -> String {
+        let Some(separator) = &self.grouping else {
+            return number.to_owned();
+        };
+        let (integer, fraction) = match number.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (number, None),
+        };
+        let mut grouped = String::with_capacity(number.len() * 2);
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push_str(separator);
+            }
+            grouped.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            grouped.push('.');
+            grouped.push_str(fraction);
+        }
+        grouped
+    }
+}
+
+/// Split a non-negative number into its rounded mantissa and exponent.
+fn scientific_parts(value: f64, precision: Option<usize>) -> (String, i32) {
+    let formatted = match precision {
+        Some(precision) => format!("{value:.precision$e}"),
+        None => format!("{value:e}"),
+    };
+    let (mantissa, exponent) = formatted
+        .split_once('e')
+        .expect("the exponent is always written");
+    let exponent = exponent.parse().expect("the exponent is an integer");
+    (mantissa.to_owned(), exponent)
+}
+
+/// Write a mantissa and an exponent in fixed notation, keeping the digits of
+/// the mantissa, e.g. `1.20` and `-3` as `0.00120`.
+fn from_scientific(mantissa: &str, exponent: i32) -> String {
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    // The position of the decimal point in `digits`.
+    let point = exponent + 1;
+    if point <= 0 {
+        let zeros = "0".repeat(point.unsigned_abs() as usize);
+        return format!("0.{zeros}{digits}");
+    }
+    let point = point as usize;
+    if point >= digits.len() {
+        let zeros = "0".repeat(point - digits.len());
+        return format!("{digits}{zeros}");
+    }
+    format!("{}.{}", &digits[..point], &digits[point..])
+}
@@ -1,9 +1,20 @@
 //! Custom KaTeX behaviors.
 
-use crate::{error::Result, js_engine::JsEngine};
+use crate::{
+    error::{Error, Result},
+    js_engine::JsEngine,
+    macro_parser::{self, MacroFormat},
+    mathjax,
+};
+use core::fmt;
 use derive_builder::Builder;
-use itertools::process_results;
-use std::collections::HashMap;
+use itertools::{process_results, Itertools};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::Arc,
+};
 
 /// Options to be passed to KaTeX.
 ///
@@ -12,11 +23,12 @@ use std::collections::HashMap;
 #[derive(Clone, Builder, Debug, Default)]
 #[builder(default)]
 #[builder(setter(into, strip_option))]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Opts {
     /// Whether to render the math in the display mode.
-    display_mode: Option<bool>,
+    pub(crate) display_mode: Option<bool>,
     /// KaTeX output type.
-    output_type: Option<OutputType>,
+    pub(crate) output_type: Option<OutputType>,
     /// Whether to have `\tags` rendered on the left instead of the right.
     leqno: Option<bool>,
     /// Whether to make display math flush left.
@@ -27,23 +39,101 @@ pub struct Opts {
     error_color: Option<String>,
     /// Collection of custom macros.
     /// Read <https://katex.org/docs/options.html> for more information.
-    macros: HashMap<String, String>,
+    #[builder(setter(custom))]
+    pub(crate) macros: HashMap<String, Macro>,
+    /// Palette of named colors, as a map from name to color, e.g. `accent` to
+    /// `#ff6600`. Each name defines a macro typesetting its argument in the
+    /// color, e.g. `\accent{x}`.
+    colors: HashMap<String, String>,
     /// Specifies a minimum thickness, in ems.
     /// Read <https://katex.org/docs/options.html> for more information.
     min_rule_thickness: Option<f64>,
-    /// Max size for user-specified sizes.
-    /// If set to `None`, users can make elements and spaces arbitrarily large.
+    /// Max size for user-specified sizes, in ems.
+    /// If [unlimited](Limit::Unlimited), users can make elements and spaces arbitrarily large.
     /// Read <https://katex.org/docs/options.html> for more information.
-    #[allow(clippy::option_option)]
-    max_size: Option<Option<f64>>,
+    max_size: Limit<f64>,
     /// Limit the number of macro expansions to the specified number.
-    /// If set to `None`, the macro expander will try to fully expand as in LaTeX.
+    /// If [unlimited](Limit::Unlimited), the macro expander will try to fully expand as in LaTeX.
     /// Read <https://katex.org/docs/options.html> for more information.
-    #[allow(clippy::option_option)]
-    max_expand: Option<Option<i32>>,
+    max_expand: Limit<u32>,
     /// Whether to trust users' input.
     /// Read <https://katex.org/docs/options.html> for more information.
     trust: Option<bool>,
+    /// Commands to trust, e.g. `\htmlClass`, when [`trust`](OptsBuilder::trust) is not set.
+    /// Read <https://katex.org/docs/options.html> for more information.
+    trusted_commands: Vec<String>,
+    /// How to handle input which KaTeX accepts but LaTeX does not, e.g.
+    /// Unicode text in math mode.
+    /// Read <https://katex.org/docs/options.html> for more information.
+    strict: Option<StrictMode>,
+    /// How to handle specific [strict errors](StrictErrorCode), overriding
+    /// [`strict`](OptsBuilder::strict) for them.
+    strict_overrides: HashMap<StrictErrorCode, StrictMode>,
+    /// Options passed to KaTeX as is, overriding the typed ones.
+    #[builder(setter(custom))]
+    raw_options: BTreeMap<String, JsonValue>,
+    /// How to handle emoji inside text-mode commands such as `\text{}`.
+    pub(crate) text_unicode_policy: Option<TextUnicodePolicy>,
+    /// How to handle input which looks HTML-escaped, e.g. `a &amp; b`.
+    pub(crate) escaped_input_policy: Option<EscapedInputPolicy>,
+    /// Whether to normalize the input to Unicode NFC, e.g. to compose a
+    /// letter followed by a combining accent.
+    pub(crate) normalize_unicode: Option<bool>,
+    /// Whether to replace Unicode math characters outside text-mode
+    /// commands with LaTeX commands, e.g. `≤` with `\le` and `α` with
+    /// `\alpha`.
+    pub(crate) replace_unicode_math: Option<bool>,
+    /// Grouping of digits in rendered numbers.
+    pub(crate) digit_grouping: Option<DigitGrouping>,
+    /// Callback rewriting the URLs of resources, e.g. images of
+    /// `\includegraphics`.
+    pub(crate) resource_resolver: Option<ResourceResolver>,
+    /// Whether to inline the few styles needed to display the output without
+    /// the KaTeX stylesheet.
+    pub(crate) minimal_inline_css: Option<bool>,
+    /// Whether to sort the attributes of the rendered HTML, so that equal
+    /// outputs compare equal as text across JS backends.
+    pub(crate) deterministic: Option<bool>,
+    /// Font size of the output, in ems of the surrounding text. KaTeX's
+    /// stylesheet uses 1.21em.
+    pub(crate) font_size_em: Option<f64>,
+    /// Whether to typeset inline math in display style, e.g. with full-size
+    /// fractions and limits above and below sums.
+    pub(crate) force_display_style: Option<bool>,
+    /// Whether to emulate the MathJax commands and syntax which KaTeX lacks,
+    /// e.g. `\require`, `\unicode`, `\DeclareMathOperator` or
+    /// `\color[rgb]{1,0,0}`, so that equations written for MathJax render
+    /// unchanged. The [macros](OptsBuilder::macros) override the emulated
+    /// commands, and `\class`, `\cssId` and `\style` need the
+    /// [trust](OptsBuilder::trust) of the commands they map to, e.g.
+    /// `\htmlClass`.
+    pub(crate) mathjax_compat: Option<bool>,
+    /// Whether to remove the MathML annotation duplicating the LaTeX source,
+    /// which takes a large part of the output of math-heavy pages.
+    pub(crate) strip_annotations: Option<bool>,
+    /// Whether to add the LaTeX source as a `data-latex` attribute of the
+    /// root `katex` element, e.g. to copy it as LaTeX or to hydrate the
+    /// equation on the client.
+    pub(crate) embed_source: Option<bool>,
+    /// Whether to add the [spoken description](crate::a11y) of the equation
+    /// as the `aria-label` of the root `katex` element, with the `img` role,
+    /// so that screen readers without MathML support read it. Screen readers
+    /// then ignore the MathML. It needs an [output type](OptsBuilder::output_type)
+    /// with MathML.
+    pub(crate) aria_label: Option<bool>,
+    /// Whether to record which of the [macros](OptsBuilder::add_macro) the
+    /// render expands, in [`RenderOutput::used_macros`](crate::RenderOutput::used_macros).
+    track_macros: Option<bool>,
+    /// Maximum length of the input in bytes, checked before KaTeX runs.
+    pub(crate) max_input_len: Option<usize>,
+    /// Maximum size of the rendered HTML in bytes, checked by the JS engine
+    /// before passing the HTML to Rust.
+    pub(crate) max_output_bytes: Option<usize>,
+    /// Whether to remove anything but KaTeX's own elements, attributes and
+    /// styles from the rendered HTML, including the attributes set by
+    /// `\htmlClass`, `\htmlStyle` and alike, and unsafe URLs.
+    #[cfg(feature = "sanitize")]
+    pub(crate) sanitize: Option<bool>,
 }
 
 impl Opts {
@@ -52,6 +142,92 @@ impl Opts {
         OptsBuilder::default()
     }
 
+    /// Return options tuned for rendering untrusted input, e.g. from users
+    /// of a web service.
+    ///
+    /// They bound the work done per render and keep the output inert:
+    ///
+    /// * inputs longer than 10 000 bytes are rejected before KaTeX runs, and
+    ///   outputs larger than 1 MiB before they are passed to Rust;
+    /// * macro expansion is limited to 500 steps and user-specified sizes
+    ///   to 10 em;
+    /// * commands such as `\href` and `\htmlClass` are not trusted;
+    /// * invalid input fails with an error, instead of being rendered in the
+    ///   [error color](OptsBuilder::error_color);
+    /// * with the `sanitize` feature, the output is
+    ///   [sanitized](OptsBuilder::sanitize).
+    ///
+    /// The options can be changed afterwards with the `set_*` methods. To
+    /// also bound the memory of the JS engine, render with a
+    /// [`Renderer`](crate::Renderer) built with
+    /// [`EngineBuilder::memory_limit`](crate::EngineBuilder::memory_limit).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let opts = katex::Opts::hardened();
+    /// assert!(katex::render_with_opts("x^2", &opts).is_ok());
+    /// assert!(katex::render_with_opts(&"x".repeat(20_000), &opts).is_err());
+    /// let html = katex::render_with_opts(r"\href{https://katex.org}{x}", &opts).unwrap();
+    /// assert!(!html.contains("href="));
+    /// ```
+    pub fn hardened() -> Self {
+        let mut opts = Self::default();
+        opts.set_max_input_len(10_000);
+        opts.set_max_output_bytes(1 << 20);
+        opts.set_max_expand(500);
+        opts.set_max_size(10.0);
+        opts.set_trust(false);
+        opts.set_throw_on_error(true);
+        #[cfg(feature = "sanitize")]
+        opts.set_sanitize(true);
+        opts
+    }
+
+    /// Check the options.
+    ///
+    /// [`OptsBuilder::build`] runs the same checks, so this is only needed
+    /// after changing options with the `set_*` methods. `build` reports
+    /// failed checks as a message, while this returns
+    /// [`Error::InvalidOption`], like [`OptsBuilder::try_build`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut opts = katex::Opts::default();
+    /// opts.set_min_rule_thickness(-1.0);
+    /// assert!(matches!(
+    ///     opts.validate(),
+    ///     Err(katex::Error::InvalidOption { field: "min_rule_thickness", .. })
+    /// ));
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        if let Some(output_type) = &self.output_type {
+            validate_output_type(output_type)?;
+        }
+        if let Some(error_color) = &self.error_color {
+            validate_error_color(error_color)?;
+        }
+        validate_macros(&self.macros)?;
+        validate_colors(&self.colors, Some(&self.macros))?;
+        if let Some(min_rule_thickness) = self.min_rule_thickness {
+            validate_min_rule_thickness(min_rule_thickness)?;
+        }
+        validate_raw_options(&self.raw_options)?;
+        if let Some(font_size_em) = self.font_size_em {
+            validate_font_size_em(font_size_em)?;
+        }
+        validate_strip_annotations(
+            self.output_type.as_ref(),
+            self.strip_annotations.unwrap_or_default(),
+        )?;
+        validate_aria_label(
+            self.output_type.as_ref(),
+            self.aria_label.unwrap_or_default(),
+        )?;
+        validate_trusted_commands(&self.trusted_commands)
+    }
+
     /// Set whether to render the math in the display mode.
     pub fn set_display_mode(&mut self, flag: bool) {
         self.display_mode = Some(flag);
@@ -85,27 +261,88 @@ impl Opts {
     /// Add a custom macro.
     /// Read <https://katex.org/docs/options.html> for more information.
     pub fn add_macro(&mut self, entry_name: String, entry_data: String) {
+        self.macros.insert(entry_name, Macro::new(entry_data));
+    }
+
+    /// Add a custom macro with explicit parameters.
+    pub fn add_macro_with_args(&mut self, entry_name: String, entry_data: Macro) {
         self.macros.insert(entry_name, entry_data);
     }
 
+    /// Set the palette of named colors, as a map from name to color.
+    pub fn set_colors(&mut self, colors: HashMap<String, String>) {
+        self.colors = colors;
+    }
+
+    /// Add the macros defined in a string in the given format.
+    ///
+    /// Macros defined with `\providecommand` do not replace existing ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use katex::MacroFormat;
+    ///
+    /// let mut opts = katex::Opts::default();
+    /// opts.load_macros_from_str(
+    ///     MacroFormat::Latex,
+    ///     r"\newcommand{\norm}[1]{\lVert #1 \rVert}",
+    /// )
+    /// .unwrap();
+    /// let html = katex::render_with_opts(r"\norm{x}", &opts).unwrap();
+    /// assert!(html.contains("∥"));
+    /// ```
+    pub fn load_macros_from_str(&mut self, format: MacroFormat, input: &str) -> Result<()> {
+        let definitions = macro_parser::parse(format, input)?;
+        let mut macros = self.macros.clone();
+        for macro_parser::Definition {
+            name,
+            definition,
+            replace,
+        } in definitions
+        {
+            if replace || !macros.contains_key(&name) {
+                macros.insert(name, definition);
+            }
+        }
+        validate_macros(&macros)?;
+        self.macros = macros;
+        Ok(())
+    }
+
+    /// Add the macros defined in a file, whose format is guessed from its
+    /// extension with [`MacroFormat::from_path`].
+    pub fn load_macros_from_path(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let invalid = |reason| Error::InvalidOption {
+            field: "macros",
+            reason,
+        };
+        let format = MacroFormat::from_path(path)
+            .ok_or_else(|| invalid(format!("unknown macro file format {}", path.display())))?;
+        let input = std::fs::read_to_string(path)
+            .map_err(|e| invalid(format!("cannot read {}: {e}", path.display())))?;
+        self.load_macros_from_str(format, &input)
+    }
+
     /// Set the minimum thickness, in ems.
     /// Read <https://katex.org/docs/options.html> for more information.
     pub fn set_min_rule_thickness(&mut self, value: f64) {
         self.min_rule_thickness = Some(value);
     }
 
-    /// Set the max size for user-specified sizes.
-    /// If set to `None`, users can make elements and spaces arbitrarily large.
+    /// Set the max size for user-specified sizes, in ems.
+    /// If [unlimited](Limit::Unlimited), users can make elements and spaces arbitrarily large.
     /// Read <https://katex.org/docs/options.html> for more information.
-    pub fn set_max_size(&mut self, value: Option<f64>) {
-        self.max_size = Some(value);
+    pub fn set_max_size(&mut self, value: impl Into<Limit<f64>>) {
+        self.max_size = value.into();
     }
 
     /// Set the limit for the number of macro expansions.
-    /// If set to `None`, the macro expander will try to fully expand as in LaTeX.
+    /// If [unlimited](Limit::Unlimited), the macro expander will try to fully expand as in LaTeX.
     /// Read <https://katex.org/docs/options.html> for more information.
-    pub fn set_max_expand(&mut self, value: Option<i32>) {
-        self.max_expand = Some(value);
+    pub fn set_max_expand(&mut self, value: impl Into<Limit<u32>>) {
+        self.max_expand = value.into();
     }
 
     /// Set whether to trust users' input.
@@ -114,28 +351,151 @@ impl Opts {
         self.trust = Some(flag);
     }
 
+    /// Trust only the given commands, e.g. `\htmlClass` or `\href`.
+    /// Read <https://katex.org/docs/options.html> for more information.
+    pub fn allow_commands<S: AsRef<str>>(&mut self, commands: &[S]) {
+        self.trusted_commands = commands.iter().map(|c| c.as_ref().to_owned()).collect();
+    }
+
+    /// Set how to handle input which KaTeX accepts but LaTeX does not.
+    /// Read <https://katex.org/docs/options.html> for more information.
+    pub fn set_strict(&mut self, mode: StrictMode) {
+        self.strict = Some(mode);
+    }
+
+    /// Set how to handle specific [strict errors](StrictErrorCode),
+    /// overriding [`strict`](OptsBuilder::strict) for them.
+    pub fn set_strict_overrides(&mut self, overrides: HashMap<StrictErrorCode, StrictMode>) {
+        self.strict_overrides = overrides;
+    }
+
+    /// Pass an option to KaTeX as is, e.g. one added by a KaTeX release newer
+    /// than this crate. It overrides the typed option of the same name.
+    pub fn set_raw_option(&mut self, key: impl Into<String>, value: impl Into<JsonValue>) {
+        self.raw_options.insert(key.into(), value.into());
+    }
+
+    /// Set how to handle emoji inside text-mode commands such as `\text{}`.
+    pub fn set_text_unicode_policy(&mut self, policy: TextUnicodePolicy) {
+        self.text_unicode_policy = Some(policy);
+    }
+
+    /// Set how to handle input which looks HTML-escaped.
+    pub fn set_escaped_input_policy(&mut self, policy: EscapedInputPolicy) {
+        self.escaped_input_policy = Some(policy);
+    }
+
+    /// Set whether to normalize the input to Unicode NFC.
+    pub fn set_normalize_unicode(&mut self, flag: bool) {
+        self.normalize_unicode = Some(flag);
+    }
+
+    /// Set whether to replace Unicode math characters with LaTeX commands.
+    pub fn set_replace_unicode_math(&mut self, flag: bool) {
+        self.replace_unicode_math = Some(flag);
+    }
+
+    /// Set the grouping of digits in rendered numbers.
+    pub fn set_digit_grouping(&mut self, grouping: DigitGrouping) {
+        self.digit_grouping = Some(grouping);
+    }
+
+    /// Set the callback rewriting the URLs of resources.
+    pub fn set_resource_resolver(&mut self, resolver: ResourceResolver) {
+        self.resource_resolver = Some(resolver);
+    }
+
+    /// Set whether to inline the few styles needed to display the output
+    /// without the KaTeX stylesheet.
+    pub fn set_minimal_inline_css(&mut self, flag: bool) {
+        self.minimal_inline_css = Some(flag);
+    }
+
+    /// Set whether to sort the attributes of the rendered HTML.
+    pub fn set_deterministic(&mut self, flag: bool) {
+        self.deterministic = Some(flag);
+    }
+
+    /// Set the font size of the output, in ems of the surrounding text.
+    pub fn set_font_size_em(&mut self, size: f64) {
+        self.font_size_em = Some(size);
+    }
+
+    /// Set whether to typeset inline math in display style.
+    pub fn set_force_display_style(&mut self, flag: bool) {
+        self.force_display_style = Some(flag);
+    }
+
+    /// Set whether to emulate the MathJax commands and syntax which KaTeX
+    /// lacks.
+    pub fn set_mathjax_compat(&mut self, flag: bool) {
+        self.mathjax_compat = Some(flag);
+    }
+
+    /// Set whether to remove the MathML annotation duplicating the LaTeX
+    /// source.
+    pub fn set_strip_annotations(&mut self, flag: bool) {
+        self.strip_annotations = Some(flag);
+    }
+
+    /// Set whether to keep the MathML annotation duplicating the LaTeX
+    /// source, the inverse of [`set_strip_annotations`](Self::set_strip_annotations).
+    pub fn set_include_annotation(&mut self, flag: bool) {
+        self.strip_annotations = Some(!flag);
+    }
+
+    /// Set whether to add the LaTeX source as a `data-latex` attribute.
+    pub fn set_embed_source(&mut self, flag: bool) {
+        self.embed_source = Some(flag);
+    }
+
+    /// Set whether to add the spoken description as the `aria-label`.
+    pub fn set_aria_label(&mut self, flag: bool) {
+        self.aria_label = Some(flag);
+    }
+
+    /// Set whether to record which macros the render expands.
+    pub fn set_track_macros(&mut self, flag: bool) {
+        self.track_macros = Some(flag);
+    }
+
+    /// Set the maximum length of the input in bytes.
+    ///
+    /// Longer inputs fail with [`Error::InvalidInput`] without running KaTeX.
+    pub fn set_max_input_len(&mut self, len: usize) {
+        self.max_input_len = Some(len);
+    }
+
+    /// Set the maximum size of the rendered HTML in bytes.
+    ///
+    /// Larger outputs fail with [`Error::OutputTooLarge`], without copying
+    /// them out of the JS engine.
+    pub fn set_max_output_bytes(&mut self, bytes: usize) {
+        self.max_output_bytes = Some(bytes);
+    }
+
+    /// Set whether to sanitize the rendered HTML.
+    #[cfg(feature = "sanitize")]
+    pub fn set_sanitize(&mut self, flag: bool) {
+        self.sanitize = Some(flag);
+    }
+
     pub(crate) fn to_js_value<'a, E>(&self, engine: &'a E) -> Result<E::JsValue<'a>>
     where
         E: JsEngine,
     {
-        let mut opt: HashMap<String, E::JsValue<'a>> = HashMap::new();
+        // Sorted, so that KaTeX sees the same options with every backend.
+        let mut opt: BTreeMap<String, E::JsValue<'a>> = BTreeMap::new();
         if let Some(display_mode) = self.display_mode {
             opt.insert(
                 "displayMode".to_owned(),
                 engine.create_bool_value(display_mode)?,
             );
         }
-        if let Some(output_type) = self.output_type {
+        if let Some(output_type) = &self.output_type {
             opt.insert(
                 "output".to_owned(),
-                engine.create_string_value(
-                    match output_type {
-                        OutputType::Html => "html",
-                        OutputType::Mathml => "mathml",
-                        OutputType::HtmlAndMathml => "htmlAndMathml",
-                    }
-                    .to_owned(),
-                )?,
+                engine.create_string_value(output_type.as_str().into())?,
             );
         }
         if let Some(leqno) = self.leqno {
@@ -153,16 +513,17 @@ impl Opts {
         if let Some(error_color) = &self.error_color {
             opt.insert(
                 "errorColor".to_owned(),
-                engine.create_string_value(error_color.clone())?,
+                engine.create_string_value(error_color.into())?,
             );
         }
-        if !self.macros.is_empty() {
+        let all_macros = self.all_macros();
+        if !all_macros.is_empty() {
             let macros = process_results(
-                self.macros
-                    .iter()
-                    .map(|(k, v)| -> Result<(String, E::JsValue<'a>)> {
-                        Ok((k.clone(), engine.create_string_value(v.clone())?))
-                    }),
+                all_macros.iter().sorted_by_key(|(k, _)| *k).map(
+                    |(k, v)| -> Result<(String, E::JsValue<'a>)> {
+                        Ok((k.clone(), v.to_js_value(engine)?))
+                    },
+                ),
                 |iter| -> Result<E::JsValue<'a>> { engine.create_object_value(iter) },
             )??;
 
@@ -174,69 +535,1704 @@ impl Opts {
                 engine.create_float_value(min_rule_thickness)?,
             );
         }
-        if let Some(Some(max_size)) = self.max_size {
-            opt.insert("maxSize".to_owned(), engine.create_float_value(max_size)?);
+        if let Some(max_size) = self.max_size.to_js_value(engine, |v| v)? {
+            opt.insert("maxSize".to_owned(), max_size);
         }
-        if let Some(max_expand) = self.max_expand {
-            match max_expand {
-                Some(max_expand) => {
-                    opt.insert("maxExpand".to_owned(), engine.create_int_value(max_expand)?);
-                }
-                None => {
-                    opt.insert(
-                        "maxExpand".to_owned(),
-                        engine.create_int_value(i32::max_value())?,
-                    );
-                }
-            }
+        if let Some(max_expand) = self.max_expand.to_js_value(engine, f64::from)? {
+            opt.insert("maxExpand".to_owned(), max_expand);
         }
         if let Some(trust) = self.trust {
             opt.insert("trust".to_owned(), engine.create_bool_value(trust)?);
+        } else if !self.trusted_commands.is_empty() {
+            // entry.js compiles the list into a trust callback.
+            let commands = process_results(
+                self.trusted_commands
+                    .iter()
+                    .map(|c| engine.create_string_value(c.into())),
+                |iter| engine.create_array_value(iter),
+            )??;
+            opt.insert("trust".to_owned(), commands);
+        }
+        if let Some(strict) = self.strict {
+            opt.insert(
+                "strict".to_owned(),
+                engine.create_string_value(strict.as_str().into())?,
+            );
+        }
+        if !self.strict_overrides.is_empty() {
+            // entry.js compiles the overrides into a strict callback.
+            let overrides = process_results(
+                self.strict_overrides
+                    .iter()
+                    .sorted_by_key(|(code, _)| code.as_str())
+                    .map(|(code, mode)| -> Result<(String, E::JsValue<'a>)> {
+                        Ok((
+                            code.as_str().to_owned(),
+                            engine.create_string_value(mode.as_str().into())?,
+                        ))
+                    }),
+                |iter| engine.create_object_value(iter),
+            )??;
+            opt.insert("strictOverrides".to_owned(), overrides);
+        }
+        if let Some(bytes) = self.max_output_bytes {
+            // entry.js checks the size of the HTML before returning it.
+            opt.insert(
+                "maxOutputBytes".to_owned(),
+                engine.create_float_value(bytes as f64)?,
+            );
+        }
+        if self.track_macros.unwrap_or_default() {
+            // entry.js wraps the macros to record the lookups.
+            opt.insert("trackMacros".to_owned(), engine.create_bool_value(true)?);
+        }
+        for (key, value) in &self.raw_options {
+            opt.insert(key.clone(), value.to_js_value(engine)?);
         }
         engine.create_object_value(opt.into_iter())
     }
-}
 
-impl AsRef<Opts> for Opts {
-    fn as_ref(&self) -> &Opts {
-        self
+    /// The [macros](OptsBuilder::macros), those defined by the
+    /// [colors](OptsBuilder::colors), and those emulating MathJax with
+    /// [`mathjax_compat`](OptsBuilder::mathjax_compat).
+    fn all_macros(&self) -> Cow<'_, HashMap<String, Macro>> {
+        if self.colors.is_empty() && !self.mathjax_compat.unwrap_or_default() {
+            return Cow::Borrowed(&self.macros);
+        }
+        let mut macros = HashMap::new();
+        if self.mathjax_compat.unwrap_or_default() {
+            macros.extend(mathjax::macros());
+        }
+        macros.extend(self.macros.clone());
+        for (name, color) in &self.colors {
+            // `#` starts a parameter in a macro body, `##` is a literal `#`.
+            let color = color.replace('#', "##");
+            let body = format!(r"\textcolor{{{color}}}{{#1}}");
+            macros.insert(format!("\\{name}"), Macro::new(body));
+        }
+        Cow::Owned(macros)
     }
-}
 
-impl OptsBuilder {
-    /// Add an entry to [`macros`](OptsBuilder::macros).
+    /// [Canonical description](Self::fingerprint) of the options, omitting
+    /// the KaTeX options set to their default, which render the same as unset
+    /// ones.
+    pub(crate) fn effective_fingerprint(&self) -> String {
+        let mut opts = self.clone();
+        let unset_default = |option: &mut Option<bool>, default: bool| {
+            if *option == Some(default) {
+                *option = None;
+            }
+        };
+        unset_default(&mut opts.display_mode, false);
+        unset_default(&mut opts.leqno, false);
+        unset_default(&mut opts.fleqn, false);
+        unset_default(&mut opts.throw_on_error, true);
+        unset_default(&mut opts.trust, false);
+        if matches!(opts.output_type, Some(OutputType::HtmlAndMathml)) {
+            opts.output_type = None;
+        }
+        if opts
+            .error_color
+            .as_ref()
+            .is_some_and(|color| color.eq_ignore_ascii_case("#cc0000"))
+        {
+            opts.error_color = None;
+        }
+        if opts.min_rule_thickness == Some(0.0) {
+            opts.min_rule_thickness = None;
+        }
+        if opts.max_expand == Limit::Finite(1000) {
+            opts.max_expand = Limit::Default;
+        }
+        if opts.max_size == Limit::Unlimited {
+            opts.max_size = Limit::Default;
+        }
+        if opts.strict == Some(StrictMode::Warn) {
+            opts.strict = None;
+        }
+        opts.fingerprint()
+    }
+
+    /// Whether renders with these options can be told apart by their
+    /// [cache key](crate::cache_key), i.e. unless a
+    /// [`ResourceResolver`] has no id.
+    pub(crate) fn is_cacheable(&self) -> bool {
+        self.resource_resolver
+            .as_ref()
+            .is_none_or(|resolver| resolver.id.is_some())
+    }
+
+    /// Canonical description of the options, independent of the JS backend.
+    ///
+    /// It is a JSON object with sorted keys and without whitespace, omitting
+    /// unset options. Crate-side options use their own keys next to the
+    /// KaTeX ones.
+    pub(crate) fn fingerprint(&self) -> String {
+        let mut opt: BTreeMap<&str, String> = BTreeMap::new();
+        let flags = [
+            ("displayMode", self.display_mode),
+            ("leqno", self.leqno),
+            ("fleqn", self.fleqn),
+            ("throwOnError", self.throw_on_error),
+            ("trust", self.trust),
+        ];
+        for (key, flag) in flags {
+            if let Some(flag) = flag {
+                opt.insert(key, flag.to_string());
+            }
+        }
+        if let Some(output_type) = &self.output_type {
+            let output = match output_type {
+                OutputType::HtmlAndMathmlWithAnnotation => "htmlAndMathmlWithAnnotation",
+                output_type => output_type.as_str(),
+            };
+            opt.insert("output", json_string(output));
+        }
+        if let Some(error_color) = &self.error_color {
+            opt.insert("errorColor", json_string(error_color));
+        }
+        if !self.macros.is_empty() {
+            let macros: BTreeMap<_, _> = self.macros.iter().collect();
+            let macros = macros
+                .into_iter()
+                .map(|(k, v)| format!("{}:{}", json_string(k), v.fingerprint()))
+                .join(",");
+            opt.insert("macros", format!("{{{macros}}}"));
+        }
+        if !self.colors.is_empty() {
+            let colors: BTreeMap<_, _> = self.colors.iter().collect();
+            let colors = colors
+                .into_iter()
+                .map(|(k, v)| format!("{}:{}", json_string(k), json_string(v)))
+                .join(",");
+            opt.insert("colors", format!("{{{colors}}}"));
+        }
+        if let Some(min_rule_thickness) = self.min_rule_thickness {
+            opt.insert("minRuleThickness", min_rule_thickness.to_string());
+        }
+        if let Some(max_size) = self.max_size.fingerprint() {
+            opt.insert("maxSize", max_size);
+        }
+        if let Some(max_expand) = self.max_expand.fingerprint() {
+            opt.insert("maxExpand", max_expand);
+        }
+        if self.trust.is_none() && !self.trusted_commands.is_empty() {
+            let mut commands: Vec<_> = self
+                .trusted_commands
+                .iter()
+                .map(|c| json_string(c))
+                .collect();
+            commands.sort_unstable();
+            opt.insert("trust", format!("[{}]", commands.join(",")));
+        }
+        if let Some(strict) = self.strict {
+            opt.insert("strict", json_string(strict.as_str()));
+        }
+        if !self.strict_overrides.is_empty() {
+            let overrides = self
+                .strict_overrides
+                .iter()
+                .map(|(code, mode)| {
+                    format!(
+                        "{}:{}",
+                        json_string(code.as_str()),
+                        json_string(mode.as_str())
+                    )
+                })
+                .sorted()
+                .join(",");
+            opt.insert("strictOverrides", format!("{{{overrides}}}"));
+        }
+        if !self.raw_options.is_empty() {
+            let raw_options = self
+                .raw_options
+                .iter()
+                .map(|(k, v)| format!("{}:{}", json_string(k), v.to_json()))
+                .join(",");
+            opt.insert("rawOptions", format!("{{{raw_options}}}"));
+        }
+        match self
+            .text_unicode_policy
+            .as_ref()
+            .unwrap_or(&TextUnicodePolicy::PassThrough)
+        {
+            TextUnicodePolicy::PassThrough => {}
+            TextUnicodePolicy::StripEmoji => {
+                opt.insert("textUnicodePolicy", json_string("stripEmoji"));
+            }
+            TextUnicodePolicy::ReplaceEmoji(placeholder) => {
+                let policy = format!("replaceEmoji:{placeholder}");
+                opt.insert("textUnicodePolicy", json_string(&policy));
+            }
+            TextUnicodePolicy::RejectEmoji => {
+                opt.insert("textUnicodePolicy", json_string("rejectEmoji"));
+            }
+        }
+        match self.escaped_input_policy.unwrap_or_default() {
+            EscapedInputPolicy::Ignore => {}
+            EscapedInputPolicy::Unescape => {
+                opt.insert("escapedInputPolicy", json_string("unescape"));
+            }
+            EscapedInputPolicy::Reject => {
+                opt.insert("escapedInputPolicy", json_string("reject"));
+            }
+        }
+        if self.normalize_unicode.unwrap_or_default() {
+            opt.insert("normalizeUnicode", true.to_string());
+        }
+        if self.replace_unicode_math.unwrap_or_default() {
+            opt.insert("replaceUnicodeMath", true.to_string());
+        }
+        if let Some(grouping) = &self.digit_grouping {
+            opt.insert(
+                "digitGrouping",
+                format!(
+                    "{{\"minDigits\":{},\"separator\":{}}}",
+                    grouping.min_digits,
+                    json_string(&grouping.separator)
+                ),
+            );
+        }
+        if let Some(resolver) = &self.resource_resolver {
+            // Callbacks cannot be compared, so only their id counts.
+            let id = resolver
+                .id
+                .as_deref()
+                .map_or("null".to_owned(), json_string);
+            opt.insert("resourceResolver", id);
+        }
+        if self.minimal_inline_css.unwrap_or_default() {
+            opt.insert("minimalInlineCss", true.to_string());
+        }
+        if self.deterministic.unwrap_or_default() {
+            opt.insert("deterministic", true.to_string());
+        }
+        if let Some(size) = self.font_size_em {
+            opt.insert("fontSizeEm", size.to_string());
+        }
+        if self.force_display_style.unwrap_or_default() {
+            opt.insert("forceDisplayStyle", true.to_string());
+        }
+        if self.mathjax_compat.unwrap_or_default() {
+            opt.insert("mathjaxCompat", true.to_string());
+        }
+        if self.strip_annotations.unwrap_or_default() {
+            opt.insert("stripAnnotations", true.to_string());
+        }
+        if self.embed_source.unwrap_or_default() {
+            opt.insert("embedSource", true.to_string());
+        }
+        if self.aria_label.unwrap_or_default() {
+            opt.insert("ariaLabel", true.to_string());
+        }
+        // Tracking the macros does not change the HTML.
+        if let Some(len) = self.max_input_len {
+            opt.insert("maxInputLen", len.to_string());
+        }
+        if let Some(bytes) = self.max_output_bytes {
+            opt.insert("maxOutputBytes", bytes.to_string());
+        }
+        #[cfg(feature = "sanitize")]
+        if self.sanitize.unwrap_or_default() {
+            opt.insert("sanitize", true.to_string());
+        }
+        let opt = opt
+            .into_iter()
+            .map(|(k, v)| format!("{}:{v}", json_string(k)))
+            .join(",");
+        format!("{{{opt}}}")
+    }
+
+    /// Return the KaTeX options as the JSON object documented by KaTeX, e.g.
+    /// to reproduce an issue in the browser or to configure client-side
+    /// rendering the same way.
+    ///
+    /// Crate-side options are omitted, as well as a list of
+    /// [trusted commands](OptsBuilder::allow_commands) and
+    /// [strict overrides](OptsBuilder::strict_overrides), which KaTeX takes as
+    /// callbacks. Macros are written in their string form, so an explicit
+    /// number of arguments or expansion mode is lost, and include the ones
+    /// defined by the [colors](OptsBuilder::colors).
     ///
     /// # Examples
     ///
     /// ```
     /// let opts = katex::Opts::builder()
-    ///     .add_macro(r#"\RR"#.to_owned(), r#"\mathbb{R}"#.to_owned())
+    ///     .add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned())
+    ///     .display_mode(true)
     ///     .build()
     ///     .unwrap();
-    /// let html = katex::render_with_opts(r#"\RR"#, &opts).unwrap();
+    /// assert_eq!(
+    ///     opts.to_katex_json(),
+    ///     r#"{
+    ///   "displayMode": true,
+    ///   "macros": {
+    ///     "\\RR": "\\mathbb{R}"
+    ///   }
+    /// }"#
+    /// );
+    /// ```
+    pub fn to_katex_json(&self) -> String {
+        // In the order of KaTeX's documentation.
+        let mut opt: Vec<(&str, String)> = Vec::new();
+        if let Some(display_mode) = self.display_mode {
+            opt.push(("displayMode", display_mode.to_string()));
+        }
+        if let Some(output_type) = &self.output_type {
+            opt.push(("output", json_string(output_type.as_str())));
+        }
+        if let Some(leqno) = self.leqno {
+            opt.push(("leqno", leqno.to_string()));
+        }
+        if let Some(fleqn) = self.fleqn {
+            opt.push(("fleqn", fleqn.to_string()));
+        }
+        if let Some(throw_on_error) = self.throw_on_error {
+            opt.push(("throwOnError", throw_on_error.to_string()));
+        }
+        if let Some(error_color) = &self.error_color {
+            opt.push(("errorColor", json_string(error_color)));
+        }
+        let macros = self.all_macros();
+        if !macros.is_empty() {
+            let macros = macros
+                .iter()
+                .sorted_by_key(|(k, _)| *k)
+                .map(|(k, v)| format!("    {}: {}", json_string(k), json_string(&v.body)))
+                .join(",\n");
+            opt.push(("macros", format!("{{\n{macros}\n  }}")));
+        }
+        if let Some(min_rule_thickness) = self.min_rule_thickness {
+            opt.push(("minRuleThickness", min_rule_thickness.to_string()));
+        }
+        // JSON has no `Infinity`. It is KaTeX's default for sizes, and an
+        // unlimited expansion is written as the largest finite limit.
+        if let Limit::Finite(max_size) = self.max_size {
+            opt.push(("maxSize", max_size.to_string()));
+        }
+        match self.max_expand {
+            Limit::Default => {}
+            Limit::Finite(max_expand) => opt.push(("maxExpand", max_expand.to_string())),
+            Limit::Unlimited => opt.push(("maxExpand", u32::MAX.to_string())),
+        }
+        if let Some(trust) = self.trust {
+            opt.push(("trust", trust.to_string()));
+        }
+        if let Some(strict) = self.strict {
+            opt.push(("strict", json_string(strict.as_str())));
+        }
+        for (key, value) in &self.raw_options {
+            opt.retain(|(k, _)| k != key);
+            opt.push((key, value.to_json()));
+        }
+        if opt.is_empty() {
+            return "{}".to_owned();
+        }
+        let opt = opt
+            .into_iter()
+            .map(|(k, v)| format!("  {}: {v}", json_string(k)))
+            .join(",\n");
+        format!("{{\n{opt}\n}}")
+    }
+
+    /// Build options from the JSON object documented by KaTeX, e.g. sent by
+    /// a client which configures KaTeX in JS.
+    ///
+    /// Trusted commands may be given as an array, and options unknown to
+    /// this crate are passed to KaTeX as
+    /// [raw options](OptsBuilder::raw_option). Fail with
+    /// [`Error::InvalidInput`] if the value is not such an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use katex::{JsonValue, Opts};
+    ///
+    /// let json = JsonValue::Object(vec![
+    ///     ("displayMode".to_owned(), JsonValue::Bool(true)),
+    ///     ("maxSize".to_owned(), JsonValue::Number(10.0)),
+    /// ]);
+    /// let opts = Opts::from_katex_json(json).unwrap();
+    /// assert_eq!(
+    ///     opts.to_katex_json(),
+    ///     "{\n  \"displayMode\": true,\n  \"maxSize\": 10\n}"
+    /// );
+    /// ```
+    pub fn from_katex_json(options: JsonValue) -> Result<Self> {
+        let invalid = |reason: String| Error::InvalidInput(format!("options: {reason}"));
+        let mut builder = Opts::builder();
+        let fields = match options {
+            JsonValue::Object(fields) => fields,
+            JsonValue::Null => Vec::new(),
+            _ => return Err(invalid("expected an object".to_owned())),
+        };
+        for (key, value) in fields {
+            match (key.as_str(), value) {
+                ("displayMode", JsonValue::Bool(flag)) => {
+                    builder.display_mode(flag);
+                }
+                ("output", JsonValue::String(output)) => {
+                    builder.output_type(match output.as_str() {
+                        "html" => OutputType::Html,
+                        "mathml" => OutputType::Mathml,
+                        "htmlAndMathml" => OutputType::HtmlAndMathml,
+                        _ => OutputType::Custom(output),
+                    });
+                }
+                ("leqno", JsonValue::Bool(flag)) => {
+                    builder.leqno(flag);
+                }
+                ("fleqn", JsonValue::Bool(flag)) => {
+                    builder.fleqn(flag);
+                }
+                ("throwOnError", JsonValue::Bool(flag)) => {
+                    builder.throw_on_error(flag);
+                }
+                ("errorColor", JsonValue::String(color)) => {
+                    builder.error_color(color);
+                }
+                ("minRuleThickness", JsonValue::Number(thickness)) => {
+                    builder.min_rule_thickness(thickness);
+                }
+                ("trust", JsonValue::Bool(flag)) => {
+                    builder.trust(flag);
+                }
+                ("trust", JsonValue::Array(commands)) => {
+                    let commands = commands
+                        .into_iter()
+                        .map(|command| match command {
+                            JsonValue::String(command) => Ok(command),
+                            _ => Err(invalid(
+                                "expected the trusted commands to be strings".to_owned(),
+                            )),
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    builder = builder.allow_commands(&commands);
+                }
+                ("macros", JsonValue::Object(macros)) => {
+                    for (name, body) in macros {
+                        let JsonValue::String(body) = body else {
+                            return Err(invalid(format!("expected a body for {name}")));
+                        };
+                        builder = builder.add_macro(name, body);
+                    }
+                }
+                (key, value) => builder = builder.raw_option(key, value),
+            }
+        }
+        builder.build().map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Layer `other` over these options, e.g. the options of an equation over
+    /// those of a chapter.
+    ///
+    /// The options set in `other` override these ones, and the unset ones are
+    /// kept, for crate-side options too, so that `other` can turn off a flag
+    /// or reset a policy set by these options. Macros, colors, trusted
+    /// commands, strict overrides and raw options are combined, with the
+    /// entries of `other` replacing those of the same name. The result may be
+    /// [invalid](Self::validate), e.g. if a color of one conflicts with a
+    /// macro of the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let book = katex::Opts::builder()
+    ///     .add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned())
+    ///     .display_mode(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let equation = katex::Opts::builder()
+    ///     .add_macro(r"\NN".to_owned(), r"\mathbb{N}".to_owned())
+    ///     .leqno(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let opts = book.merge(&equation);
+    /// let effective = opts.effective();
+    /// assert!(effective.display_mode);
+    /// assert!(effective.leqno);
+    /// assert_eq!(effective.macros.len(), 2);
     /// ```
-    pub fn add_macro(mut self, entry_name: String, entry_data: String) -> Self {
-        match self.macros.as_mut() {
-            Some(macros) => {
-                macros.insert(entry_name, entry_data);
+    pub fn merge(&self, other: &Opts) -> Opts {
+        // Destructured, so that new options are not forgotten here.
+        let Opts {
+            display_mode,
+            output_type,
+            leqno,
+            fleqn,
+            throw_on_error,
+            error_color,
+            macros,
+            colors,
+            min_rule_thickness,
+            max_size,
+            max_expand,
+            trust,
+            trusted_commands,
+            strict,
+            strict_overrides,
+            raw_options,
+            text_unicode_policy,
+            escaped_input_policy,
+            normalize_unicode,
+            replace_unicode_math,
+            digit_grouping,
+            resource_resolver,
+            minimal_inline_css,
+            deterministic,
+            font_size_em,
+            force_display_style,
+            mathjax_compat,
+            strip_annotations,
+            embed_source,
+            aria_label,
+            track_macros,
+            max_input_len,
+            max_output_bytes,
+            #[cfg(feature = "sanitize")]
+            sanitize,
+        } = other.clone();
+        fn set<T>(option: &mut Option<T>, value: Option<T>) {
+            if value.is_some() {
+                *option = value;
             }
-            None => {
-                let mut macros = HashMap::new();
-                macros.insert(entry_name, entry_data);
-                self.macros = Some(macros);
+        }
+        let mut merged = self.clone();
+        set(&mut merged.display_mode, display_mode);
+        set(&mut merged.output_type, output_type);
+        set(&mut merged.leqno, leqno);
+        set(&mut merged.fleqn, fleqn);
+        set(&mut merged.throw_on_error, throw_on_error);
+        set(&mut merged.error_color, error_color);
+        merged.macros.extend(macros);
+        merged.colors.extend(colors);
+        set(&mut merged.min_rule_thickness, min_rule_thickness);
+        if max_size != Limit::Default {
+            merged.max_size = max_size;
+        }
+        if max_expand != Limit::Default {
+            merged.max_expand = max_expand;
+        }
+        set(&mut merged.trust, trust);
+        for command in trusted_commands {
+            if !merged.trusted_commands.contains(&command) {
+                merged.trusted_commands.push(command);
             }
         }
-        self
+        set(&mut merged.strict, strict);
+        merged.strict_overrides.extend(strict_overrides);
+        merged.raw_options.extend(raw_options);
+        set(&mut merged.text_unicode_policy, text_unicode_policy);
+        set(&mut merged.escaped_input_policy, escaped_input_policy);
+        set(&mut merged.normalize_unicode, normalize_unicode);
+        set(&mut merged.replace_unicode_math, replace_unicode_math);
+        set(&mut merged.digit_grouping, digit_grouping);
+        if resource_resolver.is_some() {
+            merged.resource_resolver = resource_resolver;
+        }
+        set(&mut merged.minimal_inline_css, minimal_inline_css);
+        set(&mut merged.deterministic, deterministic);
+        set(&mut merged.font_size_em, font_size_em);
+        set(&mut merged.force_display_style, force_display_style);
+        set(&mut merged.mathjax_compat, mathjax_compat);
+        set(&mut merged.strip_annotations, strip_annotations);
+        set(&mut merged.embed_source, embed_source);
+        set(&mut merged.aria_label, aria_label);
+        set(&mut merged.track_macros, track_macros);
+        set(&mut merged.max_input_len, max_input_len);
+        set(&mut merged.max_output_bytes, max_output_bytes);
+        #[cfg(feature = "sanitize")]
+        set(&mut merged.sanitize, sanitize);
+        merged
+    }
+
+    /// Return the KaTeX options, with KaTeX's defaults for the unset ones.
+    ///
+    /// [Raw options](OptsBuilder::raw_option) are returned as is, although
+    /// they override the typed options of the same name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use katex::Limit;
+    ///
+    /// let effective = katex::Opts::default().effective();
+    /// assert!(effective.throw_on_error);
+    /// assert_eq!(effective.error_color, "#cc0000");
+    /// assert_eq!(effective.max_expand, Limit::Finite(1000));
+    /// assert_eq!(effective.max_size, Limit::Unlimited);
+    /// ```
+    pub fn effective(&self) -> EffectiveOpts {
+        EffectiveOpts {
+            display_mode: self.display_mode.unwrap_or(false),
+            output_type: self
+                .output_type
+                .clone()
+                .unwrap_or(OutputType::HtmlAndMathml),
+            leqno: self.leqno.unwrap_or(false),
+            fleqn: self.fleqn.unwrap_or(false),
+            throw_on_error: self.throw_on_error.unwrap_or(true),
+            error_color: self
+                .error_color
+                .clone()
+                .unwrap_or_else(|| "#cc0000".to_owned()),
+            macros: self.all_macros().into_owned().into_iter().collect(),
+            min_rule_thickness: self.min_rule_thickness.unwrap_or(0.0),
+            max_size: match self.max_size {
+                Limit::Default => Limit::Unlimited,
+                max_size => max_size,
+            },
+            max_expand: match self.max_expand {
+                Limit::Default => Limit::Finite(1000),
+                max_expand => max_expand,
+            },
+            trust: self.trust.unwrap_or(false),
+            trusted_commands: match self.trust {
+                Some(_) => Vec::new(),
+                None => self.trusted_commands.clone(),
+            },
+            strict: self.strict.unwrap_or(StrictMode::Warn),
+            strict_overrides: self.strict_overrides.clone().into_iter().collect(),
+            raw_options: self.raw_options.clone(),
+        }
     }
 }
 
-/// Output type from KaTeX.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum OutputType {
-    /// Outputs KaTeX in HTML only.
-    Html,
-    /// Outputs KaTeX in MathML only.
+/// The KaTeX options with KaTeX's defaults for the unset ones, returned by
+/// [`Opts::effective`].
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectiveOpts {
+    /// Whether to render the math in the display mode.
+    pub display_mode: bool,
+    /// KaTeX output type.
+    pub output_type: OutputType,
+    /// Whether to have `\tags` rendered on the left instead of the right.
+    pub leqno: bool,
+    /// Whether to make display math flush left.
+    pub fleqn: bool,
+    /// Whether to let KaTeX throw a ParseError for invalid LaTeX.
+    pub throw_on_error: bool,
+    /// Color used for invalid LaTeX.
+    pub error_color: String,
+    /// Custom macros, including those defined by the
+    /// [colors](OptsBuilder::colors).
+    pub macros: BTreeMap<String, Macro>,
+    /// Minimum thickness of rules, in ems.
+    pub min_rule_thickness: f64,
+    /// Max size for user-specified sizes, in ems, never
+    /// [`Limit::Default`].
+    pub max_size: Limit<f64>,
+    /// Limit of macro expansions, never [`Limit::Default`].
+    pub max_expand: Limit<u32>,
+    /// Whether to trust users' input.
+    pub trust: bool,
+    /// Commands to trust, when [`trust`](Self::trust) is not set explicitly.
+    pub trusted_commands: Vec<String>,
+    /// How to handle input which KaTeX accepts but LaTeX does not.
+    pub strict: StrictMode,
+    /// How to handle specific strict errors, overriding
+    /// [`strict`](Self::strict) for them.
+    pub strict_overrides: BTreeMap<StrictErrorCode, StrictMode>,
+    /// Options passed to KaTeX as is.
+    pub raw_options: BTreeMap<String, JsonValue>,
+}
+
+/// Encode a string as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut output = String::with_capacity(s.len() + 2);
+    output.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+impl AsRef<Opts> for Opts {
+    fn as_ref(&self) -> &Opts {
+        self
+    }
+}
+
+impl OptsBuilder {
+    /// Build [`Opts`] like [`build`](Self::build), but return
+    /// [`Error::InvalidOption`] naming the rejected option, instead of a
+    /// message, if a check fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = katex::Opts::builder().min_rule_thickness(-1.0).try_build();
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(katex::Error::InvalidOption { field: "min_rule_thickness", .. })
+    /// ));
+    /// ```
+    pub fn try_build(&self) -> Result<Opts> {
+        self.check()?;
+        self.build().map_err(|e| match e {
+            OptsBuilderError::UninitializedField(field) => {
+                invalid_option(field, "is not set".to_owned())
+            }
+            // Unreachable, as `check` already passed.
+            OptsBuilderError::ValidationError(reason) => Error::InvalidInput(reason),
+        })
+    }
+
+    /// Check the options before building [`Opts`].
+    fn validate(&self) -> Result<(), String> {
+        self.check().map_err(|e| e.to_string())
+    }
+
+    fn check(&self) -> Result<()> {
+        if let Some(Some(output_type)) = &self.output_type {
+            validate_output_type(output_type)?;
+        }
+        if let Some(Some(error_color)) = &self.error_color {
+            validate_error_color(error_color)?;
+        }
+        if let Some(macros) = &self.macros {
+            validate_macros(macros)?;
+        }
+        if let Some(colors) = &self.colors {
+            validate_colors(colors, self.macros.as_ref())?;
+        }
+        if let Some(Some(min_rule_thickness)) = self.min_rule_thickness {
+            validate_min_rule_thickness(min_rule_thickness)?;
+        }
+        if let Some(commands) = &self.trusted_commands {
+            validate_trusted_commands(commands)?;
+        }
+        if let Some(raw_options) = &self.raw_options {
+            validate_raw_options(raw_options)?;
+        }
+        if let Some(Some(font_size_em)) = self.font_size_em {
+            validate_font_size_em(font_size_em)?;
+        }
+        validate_strip_annotations(
+            self.output_type.as_ref().and_then(Option::as_ref),
+            self.strip_annotations.flatten().unwrap_or_default(),
+        )?;
+        validate_aria_label(
+            self.output_type.as_ref().and_then(Option::as_ref),
+            self.aria_label.flatten().unwrap_or_default(),
+        )?;
+        Ok(())
+    }
+
+    /// Pass an option to KaTeX as is, e.g. one added by a KaTeX release newer
+    /// than this crate. It overrides the typed option of the same name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let opts = katex::Opts::builder()
+    ///     .raw_option("maxExpand", 1)
+    ///     .build()
+    ///     .unwrap();
+    /// let html = katex::render_with_opts(r"\def\a{b}\a\a", &opts);
+    /// assert!(html.is_err());
+    /// ```
+    pub fn raw_option(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.raw_options
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Trust only the given commands, e.g. `\htmlClass` or `\href`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let opts = katex::Opts::builder()
+    ///     .allow_commands(&[r#"\htmlClass"#])
+    ///     .build()
+    ///     .unwrap();
+    /// let html = katex::render_with_opts(r#"\htmlClass{foo}{x}"#, &opts).unwrap();
+    /// assert!(html.contains("foo"));
+    /// ```
+    pub fn allow_commands<S: AsRef<str>>(mut self, commands: &[S]) -> Self {
+        self.trusted_commands = Some(commands.iter().map(|c| c.as_ref().to_owned()).collect());
+        self
+    }
+
+    /// Add an entry to [`macros`](OptsBuilder::macros).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let opts = katex::Opts::builder()
+    ///     .add_macro(r#"\RR"#.to_owned(), r#"\mathbb{R}"#.to_owned())
+    ///     .build()
+    ///     .unwrap();
+    /// let html = katex::render_with_opts(r#"\RR"#, &opts).unwrap();
+    /// ```
+    pub fn add_macro(self, entry_name: String, entry_data: String) -> Self {
+        self.add_macro_with_args(entry_name, Macro::new(entry_data))
+    }
+
+    /// Add an entry to [`macros`](OptsBuilder::macros), with explicit
+    /// parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use katex::Macro;
+    ///
+    /// let opts = katex::Opts::builder()
+    ///     .add_macro_with_args(
+    ///         r#"\first"#.to_owned(),
+    ///         Macro {
+    ///             num_args: Some(2),
+    ///             ..Macro::new("#1")
+    ///         },
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// let html = katex::render_with_opts(r#"\first{a}{b}"#, &opts).unwrap();
+    /// assert!(html.contains(r#"<mi>a</mi>"#));
+    /// assert!(!html.contains(r#"<mi>b</mi>"#));
+    /// ```
+    pub fn add_macro_with_args(mut self, entry_name: String, entry_data: Macro) -> Self {
+        self.macros
+            .get_or_insert_with(HashMap::new)
+            .insert(entry_name, entry_data);
+        self
+    }
+
+    /// Whether to keep the MathML annotation duplicating the LaTeX source,
+    /// for any [output type](OutputType) with MathML. It is kept by default,
+    /// and `false` is the same as [`strip_annotations(true)`](OptsBuilder::strip_annotations).
+    ///
+    /// The bundled KaTeX has no option for it, so the annotation is removed
+    /// from the rendered HTML.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let opts = katex::Opts::builder()
+    ///     .output_type(katex::OutputType::Mathml)
+    ///     .include_annotation(false)
+    ///     .build()
+    ///     .unwrap();
+    /// let html = katex::render_with_opts(r"\frac{a}{b}", &opts).unwrap();
+    /// assert!(html.contains("<mfrac>"));
+    /// assert!(!html.contains("<annotation"));
+    /// ```
+    pub fn include_annotation(&mut self, flag: bool) -> &mut Self {
+        self.strip_annotations = Some(Some(!flag));
+        self
+    }
+
+    /// Collection of custom macros, as a map from name to body.
+    /// Read <https://katex.org/docs/options.html> for more information.
+    pub fn macros<VALUE: Into<HashMap<String, String>>>(&mut self, value: VALUE) -> &mut Self {
+        let macros = value
+            .into()
+            .into_iter()
+            .map(|(name, body)| (name, Macro::new(body)))
+            .collect();
+        self.macros = Some(macros);
+        self
+    }
+}
+
+/// Create the error rejecting the option `field`, named as in [`OptsBuilder`].
+fn invalid_option(field: &'static str, reason: String) -> Error {
+    Error::InvalidOption { field, reason }
+}
+
+/// Values of KaTeX's `output` option accepted by [`OutputType::Custom`].
+const KNOWN_OUTPUT_TYPES: &[&str] = &["html", "mathml", "htmlAndMathml"];
+
+fn validate_output_type(output_type: &OutputType) -> Result<()> {
+    match output_type {
+        OutputType::Custom(output) if !KNOWN_OUTPUT_TYPES.contains(&output.as_str()) => {
+            Err(invalid_option(
+                "output_type",
+                format!("unknown output type {output:?}, use OutputType::Unchecked for those of other KaTeX versions"),
+            ))
+        }
+        OutputType::Unchecked(output)
+            if output.is_empty() || !output.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            Err(invalid_option(
+                "output_type",
+                format!("invalid output type {output:?}"),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_font_size_em(size: f64) -> Result<()> {
+    if !(size.is_finite() && size > 0.0) {
+        return Err(invalid_option(
+            "font_size_em",
+            format!("expected a positive size, got {size}"),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_strip_annotations(output_type: Option<&OutputType>, strip: bool) -> Result<()> {
+    if strip && output_type == Some(&OutputType::HtmlAndMathmlWithAnnotation) {
+        return Err(invalid_option(
+            "strip_annotations",
+            "conflicts with the output type htmlAndMathmlWithAnnotation".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_aria_label(output_type: Option<&OutputType>, aria_label: bool) -> Result<()> {
+    if aria_label && output_type == Some(&OutputType::Html) {
+        return Err(invalid_option(
+            "aria_label",
+            "needs MathML, which the output type html omits".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// CSS color names are left to the browser, but hex colors are checked as
+/// KaTeX passes them through unchanged.
+fn validate_error_color(color: &str) -> Result<()> {
+    match color.strip_prefix('#') {
+        Some(hex)
+            if !matches!(hex.len(), 3 | 4 | 6 | 8)
+                || !hex.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            Err(invalid_option(
+                "error_color",
+                format!("invalid hex color {color:?}"),
+            ))
+        }
+        _ if color.trim().is_empty() => {
+            Err(invalid_option("error_color", "empty color".to_owned()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Macro names are control sequences: a backslash followed by either letters
+/// or a single other character, e.g. `\RR` or `\,`.
+fn validate_macros(macros: &HashMap<String, Macro>) -> Result<()> {
+    let is_macro_name = |name: &str| {
+        name.strip_prefix('\\').is_some_and(|n| {
+            let mut chars = n.chars();
+            match chars.next() {
+                Some(c) if c.is_ascii_alphabetic() => chars.all(|c| c.is_ascii_alphabetic()),
+                Some(_) => chars.next().is_none(),
+                None => false,
+            }
+        })
+    };
+    for (name, definition) in macros {
+        if !is_macro_name(name) {
+            return Err(invalid_option(
+                "macros",
+                format!("invalid macro name {name:?}"),
+            ));
+        }
+        if definition.num_args.is_some_and(|n| n > 9) {
+            return Err(invalid_option(
+                "macros",
+                format!("macro {name} has more than 9 parameters"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Color names are command names without the backslash, and colors are
+/// those accepted by `\textcolor`: a 3 or 6 digit hex color, or a CSS color
+/// name.
+fn validate_colors(
+    colors: &HashMap<String, String>,
+    macros: Option<&HashMap<String, Macro>>,
+) -> Result<()> {
+    let is_color = |color: &str| match color.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    };
+    for (name, color) in colors.iter().sorted() {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid_option(
+                "colors",
+                format!("invalid color name {name:?}"),
+            ));
+        }
+        if macros.is_some_and(|macros| macros.contains_key(&format!("\\{name}"))) {
+            return Err(invalid_option(
+                "colors",
+                format!("color {name} conflicts with the macro \\{name}"),
+            ));
+        }
+        if !is_color(color) {
+            return Err(invalid_option(
+                "colors",
+                format!("invalid color {color:?} for {name}"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_min_rule_thickness(value: f64) -> Result<()> {
+    if !value.is_finite() || value < 0.0 {
+        return Err(invalid_option(
+            "min_rule_thickness",
+            format!("expected a non-negative number, got {value}"),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_trusted_commands(commands: &[String]) -> Result<()> {
+    match commands.iter().find(|c| !is_command_name(c)) {
+        Some(command) => Err(invalid_option(
+            "trusted_commands",
+            format!("invalid trusted command {command:?}"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// KaTeX options are named like JS identifiers, e.g. `minRuleThickness`.
+fn validate_raw_options(options: &BTreeMap<String, JsonValue>) -> Result<()> {
+    let is_identifier = |key: &str| {
+        !key.is_empty()
+            && !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    match options.keys().find(|key| !is_identifier(key)) {
+        Some(key) => Err(invalid_option(
+            "raw_options",
+            format!("invalid option name {key:?}"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Whether the string is a control word such as `\href`.
+fn is_command_name(name: &str) -> bool {
+    name.strip_prefix('\\')
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Output type from KaTeX.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OutputType {
+    /// Outputs KaTeX in HTML only.
+    Html,
+    /// Outputs KaTeX in MathML only.
     Mathml,
     /// Outputs HTML for visual rendering and includes MathML for accessibility.
     HtmlAndMathml,
+    /// Outputs HTML and MathML, with the TeX source kept as a MathML annotation.
+    ///
+    /// The bundled KaTeX always includes the annotation, so this is currently
+    /// equivalent to [`OutputType::HtmlAndMathml`].
+    HtmlAndMathmlWithAnnotation,
+    /// Pass the value of KaTeX's `output` option through as is.
+    ///
+    /// The value must be one of those of the bundled KaTeX, i.e. `html`,
+    /// `mathml` or `htmlAndMathml`, otherwise [`OptsBuilder::build`] fails.
+    Custom(String),
+    /// Pass the value of KaTeX's `output` option through without checking
+    /// that KaTeX knows it.
+    ///
+    /// This allows to use output types added by other KaTeX versions, e.g.
+    /// [loaded from a file](crate::Renderer::with_katex_source). The value
+    /// must be a non-empty ASCII identifier, otherwise
+    /// [`OptsBuilder::build`] fails.
+    Unchecked(String),
+}
+
+impl fmt::Display for OutputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl OutputType {
+    /// Return the value of KaTeX's `output` option.
+    pub fn as_str(&self) -> &str {
+        match self {
+            OutputType::Html => "html",
+            OutputType::Mathml => "mathml",
+            OutputType::HtmlAndMathml | OutputType::HtmlAndMathmlWithAnnotation => "htmlAndMathml",
+            OutputType::Custom(output) | OutputType::Unchecked(output) => output,
+        }
+    }
+}
+
+/// How KaTeX handles input which it accepts but LaTeX does not.
+///
+/// Read <https://katex.org/docs/options.html> for more information.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum StrictMode {
+    /// Allow the input silently.
+    Ignore,
+    /// Allow the input, with a warning on the console.
+    #[default]
+    Warn,
+    /// Fail with a parse error.
+    Error,
+}
+
+impl StrictMode {
+    /// Return the value of KaTeX's `strict` option.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StrictMode::Ignore => "ignore",
+            StrictMode::Warn => "warn",
+            StrictMode::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for StrictMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Code of an input which KaTeX accepts but LaTeX does not, reported to its
+/// [`strict`](OptsBuilder::strict) handling.
+///
+/// # Examples
+///
+/// Allow CJK text in math mode while keeping the other strict errors.
+///
+/// ```
+/// use katex::{StrictErrorCode, StrictMode};
+/// use std::collections::HashMap;
+///
+/// let opts = katex::Opts::builder()
+///     .strict(StrictMode::Error)
+///     .strict_overrides(HashMap::from([(
+///         StrictErrorCode::UnicodeTextInMathMode,
+///         StrictMode::Ignore,
+///     )]))
+///     .build()
+///     .unwrap();
+/// assert!(katex::render_with_opts("x = 中", &opts).is_ok());
+/// assert!(katex::render_with_opts("x % comment", &opts).is_err());
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum StrictErrorCode {
+    /// A symbol unknown to LaTeX, e.g. a non-ASCII letter in math mode.
+    UnknownSymbol,
+    /// Unicode text in math mode, e.g. CJK characters.
+    UnicodeTextInMathMode,
+    /// A math-mode unit such as `mu` in a text-mode size, or the reverse.
+    MathVsTextUnits,
+    /// A text-mode accent in math mode, or the reverse.
+    MathVsTextAccents,
+    /// A `%` comment at the end of the input, without a newline.
+    CommentAtEnd,
+    /// A KaTeX extension such as `\htmlClass`.
+    HtmlExtension,
+    /// An environment such as `align` in text mode.
+    TextEnv,
+    /// A line break `\\` outside an environment in display mode.
+    NewLineInDisplayMode,
+}
+
+impl StrictErrorCode {
+    /// Return the code as reported by KaTeX, e.g. `unicodeTextInMathMode`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StrictErrorCode::UnknownSymbol => "unknownSymbol",
+            StrictErrorCode::UnicodeTextInMathMode => "unicodeTextInMathMode",
+            StrictErrorCode::MathVsTextUnits => "mathVsTextUnits",
+            StrictErrorCode::MathVsTextAccents => "mathVsTextAccents",
+            StrictErrorCode::CommentAtEnd => "commentAtEnd",
+            StrictErrorCode::HtmlExtension => "htmlExtension",
+            StrictErrorCode::TextEnv => "textEnv",
+            StrictErrorCode::NewLineInDisplayMode => "newLineInDisplayMode",
+        }
+    }
+}
+
+impl fmt::Display for StrictErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A custom macro.
+///
+/// The body refers to the arguments as `#1` to `#9`. Unless given, the number
+/// of parameters is the highest one used in the body, as KaTeX does for
+/// macros defined by a string.
+///
+/// # Examples
+///
+/// ```
+/// use katex::{ExpansionMode, Macro};
+///
+/// let plus = Macro::new("#1 + #2");
+/// let primitive = Macro {
+///     expansion_mode: ExpansionMode::Unexpandable,
+///     ..Macro::new(r"\mathrm{d}")
+/// };
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Macro {
+    /// Replacement text.
+    pub body: String,
+    /// Number of parameters, at most 9.
+    pub num_args: Option<u8>,
+    /// How the macro behaves in expansion-only contexts.
+    pub expansion_mode: ExpansionMode,
+}
+
+impl Macro {
+    /// Create a macro with parameters inferred from the body.
+    pub fn new(body: impl Into<String>) -> Self {
+        Self {
+            body: body.into(),
+            num_args: None,
+            expansion_mode: ExpansionMode::default(),
+        }
+    }
+
+    /// Whether KaTeX's string form of macros is enough to describe the macro.
+    fn is_plain(&self) -> bool {
+        self.num_args.is_none() && self.expansion_mode == ExpansionMode::Expandable
+    }
+
+    /// Plain macros are passed as strings, others as objects which entry.js
+    /// converts into KaTeX's object form.
+    fn to_js_value<'a, E>(&self, engine: &'a E) -> Result<E::JsValue<'a>>
+    where
+        E: JsEngine,
+    {
+        if self.is_plain() {
+            return engine.create_string_value(self.body.as_str().into());
+        }
+        let mut fields = vec![(
+            "body".to_owned(),
+            engine.create_string_value(self.body.as_str().into())?,
+        )];
+        if let Some(num_args) = self.num_args {
+            fields.push((
+                "numArgs".to_owned(),
+                engine.create_int_value(num_args.into())?,
+            ));
+        }
+        if self.expansion_mode == ExpansionMode::Unexpandable {
+            fields.push(("unexpandable".to_owned(), engine.create_bool_value(true)?));
+        }
+        engine.create_object_value(fields.into_iter())
+    }
+
+    fn fingerprint(&self) -> String {
+        if self.is_plain() {
+            return json_string(&self.body);
+        }
+        let mut fields = vec![format!("\"body\":{}", json_string(&self.body))];
+        if let Some(num_args) = self.num_args {
+            fields.push(format!("\"numArgs\":{num_args}"));
+        }
+        if self.expansion_mode == ExpansionMode::Unexpandable {
+            fields.push("\"unexpandable\":true".to_owned());
+        }
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+impl From<String> for Macro {
+    fn from(body: String) -> Self {
+        Self::new(body)
+    }
+}
+
+impl From<&str> for Macro {
+    fn from(body: &str) -> Self {
+        Self::new(body)
+    }
+}
+
+/// How a [`Macro`] behaves where only expandable commands are expanded, such
+/// as in `\edef` or after `\expandafter`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ExpansionMode {
+    /// Expand the macro, as for macros defined with `\def`.
+    #[default]
+    Expandable,
+    /// Keep the macro as is, like a primitive command. It is still expanded
+    /// when typesetting.
+    Unexpandable,
+}
+
+/// A limit passed to KaTeX, such as the [maximum number of macro
+/// expansions](OptsBuilder::max_expand).
+///
+/// For compatibility with earlier releases, `Some(value)` converts to
+/// [`Finite`](Self::Finite) and `None` to [`Unlimited`](Self::Unlimited).
+///
+/// # Examples
+///
+/// ```
+/// use katex::Limit;
+///
+/// let opts = katex::Opts::builder()
+///     .max_expand(Limit::Unlimited)
+///     .max_size(20.0)
+///     .build()
+///     .unwrap();
+/// assert!(katex::render_with_opts(r"\rule{100em}{1em}", &opts).is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Limit<T> {
+    /// Use KaTeX's default, i.e. 1000 macro expansions and no size limit.
+    #[default]
+    Default,
+    /// Limit to the given value.
+    Finite(T),
+    /// Do not limit, i.e. KaTeX's `Infinity`.
+    Unlimited,
+}
+
+impl<T: Copy + fmt::Display> Limit<T> {
+    /// Convert to a JS value, unless the default is used.
+    fn to_js_value<'a, E: JsEngine>(
+        self,
+        engine: &'a E,
+        to_f64: impl FnOnce(T) -> f64,
+    ) -> Result<Option<E::JsValue<'a>>> {
+        match self {
+            Self::Default => Ok(None),
+            Self::Finite(value) => engine.create_float_value(to_f64(value)).map(Some),
+            Self::Unlimited => engine.create_float_value(f64::INFINITY).map(Some),
+        }
+    }
+
+    fn fingerprint(self) -> Option<String> {
+        match self {
+            Self::Default => None,
+            Self::Finite(value) => Some(value.to_string()),
+            Self::Unlimited => Some("null".to_owned()),
+        }
+    }
+}
+
+impl From<u32> for Limit<u32> {
+    fn from(value: u32) -> Self {
+        Self::Finite(value)
+    }
+}
+
+impl From<f64> for Limit<f64> {
+    fn from(value: f64) -> Self {
+        Self::Finite(value)
+    }
+}
+
+impl<T> From<Option<T>> for Limit<T> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Unlimited, Self::Finite)
+    }
+}
+
+/// Callback rewriting the URLs of resources loaded by the rendered HTML, i.e.
+/// the `src` of images included with `\includegraphics` when
+/// [trusted](OptsBuilder::trust).
+///
+/// It receives each URL, and returns its replacement, e.g. a CDN path or a
+/// data URI, or `None` to keep it.
+///
+/// # Examples
+///
+/// ```
+/// use katex::ResourceResolver;
+///
+/// let opts = katex::Opts::builder()
+///     .trust(true)
+///     .resource_resolver(ResourceResolver::new(|url| {
+///         Some(format!("https://cdn.example.com/{url}"))
+///     }))
+///     .build()
+///     .unwrap();
+/// let html = katex::render_with_opts(r"\includegraphics[height=1em]{a.png}", &opts).unwrap();
+/// assert!(html.contains("https://cdn.example.com/a.png"));
+/// ```
+///
+/// Callbacks cannot be compared, so the [cache keys](crate::cache_key) only
+/// tell resolvers apart by the id given to [`with_id`](Self::with_id).
+/// Renders with a resolver without id are neither shared by
+/// [`queue::render_async`](crate::queue::render_async) nor stored by
+/// [`batch::render_batch_resumable`](crate::batch::render_batch_resumable).
+#[derive(Clone)]
+pub struct ResourceResolver {
+    id: Option<String>,
+    resolve: Arc<ResolveFn>,
+}
+
+type ResolveFn = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+impl ResourceResolver {
+    /// Create a resolver from a callback.
+    pub fn new(resolver: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self {
+            id: None,
+            resolve: Arc::new(resolver),
+        }
+    }
+
+    /// Create a resolver from a callback, identified by `id` in the
+    /// [cache keys](crate::cache_key), e.g. its name and a version.
+    ///
+    /// Resolvers with the same id must rewrite URLs the same way. Change the
+    /// id when the callback changes, e.g. to a new CDN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use katex::ResourceResolver;
+    ///
+    /// let opts = |id: &str| {
+    ///     katex::Opts::builder()
+    ///         .resource_resolver(ResourceResolver::with_id(id, |_| None))
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// assert_eq!(katex::cache_key("x", opts("cdn-v1")), katex::cache_key("x", opts("cdn-v1")));
+    /// assert_ne!(katex::cache_key("x", opts("cdn-v1")), katex::cache_key("x", opts("cdn-v2")));
+    /// ```
+    pub fn with_id(
+        id: impl Into<String>,
+        resolver: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id: Some(id.into()),
+            resolve: Arc::new(resolver),
+        }
+    }
+
+    /// Return the replacement of `url`, if any.
+    pub(crate) fn resolve(&self, url: &str) -> Option<String> {
+        (self.resolve)(url)
+    }
+}
+
+impl fmt::Debug for ResourceResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceResolver")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A JSON value, e.g. of a [raw option](OptsBuilder::raw_option).
+///
+/// With the `serde_json` feature, it converts from [`serde_json::Value`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    /// `null`
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A number.
+    Number(f64),
+    /// A string.
+    String(String),
+    /// An array.
+    Array(Vec<JsonValue>),
+    /// An object, whose fields are kept in order.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn to_js_value<'a, E>(&self, engine: &'a E) -> Result<E::JsValue<'a>>
+    where
+        E: JsEngine,
+    {
+        match self {
+            JsonValue::Null => engine.eval("null"),
+            JsonValue::Bool(b) => engine.create_bool_value(*b),
+            JsonValue::Number(n) => engine.create_float_value(*n),
+            JsonValue::String(s) => engine.create_string_value(s.into()),
+            JsonValue::Array(values) => {
+                process_results(values.iter().map(|v| v.to_js_value(engine)), |iter| {
+                    engine.create_array_value(iter)
+                })?
+            }
+            JsonValue::Object(fields) => process_results(
+                fields
+                    .iter()
+                    .map(|(k, v)| -> Result<_> { Ok((k.clone(), v.to_js_value(engine)?)) }),
+                |iter| engine.create_object_value(iter),
+            )?,
+        }
+    }
+
+    /// Encode the value as JSON, without whitespace.
+    fn to_json(&self) -> String {
+        match self {
+            JsonValue::Null => "null".to_owned(),
+            JsonValue::Bool(b) => b.to_string(),
+            JsonValue::Number(n) if n.is_finite() => n.to_string(),
+            JsonValue::Number(_) => "null".to_owned(),
+            JsonValue::String(s) => json_string(s),
+            JsonValue::Array(values) => format!("[{}]", values.iter().map(Self::to_json).join(",")),
+            JsonValue::Object(fields) => format!(
+                "{{{}}}",
+                fields
+                    .iter()
+                    .map(|(k, v)| format!("{}:{}", json_string(k), v.to_json()))
+                    .join(",")
+            ),
+        }
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Bool(b)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(n: f64) -> Self {
+        JsonValue::Number(n)
+    }
+}
+
+impl From<i32> for JsonValue {
+    fn from(n: i32) -> Self {
+        JsonValue::Number(n.into())
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::String(s.to_owned())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::String(s)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(values: Vec<T>) -> Self {
+        JsonValue::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Value> for JsonValue {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => JsonValue::Null,
+            serde_json::Value::Bool(b) => JsonValue::Bool(b),
+            serde_json::Value::Number(n) => n.as_f64().map_or(JsonValue::Null, JsonValue::Number),
+            serde_json::Value::String(s) => JsonValue::String(s),
+            serde_json::Value::Array(values) => {
+                JsonValue::Array(values.into_iter().map(Into::into).collect())
+            }
+            serde_json::Value::Object(fields) => {
+                JsonValue::Object(fields.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+        }
+    }
+}
+
+/// Grouping of digits in rendered numbers, e.g. `1 234 567`.
+///
+/// The separator is inserted every three digits in the integer part of
+/// numbers having at least `min_digits` digits. This is applied to the output,
+/// so the TeX source does not need to be changed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DigitGrouping {
+    /// Separator inserted between groups.
+    pub separator: String,
+    /// Minimal number of digits in the integer part for grouping to apply.
+    pub min_digits: usize,
+}
+
+impl DigitGrouping {
+    /// Group digits with thin spaces, as recommended by SI and ISO 80000.
+    pub fn thin_space() -> Self {
+        Self {
+            separator: "\u{2009}".to_owned(),
+            min_digits: 5,
+        }
+    }
+
+    /// Group digits according to the conventions of a locale given as a BCP 47
+    /// tag such as `en-US`, `de` or `fr-CA`.
+    ///
+    /// Return `None` for unknown locales.
+    pub fn for_locale(locale: &str) -> Option<Self> {
+        let mut parts = locale.split(['-', '_']);
+        let language = parts.next()?.to_ascii_lowercase();
+        let region = parts.next().map(|r| r.to_ascii_uppercase());
+        let separator = match (language.as_str(), region.as_deref()) {
+            ("de" | "it" | "rm", Some("CH" | "LI")) => "\u{2019}",
+            ("en" | "ja" | "ko" | "zh" | "he" | "th", _) => ",",
+            ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el", _) => ".",
+            ("fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu", _) => {
+                "\u{202F}"
+            }
+            _ => return None,
+        };
+        Some(Self {
+            separator: separator.to_owned(),
+            min_digits: 4,
+        })
+    }
+}
+
+/// Policy for emoji inside text-mode commands such as `\text{}`.
+///
+/// The rendering of emoji depends on the fonts available to the reader, which
+/// frequently breaks downstream converters such as HTML to PDF tools.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum TextUnicodePolicy {
+    /// Pass emoji to KaTeX as is.
+    #[default]
+    PassThrough,
+    /// Remove emoji from the input.
+    StripEmoji,
+    /// Replace each emoji sequence with the given placeholder.
+    ReplaceEmoji(String),
+    /// Return [`Error::InvalidInput`](crate::Error::InvalidInput) on emoji.
+    RejectEmoji,
+}
+
+/// Policy for input which looks HTML-escaped.
+///
+/// Content pipelines often escape LaTeX twice, so that `&` reaches KaTeX as
+/// `&amp;`, which then fails with a confusing parse error. Input is considered
+/// escaped when it contains a character reference such as `&amp;`, `&lt;` or
+/// `&#39;`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum EscapedInputPolicy {
+    /// Pass the input to KaTeX as is.
+    #[default]
+    Ignore,
+    /// Unescape character references before rendering.
+    Unescape,
+    /// Return [`Error::InvalidInput`](crate::Error::InvalidInput) naming the
+    /// first character reference.
+    Reject,
 }
@@ -0,0 +1,270 @@
+//! Structured render output.
+
+use crate::{html, renderer::ConsoleLevel, Rendered};
+use std::collections::HashSet;
+
+/// Output of a render.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOutput {
+    /// The rendered HTML.
+    pub html: String,
+    /// Messages written to the JS console during the render, e.g. KaTeX's
+    /// strict mode warnings about input that is not valid LaTeX, after the
+    /// warnings about emoji stripped or replaced by the
+    /// [`TextUnicodePolicy`](crate::TextUnicodePolicy).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use katex::renderer::ConsoleLevel;
+    ///
+    /// let output = katex::render_to_output("é", katex::Opts::default()).unwrap();
+    /// assert_eq!(output.logs[0].level, ConsoleLevel::Warn);
+    /// assert!(output.logs[0].message.contains("unicodeTextInMathMode"));
+    /// ```
+    pub logs: Vec<LogMessage>,
+    /// Height of the formula above the baseline, in em, or `None` with the
+    /// [MathML output type](crate::OutputType::Mathml).
+    pub height_em: Option<f64>,
+    /// Depth of the formula below the baseline, in em, or `None` with the
+    /// [MathML output type](crate::OutputType::Mathml).
+    ///
+    /// This is the offset of the baseline from the bottom of the formula,
+    /// e.g. to align it with the surrounding text in a canvas or a PDF.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let output = katex::render_to_output(r"\frac{a}{b}", katex::Opts::default()).unwrap();
+    /// let depth = output.baseline_em.unwrap();
+    /// assert!(depth > 0.3 && depth < 0.5, "{depth}");
+    /// ```
+    pub baseline_em: Option<f64>,
+    /// Names of the user macros expanded by the render, in order of first
+    /// use, with [`track_macros`](crate::OptsBuilder::track_macros), or empty
+    /// otherwise.
+    ///
+    /// A change to any other macro does not change the output, e.g. to only
+    /// render again the affected formulas.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let opts = katex::Opts::builder()
+    ///     .add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned())
+    ///     .add_macro(r"\NN".to_owned(), r"\mathbb{N}".to_owned())
+    ///     .track_macros(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let output = katex::render_to_output(r"x \in \RR", &opts).unwrap();
+    /// assert_eq!(output.used_macros, [r"\RR"]);
+    /// ```
+    pub used_macros: Vec<String>,
+}
+
+impl RenderOutput {
+    pub(crate) fn new(rendered: Rendered, logs: Vec<LogMessage>) -> Self {
+        let warnings = rendered.warnings.into_iter().map(|message| LogMessage {
+            level: ConsoleLevel::Warn,
+            message,
+        });
+        Self {
+            html: rendered.html,
+            logs: warnings.chain(logs).collect(),
+            height_em: rendered.height,
+            baseline_em: rendered.depth,
+            used_macros: rendered.used_macros,
+        }
+    }
+
+    /// Return the rendered HTML.
+    pub fn into_html(self) -> String {
+        self.html
+    }
+
+    /// Return the MathML element of the output, or `None` with the
+    /// [HTML output type](crate::OutputType::Html) or an error rendered in
+    /// place of the equation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let output = katex::render_to_output("x", katex::Opts::default()).unwrap();
+    /// let mathml = output.mathml().unwrap();
+    /// assert!(mathml.starts_with("<math") && mathml.ends_with("</math>"));
+    /// ```
+    pub fn mathml(&self) -> Option<&str> {
+        Self::mathml_of(&self.html)
+    }
+
+    /// Return the MathML element of rendered HTML.
+    pub(crate) fn mathml_of(html: &str) -> Option<&str> {
+        const END: &str = "</math>";
+        let start = html.find("<math")?;
+        let end = start + html[start..].find(END)? + END.len();
+        Some(&html[start..end])
+    }
+
+    /// Describe the equation in English words for screen readers, e.g. as
+    /// the `alt` text of an image, see [`a11y`](crate::a11y). Return `None`
+    /// when the output has no [MathML](Self::mathml).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let output = katex::render_to_output("E = mc^2", katex::Opts::default()).unwrap();
+    /// assert_eq!(output.alt_text().unwrap(), "E equals m c squared");
+    /// ```
+    pub fn alt_text(&self) -> Option<String> {
+        self.mathml().map(crate::a11y::describe)
+    }
+
+    /// Return the KaTeX fonts used by the rendered HTML.
+    ///
+    /// This is computed by scanning the KaTeX CSS classes in the HTML, so that
+    /// only the required font files need to be shipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use katex::output::FontName;
+    ///
+    /// let output = katex::render_to_output(r#"\mathbb{R}"#, katex::Opts::default()).unwrap();
+    /// assert!(output.used_fonts().contains(&FontName::AmsRegular));
+    /// ```
+    pub fn used_fonts(&self) -> HashSet<FontName> {
+        let mut fonts = HashSet::new();
+        for (start, end) in html::attribute_values(&self.html, "class") {
+            let classes: HashSet<&str> = self.html[start..end].split(' ').collect();
+            if classes.contains("katex-html") {
+                fonts.insert(FontName::MainRegular);
+            }
+            fonts.extend(fonts_of_classes(&classes));
+        }
+        fonts
+    }
+}
+
+/// A message written to the JS console.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LogMessage {
+    /// Level of the message.
+    pub level: ConsoleLevel,
+    /// The arguments of the console call, joined by spaces.
+    pub message: String,
+}
+
+impl LogMessage {
+    /// Parse the messages returned by `__katex_rs.takeLogs`.
+    pub(crate) fn parse_all(logs: &str) -> Vec<Self> {
+        logs.split('\0')
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (level, message) = entry.split_once('\t').unwrap_or(("log", entry));
+                Self {
+                    level: ConsoleLevel::from_js(level),
+                    message: message.to_owned(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Return the fonts selected by the KaTeX CSS classes of an element.
+fn fonts_of_classes(classes: &HashSet<&str>) -> Vec<FontName> {
+    use FontName::*;
+
+    let mut fonts = Vec::new();
+    for class in classes {
+        let font = match *class {
+            "mathnormal" => MathItalic,
+            "boldsymbol" => MathBoldItalic,
+            "mathit" | "textit" => MainItalic,
+            "mathrm" | "textrm" | "mainrm" => MainRegular,
+            "mathbf" | "textbf" => MainBold,
+            "amsrm" | "mathbb" | "textbb" => AmsRegular,
+            "mathcal" => CaligraphicRegular,
+            "mathfrak" | "textfrak" => FrakturRegular,
+            "mathboldfrak" | "textboldfrak" => FrakturBold,
+            "mathtt" | "texttt" => TypewriterRegular,
+            "mathscr" | "textscr" => ScriptRegular,
+            "mathsf" | "textsf" => SansSerifRegular,
+            "mathboldsf" | "textboldsf" => SansSerifBold,
+            "mathitsf" | "textitsf" => SansSerifItalic,
+            "delim-size1" | "small-op" => Size1Regular,
+            "large-op" => Size2Regular,
+            "delim-size4" => Size4Regular,
+            // `size1`...`size4` are also used for font scaling by `.sizing`.
+            "size1" if classes.contains("delimsizing") => Size1Regular,
+            "size2" if classes.contains("delimsizing") => Size2Regular,
+            "size3" if classes.contains("delimsizing") => Size3Regular,
+            "size4" if classes.contains("delimsizing") => Size4Regular,
+            _ => continue,
+        };
+        fonts.push(font);
+    }
+    if fonts.contains(&MainBold) && fonts.contains(&MainItalic) {
+        fonts.retain(|f| !matches!(f, MainBold | MainItalic));
+        fonts.push(MainBoldItalic);
+    }
+    fonts
+}
+
+/// KaTeX fonts.
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum FontName {
+    AmsRegular,
+    CaligraphicRegular,
+    CaligraphicBold,
+    FrakturRegular,
+    FrakturBold,
+    MainRegular,
+    MainBold,
+    MainItalic,
+    MainBoldItalic,
+    MathItalic,
+    MathBoldItalic,
+    SansSerifRegular,
+    SansSerifBold,
+    SansSerifItalic,
+    ScriptRegular,
+    Size1Regular,
+    Size2Regular,
+    Size3Regular,
+    Size4Regular,
+    TypewriterRegular,
+}
+
+impl FontName {
+    /// Return the file stem of the font files shipped with KaTeX,
+    /// e.g. `KaTeX_Main-Regular` for `KaTeX_Main-Regular.woff2`.
+    pub fn file_stem(self) -> &'static str {
+        use FontName::*;
+
+        match self {
+            AmsRegular => "KaTeX_AMS-Regular",
+            CaligraphicRegular => "KaTeX_Caligraphic-Regular",
+            CaligraphicBold => "KaTeX_Caligraphic-Bold",
+            FrakturRegular => "KaTeX_Fraktur-Regular",
+            FrakturBold => "KaTeX_Fraktur-Bold",
+            MainRegular => "KaTeX_Main-Regular",
+            MainBold => "KaTeX_Main-Bold",
+            MainItalic => "KaTeX_Main-Italic",
+            MainBoldItalic => "KaTeX_Main-BoldItalic",
+            MathItalic => "KaTeX_Math-Italic",
+            MathBoldItalic => "KaTeX_Math-BoldItalic",
+            SansSerifRegular => "KaTeX_SansSerif-Regular",
+            SansSerifBold => "KaTeX_SansSerif-Bold",
+            SansSerifItalic => "KaTeX_SansSerif-Italic",
+            ScriptRegular => "KaTeX_Script-Regular",
+            Size1Regular => "KaTeX_Size1-Regular",
+            Size2Regular => "KaTeX_Size2-Regular",
+            Size3Regular => "KaTeX_Size3-Regular",
+            Size4Regular => "KaTeX_Size4-Regular",
+            TypewriterRegular => "KaTeX_Typewriter-Regular",
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Post-processing of the rendered HTML.
+
+use crate::{
+    a11y,
+    error::Result,
+    html,
+    opts::{DigitGrouping, Opts, ResourceResolver},
+};
+
+/// A post-processor rewriting the HTML produced by KaTeX.
+///
+/// It is implemented for closures of `Fn(&str, String) -> Result<String>`.
+///
+/// # Examples
+///
+/// ```
+/// let mut renderer = katex::Renderer::new().unwrap();
+/// renderer.add_post_processor(|_input: &str, html: String| {
+///     Ok(html.replace("<span", "<span data-math"))
+/// });
+/// let html = renderer.render("E = mc^2").unwrap();
+/// assert!(html.starts_with("<span data-math"));
+/// ```
+pub trait PostProcessor {
+    /// Rewrite `html`, which is the rendering of the LaTeX `input`.
+    fn process(&self, input: &str, html: String) -> Result<String>;
+}
+
+impl<F> PostProcessor for F
+where
+    F: Fn(&str, String) -> Result<String>,
+{
+    fn process(&self, input: &str, html: String) -> Result<String> {
+        self(input, html)
+    }
+}
+
+/// Apply the post-processing selected by the [options](`Opts`) to the
+/// rendering of `input`.
+pub(crate) fn apply(input: &str, html: String, opts: &Opts) -> Result<String> {
+    let mut html = html;
+    // From the MathML as rendered by KaTeX, e.g. before grouping digits.
+    let alt_text = opts
+        .aria_label
+        .unwrap_or_default()
+        .then(|| crate::RenderOutput::mathml_of(&html).map(a11y::describe))
+        .flatten();
+    if opts.strip_annotations.unwrap_or_default() {
+        html = strip_annotations(&html);
+    }
+    if let Some(grouping) = &opts.digit_grouping {
+        html = group_digits(&html, grouping);
+    }
+    if opts.minimal_inline_css.unwrap_or_default() {
+        html = inline_minimal_css(&html);
+    }
+    // Before sanitizing, so that the resolved URLs are checked.
+    if let Some(resolver) = &opts.resource_resolver {
+        html = resolve_resources(&html, resolver);
+    }
+    #[cfg(feature = "sanitize")]
+    if opts.sanitize.unwrap_or_default() {
+        html = crate::sanitize::sanitize(&html);
+    }
+    if let Some(size) = opts.font_size_em {
+        html = set_font_size(&html, size);
+    }
+    // After sanitizing, which would remove it.
+    if opts.embed_source.unwrap_or_default() {
+        html = embed_source(&html, input);
+    }
+    if let Some(alt_text) = alt_text {
+        html = add_aria_label(&html, &alt_text);
+    }
+    if opts.deterministic.unwrap_or_default() {
+        html = html::sort_attributes(&html);
+    }
+    Ok(html)
+}
+
+/// Set the font size of the root element of the output, after the styles
+/// inlined by [`inline_minimal_css`] if any.
+fn set_font_size(html: &str, size: f64) -> String {
+    const ROOT: &str = r#"<span class="katex""#;
+    let Some(start) = html.find(ROOT) else {
+        return html.to_owned();
+    };
+    let end = start + ROOT.len();
+    let declaration = format!("font-size:{size}em;");
+    let mut html = html.to_owned();
+    if html[end..].starts_with(r#" style=""#) {
+        let style_end = end + r#" style=""#.len();
+        match html[style_end..].find('"') {
+            Some(quote) => html.insert_str(style_end + quote, &declaration),
+            None => return html,
+        }
+    } else {
+        html.insert_str(end, &format!(r#" style="{declaration}""#));
+    }
+    html
+}
+
+/// Add the LaTeX source as a `data-latex` attribute of the root element, as
+/// the copy-tex extension of KaTeX reads it.
+fn embed_source(html: &str, input: &str) -> String {
+    const ROOT: &str = r#"<span class="katex""#;
+    let Some(start) = html.find(ROOT) else {
+        return html.to_owned();
+    };
+    let mut html = html.to_owned();
+    let attribute = format!(r#" data-latex="{}""#, html::escape(input));
+    html.insert_str(start + ROOT.len(), &attribute);
+    html
+}
+
+/// Add the spoken description as the `aria-label` of the root element, with
+/// the `img` role which screen readers need to read it.
+fn add_aria_label(html: &str, alt_text: &str) -> String {
+    const ROOT: &str = r#"<span class="katex""#;
+    let Some(start) = html.find(ROOT) else {
+        return html.to_owned();
+    };
+    let mut html = html.to_owned();
+    let attributes = format!(r#" role="img" aria-label="{}""#, html::escape(alt_text));
+    html.insert_str(start + ROOT.len(), &attributes);
+    html
+}
+
+/// Remove the TeX source annotations of the MathML, and the `semantics`
+/// elements wrapping them, which are then useless.
+fn strip_annotations(html: &str) -> String {
+    const START: &str = r#"<annotation encoding="application/x-tex">"#;
+    const END: &str = "</annotation></semantics>";
+    if !html.contains(START) {
+        return html.to_owned();
+    }
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(START) {
+        let Some(end) = rest[start..].find(END) else {
+            break;
+        };
+        let (before, after) = (&rest[..start], &rest[start + end + END.len()..]);
+        // The annotation follows the content of the innermost `semantics`.
+        match before.rfind("<semantics>") {
+            Some(open) => {
+                output.push_str(&before[..open]);
+                output.push_str(&before[open + "<semantics>".len()..]);
+            }
+            None => output.push_str(&rest[..start + end + END.len()]),
+        }
+        rest = after;
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Rewrite the `src` attributes with the resolver.
+fn resolve_resources(html: &str, resolver: &ResourceResolver) -> String {
+    html::map_attribute(html, "src", |value| {
+        // KaTeX writes a space after the URL, which is kept as is.
+        let url = value.trim_end();
+        let resolved = resolver.resolve(&html::unescape(url))?;
+        Some(format!(
+            "{}{}",
+            html::escape(&resolved),
+            &value[url.len()..]
+        ))
+    })
+}
+
+/// Inline styles for the root elements of the output, for pages without the
+/// KaTeX stylesheet.
+///
+/// They center display math and select the KaTeX fonts, with fallbacks. The
+/// HTML part of the output is hidden when there is a MathML part, as it is
+/// unreadable without the stylesheet while browsers render MathML natively.
+fn inline_minimal_css(html: &str) -> String {
+    const ROOTS: &[(&str, &str)] = &[
+        (
+            r#"<span class="katex-display fleqn">"#,
+            "display:block;margin:1em 0;text-align:left;padding-left:2em;",
+        ),
+        (
+            r#"<span class="katex-display">"#,
+            "display:block;margin:1em 0;text-align:center;",
+        ),
+        (
+            r#"<span class="katex">"#,
+            "font:normal 1.21em KaTeX_Main,'Times New Roman',serif;\
+             line-height:1.2;text-indent:0;white-space:nowrap;",
+        ),
+    ];
+    const HTML_PART: &str = r#"<span class="katex-html" aria-hidden="true">"#;
+
+    let mut html = html.to_owned();
+    for (tag, style) in ROOTS {
+        let styled = format!(r#"{} style="{style}">"#, &tag[..tag.len() - 1]);
+        html = html.replacen(tag, &styled, 1);
+    }
+    if html.contains(r#"<span class="katex-mathml">"#) {
+        let hidden = format!(
+            r#"{} style="display:none;">"#,
+            &HTML_PART[..HTML_PART.len() - 1]
+        );
+        html = html.replacen(HTML_PART, &hidden, 1);
+    }
+    html
+}
+
+/// Insert separators between groups of three digits in the integer part of
+/// numbers, leaving the TeX source annotation untouched.
+fn group_digits(html: &str, grouping: &DigitGrouping) -> String {
+    let separator = html::escape(&grouping.separator);
+    html::map_text(html, &["annotation"], |text| {
+        let mut output = String::with_capacity(text.len());
+        let mut changed = false;
+        let mut prev = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if !c.is_ascii_digit() {
+                output.push(c);
+                prev = Some(c);
+                continue;
+            }
+            let mut end = start + 1;
+            while let Some((i, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                end = i + 1;
+            }
+            let digits = &text[start..end];
+            // Do not group fractional parts.
+            if prev != Some('.') && digits.len() >= grouping.min_digits {
+                for (i, d) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % 3 == 0 {
+                        output.push_str(&separator);
+                    }
+                    output.push(d);
+                }
+                changed = true;
+            } else {
+                output.push_str(digits);
+            }
+            prev = digits.chars().last();
+        }
+        changed.then_some(output)
+    })
+}
+
+/// Replace the `katex` prefix of CSS class names with a custom prefix.
+///
+/// This prevents CSS collisions on pages embedding multiple math libraries.
+/// Only classes named `katex` or starting with `katex-` are renamed.
+///
+/// # Examples
+///
+/// ```
+/// use katex::postprocess::ClassPrefix;
+///
+/// let mut renderer = katex::Renderer::new().unwrap();
+/// renderer.add_post_processor(ClassPrefix::new("math"));
+/// let html = renderer.render("E = mc^2").unwrap();
+/// assert!(html.contains(r#"class="math-html""#));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClassPrefix {
+    prefix: String,
+}
+
+impl ClassPrefix {
+    /// Create a post-processor replacing the `katex` prefix by `prefix`.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl PostProcessor for ClassPrefix {
+    fn process(&self, _input: &str, html: String) -> Result<String> {
+        let prefix = html::escape(&self.prefix);
+        Ok(html::map_attribute(&html, "class", |classes| {
+            let mut changed = false;
+            let classes: Vec<_> = classes
+                .split(' ')
+                .map(|class| match class.strip_prefix("katex") {
+                    Some(rest) if rest.is_empty() || rest.starts_with('-') => {
+                        changed = true;
+                        format!("{prefix}{rest}")
+                    }
+                    _ => class.to_owned(),
+                })
+                .collect();
+            changed.then(|| classes.join(" "))
+        }))
+    }
+}
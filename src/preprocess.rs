@@ -0,0 +1,602 @@
+//! Crate-side preprocessing of the input before it is passed to KaTeX.
+
+use crate::{
+    error::{Error, Result},
+    html, mathjax,
+    opts::{EscapedInputPolicy, Opts, TextUnicodePolicy},
+    unicode,
+};
+use core::ops::Range;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+/// Commands whose argument is typeset in text mode.
+const TEXT_COMMANDS: &[&str] = &[
+    "text",
+    "textrm",
+    "textsf",
+    "texttt",
+    "textnormal",
+    "textbf",
+    "textmd",
+    "textit",
+    "textup",
+    "emph",
+    "mbox",
+    "hbox",
+];
+
+/// Commands defining a macro whose body is expanded lazily.
+///
+/// `\edef` and `\xdef` are left out, as their body is expanded on definition.
+const DEFINE_COMMANDS: &[&str] = &[
+    "def",
+    "gdef",
+    "newcommand",
+    "renewcommand",
+    "providecommand",
+];
+
+/// A replacement done by preprocessing, as the byte ranges of the replaced
+/// text and of its replacement.
+pub(crate) type Edit = (Range<usize>, Range<usize>);
+
+/// Map from byte offsets of the preprocessed input to the original input.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SourceMap {
+    /// Edits of each preprocessing step, in order.
+    steps: Vec<Vec<Edit>>,
+}
+
+impl SourceMap {
+    /// Map a byte offset of the preprocessed input to the original input.
+    ///
+    /// Offsets inside a replacement map to the start of the replaced text, or
+    /// to its end if `end` is set, e.g. for the exclusive end of a range,
+    /// which maps to the start of a replacement beginning at it.
+    pub(crate) fn map(&self, offset: usize, end: bool) -> usize {
+        self.steps
+            .iter()
+            .rev()
+            .fold(offset, |offset, edits| map_offset(edits, offset, end))
+    }
+}
+
+fn map_offset(edits: &[Edit], offset: usize, end: bool) -> usize {
+    let (mut original_end, mut preprocessed_end) = (0, 0);
+    for (original, preprocessed) in edits {
+        if offset < preprocessed.start || (end && offset == preprocessed.start) {
+            break;
+        }
+        if offset < preprocessed.end {
+            return if end { original.end } else { original.start };
+        }
+        (original_end, preprocessed_end) = (original.end, preprocessed.end);
+    }
+    original_end + (offset - preprocessed_end)
+}
+
+/// Preprocess the input according to the [options](`Opts`).
+pub(crate) fn preprocess<'a>(input: &'a str, opts: &Opts) -> Result<Cow<'a, str>> {
+    preprocess_all(input, opts).map(|(output, _, _)| output)
+}
+
+/// Preprocess the input, and return how to map offsets of the result back
+/// to the input.
+pub(crate) fn preprocess_with_map<'a>(
+    input: &'a str,
+    opts: &Opts,
+) -> Result<(Cow<'a, str>, SourceMap)> {
+    preprocess_all(input, opts).map(|(output, map, _)| (output, map))
+}
+
+/// Preprocess the input, and return warnings about the changes to the
+/// rendered text, i.e. the emoji stripped or replaced by the
+/// [`TextUnicodePolicy`].
+pub(crate) fn preprocess_with_warnings<'a>(
+    input: &'a str,
+    opts: &Opts,
+) -> Result<(Cow<'a, str>, Vec<String>)> {
+    preprocess_all(input, opts).map(|(output, _, warnings)| (output, warnings))
+}
+
+fn preprocess_all<'a>(
+    input: &'a str,
+    opts: &Opts,
+) -> Result<(Cow<'a, str>, SourceMap, Vec<String>)> {
+    if let Some(max_len) = opts.max_input_len {
+        if input.len() > max_len {
+            return Err(Error::InvalidInput(format!(
+                "input of {} bytes exceeds the limit of {max_len} bytes",
+                input.len()
+            )));
+        }
+    }
+    let mut map = SourceMap::default();
+    let mut edits = Vec::new();
+    let input = apply_escaped_input_policy(
+        input,
+        opts.escaped_input_policy.unwrap_or_default(),
+        &mut edits,
+    )?;
+    map.steps.push(edits);
+    let input = if opts.normalize_unicode.unwrap_or_default() {
+        let mut edits = Vec::new();
+        let output = unicode::nfc(&input, &mut edits).into_owned();
+        map.steps.push(edits);
+        Cow::Owned(output)
+    } else {
+        input
+    };
+    let input = if opts.mathjax_compat.unwrap_or_default() {
+        let mut edits = Vec::new();
+        let output = mathjax::rewrite(&input, &mut edits).into_owned();
+        map.steps.push(edits);
+        Cow::Owned(output)
+    } else {
+        input
+    };
+    let input = if opts.replace_unicode_math.unwrap_or_default() {
+        let mut edits = Vec::new();
+        let math = math_ranges(&input);
+        let output =
+            unicode::replace_unicode_math(&input, math.into_iter(), &mut edits).into_owned();
+        map.steps.push(edits);
+        Cow::Owned(output)
+    } else {
+        input
+    };
+    let input = if opts.force_display_style.unwrap_or_default() {
+        const PREFIX: &str = "\\displaystyle ";
+        map.steps.push(vec![(0..0, 0..PREFIX.len())]);
+        Cow::Owned(format!("{PREFIX}{input}"))
+    } else {
+        input
+    };
+    check_macro_recursion(&input, opts)?;
+    let mut edits = Vec::new();
+    let mut emoji = Vec::new();
+    let policy = opts
+        .text_unicode_policy
+        .as_ref()
+        .unwrap_or(&TextUnicodePolicy::PassThrough);
+    let output = match apply_text_unicode_policy(&input, policy, &map, &mut edits, &mut emoji)? {
+        Cow::Owned(output) => Some(output),
+        Cow::Borrowed(_) => None,
+    };
+    let warnings = emoji
+        .into_iter()
+        .map(|range| {
+            let change = match policy {
+                TextUnicodePolicy::ReplaceEmoji(placeholder) => {
+                    format!("replaced with {placeholder:?}")
+                }
+                _ => "stripped".to_owned(),
+            };
+            format!(
+                "emoji {:?} inside text at byte offset {} {change}",
+                &input[range.clone()],
+                map.map(range.start, false)
+            )
+        })
+        .collect();
+    map.steps.push(edits);
+    Ok((output.map_or(input, Cow::Owned), map, warnings))
+}
+
+/// Apply [`EscapedInputPolicy`] to the input, recording the replaced
+/// character references in `edits`.
+fn apply_escaped_input_policy<'a>(
+    input: &'a str,
+    policy: EscapedInputPolicy,
+    edits: &mut Vec<Edit>,
+) -> Result<Cow<'a, str>> {
+    if policy == EscapedInputPolicy::Ignore {
+        return Ok(Cow::Borrowed(input));
+    }
+    let Some((start, end)) = find_character_reference(input) else {
+        return Ok(Cow::Borrowed(input));
+    };
+    match policy {
+        EscapedInputPolicy::Ignore => unreachable!(),
+        EscapedInputPolicy::Unescape => Ok(Cow::Owned(unescape(input, edits))),
+        EscapedInputPolicy::Reject => Err(Error::InvalidInput(format!(
+            "input looks HTML-escaped, found {:?} at byte offset {start}; \
+             unescape it before rendering",
+            &input[start..end]
+        ))),
+    }
+}
+
+/// Decode character references like [`html::unescape`], recording them in
+/// `edits`.
+fn unescape(input: &str, edits: &mut Vec<Edit>) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut last = 0;
+    for (start, _) in input.match_indices('&') {
+        if start < last {
+            continue;
+        }
+        let Some(end) = input[start..].find(';').filter(|&end| end <= 10) else {
+            continue;
+        };
+        let reference = &input[start..=start + end];
+        let decoded = html::unescape(reference);
+        if decoded.chars().count() != 1 || decoded == reference {
+            continue;
+        }
+        output.push_str(&input[last..start]);
+        let output_start = output.len();
+        output.push_str(&decoded);
+        last = start + end + 1;
+        edits.push((start..last, output_start..output.len()));
+    }
+    output.push_str(&input[last..]);
+    output
+}
+
+/// Find the byte range of the first HTML character reference, e.g. `&amp;`.
+fn find_character_reference(input: &str) -> Option<(usize, usize)> {
+    input.match_indices('&').find_map(|(start, _)| {
+        let rest = &input[start + 1..];
+        let end = rest.find(';').filter(|&end| end <= 10)?;
+        let name = &rest[..end];
+        let valid = match name.strip_prefix('#') {
+            Some(n) => match n.strip_prefix(['x', 'X']) {
+                Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+                None => !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()),
+            },
+            None => ["amp", "lt", "gt", "quot", "apos", "nbsp"].contains(&name),
+        };
+        valid.then_some((start, start + end + 2))
+    })
+}
+
+/// Refuse macros which expand to themselves, directly or through other macros,
+/// if the input uses them.
+///
+/// KaTeX would keep expanding such macros until `maxExpand` is exceeded,
+/// which can take a long time. The check is static: it considers the macros
+/// from the [options](`Opts`) and the definitions found in the input, where a
+/// later definition replaces an earlier one, and the macros used outside of
+/// the definitions. Recursive macros which are never expanded are accepted,
+/// like KaTeX does.
+fn check_macro_recursion(input: &str, opts: &Opts) -> Result<()> {
+    if opts.macros.is_empty() && inline_definitions(input).is_empty() {
+        return Ok(());
+    }
+    let definitions = macro_definitions(input, opts);
+
+    let mut done = HashSet::new();
+    let mut names: Vec<_> = macro_uses(input)
+        .filter_map(|name| definitions.get_key_value(name).map(|(&name, _)| name))
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    for name in names {
+        let mut path = Vec::new();
+        if let Some(cycle) = find_cycle(name, &definitions, &mut path, &mut done) {
+            return Err(Error::MacroRecursion(
+                cycle.into_iter().map(|name| format!("\\{name}")).collect(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Collect the macros from the [options](`Opts`) and the definitions found in
+/// the input, as a map from name (without the backslash) to body.
+fn macro_definitions<'a>(input: &'a str, opts: &'a Opts) -> HashMap<&'a str, &'a str> {
+    let mut definitions: HashMap<&str, &str> = opts
+        .macros
+        .iter()
+        .map(|(name, definition)| (name.trim_start_matches('\\'), definition.body.as_str()))
+        .collect();
+    definitions.extend(inline_definitions(input));
+    definitions
+}
+
+/// Estimate the number of macro expansions needed to render the input.
+///
+/// Each use of a macro counts once, plus the expansions of its body. The
+/// macros used by the input must not be [recursive](`check_macro_recursion`).
+pub(crate) fn macro_expansions(input: &str, opts: &Opts) -> usize {
+    fn expansions<'a>(
+        uses: &mut dyn Iterator<Item = &'a str>,
+        definitions: &HashMap<&'a str, &'a str>,
+        memo: &mut HashMap<&'a str, usize>,
+    ) -> usize {
+        uses.filter_map(|name| definitions.get_key_value(name))
+            .map(|(&name, &body)| {
+                if let Some(&n) = memo.get(name) {
+                    return n;
+                }
+                let mut uses = control_words(body).map(|(name, _, _)| name);
+                let n = 1usize.saturating_add(expansions(&mut uses, definitions, memo));
+                memo.insert(name, n);
+                n
+            })
+            .fold(0, usize::saturating_add)
+    }
+
+    let definitions = macro_definitions(input, opts);
+    expansions(&mut macro_uses(input), &definitions, &mut HashMap::new())
+}
+
+/// Iterate over the names of the control words of the input outside of the
+/// macro definitions, i.e. the macros which the input expands.
+fn macro_uses(input: &str) -> impl Iterator<Item = &str> {
+    let definitions: Vec<Range<usize>> = definition_spans(input)
+        .into_iter()
+        .map(|(_, _, span)| span)
+        .collect();
+    control_words(input)
+        .filter(move |(_, start, _)| !definitions.iter().any(|span| span.contains(start)))
+        .map(|(name, _, _)| name)
+}
+
+/// Depth-first search for a cycle reachable from `name`.
+fn find_cycle<'a>(
+    name: &'a str,
+    definitions: &HashMap<&'a str, &'a str>,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if let Some(start) = path.iter().position(|n| *n == name) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(name);
+        return Some(cycle);
+    }
+    if done.contains(name) {
+        return None;
+    }
+    let body = definitions.get(name)?;
+    path.push(name);
+    for (referenced, _, _) in control_words(body) {
+        if let Some((referenced, _)) = definitions.get_key_value(referenced) {
+            if let Some(cycle) = find_cycle(referenced, definitions, path, done) {
+                return Some(cycle);
+            }
+        }
+    }
+    path.pop();
+    done.insert(name);
+    None
+}
+
+/// Find macro definitions in the input, as pairs of name (without the
+/// backslash) and body.
+pub(crate) fn inline_definitions(input: &str) -> Vec<(&str, &str)> {
+    definition_spans(input)
+        .into_iter()
+        .map(|(name, body, _)| (name, body))
+        .collect()
+}
+
+/// Find macro definitions in the input, as tuples of name, body and byte
+/// range of the whole definition, from the defining command to the closing
+/// brace of the body.
+fn definition_spans(input: &str) -> Vec<(&str, &str, Range<usize>)> {
+    let bytes = input.as_bytes();
+    let mut definitions = Vec::new();
+    for (command, start, end) in control_words(input) {
+        if !DEFINE_COMMANDS.contains(&command) {
+            continue;
+        }
+        // The name is either `\name` or `{\name}`.
+        let mut i = skip_whitespace(bytes, end);
+        let braced = bytes.get(i) == Some(&b'{');
+        if braced {
+            i = skip_whitespace(bytes, i + 1);
+        }
+        let Some((name, _, name_end)) = control_words(&input[i..]).next() else {
+            continue;
+        };
+        if !input[i..].starts_with('\\') {
+            continue;
+        }
+        let mut j = i + name_end;
+        if braced {
+            j = skip_whitespace(bytes, j);
+            if bytes.get(j) != Some(&b'}') {
+                continue;
+            }
+            j += 1;
+        }
+        // Skip the parameter text, e.g. `#1#2` or `[2][default]`, up to the body.
+        while j < bytes.len() && bytes[j] != b'{' {
+            j += 1;
+        }
+        if let Some(close) = matching_brace(bytes, j) {
+            definitions.push((name, &input[j + 1..close], start..close + 1));
+        }
+    }
+    definitions
+}
+
+/// Iterate over the control words (e.g. `\frac`) of the input, as tuples of
+/// name (without the backslash), start and end byte offsets.
+pub(crate) fn control_words(input: &str) -> impl Iterator<Item = (&str, usize, usize)> {
+    let bytes = input.as_bytes();
+    let mut i = 0;
+    core::iter::from_fn(move || {
+        while i < bytes.len() {
+            if bytes[i] != b'\\' {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            let name_start = i + 1;
+            let mut name_end = name_start;
+            while name_end < bytes.len() && bytes[name_end].is_ascii_alphabetic() {
+                name_end += 1;
+            }
+            if name_end == name_start {
+                // Control symbol such as `\{`, skip the escaped character.
+                i = name_start + 1;
+                continue;
+            }
+            i = name_end;
+            return Some((&input[name_start..name_end], start, name_end));
+        }
+        None
+    })
+}
+
+pub(crate) fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// Apply [`TextUnicodePolicy`] to the arguments of text-mode commands.
+///
+/// The removed or replaced emoji are recorded in `edits`, and each emoji
+/// sequence in `emoji`. Rejected emoji are reported at their offset in the
+/// original input, through `map`.
+fn apply_text_unicode_policy<'a>(
+    input: &'a str,
+    policy: &TextUnicodePolicy,
+    map: &SourceMap,
+    edits: &mut Vec<Edit>,
+    emoji: &mut Vec<Range<usize>>,
+) -> Result<Cow<'a, str>> {
+    if *policy == TextUnicodePolicy::PassThrough || input.is_ascii() {
+        return Ok(Cow::Borrowed(input));
+    }
+
+    let mut output = String::with_capacity(input.len());
+    let mut last = 0;
+    for (start, end) in text_groups(input) {
+        output.push_str(&input[last..start]);
+        let mut in_emoji = false;
+        for (pos, c) in input[start..end].char_indices() {
+            // Joiners and selectors only count as emoji when they follow one.
+            let joiner = matches!(c, '\u{200D}' | '\u{FE0F}');
+            if !is_emoji_component(c) || (joiner && !in_emoji) {
+                in_emoji = false;
+                output.push(c);
+                continue;
+            }
+            let output_start = output.len();
+            match policy {
+                TextUnicodePolicy::PassThrough => unreachable!(),
+                TextUnicodePolicy::StripEmoji => {}
+                TextUnicodePolicy::ReplaceEmoji(placeholder) => {
+                    // Replace a whole emoji sequence (e.g. with ZWJ or modifiers) only once.
+                    if !in_emoji {
+                        output.push_str(placeholder);
+                    }
+                }
+                TextUnicodePolicy::RejectEmoji => {
+                    return Err(Error::InvalidInput(format!(
+                        "emoji U+{:04X} found inside text at byte offset {}",
+                        c as u32,
+                        map.map(start + pos, false)
+                    )));
+                }
+            }
+            let original = start + pos;
+            match emoji.last_mut() {
+                Some(sequence) if in_emoji => sequence.end = original + c.len_utf8(),
+                _ => emoji.push(original..original + c.len_utf8()),
+            }
+            in_emoji = true;
+            edits.push((
+                original..original + c.len_utf8(),
+                output_start..output.len(),
+            ));
+        }
+        last = end;
+    }
+    output.push_str(&input[last..]);
+    Ok(Cow::Owned(output))
+}
+
+/// Find byte ranges of the input outside the arguments of text-mode commands.
+fn math_ranges(input: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut last = 0;
+    for (start, end) in text_groups(input) {
+        ranges.push(last..start);
+        last = end;
+    }
+    ranges.push(last..input.len());
+    ranges
+}
+
+/// Find byte ranges of the arguments of text-mode commands, excluding the braces.
+fn text_groups(input: &str) -> Vec<(usize, usize)> {
+    let bytes = input.as_bytes();
+    let mut groups = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            i += 1;
+            continue;
+        }
+        let name_start = i + 1;
+        let mut name_end = name_start;
+        while name_end < bytes.len() && bytes[name_end].is_ascii_alphabetic() {
+            name_end += 1;
+        }
+        if name_end == name_start {
+            // Control symbol such as `\{`, skip the escaped character.
+            i = name_start + 1;
+            continue;
+        }
+        i = name_end;
+        if !TEXT_COMMANDS.contains(&&input[name_start..name_end]) {
+            continue;
+        }
+        let mut j = name_end;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        if j >= bytes.len() || bytes[j] != b'{' {
+            continue;
+        }
+        if let Some(end) = matching_brace(bytes, j) {
+            groups.push((j + 1, end));
+            i = end + 1;
+        }
+    }
+    groups
+}
+
+/// Find the position of the `}` matching the `{` at `open`.
+pub(crate) fn matching_brace(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Whether the character is an emoji or part of an emoji sequence.
+fn is_emoji_component(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, flags, skin tone modifiers, ...
+        | 0x2600..=0x27BF // miscellaneous symbols and dingbats
+        | 0x2B00..=0x2BFF // miscellaneous symbols and arrows
+        | 0xFE0F // variation selector-16
+        | 0x200D // zero width joiner
+        | 0xE0020..=0xE007F // tags
+    )
+}
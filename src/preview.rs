@@ -0,0 +1,191 @@
+//! Live previews of equations being edited.
+//!
+//! Editors re-render the equation under the cursor on every keystroke, so
+//! most inputs they render are incomplete, e.g. `\frac{a}{` on the way to
+//! `\frac{a}{b}`. A [`PreviewSession`] keeps the last good render of each
+//! equation and returns it, marked as stale, while the input does not parse,
+//! instead of blanking the preview or showing KaTeX's error markup. It also
+//! skips renders of unchanged input, and debounces renders arriving faster
+//! than the configured interval.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::preview::PreviewSession;
+//!
+//! let mut session = PreviewSession::new(katex::Opts::default());
+//! let update = session.update("eq1", r"\frac{a}{b}");
+//! assert!(!update.is_stale);
+//! let good = update.html.unwrap();
+//!
+//! // While typing `\frac{a}{b} + \sqrt{x}`.
+//! let update = session.update("eq1", r"\frac{a}{b} + \sqrt{");
+//! assert!(update.is_stale);
+//! assert_eq!(update.html, Some(good));
+//! assert!(matches!(update.error, Some(katex::Error::ParseError(_))));
+//! ```
+
+use crate::{
+    check::check_with_opts,
+    error::{Error, Result},
+    opts::Opts,
+};
+use core::time::Duration;
+use std::{collections::HashMap, time::Instant};
+
+/// Renders of the equations of an editor, identified by ids, e.g. the
+/// positions of the equations in the document.
+#[derive(Debug)]
+pub struct PreviewSession {
+    opts: Opts,
+    debounce: Duration,
+    previews: HashMap<String, Preview>,
+}
+
+/// State of the preview of one equation.
+#[derive(Debug, Default)]
+struct Preview {
+    /// Input of the last successful render, and its HTML.
+    good: Option<(String, String)>,
+    /// Input waiting for the debounce interval to elapse.
+    pending: Option<String>,
+    /// When the equation was last rendered, or checked.
+    rendered_at: Option<Instant>,
+}
+
+/// Result of updating the input of a preview.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct PreviewUpdate {
+    /// HTML to display: the render of the new input, or the last successful
+    /// render of the equation if the new input is not rendered, or `None` if
+    /// there is none.
+    pub html: Option<String>,
+    /// Whether [`html`](Self::html) is not the render of the new input,
+    /// because the input fails to render or the render is debounced.
+    pub is_stale: bool,
+    /// Why the new input fails to render, e.g. an [`Error::ParseError`] for
+    /// incomplete input.
+    pub error: Option<Error>,
+}
+
+impl PreviewSession {
+    /// Create a session rendering with the given [options](`Opts`), without
+    /// debouncing.
+    pub fn new(opts: impl AsRef<Opts>) -> Self {
+        Self {
+            opts: opts.as_ref().clone(),
+            debounce: Duration::ZERO,
+            previews: HashMap::new(),
+        }
+    }
+
+    /// Render each equation at most once per `interval`. Inputs arriving
+    /// sooner wait for [`flush`](Self::flush) or
+    /// [`flush_due`](Self::flush_due), e.g. called from a timer of the
+    /// editor.
+    ///
+    /// Intervals are measured with [`Instant`], which is not available on
+    /// `wasm32-unknown-unknown`, so keep the default of no debouncing there.
+    pub fn with_debounce(mut self, interval: Duration) -> Self {
+        self.debounce = interval;
+        self
+    }
+
+    /// Update the input of the equation `id`, rendering it unless it is
+    /// unchanged or debounced.
+    ///
+    /// The input is [checked](crate::check) before rendering, so that a
+    /// parse error keeps the last successful render even with
+    /// [`throw_on_error(false)`](crate::OptsBuilder::throw_on_error).
+    pub fn update(&mut self, id: &str, input: &str) -> PreviewUpdate {
+        let debounce = self.debounce;
+        let preview = self.previews.entry(id.to_owned()).or_default();
+        if preview.is_rendered(input) {
+            preview.pending = None;
+            return preview.current(false, None);
+        }
+        let debounced = !debounce.is_zero()
+            && preview
+                .rendered_at
+                .is_some_and(|rendered_at| rendered_at.elapsed() < debounce);
+        if debounced {
+            preview.pending = Some(input.to_owned());
+            return preview.current(true, None);
+        }
+        render(&self.opts, debounce, preview, input)
+    }
+
+    /// Render the debounced input of the equation `id` now, if any.
+    pub fn flush(&mut self, id: &str) -> Option<PreviewUpdate> {
+        let preview = self.previews.get_mut(id)?;
+        let input = preview.pending.take()?;
+        Some(render(&self.opts, self.debounce, preview, &input))
+    }
+
+    /// Render the debounced inputs whose interval has elapsed, returning
+    /// the updates by id.
+    pub fn flush_due(&mut self) -> Vec<(String, PreviewUpdate)> {
+        let debounce = self.debounce;
+        self.previews
+            .iter_mut()
+            .filter(|(_, preview)| {
+                preview.pending.is_some()
+                    && preview
+                        .rendered_at
+                        .is_none_or(|rendered_at| rendered_at.elapsed() >= debounce)
+            })
+            .filter_map(|(id, preview)| {
+                let input = preview.pending.take()?;
+                Some((id.clone(), render(&self.opts, debounce, preview, &input)))
+            })
+            .collect()
+    }
+
+    /// Whether the equation `id` has debounced input waiting to be rendered.
+    pub fn is_pending(&self, id: &str) -> bool {
+        self.previews
+            .get(id)
+            .is_some_and(|preview| preview.pending.is_some())
+    }
+
+    /// Forget the equation `id`, e.g. when it is deleted from the document.
+    pub fn remove(&mut self, id: &str) {
+        self.previews.remove(id);
+    }
+}
+
+/// Check and render the input of a preview, keeping the last successful
+/// render on failure.
+fn render(opts: &Opts, debounce: Duration, preview: &mut Preview, input: &str) -> PreviewUpdate {
+    preview.pending = None;
+    if !debounce.is_zero() {
+        preview.rendered_at = Some(Instant::now());
+    }
+    let result: Result<String> =
+        check_with_opts(input, opts).and_then(|()| crate::render_with_opts(input, opts));
+    match result {
+        Ok(html) => {
+            preview.good = Some((input.to_owned(), html));
+            preview.current(false, None)
+        }
+        Err(error) => preview.current(true, Some(error)),
+    }
+}
+
+impl Preview {
+    /// Whether the input is the one of the last successful render.
+    fn is_rendered(&self, input: &str) -> bool {
+        self.good
+            .as_ref()
+            .is_some_and(|(rendered, _)| rendered == input)
+    }
+
+    fn current(&self, is_stale: bool, error: Option<Error>) -> PreviewUpdate {
+        PreviewUpdate {
+            html: self.good.as_ref().map(|(_, html)| html.clone()),
+            is_stale,
+            error,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Render LaTeX equations to positioned text runs and rules for PDF writers.
+//!
+//! This module is only available with the `print` feature. The equation is
+//! laid out as in [`svg`](crate::svg), and the result is a flat list of
+//! [items](`Item`) which map directly to the text and rectangle operators of
+//! PDF libraries such as [printpdf](https://crates.io/crates/printpdf), so
+//! reports can embed vector math without going through HTML.
+//!
+//! Dimensions are in points, or in whatever unit the font size is given in.
+//! Following PDF conventions, the y axis points upwards, and the origin is at
+//! the left end of the baseline of the equation.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::print::{render_print, Item};
+//!
+//! let layout = render_print("x^2", katex::Opts::default(), 12.0).unwrap();
+//! for item in &layout.items {
+//!     match item {
+//!         Item::Text { text, font, size, x, y } => {
+//!             println!("{text} in {} at {size}pt, ({x}, {y})", font.file_stem());
+//!         }
+//!         Item::Rule { x, y, width, height } => {
+//!             println!("rule {width}x{height} at ({x}, {y})");
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    layout::Primitive,
+    opts::Opts,
+    svg::layout_equation,
+};
+
+/// A laid out equation.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrintLayout {
+    /// Width of the equation.
+    pub width: f64,
+    /// Height above the baseline.
+    pub height: f64,
+    /// Depth below the baseline.
+    pub depth: f64,
+    /// Items to draw, relative to the left end of the baseline.
+    pub items: Vec<Item>,
+}
+
+/// A positioned drawing item.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    /// A run of glyphs whose baseline starts at `(x, y)`.
+    Text {
+        /// Characters of the run.
+        text: String,
+        /// KaTeX font of the run.
+        font: Font,
+        /// Font size.
+        size: f64,
+        /// Horizontal position of the start of the run.
+        x: f64,
+        /// Vertical position of the baseline of the run.
+        y: f64,
+    },
+    /// A filled rectangle, e.g. a fraction bar, with its bottom left corner at
+    /// `(x, y)`.
+    Rule {
+        /// Horizontal position of the left edge.
+        x: f64,
+        /// Vertical position of the bottom edge.
+        y: f64,
+        /// Width of the rectangle.
+        width: f64,
+        /// Height of the rectangle.
+        height: f64,
+    },
+}
+
+/// KaTeX font used by a text run.
+///
+/// The fonts are not bundled; PDF writers need to load the matching files
+/// from the KaTeX distribution.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Font {
+    /// Upright text, digits and operators.
+    MainRegular,
+    /// Bold upright text.
+    MainBold,
+    /// Italic variables.
+    MathItalic,
+    /// Bold italic variables.
+    MathBoldItalic,
+}
+
+impl Font {
+    /// Return the name of the font files in the KaTeX distribution, without
+    /// extension, e.g. `KaTeX_Main-Regular`.
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            Font::MainRegular => "KaTeX_Main-Regular",
+            Font::MainBold => "KaTeX_Main-Bold",
+            Font::MathItalic => "KaTeX_Math-Italic",
+            Font::MathBoldItalic => "KaTeX_Math-BoldItalic",
+        }
+    }
+
+    fn new(italic: bool, bold: bool) -> Self {
+        match (italic, bold) {
+            (false, false) => Font::MainRegular,
+            (false, true) => Font::MainBold,
+            (true, false) => Font::MathItalic,
+            (true, true) => Font::MathBoldItalic,
+        }
+    }
+}
+
+/// Render LaTeX equation to drawing items with additional [options](`Opts`).
+///
+/// `font_size` is the size of one em. The [output type](`crate::OutputType`)
+/// is ignored.
+pub fn render_print(input: &str, opts: impl AsRef<Opts>, font_size: f64) -> Result<PrintLayout> {
+    if !(font_size.is_finite() && font_size > 0.0) {
+        return Err(Error::InvalidInput(format!(
+            "invalid font size {font_size}"
+        )));
+    }
+    let b = layout_equation(input, opts)?;
+    let items = b
+        .items
+        .into_iter()
+        .map(|item| match item {
+            Primitive::Text {
+                text,
+                x,
+                y,
+                size,
+                italic,
+                bold,
+            } => Item::Text {
+                text,
+                font: Font::new(italic, bold),
+                size: size * font_size,
+                x: x * font_size,
+                y: -y * font_size,
+            },
+            Primitive::Rule {
+                x,
+                y,
+                width,
+                height,
+            } => Item::Rule {
+                x: x * font_size,
+                y: -(y + height) * font_size,
+                width: width * font_size,
+                height: height * font_size,
+            },
+        })
+        .collect();
+    Ok(PrintLayout {
+        width: b.width * font_size,
+        height: b.ascent * font_size,
+        depth: b.descent * font_size,
+        items,
+    })
+}
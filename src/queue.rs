@@ -0,0 +1,260 @@
+//! Serialized asynchronous rendering.
+//!
+//! With the `wasm-js` backend, every render runs on the single JS thread of
+//! the page, so a long batch of equations rendered by concurrent tasks
+//! freezes it. [`render_async`] queues the renders of the thread instead: they run one at a time in the
+//! order they were requested, yielding to the event loop before each
+//! equation, so the page stays responsive between equations. Concurrent
+//! requests for the same equation and options are rendered once and share
+//! the result.
+//!
+//! With the other backends, the queue works the same, without an event loop
+//! to yield to.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::future::Future;
+//! # let waker = std::task::Waker::noop();
+//! # let mut cx = std::task::Context::from_waker(&waker);
+//! let opts = katex::Opts::default();
+//! let mut html = std::pin::pin!(katex::queue::render_async("E = mc^2", &opts));
+//! // In a browser, e.g. with `wasm_bindgen_futures::spawn_local`:
+//! // let html = katex::queue::render_async("E = mc^2", &opts).await?;
+//! # let html = loop {
+//! #     if let std::task::Poll::Ready(html) = html.as_mut().poll(&mut cx) {
+//! #         break html;
+//! #     }
+//! # };
+//! assert!(html.unwrap().contains("katex"));
+//! ```
+
+use crate::{cache::cache_key, error::Result, opts::Opts};
+use core::{
+    cell::RefCell,
+    future::{poll_fn, Future},
+    task::{Poll, Waker},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
+
+thread_local! {
+    static QUEUE: RefCell<Queue> = RefCell::new(Queue::default());
+}
+
+/// Renders waiting for their turn, and the results being rendered.
+#[derive(Default)]
+struct Queue {
+    next_ticket: u64,
+    /// Tickets in order, the first one being the current turn.
+    turns: VecDeque<(u64, Option<Waker>)>,
+    /// Results being rendered, by [cache key](crate::cache_key).
+    in_flight: HashMap<String, Rc<RefCell<Slot>>>,
+}
+
+/// Result of a render shared with the identical requests.
+#[derive(Default)]
+struct Slot {
+    result: Option<Result<String>>,
+    /// Whether the render was dropped before it finished.
+    abandoned: bool,
+    wakers: Vec<Waker>,
+}
+
+/// Render LaTeX equation to HTML with additional [options](`Opts`), after the
+/// renders queued before it.
+///
+/// Return the result of an identical render in progress, if any, unless a
+/// [`ResourceResolver`](crate::ResourceResolver) without id is set. Dropping
+/// the future leaves the queue.
+pub fn render_async(
+    input: &str,
+    opts: impl AsRef<Opts>,
+) -> impl Future<Output = Result<String>> + '_ {
+    let opts = opts.as_ref().clone();
+    async move {
+        if !opts.is_cacheable() {
+            // A resolver without id may rewrite the HTML differently.
+            let turn = Turn::new();
+            poll_fn(|cx| turn.poll(cx.waker())).await;
+            yield_now().await;
+            return crate::render_with_opts(input, &opts);
+        }
+        let key = cache_key(input, &opts);
+        loop {
+            let in_flight = QUEUE.with(|queue| queue.borrow().in_flight.get(&key).cloned());
+            match in_flight {
+                Some(slot) => {
+                    if let Some(result) = wait_for(&slot).await {
+                        return result;
+                    }
+                    // The render was dropped, so take its place.
+                }
+                None => return render_in_turn(input, &opts, key).await,
+            }
+        }
+    }
+}
+
+/// Render in the turn of a new ticket, sharing the result with identical
+/// requests.
+async fn render_in_turn(input: &str, opts: &Opts, key: String) -> Result<String> {
+    let slot = Rc::new(RefCell::new(Slot::default()));
+    QUEUE.with(|queue| {
+        queue
+            .borrow_mut()
+            .in_flight
+            .insert(key.clone(), slot.clone())
+    });
+    let _leader = Leader {
+        key,
+        slot: slot.clone(),
+    };
+    let turn = Turn::new();
+    poll_fn(|cx| turn.poll(cx.waker())).await;
+    yield_now().await;
+    let result = crate::render_with_opts(input, opts);
+    slot.borrow_mut().result = Some(result.clone());
+    result
+}
+
+/// Wait for the result of a render, or `None` if it was dropped.
+fn wait_for(slot: &Rc<RefCell<Slot>>) -> impl Future<Output = Option<Result<String>>> + '_ {
+    poll_fn(move |cx| {
+        let mut slot = slot.borrow_mut();
+        if let Some(result) = &slot.result {
+            return Poll::Ready(Some(result.clone()));
+        }
+        if slot.abandoned {
+            return Poll::Ready(None);
+        }
+        slot.wakers.push(cx.waker().clone());
+        Poll::Pending
+    })
+}
+
+/// Render sharing its result, which wakes the identical requests when done
+/// or dropped.
+struct Leader {
+    key: String,
+    slot: Rc<RefCell<Slot>>,
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        QUEUE.with(|queue| {
+            let mut queue = queue.borrow_mut();
+            if queue
+                .in_flight
+                .get(&self.key)
+                .is_some_and(|slot| Rc::ptr_eq(slot, &self.slot))
+            {
+                queue.in_flight.remove(&self.key);
+            }
+        });
+        let wakers = {
+            let mut slot = self.slot.borrow_mut();
+            slot.abandoned = slot.result.is_none();
+            core::mem::take(&mut slot.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Place in the queue, left when dropped.
+struct Turn(u64);
+
+impl Turn {
+    fn new() -> Self {
+        QUEUE.with(|queue| {
+            let mut queue = queue.borrow_mut();
+            let ticket = queue.next_ticket;
+            queue.next_ticket += 1;
+            queue.turns.push_back((ticket, None));
+            Turn(ticket)
+        })
+    }
+
+    fn poll(&self, waker: &Waker) -> Poll<()> {
+        QUEUE.with(|queue| {
+            let mut queue = queue.borrow_mut();
+            if queue
+                .turns
+                .front()
+                .is_some_and(|(ticket, _)| *ticket == self.0)
+            {
+                return Poll::Ready(());
+            }
+            if let Some((_, slot)) = queue.turns.iter_mut().find(|(ticket, _)| *ticket == self.0) {
+                *slot = Some(waker.clone());
+            }
+            Poll::Pending
+        })
+    }
+}
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        let next = QUEUE.with(|queue| {
+            let mut queue = queue.borrow_mut();
+            let was_first = queue
+                .turns
+                .front()
+                .is_some_and(|(ticket, _)| *ticket == self.0);
+            queue.turns.retain(|(ticket, _)| *ticket != self.0);
+            if was_first {
+                queue.turns.front_mut().and_then(|(_, waker)| waker.take())
+            } else {
+                None
+            }
+        });
+        if let Some(waker) = next {
+            waker.wake();
+        }
+    }
+}
+
+/// Let the event loop run before continuing.
+async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        defer_wake(cx.waker().clone());
+        Poll::Pending
+    })
+    .await
+}
+
+/// Wake the task in a new task of the event loop, after the browser had a
+/// chance to handle input and paint.
+#[cfg(all(feature = "wasm-js", target_arch = "wasm32", target_os = "unknown"))]
+fn defer_wake(waker: Waker) {
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+    let global = js_sys::global();
+    let set_timeout = js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout"))
+        .ok()
+        .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+    let Some(set_timeout) = set_timeout else {
+        return waker.wake();
+    };
+    let waker_on_error = waker.clone();
+    let callback = Closure::once_into_js(move || waker.wake());
+    if set_timeout
+        .call2(&global, &callback, &JsValue::from(0))
+        .is_err()
+    {
+        waker_on_error.wake();
+    }
+}
+
+/// Wake the task right away, as there is no event loop to yield to.
+#[cfg(not(all(feature = "wasm-js", target_arch = "wasm32", target_os = "unknown")))]
+fn defer_wake(waker: Waker) {
+    waker.wake();
+}
@@ -0,0 +1,60 @@
+//! Render LaTeX equations to PNG images.
+//!
+//! This module is only available with the `raster` feature. The equation is
+//! laid out as in [`svg`](crate::svg) and rasterized by
+//! [resvg](https://crates.io/crates/resvg), using the fonts installed on the
+//! system.
+//!
+//! # Examples
+//!
+//! ```
+//! let png = katex::raster::render_png("E = mc^2", katex::Opts::default(), 32.0).unwrap();
+//! assert!(png.starts_with(b"\x89PNG"));
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    opts::Opts,
+    svg::{layout_equation, to_svg},
+};
+use resvg::{tiny_skia, usvg};
+use std::sync::OnceLock;
+
+/// Render LaTeX equation to a PNG image with additional [options](`Opts`).
+///
+/// `scale` is the size of one em in pixels, i.e. the font size.
+pub fn render_png(input: &str, opts: impl AsRef<Opts>, scale: f32) -> Result<Vec<u8>> {
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(Error::RasterError(format!("invalid scale {scale}")));
+    }
+    // Lay out with one user unit per em, and scale while rendering.
+    let svg = to_svg(&layout_equation(input, opts)?, "");
+    let tree = usvg::Tree::from_str(&svg, usvg_options())
+        .map_err(|e| Error::RasterError(format!("{e}")))?;
+    // Scale before rounding up, as the tree is sized in ems, so that the
+    // image is never cropped.
+    let pixels = |ems: f32| (ems * scale).ceil() as u32;
+    let size =
+        tiny_skia::IntSize::from_wh(pixels(tree.size().width()), pixels(tree.size().height()))
+            .ok_or_else(|| Error::RasterError(format!("invalid image size for scale {scale}")))?;
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| Error::RasterError("failed to allocate the image".to_owned()))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| Error::RasterError(format!("{e}")))
+}
+
+/// Return the usvg options, loading the system fonts once.
+fn usvg_options() -> &'static usvg::Options<'static> {
+    static OPTIONS: OnceLock<usvg::Options<'static>> = OnceLock::new();
+    OPTIONS.get_or_init(|| {
+        let mut opts = usvg::Options::default();
+        opts.fontdb_mut().load_system_fonts();
+        opts
+    })
+}
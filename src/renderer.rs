@@ -0,0 +1,630 @@
+//! Renderers with their own JS engine or post-processors.
+
+use crate::{
+    a11y::SpeechStyle,
+    cache,
+    error::{Error, Result},
+    init_katex_with_sources,
+    js_engine::{Engine, JsEngine, JsValue},
+    opts::{Opts, OutputType},
+    output::RenderOutput,
+    postprocess::PostProcessor,
+    render_inner, take_logs, with_engine, Rendered, BUNDLED_SRC,
+};
+use core::fmt;
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+
+/// Level of a JS console message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConsoleLevel {
+    /// `console.log`
+    Log,
+    /// `console.info`
+    Info,
+    /// `console.warn`, used by KaTeX for `strict` warnings.
+    Warn,
+    /// `console.error`
+    Error,
+    /// `console.debug`
+    Debug,
+}
+
+impl ConsoleLevel {
+    pub(crate) fn from_js(level: &str) -> Self {
+        match level {
+            "info" => Self::Info,
+            "warn" => Self::Warn,
+            "error" => Self::Error,
+            "debug" => Self::Debug,
+            _ => Self::Log,
+        }
+    }
+}
+
+/// Context of a command asking to be trusted.
+///
+/// Read <https://katex.org/docs/options.html> for more information.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrustContext<'a> {
+    /// The command, e.g. `\href`.
+    pub command: &'a str,
+    /// The URL, for commands taking one.
+    pub url: Option<&'a str>,
+    /// The protocol of the URL, e.g. `https`.
+    pub protocol: Option<&'a str>,
+}
+
+/// KaTeX source code loaded by an [`EngineBuilder`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Source {
+    /// The bundled KaTeX, with the mhchem extension if the `mhchem` feature
+    /// is enabled.
+    #[default]
+    Bundled,
+    /// The given source code, e.g. the content of a `katex.min.js` from
+    /// another KaTeX release. The bundled mhchem extension is not loaded.
+    Custom(String),
+}
+
+/// Builder of a [`Renderer`] with a configurable JS environment.
+///
+/// Scripts are evaluated in this order:
+///
+/// 1. [polyfills](Self::add_polyfill), in the order they are added;
+/// 2. the [KaTeX source](Self::katex_source);
+/// 3. [scripts](Self::add_script), e.g. KaTeX contrib extensions, in the order
+///    they are added;
+/// 4. the katex-rs entry points.
+///
+/// # Examples
+///
+/// ```
+/// use katex::{EngineBuilder, Source};
+///
+/// let renderer = EngineBuilder::new()
+///     .katex_source(Source::Bundled)
+///     .add_script(r"katex.__defineMacro('\\answer', '42');")
+///     .build()
+///     .unwrap();
+/// let html = renderer.render(r"\answer").unwrap();
+/// assert!(html.contains("<mn>42</mn>"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EngineBuilder {
+    katex_source: Source,
+    polyfills: Vec<String>,
+    scripts: Vec<String>,
+    memory_limit: Option<usize>,
+    stack_size: Option<usize>,
+}
+
+impl EngineBuilder {
+    /// Create a builder loading the bundled KaTeX without extra scripts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the KaTeX source code.
+    pub fn katex_source(mut self, source: Source) -> Self {
+        self.katex_source = source;
+        self
+    }
+
+    /// Add a script evaluated before KaTeX, e.g. a polyfill of a JS feature
+    /// missing from the backend.
+    pub fn add_polyfill(mut self, script: impl Into<String>) -> Self {
+        self.polyfills.push(script.into());
+        self
+    }
+
+    /// Add a script evaluated after KaTeX, e.g. a KaTeX contrib extension
+    /// such as `copy-tex` or a custom build of `mhchem`.
+    ///
+    /// Like KaTeX, scripts are loaded as browser scripts: `module` and
+    /// `exports` are undefined, and `katex` is a global variable.
+    pub fn add_script(mut self, script: impl Into<String>) -> Self {
+        self.scripts.push(script.into());
+        self
+    }
+
+    /// Limit the heap of the JS engine to `max_bytes`, including KaTeX
+    /// itself, which takes a few megabytes.
+    ///
+    /// Renders running out of memory fail with [`Error::ResourceExhausted`](crate::Error::ResourceExhausted)
+    /// and leave the engine usable. Only the `quick-js` and `rquickjs`
+    /// backends support it: [`build`](Self::build) fails with
+    /// [`Error::JsInitError`](crate::Error::JsInitError) on others.
+    pub fn memory_limit(mut self, max_bytes: usize) -> Self {
+        self.memory_limit = Some(max_bytes);
+        self
+    }
+
+    /// Run the JS engine on a dedicated thread with a stack of `bytes`.
+    ///
+    /// KaTeX recurses on nested groups, so deeply nested formulas can
+    /// overflow the stack of the calling thread. Rendering is then sent to
+    /// the thread and the result back, which also works from threads with
+    /// a small stack, e.g. async runtime workers. The recursion limit of the
+    /// JS engine itself still applies: exceeding it fails with
+    /// [`Error::ResourceExhausted`](crate::Error::ResourceExhausted).
+    ///
+    /// Console and trust handlers cannot move to the thread, so setting them
+    /// fails with [`Error::JsInitError`](crate::Error::JsInitError).
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = Some(bytes);
+        self
+    }
+
+    /// Create the JS engine and the renderer using it.
+    pub fn build(&self) -> Result<Renderer> {
+        let (engine, katex_version) = match self.stack_size {
+            Some(stack_size) => {
+                let worker = Worker::spawn(self.clone(), stack_size)?;
+                let katex_version = worker.run(katex_version)?;
+                (EngineHost::Worker(worker), katex_version)
+            }
+            None => {
+                let engine = self.init_engine()?;
+                let katex_version = katex_version(&engine)?;
+                (EngineHost::Local(engine), katex_version)
+            }
+        };
+        Ok(Renderer {
+            engine,
+            katex_version,
+            post_processors: Vec::new(),
+        })
+    }
+
+    fn init_engine(&self) -> Result<Engine> {
+        let polyfills: Vec<&str> = self.polyfills.iter().map(String::as_str).collect();
+        let mut sources = match &self.katex_source {
+            Source::Bundled => BUNDLED_SRC.to_vec(),
+            Source::Custom(src) => vec![src.as_str()],
+        };
+        sources.extend(self.scripts.iter().map(String::as_str));
+        init_katex_with_sources(self.memory_limit, &polyfills, &sources)
+    }
+}
+
+enum EngineHost {
+    Local(Engine),
+    Worker(Worker),
+}
+
+type Job = Box<dyn FnOnce(&Engine) + Send>;
+
+/// A thread owning a JS engine and running the jobs sent to it.
+struct Worker {
+    jobs: Option<mpsc::Sender<Job>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    fn spawn(builder: EngineBuilder, stack_size: usize) -> Result<Self> {
+        let (jobs, job_rx) = mpsc::channel::<Job>();
+        let (init_tx, init_rx) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("katex-render".to_owned())
+            .stack_size(stack_size)
+            .spawn(move || {
+                let engine = match builder.init_engine() {
+                    Ok(engine) => engine,
+                    Err(e) => {
+                        let _ = init_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = init_tx.send(Ok(()));
+                for job in job_rx {
+                    job(&engine);
+                }
+            })
+            .map_err(|e| Error::JsInitError(format!("cannot spawn render thread: {e}")))?;
+        init_rx.recv().map_err(|_| worker_gone())??;
+        Ok(Self {
+            jobs: Some(jobs),
+            thread: Some(thread),
+        })
+    }
+
+    fn run<T: Send + 'static>(
+        &self,
+        job: impl FnOnce(&Engine) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let (tx, rx) = mpsc::channel();
+        self.jobs
+            .as_ref()
+            .ok_or_else(worker_gone)?
+            .send(Box::new(move |engine| {
+                let _ = tx.send(job(engine));
+            }))
+            .map_err(|_| worker_gone())?;
+        rx.recv().map_err(|_| worker_gone())?
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // Closing the channel ends the loop of the thread.
+        self.jobs.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn worker_gone() -> Error {
+    Error::JsExecError("the render thread panicked".to_owned())
+}
+
+/// A renderer owning its own JS engine.
+///
+/// Unlike [`render`](crate::render), which uses a per thread engine with the
+/// bundled KaTeX, a renderer can be loaded with a user-supplied KaTeX build
+/// or extra scripts, see [`EngineBuilder`].
+///
+/// # Examples
+///
+/// ```
+/// let renderer = katex::Renderer::new().unwrap();
+/// let html = renderer.render("E = mc^2").unwrap();
+/// ```
+pub struct Renderer {
+    engine: EngineHost,
+    katex_version: String,
+    post_processors: Vec<Box<dyn PostProcessor>>,
+}
+
+impl Renderer {
+    /// Create a renderer with the bundled KaTeX.
+    pub fn new() -> Result<Self> {
+        EngineBuilder::new().build()
+    }
+
+    /// Create a renderer with the given KaTeX source code, e.g. the content of
+    /// a `katex.min.js` from another KaTeX release.
+    ///
+    /// The bundled mhchem extension is not loaded in this case.
+    pub fn with_katex_source(katex_src: &str) -> Result<Self> {
+        EngineBuilder::new()
+            .katex_source(Source::Custom(katex_src.to_owned()))
+            .build()
+    }
+
+    /// Create a renderer with the bundled KaTeX, rendering on a dedicated
+    /// thread with a stack of `bytes`, see [`EngineBuilder::stack_size`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let renderer = katex::Renderer::with_stack_size(8 * 1024 * 1024).unwrap();
+    /// let html = renderer.render("E = mc^2").unwrap();
+    /// ```
+    pub fn with_stack_size(bytes: usize) -> Result<Self> {
+        EngineBuilder::new().stack_size(bytes).build()
+    }
+
+    /// Add a [post-processor](`PostProcessor`) rewriting the rendered HTML.
+    ///
+    /// Post-processors run in the order they are added.
+    pub fn add_post_processor(&mut self, post_processor: impl PostProcessor + 'static) {
+        self.post_processors.push(Box::new(post_processor));
+    }
+
+    /// The version of the KaTeX loaded by the renderer, as reported by
+    /// `katex.version`.
+    pub fn katex_version(&self) -> &str {
+        &self.katex_version
+    }
+
+    /// Compute the [cache key](crate::cache) of rendering `input` with this
+    /// renderer's KaTeX.
+    ///
+    /// Extra [scripts](EngineBuilder::add_script) and post-processors are not
+    /// part of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let renderer = katex::Renderer::new().unwrap();
+    /// let opts = katex::Opts::default();
+    /// assert_eq!(renderer.cache_key("x", &opts), katex::cache_key("x", &opts));
+    /// ```
+    pub fn cache_key(&self, input: &str, opts: impl AsRef<Opts>) -> String {
+        cache::cache_key_with_version(&self.katex_version, input, opts.as_ref())
+    }
+
+    /// Compute the [content hash](crate::content_hash) of `input` with this
+    /// renderer's KaTeX, see [`cache_key`](Self::cache_key).
+    pub fn content_hash(&self, input: &str, opts: impl AsRef<Opts>) -> u64 {
+        cache::content_hash_with_version(&self.katex_version, input, opts.as_ref())
+    }
+
+    /// Handle messages written to the JS console, e.g. KaTeX's warnings in
+    /// [`strict`](crate::OptsBuilder::strict) mode.
+    ///
+    /// Without a handler, messages are discarded, unless the JS environment
+    /// provides its own console.
+    pub fn set_console_handler(
+        &mut self,
+        handler: impl Fn(ConsoleLevel, &str) + 'static,
+    ) -> Result<()> {
+        self.local_engine()?.register_function(
+            "__katexRsConsole",
+            Box::new(move |args| {
+                let [level, message] = <[String; 2]>::try_from(args)
+                    .map_err(|_| "expected a level and a message".to_owned())?;
+                handler(ConsoleLevel::from_js(&level), &message);
+                Ok(String::new())
+            }),
+        )
+    }
+
+    /// Decide whether to trust commands such as `\href` with a callback.
+    ///
+    /// The handler is only used when neither [`trust`](crate::OptsBuilder::trust)
+    /// nor [`allow_commands`](crate::OptsBuilder::allow_commands) is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut renderer = katex::Renderer::new().unwrap();
+    /// renderer
+    ///     .set_trust_handler(|context| context.protocol == Some("https"))
+    ///     .unwrap();
+    /// let html = renderer.render(r"\href{https://katex.org}{x}").unwrap();
+    /// assert!(html.contains("href=\"https://katex.org\""));
+    /// ```
+    pub fn set_trust_handler(
+        &mut self,
+        handler: impl Fn(&TrustContext<'_>) -> bool + 'static,
+    ) -> Result<()> {
+        self.local_engine()?.register_function(
+            "__katexRsTrust",
+            Box::new(move |args| {
+                let [command, url, protocol] = <[String; 3]>::try_from(args)
+                    .map_err(|_| "expected a command, a url and a protocol".to_owned())?;
+                let context = TrustContext {
+                    command: &command,
+                    url: Some(url.as_str()).filter(|s| !s.is_empty()),
+                    protocol: Some(protocol.as_str()).filter(|s| !s.is_empty()),
+                };
+                Ok(handler(&context).to_string())
+            }),
+        )
+    }
+
+    /// Render LaTeX equation to HTML.
+    #[inline]
+    pub fn render(&self, input: &str) -> Result<String> {
+        self.render_with_opts(input, Opts::default())
+    }
+
+    /// Render LaTeX equation to HTML with additional [options](`Opts`).
+    pub fn render_with_opts(&self, input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+        Ok(self.render_post_processed(input, opts)?.html)
+    }
+
+    /// Render LaTeX equation to a structured [output](`RenderOutput`) with additional [options](`Opts`).
+    pub fn render_to_output(&self, input: &str, opts: impl AsRef<Opts>) -> Result<RenderOutput> {
+        let rendered = self.render_post_processed(input, opts)?;
+        let logs = match &self.engine {
+            EngineHost::Local(engine) => take_logs(engine)?,
+            EngineHost::Worker(worker) => worker.run(take_logs)?,
+        };
+        Ok(RenderOutput::new(rendered, logs))
+    }
+
+    /// Render LaTeX equation to speech with the [Speech Rule Engine](https://speechruleengine.org)
+    /// (SRE), in the given [style](`SpeechStyle`).
+    ///
+    /// SRE is not bundled: [add it as a script](EngineBuilder::add_script)
+    /// of the engine, from a build of SRE defining the `SRE` global, with
+    /// its rule sets available without network or file access. Without it,
+    /// this fails with [`Error::JsExecError`].
+    ///
+    /// SRE 4 sets up its rule sets asynchronously, which quick-js, rquickjs
+    /// and JavaScriptCore wait for. The other backends cannot, and fail
+    /// with [`Error::JsExecError`] until SRE is ready.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use katex::{a11y::SpeechStyle, EngineBuilder};
+    ///
+    /// let sre = std::fs::read_to_string("sre.js").unwrap();
+    /// let renderer = EngineBuilder::new().add_script(sre).build().unwrap();
+    /// let speech = renderer
+    ///     .render_speech(r"\frac{a}{b}", SpeechStyle::ClearSpeak)
+    ///     .unwrap();
+    /// assert_eq!(speech, "a over b");
+    /// ```
+    #[inline]
+    pub fn render_speech(&self, input: &str, style: SpeechStyle) -> Result<String> {
+        self.render_speech_with_opts(input, style, Opts::default())
+    }
+
+    /// Render LaTeX equation to speech with additional [options](`Opts`),
+    /// see [`render_speech`](Self::render_speech).
+    ///
+    /// The [output type](`OutputType`) is ignored, and post-processors do
+    /// not run.
+    pub fn render_speech_with_opts(
+        &self,
+        input: &str,
+        style: SpeechStyle,
+        opts: impl AsRef<Opts>,
+    ) -> Result<String> {
+        let mut opts = opts.as_ref().clone();
+        opts.set_output_type(OutputType::Mathml);
+        match &self.engine {
+            EngineHost::Local(engine) => speech(engine, input, style, opts),
+            EngineHost::Worker(worker) => {
+                let input = input.to_owned();
+                worker.run(move |engine| speech(engine, &input, style, opts))
+            }
+        }
+    }
+
+    fn render_post_processed(&self, input: &str, opts: impl AsRef<Opts>) -> Result<Rendered> {
+        let mut rendered = match &self.engine {
+            EngineHost::Local(engine) => render_inner(engine, input, opts)?,
+            EngineHost::Worker(worker) => {
+                let (input, opts) = (input.to_owned(), opts.as_ref().clone());
+                worker.run(move |engine| render_inner(engine, &input, opts))?
+            }
+        };
+        for post_processor in &self.post_processors {
+            rendered.html = post_processor.process(input, rendered.html)?;
+        }
+        Ok(rendered)
+    }
+
+    fn local_engine(&self) -> Result<&Engine> {
+        match &self.engine {
+            EngineHost::Local(engine) => Ok(engine),
+            EngineHost::Worker(_) => Err(Error::JsInitError(
+                "handlers are not supported on a render thread".to_owned(),
+            )),
+        }
+    }
+}
+
+/// The version of the KaTeX loaded in the engine.
+fn katex_version(engine: &Engine) -> Result<String> {
+    engine
+        .eval("typeof katex.version === 'string' ? katex.version : 'unknown'")?
+        .into_string()
+}
+
+/// Render the MathML of the input and pass it to SRE.
+fn speech(engine: &Engine, input: &str, style: SpeechStyle, opts: Opts) -> Result<String> {
+    let rendered = render_inner(engine, input, opts)?;
+    let mathml = RenderOutput::mathml_of(&rendered.html).unwrap_or_default();
+    let (domain, style) = style.sre_names();
+    // The setup is settled by the call, or by the JS engine once the call
+    // returns, e.g. by JavaScriptCore.
+    let args = [
+        engine.create_string_value(domain.into())?,
+        engine.create_string_value(style.into())?,
+    ];
+    engine.call_function("__katex_rs.setupSpeech", args.into_iter())?;
+    let args = [
+        engine.create_string_value(mathml.into())?,
+        engine.create_string_value(domain.into())?,
+        engine.create_string_value(style.into())?,
+    ];
+    engine
+        .call_function("__katex_rs.speech", args.into_iter())?
+        .into_string()
+}
+
+impl fmt::Debug for Renderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Renderer")
+            .field(
+                "render_thread",
+                &matches!(self.engine, EngineHost::Worker(_)),
+            )
+            .field("katex_version", &self.katex_version)
+            .field("post_processors", &self.post_processors.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A renderer which can be shared between threads, e.g. in an [`Arc`].
+///
+/// JS engines cannot move between threads, so it renders with the per
+/// thread engine with the bundled KaTeX, like [`render`](crate::render),
+/// whichever the JS backend. Only the post-processors and the default
+/// options are shared.
+///
+/// # Examples
+///
+/// ```
+/// use std::{sync::Arc, thread};
+///
+/// let opts = katex::Opts::builder().display_mode(true).build().unwrap();
+/// let renderer = Arc::new(katex::SharedRenderer::with_opts(opts));
+/// let handle = {
+///     let renderer = Arc::clone(&renderer);
+///     thread::spawn(move || renderer.render("E = mc^2").unwrap())
+/// };
+/// assert_eq!(handle.join().unwrap(), renderer.render("E = mc^2").unwrap());
+/// ```
+#[derive(Clone, Default)]
+pub struct SharedRenderer {
+    opts: Opts,
+    post_processors: Vec<Arc<dyn PostProcessor + Send + Sync>>,
+}
+
+impl SharedRenderer {
+    /// Create a renderer with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a renderer using `opts` for [`render`](Self::render).
+    pub fn with_opts(opts: Opts) -> Self {
+        Self {
+            opts,
+            post_processors: Vec::new(),
+        }
+    }
+
+    /// Return the options used by [`render`](Self::render).
+    pub fn opts(&self) -> &Opts {
+        &self.opts
+    }
+
+    /// Add a [post-processor](`PostProcessor`) rewriting the rendered HTML.
+    ///
+    /// Post-processors run in the order they are added.
+    pub fn add_post_processor(
+        &mut self,
+        post_processor: impl PostProcessor + Send + Sync + 'static,
+    ) {
+        self.post_processors.push(Arc::new(post_processor));
+    }
+
+    /// Render LaTeX equation to HTML with the options of the renderer.
+    #[inline]
+    pub fn render(&self, input: &str) -> Result<String> {
+        self.render_with_opts(input, &self.opts)
+    }
+
+    /// Render LaTeX equation to HTML with other [options](`Opts`).
+    pub fn render_with_opts(&self, input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+        Ok(self.render_post_processed(input, opts)?.html)
+    }
+
+    /// Render LaTeX equation to a structured [output](`RenderOutput`) with
+    /// other [options](`Opts`).
+    pub fn render_to_output(&self, input: &str, opts: impl AsRef<Opts>) -> Result<RenderOutput> {
+        let rendered = self.render_post_processed(input, opts)?;
+        let logs = with_engine(take_logs)?;
+        Ok(RenderOutput::new(rendered, logs))
+    }
+
+    fn render_post_processed(&self, input: &str, opts: impl AsRef<Opts>) -> Result<Rendered> {
+        let mut rendered = with_engine(|engine| render_inner(engine, input, opts))?;
+        for post_processor in &self.post_processors {
+            rendered.html = post_processor.process(input, rendered.html)?;
+        }
+        Ok(rendered)
+    }
+}
+
+impl fmt::Debug for SharedRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedRenderer")
+            .field("opts", &self.opts)
+            .field("post_processors", &self.post_processors.len())
+            .finish()
+    }
+}
@@ -0,0 +1,211 @@
+//! Allowlist sanitization of the rendered HTML.
+//!
+//! Only the elements, attributes and styles KaTeX produces are kept. The
+//! attributes users control through `\htmlClass`, `\htmlId`, `\htmlStyle`
+//! and `\htmlData` are removed, and URLs of `\href` and `\includegraphics`
+//! are limited to safe schemes.
+
+use crate::html::{self, Node};
+
+/// Elements produced by KaTeX.
+const ELEMENTS: &[&str] = &[
+    "span",
+    "a",
+    "img",
+    "svg",
+    "path",
+    "line",
+    "math",
+    "semantics",
+    "annotation",
+    "mrow",
+    "mi",
+    "mn",
+    "mo",
+    "ms",
+    "mtext",
+    "mspace",
+    "msup",
+    "msub",
+    "msubsup",
+    "mover",
+    "munder",
+    "munderover",
+    "mfrac",
+    "msqrt",
+    "mroot",
+    "mtable",
+    "mtr",
+    "mtd",
+    "mlabeledtr",
+    "mstyle",
+    "mpadded",
+    "mphantom",
+    "menclose",
+    "mglyph",
+];
+
+/// Attributes allowed on any of the [`ELEMENTS`].
+const ATTRIBUTES: &[&str] = &[
+    "class",
+    "style",
+    "aria-hidden",
+    "xmlns",
+    "display",
+    "encoding",
+    "mathvariant",
+    "mathcolor",
+    "mathbackground",
+    "mathsize",
+    "stretchy",
+    "fence",
+    "separator",
+    "lspace",
+    "rspace",
+    "minsize",
+    "maxsize",
+    "symmetric",
+    "movablelimits",
+    "largeop",
+    "accent",
+    "accentunder",
+    "width",
+    "height",
+    "depth",
+    "voffset",
+    "linebreak",
+    "linethickness",
+    "notation",
+    "scriptlevel",
+    "displaystyle",
+    "rowspacing",
+    "columnspacing",
+    "columnalign",
+    "rowalign",
+    "columnlines",
+    "rowlines",
+    "frame",
+    "framespacing",
+    "equalrows",
+    "equalcolumns",
+    "side",
+    "viewBox",
+    "preserveAspectRatio",
+    "d",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "stroke-width",
+    "stroke-dasharray",
+];
+
+/// Sanitize the rendered HTML.
+pub(crate) fn sanitize(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for node in html::parse(input) {
+        write_node(&mut output, &node);
+    }
+    output
+}
+
+fn write_node(output: &mut String, node: &Node) {
+    let (name, attrs, children) = match node {
+        Node::Text(text) => {
+            output.push_str(&html::escape(text));
+            return;
+        }
+        Node::Element {
+            name,
+            attrs,
+            children,
+        } => (name.as_str(), attrs, children),
+    };
+    // Unknown elements are dropped together with their content.
+    if !ELEMENTS.contains(&name) {
+        return;
+    }
+    // Spans of `\htmlClass` and friends are marked with the `enclosing` class.
+    let enclosing = node
+        .attr("class")
+        .is_some_and(|class| class.split_ascii_whitespace().any(|c| c == "enclosing"));
+    output.push('<');
+    output.push_str(name);
+    for (attr, value) in attrs {
+        let value = match (name, attr.as_str()) {
+            ("span", "class") if enclosing => Some("enclosing".to_owned()),
+            (_, "style") if enclosing => None,
+            ("a", "href") => is_safe_url(value, false).then(|| value.clone()),
+            ("img" | "mglyph", "src") => is_safe_url(value, true).then(|| value.clone()),
+            ("img" | "mglyph", "alt") => Some(value.clone()),
+            (_, "style") => sanitize_style(value),
+            (_, attr) if ATTRIBUTES.contains(&attr) => Some(value.clone()),
+            _ => None,
+        };
+        if let Some(value) = value {
+            output.push(' ');
+            output.push_str(attr);
+            output.push_str("=\"");
+            output.push_str(&html::escape(&value));
+            output.push('"');
+        }
+    }
+    if name == "img" {
+        output.push_str("/>");
+        return;
+    }
+    output.push('>');
+    for child in children {
+        write_node(output, child);
+    }
+    output.push_str("</");
+    output.push_str(name);
+    output.push('>');
+}
+
+/// Whether the URL is relative or uses a safe scheme.
+///
+/// Images may also be inline raster data.
+fn is_safe_url(url: &str, image: bool) -> bool {
+    // Browsers ignore whitespace and control characters inside the scheme.
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect();
+    let url = url.to_ascii_lowercase();
+    let Some(colon) = url.find(':') else {
+        return true;
+    };
+    if url[..colon].contains(['/', '?', '#']) {
+        return true;
+    }
+    match &url[..colon] {
+        "http" | "https" => true,
+        "mailto" => !image,
+        "data" if image => ["image/png", "image/jpeg", "image/gif", "image/webp"]
+            .iter()
+            .any(|mime| url[colon + 1..].starts_with(mime)),
+        _ => false,
+    }
+}
+
+/// Keep the style declarations which cannot load resources or run code.
+fn sanitize_style(style: &str) -> Option<String> {
+    let declarations: Vec<_> = style
+        .split(';')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .filter(|d| {
+            let lower = d.to_ascii_lowercase();
+            d.contains(':')
+                && !d.contains(['\\', '<', '>', '"', '\'', '@'])
+                && !["url(", "expression(", "image(", "image-set(", "javascript:"]
+                    .iter()
+                    .any(|f| lower.contains(f))
+        })
+        .collect();
+    if declarations.is_empty() {
+        return None;
+    }
+    Some(declarations.join(";") + ";")
+}
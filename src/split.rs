@@ -0,0 +1,239 @@
+//! Find math in mixed text.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::{split_math, DelimiterConfig, Segment};
+//!
+//! let text = r"Euler: $e^{i\pi} + 1 = 0$, costs \$5.";
+//! let segments = split_math(text, &DelimiterConfig::default());
+//! assert_eq!(segments[0], Segment::Text("Euler: "));
+//! assert_eq!(segments[1].math(), Some((r"e^{i\pi} + 1 = 0", false)));
+//! assert_eq!(katex::split::join(&segments), text);
+//! ```
+
+use crate::error::Result;
+use core::fmt;
+
+/// A pair of delimiters enclosing math.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delimiter {
+    /// Opening delimiter, e.g. `\(`.
+    pub left: String,
+    /// Closing delimiter, e.g. `\)`.
+    pub right: String,
+    /// Whether the enclosed math is rendered in display mode.
+    pub display: bool,
+}
+
+impl Delimiter {
+    /// Create a pair of delimiters.
+    pub fn new(left: impl Into<String>, right: impl Into<String>, display: bool) -> Self {
+        Self {
+            left: left.into(),
+            right: right.into(),
+            display,
+        }
+    }
+
+    /// Whether this is the `$...$` pair, which follows the Pandoc rules to
+    /// tell math from prices: the opening `$` must be followed by a
+    /// non-space character, and the closing `$` must follow a non-space
+    /// character and must not be followed by a digit. A closing `$` next to
+    /// another `$` is also skipped, as it is part of `$$`.
+    fn is_single_dollar(&self) -> bool {
+        self.left == "$" && self.right == "$"
+    }
+}
+
+/// Delimiters recognized by [`split_math`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DelimiterConfig {
+    /// Delimiters, tried in order at each position.
+    ///
+    /// Put longer delimiters first when they share a prefix, e.g. `$$` before `$`.
+    pub delimiters: Vec<Delimiter>,
+}
+
+impl DelimiterConfig {
+    /// Create a config with the given delimiters.
+    pub fn new(delimiters: Vec<Delimiter>) -> Self {
+        Self { delimiters }
+    }
+}
+
+impl Default for DelimiterConfig {
+    /// `$$...$$` and `\[...\]` for display math, `$...$` and `\(...\)` for inline math.
+    fn default() -> Self {
+        Self::new(vec![
+            Delimiter::new("$$", "$$", true),
+            Delimiter::new("\\[", "\\]", true),
+            Delimiter::new("\\(", "\\)", false),
+            Delimiter::new("$", "$", false),
+        ])
+    }
+}
+
+/// A segment of mixed text.
+///
+/// All slices borrow from the text passed to [`split_math`], so that
+/// [`join`] reproduces the text verbatim.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// Plain text, with escapes such as `\$` left as is.
+    Text(&'a str),
+    /// Math enclosed by delimiters.
+    Math {
+        /// Whether the math is rendered in display mode.
+        display: bool,
+        /// LaTeX source of the math, without the delimiters.
+        content: &'a str,
+        /// Opening delimiter.
+        left: &'a str,
+        /// Closing delimiter.
+        right: &'a str,
+    },
+}
+
+impl<'a> Segment<'a> {
+    /// Content and display mode of a math segment.
+    pub fn math(&self) -> Option<(&'a str, bool)> {
+        match *self {
+            Segment::Text(_) => None,
+            Segment::Math {
+                content, display, ..
+            } => Some((content, display)),
+        }
+    }
+}
+
+impl fmt::Display for Segment<'_> {
+    /// Write the segment as it appears in the source text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Text(text) => f.write_str(text),
+            Segment::Math {
+                content,
+                left,
+                right,
+                ..
+            } => write!(f, "{left}{content}{right}"),
+        }
+    }
+}
+
+/// Split text into plain text and math segments.
+///
+/// A backslash escapes the next character, so `\$` and `\\(` never open
+/// math. Inside math, delimiters nested in braces or escaped are skipped.
+/// Opening delimiters without a matching closing one are kept as text.
+pub fn split_math<'a>(text: &'a str, config: &DelimiterConfig) -> Vec<Segment<'a>> {
+    let bytes = text.as_bytes();
+    let mut segments = Vec::new();
+    let mut last = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let found = config.delimiters.iter().find_map(|delimiter| {
+            if !bytes[i..].starts_with(delimiter.left.as_bytes()) || delimiter.left.is_empty() {
+                return None;
+            }
+            let start = i + delimiter.left.len();
+            if delimiter.is_single_dollar()
+                && (start == text.len() || text[start..].starts_with(char::is_whitespace))
+            {
+                return None;
+            }
+            find_end(text, start, delimiter).map(|end| (delimiter, start, end))
+        });
+        match found {
+            Some((delimiter, start, end)) => {
+                if last < i {
+                    segments.push(Segment::Text(&text[last..i]));
+                }
+                let close = end + delimiter.right.len();
+                segments.push(Segment::Math {
+                    display: delimiter.display,
+                    content: &text[start..end],
+                    left: &text[i..start],
+                    right: &text[end..close],
+                });
+                i = close;
+                last = close;
+            }
+            None if bytes[i] == b'\\' => i += 2,
+            None => i += 1,
+        }
+    }
+    if last < text.len() {
+        segments.push(Segment::Text(&text[last..]));
+    }
+    segments
+}
+
+/// Find the position of the closing delimiter of math starting at `start`.
+fn find_end(text: &str, start: usize, delimiter: &Delimiter) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let right = delimiter.right.as_bytes();
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < bytes.len() {
+        if depth == 0 && bytes[i..].starts_with(right) && !right.is_empty() {
+            let valid = !delimiter.is_single_dollar()
+                || (!text[..i].ends_with(char::is_whitespace)
+                    && !text[..i].ends_with('$')
+                    && !bytes
+                        .get(i + 1)
+                        .is_some_and(|b| b.is_ascii_digit() || *b == b'$'));
+            if valid && i > start {
+                return Some(i);
+            }
+        }
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Reassemble segments into text.
+///
+/// Joining the result of [`split_math`] gives back the original text.
+pub fn join(segments: &[Segment<'_>]) -> String {
+    segments.iter().map(ToString::to_string).collect()
+}
+
+/// Replace each math span of the text, keeping the plain text as is.
+///
+/// `f` receives the content and display mode of each span, e.g. to call
+/// [`render_with_opts`](crate::render_with_opts).
+///
+/// # Examples
+///
+/// ```
+/// use katex::{split::replace_math, DelimiterConfig};
+///
+/// let text = replace_math("Let $x$ be", &DelimiterConfig::default(), |content, _| {
+///     Ok(format!("<var>{content}</var>"))
+/// })
+/// .unwrap();
+/// assert_eq!(text, "Let <var>x</var> be");
+/// ```
+pub fn replace_math<F>(text: &str, config: &DelimiterConfig, mut f: F) -> Result<String>
+where
+    F: FnMut(&str, bool) -> Result<String>,
+{
+    let mut output = String::with_capacity(text.len());
+    for segment in split_math(text, config) {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Math {
+                content, display, ..
+            } => output.push_str(&f(content, display)?),
+        }
+    }
+    Ok(output)
+}
@@ -0,0 +1,110 @@
+//! Render LaTeX equations to standalone SVG.
+//!
+//! KaTeX's MathML output is laid out crate-side using the bundled metrics of
+//! the KaTeX fonts. The resulting `<svg>` does not depend on any stylesheet,
+//! so it can be embedded where CSS is stripped, e.g. in RSS readers or behind
+//! image proxies. The text is drawn with the KaTeX fonts when installed, and
+//! with a serif fallback otherwise, whose glyphs do not match the metrics
+//! exactly.
+//!
+//! # Examples
+//!
+//! ```
+//! let svg = katex::svg::render_svg("E = mc^2", katex::Opts::default()).unwrap();
+//! assert!(svg.starts_with("<svg"));
+//! ```
+
+use crate::{
+    error::Result,
+    html,
+    layout::{self, LayoutBox, Primitive},
+    opts::{Opts, OutputType},
+};
+use core::fmt::Write;
+
+/// Padding around the equation, in ems.
+const PADDING: f64 = 0.1;
+
+/// Render LaTeX equation to a standalone SVG document with additional [options](`Opts`).
+///
+/// The [output type](`OutputType`) is ignored.
+pub fn render_svg(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    Ok(to_svg(&layout_equation(input, opts)?, "em"))
+}
+
+/// Render LaTeX equation to MathML and lay it out.
+pub(crate) fn layout_equation(input: &str, opts: impl AsRef<Opts>) -> Result<LayoutBox> {
+    let mut opts = opts.as_ref().clone();
+    opts.set_output_type(OutputType::Mathml);
+    let mathml = crate::render_with_opts(input, opts)?;
+    Ok(layout::layout(&html::parse(&mathml)))
+}
+
+/// Serialize a laid out equation to SVG, with the size of the SVG expressed
+/// in `unit` per em.
+pub(crate) fn to_svg(b: &LayoutBox, unit: &str) -> String {
+    let width = b.width + 2.0 * PADDING;
+    let height = b.ascent + b.descent + 2.0 * PADDING;
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}{unit}" height="{h}{unit}" viewBox="{x} {y} {w} {h}" font-family="KaTeX_Main, 'Times New Roman', serif">"#,
+        w = num(width),
+        h = num(height),
+        x = num(-PADDING),
+        y = num(-b.ascent - PADDING),
+    );
+    for item in &b.items {
+        match item {
+            Primitive::Text {
+                text,
+                x,
+                y,
+                size,
+                italic,
+                bold,
+            } => {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-size="{}""#,
+                    num(*x),
+                    num(*y),
+                    num(*size)
+                );
+                if *italic {
+                    svg.push_str(r#" font-style="italic""#);
+                }
+                if *bold {
+                    svg.push_str(r#" font-weight="bold""#);
+                }
+                let _ = write!(svg, ">{}</text>", html::escape(text));
+            }
+            Primitive::Rule {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+                    num(*x),
+                    num(*y),
+                    num(*width),
+                    num(*height)
+                );
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Format a number with at most 3 decimals.
+fn num(value: f64) -> String {
+    let s = format!("{value:.3}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_owned()
+    } else {
+        s.to_owned()
+    }
+}
@@ -0,0 +1,365 @@
+//! Render LaTeX equations for terminals.
+//!
+//! The equation is rendered to MathML by KaTeX and then laid out as plain
+//! text with ANSI styles: identifiers are italicized, scripts use Unicode
+//! superscript/subscript characters when possible, and fractions are stacked
+//! over multiple lines.
+//!
+//! # Examples
+//!
+//! ```
+//! let text = katex::term::render_ansi("x^2 + y_1").unwrap();
+//! assert!(text.contains('²'));
+//! assert!(text.contains('₁'));
+//! ```
+
+use crate::{
+    error::Result,
+    html::{self, Node},
+    opts::{Opts, OutputType},
+};
+
+/// ANSI escape sequence starting italic text.
+const ITALIC_START: &str = "\x1b[3m";
+/// ANSI escape sequence ending italic text.
+const ITALIC_END: &str = "\x1b[23m";
+
+/// Render LaTeX equation to ANSI-styled text.
+#[inline]
+pub fn render_ansi(input: &str) -> Result<String> {
+    render_ansi_with_opts(input, Opts::default())
+}
+
+/// Render LaTeX equation to ANSI-styled text with additional [options](`Opts`).
+///
+/// The [output type](`OutputType`) is ignored.
+pub fn render_ansi_with_opts(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    let mut opts = opts.as_ref().clone();
+    opts.set_output_type(OutputType::Mathml);
+    let mathml = crate::render_with_opts(input, opts)?;
+    let nodes = html::parse(&mathml);
+    let block = Block::row(nodes.iter().map(layout));
+    let lines: Vec<_> = block.lines.iter().map(|l| l.trim_end()).collect();
+    Ok(lines.join("\n"))
+}
+
+/// A rectangular block of text.
+#[derive(Clone, Debug)]
+struct Block {
+    /// Lines of the block, each padded to `width` visible characters.
+    lines: Vec<String>,
+    /// Number of visible characters per line.
+    width: usize,
+    /// Index of the line aligned with the surrounding baseline.
+    ///
+    /// It may be outside of `lines` for raised or lowered scripts.
+    baseline: isize,
+}
+
+impl Block {
+    fn empty() -> Self {
+        Self {
+            lines: vec![String::new()],
+            width: 0,
+            baseline: 0,
+        }
+    }
+
+    fn text(text: &str) -> Self {
+        Self {
+            lines: vec![text.to_owned()],
+            width: text.chars().count(),
+            baseline: 0,
+        }
+    }
+
+    fn styled(text: &str, start: &str, end: &str) -> Self {
+        Self {
+            lines: vec![format!("{start}{text}{end}")],
+            width: text.chars().count(),
+            baseline: 0,
+        }
+    }
+
+    fn height(&self) -> isize {
+        self.lines.len() as isize
+    }
+
+    /// Concatenate blocks horizontally, aligning their baselines.
+    fn row(blocks: impl Iterator<Item = Block>) -> Self {
+        let blocks: Vec<_> = blocks.collect();
+        if blocks.is_empty() {
+            return Self::empty();
+        }
+        let above = blocks.iter().map(|b| b.baseline).max().unwrap_or(0).max(0);
+        let below = blocks
+            .iter()
+            .map(|b| b.height() - b.baseline)
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let mut lines = vec![String::new(); (above + below) as usize];
+        let mut width = 0;
+        for block in &blocks {
+            let offset = above - block.baseline;
+            for (row, line) in lines.iter_mut().enumerate() {
+                let r = row as isize - offset;
+                if r >= 0 && r < block.height() {
+                    line.push_str(&block.lines[r as usize]);
+                } else {
+                    line.push_str(&" ".repeat(block.width));
+                }
+            }
+            width += block.width;
+        }
+        Self {
+            lines,
+            width,
+            baseline: above,
+        }
+    }
+
+    /// Stack blocks vertically and center them, using the baseline of the
+    /// block at `base`.
+    fn column(blocks: Vec<Block>, base: usize) -> Self {
+        let width = blocks.iter().map(|b| b.width).max().unwrap_or(0);
+        let mut lines = Vec::new();
+        let mut baseline = 0;
+        for (i, block) in blocks.into_iter().enumerate() {
+            if i == base {
+                baseline = lines.len() as isize + block.baseline;
+            }
+            let left = (width - block.width) / 2;
+            let right = width - block.width - left;
+            for line in block.lines {
+                lines.push(format!("{}{line}{}", " ".repeat(left), " ".repeat(right)));
+            }
+        }
+        Self {
+            lines,
+            width,
+            baseline,
+        }
+    }
+
+    /// Move the block fully above the baseline.
+    fn raised(mut self) -> Self {
+        self.baseline = self.height();
+        self
+    }
+
+    /// Move the block fully below the baseline.
+    fn lowered(mut self) -> Self {
+        self.baseline = -1;
+        self
+    }
+}
+
+/// Lay out a MathML node.
+fn layout(node: &Node) -> Block {
+    let (name, children) = match node {
+        Node::Text(text) => return Block::text(text.trim()),
+        Node::Element { name, children, .. } => (name.as_str(), children.as_slice()),
+    };
+    let child = |i: usize| children.get(i).map(layout).unwrap_or_else(Block::empty);
+    match name {
+        "annotation" | "annotation-xml" | "mphantom" => Block::empty(),
+        "mi" => {
+            let text = node.text();
+            if text.chars().count() == 1 && node.attr("mathvariant") != Some("normal") {
+                Block::styled(&text, ITALIC_START, ITALIC_END)
+            } else {
+                Block::text(&text)
+            }
+        }
+        "mn" | "mtext" | "ms" => Block::text(&node.text()),
+        "mo" => {
+            let text = node.text();
+            if [
+                "=", "+", "−", "-", "×", "÷", "<", ">", "≤", "≥", "≠", "≈", "→", "±", "∈",
+            ]
+            .contains(&text.as_str())
+            {
+                Block::text(&format!(" {text} "))
+            } else {
+                Block::text(&text)
+            }
+        }
+        "mspace" => Block::text(" "),
+        "msup" => script(child(0), children.get(1), None),
+        "msub" => script(child(0), None, children.get(1)),
+        "msubsup" => script(child(0), children.get(2), children.get(1)),
+        "mover" => Block::column(vec![child(1), child(0)], 1),
+        "munder" => Block::column(vec![child(0), child(1)], 0),
+        "munderover" => Block::column(vec![child(2), child(0), child(1)], 1),
+        "mfrac" => {
+            let (num, den) = (child(0), child(1));
+            let width = num.width.max(den.width);
+            let bar = Block::text(&"─".repeat(width));
+            Block::column(vec![num, bar, den], 1)
+        }
+        "msqrt" => sqrt(Block::row(children.iter().map(layout)), None),
+        "mroot" => sqrt(child(0), children.get(1)),
+        "mtable" => table(children),
+        _ => Block::row(children.iter().map(layout)),
+    }
+}
+
+/// Lay out a base with optional superscript and subscript.
+fn script(base: Block, sup: Option<&Node>, sub: Option<&Node>) -> Block {
+    let mut blocks = vec![base];
+    if let Some(sub) = sub {
+        blocks.push(match to_script(&sub.text(), SUBSCRIPTS) {
+            Some(text) => Block::text(&text),
+            None => layout(sub).lowered(),
+        });
+    }
+    if let Some(sup) = sup {
+        blocks.push(match to_script(&sup.text(), SUPERSCRIPTS) {
+            Some(text) => Block::text(&text),
+            None => layout(sup).raised(),
+        });
+    }
+    Block::row(blocks.into_iter())
+}
+
+/// Lay out a square root with an optional index.
+fn sqrt(radicand: Block, index: Option<&Node>) -> Block {
+    let index = index
+        .and_then(|i| to_script(&i.text(), SUPERSCRIPTS))
+        .unwrap_or_default();
+    let radicand = if radicand.width > 1 && radicand.lines.len() == 1 {
+        Block::row([Block::text("("), radicand, Block::text(")")].into_iter())
+    } else {
+        radicand
+    };
+    Block::row([Block::text(&format!("{index}√")), radicand].into_iter())
+}
+
+/// Lay out a table, one line per row if possible.
+fn table(rows: &[Node]) -> Block {
+    let cells: Vec<Vec<Block>> = rows
+        .iter()
+        .map(|row| row.children().iter().map(layout).collect())
+        .collect();
+    let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            cells
+                .iter()
+                .filter_map(|row| row.get(c))
+                .map(|b| b.width)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let rows: Vec<Block> = cells
+        .into_iter()
+        .map(|row| {
+            let mut blocks = Vec::new();
+            for (c, cell) in row.into_iter().enumerate() {
+                if c > 0 {
+                    blocks.push(Block::text("  "));
+                }
+                let pad = widths[c] - cell.width;
+                blocks.push(cell);
+                blocks.push(Block::text(&" ".repeat(pad)));
+            }
+            Block::row(blocks.into_iter())
+        })
+        .collect();
+    let base = rows.len().saturating_sub(1) / 2;
+    Block::column(rows, base)
+}
+
+/// Convert text to Unicode superscript or subscript characters, if all of
+/// them are available.
+fn to_script(text: &str, table: &[(char, char)]) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+    text.chars()
+        .map(|c| table.iter().find(|(from, _)| *from == c).map(|(_, to)| *to))
+        .collect()
+}
+
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('−', '⁻'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('a', 'ᵃ'),
+    ('b', 'ᵇ'),
+    ('c', 'ᶜ'),
+    ('d', 'ᵈ'),
+    ('e', 'ᵉ'),
+    ('f', 'ᶠ'),
+    ('g', 'ᵍ'),
+    ('h', 'ʰ'),
+    ('i', 'ⁱ'),
+    ('j', 'ʲ'),
+    ('k', 'ᵏ'),
+    ('l', 'ˡ'),
+    ('m', 'ᵐ'),
+    ('n', 'ⁿ'),
+    ('o', 'ᵒ'),
+    ('p', 'ᵖ'),
+    ('r', 'ʳ'),
+    ('s', 'ˢ'),
+    ('t', 'ᵗ'),
+    ('u', 'ᵘ'),
+    ('v', 'ᵛ'),
+    ('w', 'ʷ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('z', 'ᶻ'),
+    ('′', '′'),
+];
+
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('−', '₋'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
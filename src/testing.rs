@@ -0,0 +1,321 @@
+//! Declarative rendering tests, e.g. for documentation repositories.
+//!
+//! A test suite is a data file listing inputs, [options](`Opts`) and
+//! expectations on the rendered HTML, which is checked against the katex-rs
+//! and KaTeX versions it is built with. In TOML:
+//!
+//! ```toml
+//! [[test]]
+//! name = "fraction"
+//! input = '\frac{a}{b}'
+//! opts = { display_mode = true }
+//! contains = ['class="mfrac"']
+//! not_contains = ['katex-error']
+//!
+//! [[test]]
+//! input = '\frac{a}'
+//! error = true
+//! ```
+//!
+//! In JSON, the same tests are listed under `"test"`. Each test has:
+//!
+//! * `input`: the LaTeX source.
+//! * `name`: optional, defaults to the input.
+//! * `opts`: optional table of `display_mode`, `output` (`"html"`,
+//!   `"mathml"` or `"htmlAndMathml"`), `leqno`, `fleqn`, `throw_on_error`,
+//!   `error_color`, `trust`, `min_rule_thickness`, `max_size` and `macros`.
+//! * `contains` and `not_contains`: optional arrays of substrings of the output.
+//! * `hash`: optional [hash of the output](output_hash), to detect any change.
+//! * `error`: optional, whether rendering is expected to fail.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::testing::{parse_suite, run_suite, SuiteFormat};
+//!
+//! let suite = r#"
+//!     [[test]]
+//!     input = 'x^2'
+//!     contains = ['<mn>2</mn>']
+//! "#;
+//! let tests = parse_suite(SuiteFormat::Toml, suite).unwrap();
+//! let failures = run_suite(&tests);
+//! assert!(failures.is_empty(), "{failures:?}");
+//! ```
+//!
+//! With the `test-util` feature, the module also provides assertions for
+//! tests written in Rust. They check what stays the same across KaTeX
+//! releases: that an equation renders, the classes of the output, or its
+//! canonical form.
+//!
+//! ```
+//! # #[cfg(feature = "test-util")] {
+//! use katex::testing::{assert_has_class, assert_renders_ok};
+//!
+//! let html = assert_renders_ok!(r"\frac{a}{b}");
+//! assert_has_class!(html, "mfrac");
+//! # }
+//! ```
+
+use crate::{
+    cache::Fnv1a,
+    data::{self, Value},
+    error::{Error, Result},
+    html,
+    opts::{Opts, OutputType},
+};
+use core::fmt;
+use std::{collections::HashMap, path::Path};
+
+#[cfg(feature = "test-util")]
+mod assertions;
+#[cfg(feature = "test-util")]
+pub use self::assertions::{canonicalize, classes, has_class};
+#[cfg(feature = "test-util")]
+pub use crate::{assert_has_class, assert_lacks_class, assert_renders_err, assert_renders_ok};
+
+/// Format of a test suite file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SuiteFormat {
+    /// JSON object.
+    Json,
+    /// TOML document.
+    Toml,
+}
+
+impl SuiteFormat {
+    /// Guess the format from the extension of a path, `.json` or `.toml`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(SuiteFormat::Json),
+            "toml" => Some(SuiteFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// A rendering test.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct TestCase {
+    /// Name reported on failure.
+    pub name: String,
+    /// LaTeX source.
+    pub input: String,
+    /// Options to render with.
+    pub opts: Opts,
+    /// Substrings expected in the output.
+    pub contains: Vec<String>,
+    /// Substrings not expected in the output.
+    pub not_contains: Vec<String>,
+    /// Expected [hash of the output](output_hash).
+    pub hash: Option<String>,
+    /// Whether rendering is expected to fail.
+    pub error: bool,
+}
+
+impl TestCase {
+    /// Create a test of rendering `input` with the default options, without
+    /// expectations.
+    pub fn new(input: impl Into<String>) -> Self {
+        let input = input.into();
+        Self {
+            name: input.clone(),
+            input,
+            opts: Opts::default(),
+            contains: Vec::new(),
+            not_contains: Vec::new(),
+            hash: None,
+            error: false,
+        }
+    }
+
+    /// Render the input and check the expectations.
+    pub fn run(&self) -> core::result::Result<(), Failure> {
+        let fail = |message: String| Failure {
+            name: self.name.clone(),
+            message,
+        };
+        let html = match crate::render_with_opts(&self.input, &self.opts) {
+            Ok(_) if self.error => return Err(fail("expected an error".to_owned())),
+            Ok(html) => html,
+            Err(_) if self.error => return Ok(()),
+            Err(e) => return Err(fail(format!("unexpected error: {e}"))),
+        };
+        if let Some(missing) = self.contains.iter().find(|s| !html.contains(s.as_str())) {
+            return Err(fail(format!("output does not contain {missing:?}")));
+        }
+        if let Some(found) = self.not_contains.iter().find(|s| html.contains(s.as_str())) {
+            return Err(fail(format!("output contains {found:?}")));
+        }
+        if let Some(expected) = &self.hash {
+            let actual = output_hash(&html);
+            if actual != *expected {
+                return Err(fail(format!(
+                    "output hash is {actual}, expected {expected}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A failed test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// Name of the test.
+    pub name: String,
+    /// What went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+
+/// Hash the rendered HTML, as 16 lowercase hex digits.
+///
+/// The attributes are sorted first, so that the hash does not depend on the
+/// JS backend. It does depend on the KaTeX version.
+pub fn output_hash(html: &str) -> String {
+    let mut hash = Fnv1a::new();
+    hash.write(html::sort_attributes(html).as_bytes());
+    format!("{:016x}", hash.finish())
+}
+
+/// Run the tests and return the failures.
+pub fn run_suite(tests: &[TestCase]) -> Vec<Failure> {
+    tests.iter().filter_map(|test| test.run().err()).collect()
+}
+
+/// Parse a test suite.
+pub fn parse_suite(format: SuiteFormat, input: &str) -> Result<Vec<TestCase>> {
+    let fields = match format {
+        SuiteFormat::Json => data::parse_json(input),
+        SuiteFormat::Toml => data::parse_toml(input),
+    }
+    .map_err(|e| invalid(e.to_string()))?;
+    let mut tests = Vec::new();
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("test", Value::Array(values)) => {
+                for (i, value) in values.into_iter().enumerate() {
+                    let test =
+                        to_test_case(value).map_err(|e| invalid(format!("test {}: {e}", i + 1)))?;
+                    tests.push(test);
+                }
+            }
+            (key, value) => {
+                return Err(invalid(format!(
+                    "unexpected field {key:?} with {}",
+                    value.kind()
+                )))
+            }
+        }
+    }
+    Ok(tests)
+}
+
+/// Load a test suite from a file, whose format is guessed from its extension
+/// with [`SuiteFormat::from_path`].
+pub fn load_suite(path: impl AsRef<Path>) -> Result<Vec<TestCase>> {
+    let path = path.as_ref();
+    let format = SuiteFormat::from_path(path)
+        .ok_or_else(|| invalid(format!("unknown format of {}", path.display())))?;
+    let input = std::fs::read_to_string(path)
+        .map_err(|e| invalid(format!("cannot read {}: {e}", path.display())))?;
+    parse_suite(format, &input)
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidInput(format!("invalid test suite: {reason}"))
+}
+
+fn to_test_case(value: Value) -> core::result::Result<TestCase, String> {
+    let Value::Table(fields) = value else {
+        return Err(format!("expected a table, got {}", value.kind()));
+    };
+    let mut test = TestCase::new("");
+    let mut name = None;
+    let mut input = None;
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("name", Value::String(s)) => name = Some(s),
+            ("input", Value::String(s)) => input = Some(s),
+            ("opts", Value::Table(fields)) => test.opts = to_opts(fields)?,
+            ("contains", Value::Array(values)) => test.contains = to_strings(&key, values)?,
+            ("not_contains", Value::Array(values)) => test.not_contains = to_strings(&key, values)?,
+            ("hash", Value::String(s)) => test.hash = Some(s),
+            ("error", Value::Bool(b)) => test.error = b,
+            (key, value) => return Err(format!("unexpected field {key:?} with {}", value.kind())),
+        }
+    }
+    test.input = input.ok_or("missing input")?;
+    test.name = name.unwrap_or_else(|| test.input.clone());
+    Ok(test)
+}
+
+fn to_strings(key: &str, values: Vec<Value>) -> core::result::Result<Vec<String>, String> {
+    values
+        .into_iter()
+        .map(|value| match value {
+            Value::String(s) => Ok(s),
+            value => Err(format!("expected strings in {key:?}, got {}", value.kind())),
+        })
+        .collect()
+}
+
+fn to_opts(fields: Vec<(String, Value)>) -> core::result::Result<Opts, String> {
+    let mut builder = Opts::builder();
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("display_mode", Value::Bool(b)) => {
+                builder.display_mode(b);
+            }
+            ("output", Value::String(s)) => {
+                builder.output_type(match s.as_str() {
+                    "html" => OutputType::Html,
+                    "mathml" => OutputType::Mathml,
+                    "htmlAndMathml" => OutputType::HtmlAndMathml,
+                    _ => OutputType::Custom(s),
+                });
+            }
+            ("leqno", Value::Bool(b)) => {
+                builder.leqno(b);
+            }
+            ("fleqn", Value::Bool(b)) => {
+                builder.fleqn(b);
+            }
+            ("throw_on_error", Value::Bool(b)) => {
+                builder.throw_on_error(b);
+            }
+            ("error_color", Value::String(s)) => {
+                builder.error_color(s);
+            }
+            ("trust", Value::Bool(b)) => {
+                builder.trust(b);
+            }
+            ("min_rule_thickness", Value::Float(value)) => {
+                builder.min_rule_thickness(value);
+            }
+            ("max_size", Value::Float(value)) => {
+                builder.max_size(value);
+            }
+            ("macros", Value::Table(macros)) => {
+                let macros: HashMap<_, _> = macros
+                    .into_iter()
+                    .map(|(name, value)| match value {
+                        Value::String(body) => Ok((name, body)),
+                        value => Err(format!("expected a body for {name}, got {}", value.kind())),
+                    })
+                    .collect::<core::result::Result<_, _>>()?;
+                builder.macros(macros);
+            }
+            (key, value) => return Err(format!("unexpected option {key:?} with {}", value.kind())),
+        }
+    }
+    builder.build().map_err(|e| e.to_string())
+}
@@ -0,0 +1,112 @@
+//! Assertions for the tests of downstream crates, behind the `test-util`
+//! feature.
+
+use crate::html;
+use std::collections::BTreeSet;
+
+/// Attributes whose values depend on the KaTeX release rather than on the
+/// equation.
+const VERSION_SPECIFIC_ATTRIBUTES: &[&str] = &[
+    "style",
+    "d",
+    "viewBox",
+    "width",
+    "height",
+    "preserveAspectRatio",
+];
+
+/// Return a form of the rendered HTML which is stable across KaTeX releases
+/// and JS backends, unlike its [hash](super::output_hash).
+///
+/// The inline styles and the SVG geometry are removed, and the remaining
+/// attributes are sorted by name.
+///
+/// # Examples
+///
+/// ```
+/// let html = katex::render("x^2").unwrap();
+/// let canonical = katex::testing::canonicalize(&html);
+/// assert!(!canonical.contains("style="));
+/// assert!(canonical.contains(r#"<span class="katex">"#));
+/// ```
+pub fn canonicalize(html: &str) -> String {
+    html::rewrite_attributes(html, |attrs| {
+        attrs.retain(|(name, _)| !VERSION_SPECIFIC_ATTRIBUTES.contains(name));
+    })
+}
+
+/// Return the set of classes used by the rendered HTML.
+pub fn classes(html: &str) -> BTreeSet<String> {
+    html::attribute_values(html, "class")
+        .into_iter()
+        .flat_map(|(start, end)| html[start..end].split_ascii_whitespace())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Whether the rendered HTML uses the class.
+pub fn has_class(html: &str, class: &str) -> bool {
+    html::attribute_values(html, "class")
+        .into_iter()
+        .any(|(start, end)| {
+            html[start..end]
+                .split_ascii_whitespace()
+                .any(|c| c == class)
+        })
+}
+
+/// Render an equation, with [options](crate::Opts) if given, and return the
+/// HTML, or panic with the input and the error.
+#[macro_export]
+macro_rules! assert_renders_ok {
+    ($input:expr $(,)?) => {
+        $crate::assert_renders_ok!($input, $crate::Opts::default())
+    };
+    ($input:expr, $opts:expr $(,)?) => {{
+        let input: &str = &$input;
+        match $crate::render_with_opts(input, $opts) {
+            Ok(html) => html,
+            Err(e) => panic!("failed to render {input:?}: {e}"),
+        }
+    }};
+}
+
+/// Render an equation, with [options](crate::Opts) if given, and return the
+/// [error](crate::Error), or panic with the input and the HTML.
+#[macro_export]
+macro_rules! assert_renders_err {
+    ($input:expr $(,)?) => {
+        $crate::assert_renders_err!($input, $crate::Opts::default())
+    };
+    ($input:expr, $opts:expr $(,)?) => {{
+        let input: &str = &$input;
+        match $crate::render_with_opts(input, $opts) {
+            Ok(html) => panic!("rendered {input:?} without error: {html}"),
+            Err(e) => e,
+        }
+    }};
+}
+
+/// Assert that the rendered HTML uses the class.
+#[macro_export]
+macro_rules! assert_has_class {
+    ($html:expr, $class:expr $(,)?) => {{
+        let (html, class): (&str, &str) = (&$html, &$class);
+        assert!(
+            $crate::testing::has_class(html, class),
+            "class {class:?} not found in {html}"
+        );
+    }};
+}
+
+/// Assert that the rendered HTML does not use the class.
+#[macro_export]
+macro_rules! assert_lacks_class {
+    ($html:expr, $class:expr $(,)?) => {{
+        let (html, class): (&str, &str) = (&$html, &$class);
+        assert!(
+            !$crate::testing::has_class(html, class),
+            "class {class:?} found in {html}"
+        );
+    }};
+}
@@ -16,6 +16,7 @@ fn test_render() {
     assert!(!html.contains(r#"span class="katex-error""#));
 }
 
+#[cfg(feature = "mhchem")]
 #[test]
 fn test_render_mhchem() {
     let html = render(r#"\ce{CO2 + C -> 2 CO}"#).unwrap();
@@ -26,6 +27,32 @@ fn test_render_mhchem() {
     assert!(!html.contains(r#"span class="katex-error""#));
 }
 
+#[cfg(feature = "mhchem")]
+#[test]
+fn test_chem() {
+    assert_eq!(
+        chem::render_ce("CO2 + C -> 2 CO").unwrap(),
+        render(r"\ce{CO2 + C -> 2 CO}").unwrap()
+    );
+    assert_eq!(
+        chem::render_pu("123 kJ/mol").unwrap(),
+        render(r"\pu{123 kJ/mol}").unwrap()
+    );
+    // `%` would comment out the closing brace.
+    assert_eq!(
+        chem::render_ce("50% H2O").unwrap(),
+        render(r"\ce{50\% H2O}").unwrap()
+    );
+    assert_eq!(
+        chem::render_ce(r"50\% H2O").unwrap(),
+        chem::render_ce("50% H2O").unwrap()
+    );
+    assert!(matches!(
+        chem::render_ce("H2O}"),
+        Err(Error::InvalidInput(msg)) if msg.contains("ce: unbalanced")
+    ));
+}
+
 #[test]
 fn test_passing_opts_by_reference_and_value() {
     let opts = Opts::builder().display_mode(true).build().unwrap();
@@ -63,6 +90,30 @@ fn test_output_mathml_only() {
     assert!(!html.contains(r#"span class="katex-html""#));
 }
 
+#[test]
+fn test_output_custom() {
+    let opts = Opts::builder()
+        .output_type(OutputType::Custom("mathml".to_owned()))
+        .build()
+        .unwrap();
+    let html = render_with_opts("a = b + c", opts).unwrap();
+    assert!(html.contains(r#"MathML"#));
+    assert!(!html.contains(r#"span class="katex-html""#));
+
+    assert!(Opts::builder()
+        .output_type(OutputType::Custom("foo".to_owned()))
+        .build()
+        .is_err());
+    assert!(Opts::builder()
+        .output_type(OutputType::Unchecked("foo".to_owned()))
+        .build()
+        .is_ok());
+    assert!(Opts::builder()
+        .output_type(OutputType::Unchecked("html and mathml".to_owned()))
+        .build()
+        .is_err());
+}
+
 #[test]
 fn test_leqno() {
     let opts = Opts::builder()
@@ -119,6 +170,44 @@ fn test_macros() {
     assert!(html.contains("mathbb"));
 }
 
+#[test]
+fn test_color_palette() {
+    let colors = |entries: &[(&str, &str)]| -> std::collections::HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(name, color)| (name.to_string(), color.to_string()))
+            .collect()
+    };
+    let opts = Opts::builder()
+        .colors(colors(&[("accent", "#ff6600"), ("muted", "gray")]))
+        .build()
+        .unwrap();
+    let html = render_with_opts(r"\accent{x} + \muted{y}", &opts).unwrap();
+    assert!(html.contains("color:#ff6600"), "{html}");
+    assert!(html.contains("color:gray"), "{html}");
+    assert_eq!(
+        opts.fingerprint(),
+        r##"{"colors":{"accent":"#ff6600","muted":"gray"}}"##
+    );
+    assert!(opts
+        .to_katex_json()
+        .contains(r#""\\accent": "\\textcolor{##ff6600}{#1}""#));
+
+    for (entries, reason) in [
+        (colors(&[("accent", "#ff66")]), "invalid color"),
+        (colors(&[("accent", "rgb(0,0,0)")]), "invalid color"),
+        (colors(&[(r"\accent", "red")]), "invalid color name"),
+        (colors(&[("RR", "red")]), "conflicts"),
+    ] {
+        let err = Opts::builder()
+            .add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned())
+            .colors(entries)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains(reason), "{err}");
+    }
+}
+
 #[test]
 fn test_trust() {
     let opts = Opts::builder().error_color("#ff0000").build().unwrap();
@@ -137,29 +226,2925 @@ fn test_trust() {
 }
 
 #[test]
-fn test_stack_overflow() {
-    #[inline(never)]
-    fn simulate_deep_stack(i: i32) {
-        if i > 0 {
-            simulate_deep_stack(i - 1);
-        } else {
-            let html = render("a = b + c").unwrap();
-            assert!(html.contains(r#"span class="katex""#));
+fn test_text_unicode_policy() {
+    use crate::renderer::ConsoleLevel;
+
+    let input = r#"\text{ok 👍🏽 done} + \alpha"#;
+
+    let html = render(input).unwrap();
+    assert!(html.contains("👍"));
+
+    let opts = Opts::builder()
+        .text_unicode_policy(TextUnicodePolicy::StripEmoji)
+        .build()
+        .unwrap();
+    let output = render_to_output(input, opts).unwrap();
+    assert!(!output.html.contains("👍"));
+    assert!(!output.html.contains("🏽"));
+    assert_eq!(output.logs.len(), 1);
+    assert_eq!(output.logs[0].level, ConsoleLevel::Warn);
+    assert_eq!(
+        output.logs[0].message,
+        r#"emoji "👍🏽" inside text at byte offset 9 stripped"#
+    );
+
+    let opts = Opts::builder()
+        .text_unicode_policy(TextUnicodePolicy::ReplaceEmoji("[emoji]".to_owned()))
+        .force_display_style(true)
+        .build()
+        .unwrap();
+    let output = render_to_output(r#"\text{👍 and 😀}"#, opts).unwrap();
+    assert!(output.html.contains("[emoji] and [emoji]"));
+    let messages: Vec<_> = output.logs.iter().map(|log| &log.message[..]).collect();
+    assert_eq!(
+        messages,
+        [
+            r#"emoji "👍" inside text at byte offset 6 replaced with "[emoji]""#,
+            r#"emoji "😀" inside text at byte offset 15 replaced with "[emoji]""#,
+        ]
+    );
+
+    let opts = Opts::builder()
+        .text_unicode_policy(TextUnicodePolicy::RejectEmoji)
+        .build()
+        .unwrap();
+    match render_with_opts(input, opts) {
+        Err(Error::InvalidInput(msg)) => assert!(msg.contains("U+1F44D")),
+        _ => unreachable!(),
+    }
+    let opts = Opts::builder()
+        .text_unicode_policy(TextUnicodePolicy::RejectEmoji)
+        .force_display_style(true)
+        .build()
+        .unwrap();
+    match render_with_opts("\\text{\u{1F600}}", opts) {
+        Err(Error::InvalidInput(msg)) => assert!(msg.ends_with("at byte offset 6"), "{msg}"),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_document() {
+    let html = document::Builder::new()
+        .title("A & B")
+        .opts(
+            Opts::builder()
+                .add_macro(r#"\RR"#.to_owned(), r#"\mathbb{R}"#.to_owned())
+                .build()
+                .unwrap(),
+        )
+        .paragraph("x < y")
+        .numbered_equation(r#"x \in \RR"#)
+        .equation("a = b")
+        .numbered_equation("c = d")
+        .build()
+        .unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>A &amp; B</title>"));
+    assert!(html.contains("<p>x &lt; y</p>"));
+    assert!(html.contains("mathbb"));
+    assert!(html.contains("(1)"));
+    assert!(html.contains("(2)"));
+    assert!(!html.contains("(3)"));
+    assert!(html.contains(KATEX_VERSION));
+}
+
+#[test]
+fn test_renderer_with_katex_source() {
+    let renderer = Renderer::with_katex_source(KATEX_SRC).unwrap();
+    let html = renderer.render("a = b + c").unwrap();
+    assert_eq!(html, render("a = b + c").unwrap());
+    assert!(renderer.render(r#"\ce{CO2}"#).is_err());
+    assert_eq!(renderer.katex_version(), KATEX_VERSION);
+    assert_eq!(
+        renderer.cache_key("x", Opts::default()),
+        cache_key("x", Opts::default())
+    );
+
+    // Keys change with the version of the KaTeX source.
+    let renderer = EngineBuilder::new()
+        .katex_source(Source::Custom(KATEX_SRC.to_owned()))
+        .add_script("katex.version = '0.99.0';")
+        .build()
+        .unwrap();
+    assert_eq!(renderer.katex_version(), "0.99.0");
+    assert!(renderer
+        .cache_key("x", Opts::default())
+        .starts_with("v1-0.99.0-"));
+    assert_ne!(
+        renderer.content_hash("x", Opts::default()),
+        content_hash("x", Opts::default())
+    );
+
+    assert!(Renderer::with_katex_source("throw new Error('broken');").is_err());
+}
+
+#[test]
+fn test_engine_builder() {
+    // Polyfills run before KaTeX and scripts after it, in the order they are added.
+    let renderer = EngineBuilder::new()
+        .add_polyfill("var answerBody = '4';")
+        .add_script("answerBody += '2';")
+        .add_script(r"katex.__defineMacro('\\answer', answerBody);")
+        .build()
+        .unwrap();
+    assert!(renderer.render(r"\answer").unwrap().contains("<mn>42</mn>"));
+    #[cfg(feature = "mhchem")]
+    {
+        assert!(renderer.render(r"\ce{CO2}").is_ok());
+
+        let renderer = EngineBuilder::new()
+            .katex_source(Source::Custom(KATEX_SRC.to_owned()))
+            .add_script(MHCHEM_SRC)
+            .build()
+            .unwrap();
+        assert!(renderer.render(r"\ce{CO2}").is_ok());
+    }
+
+    assert!(EngineBuilder::new()
+        .add_script("katex.missing();")
+        .build()
+        .is_err());
+}
+
+#[test]
+fn test_render_speech() {
+    use a11y::SpeechStyle;
+
+    // A stand-in for the Speech Rule Engine, echoing its setup and input. Like
+    // SRE 4, it loads the rules of the setup asynchronously.
+    let renderer = EngineBuilder::new()
+        .add_script(
+            r"var SRE = {
+                setupEngine: function (options) {
+                    var sre = this;
+                    sre.options = null;
+                    sre.loading = Promise.resolve().then(function () {
+                        sre.options = options;
+                    });
+                    return sre.loading.then(function () { return 'ok'; });
+                },
+                engineReady: function () { return this.loading; },
+                toSpeech: function (mathml) {
+                    var o = this.options;
+                    return [o.domain, o.style, o.modality, mathml].join(' ');
+                },
+            };",
+        )
+        .build()
+        .unwrap();
+    let speech = renderer
+        .render_speech("x", SpeechStyle::MathSpeakBrief)
+        .unwrap();
+    assert!(
+        speech.starts_with("mathspeak brief speech <math"),
+        "{speech}"
+    );
+    assert!(speech.ends_with("</math>"), "{speech}");
+    assert!(!speech.contains("katex"), "{speech}");
+
+    let opts = Opts::builder()
+        .add_macro(r"\answer".to_owned(), "42".to_owned())
+        .output_type(OutputType::Html)
+        .build()
+        .unwrap();
+    let speech = renderer
+        .render_speech_with_opts(r"\answer", SpeechStyle::ClearSpeak, &opts)
+        .unwrap();
+    assert!(speech.starts_with("clearspeak default"), "{speech}");
+    assert!(speech.contains("<mn>42</mn>"), "{speech}");
+
+    let err = Renderer::new()
+        .unwrap()
+        .render_speech("x", SpeechStyle::default())
+        .unwrap_err();
+    assert!(err.to_string().contains("Speech Rule Engine"), "{err}");
+}
+
+#[test]
+fn test_raw_option() {
+    let opts = Opts::builder()
+        .raw_option("maxExpand", 1)
+        .max_expand(Some(1000))
+        .build()
+        .unwrap();
+    assert!(render_with_opts(r"\def\a{b}\a\a", &opts).is_err());
+    assert!(opts.to_katex_json().contains(r#""maxExpand": 1"#));
+    assert!(!opts.to_katex_json().contains("1000"));
+
+    let mut opts = Opts::default();
+    opts.set_raw_option(
+        "macros",
+        JsonValue::Object(vec![("\\RR".to_owned(), r"\mathbb{R}".into())]),
+    );
+    let html = render_with_opts(r"\RR", &opts).unwrap();
+    assert!(html.contains("mathbb"));
+    assert_ne!(cache_key("x", &opts), cache_key("x", Opts::default()));
+    opts.set_raw_option("strict", JsonValue::Null);
+    opts.set_raw_option("colorIsTextColor", true);
+    assert!(render_with_opts("x", &opts).is_ok());
+    assert_eq!(
+        JsonValue::from(vec![1, 2]),
+        JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)])
+    );
+
+    assert!(matches!(
+        Opts::builder().raw_option("bad key", true).build(),
+        Err(e) if e.to_string().contains("bad key")
+    ));
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_raw_option_serde_json() {
+    let opts = Opts::builder()
+        .raw_option("minRuleThickness", serde_json::json!(0.1))
+        .raw_option("macros", serde_json::json!({ "\\x": "y" }))
+        .build()
+        .unwrap();
+    let html = render_with_opts(r"\x", &opts).unwrap();
+    assert!(html.contains("<mi>y</mi>"));
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn test_math_macro() {
+    const EQUATION: &str = math!("E = mc^2");
+    assert_eq!(EQUATION, render("E = mc^2").unwrap());
+    let display = math!(display, r"\sum_{i=1}^n x_i",);
+    let opts = Opts::builder().display_mode(true).build().unwrap();
+    assert_eq!(
+        display,
+        render_with_opts(r"\sum_{i=1}^n x_i", opts).unwrap()
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_render_to_json() {
+    let json = render_to_json("x^2", Opts::default()).unwrap();
+    assert!(json["html"].as_str().unwrap().contains("katex-html"));
+    let mathml = json["mathml"].as_str().unwrap();
+    assert!(mathml.starts_with("<math") && mathml.ends_with("</math>"));
+    assert_eq!(json["errors"], serde_json::json!([]));
+    assert!(json["metrics"]["height_em"].as_f64().unwrap() > 0.0);
+
+    let opts = Opts::builder()
+        .strict(StrictMode::Warn)
+        .output_type(OutputType::Html)
+        .build()
+        .unwrap();
+    let json = render_to_json("é", &opts).unwrap();
+    assert!(json["mathml"].is_null());
+    assert!(json["warnings"][0]
+        .as_str()
+        .unwrap()
+        .contains("unicodeTextInMathMode"));
+
+    let opts = Opts::builder().throw_on_error(false).build().unwrap();
+    let json = render_to_json(r"\frac{1}{", &opts).unwrap();
+    assert!(json["html"].as_str().unwrap().contains("katex-error"));
+    assert_eq!(json["errors"][0]["code"], "KTX2003");
+}
+
+#[test]
+fn test_environments() {
+    let html = render_align(&["a &= b + c", r"&= d \\"]).unwrap();
+    let expected = render_with_opts(
+        "\\begin{aligned}\na &= b + c \\\\\n&= d\n\\end{aligned}",
+        Opts::builder().display_mode(true).build().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(html, expected);
+    let inline = Opts::builder().display_mode(false).build().unwrap();
+    let html = render_align_with_opts(&["a &= b"], inline).unwrap();
+    assert!(!html.contains("katex-display"));
+
+    let html = render_cases(&[("1", r"x \ge 0"), ("0", r"\text{otherwise}")]).unwrap();
+    assert!(html.contains("otherwise"));
+    assert!(html.contains("katex-display"));
+
+    let err = render_align(&["a &= b", r"\frac{1}{2"]).unwrap_err();
+    assert!(err.to_string().contains("row 2: unbalanced `{`"), "{err}");
+    assert!(render_align(&[r"a } \begin{matrix}"]).is_err());
+    assert!(render_cases(&[("a & b", "c")]).is_err());
+    assert!(render_cases(&[(r"a \& b", "c")]).is_ok());
+}
+
+#[test]
+fn test_render_numbered() {
+    let html = render_numbered("E = mc^2 % comment", "1.2").unwrap();
+    assert!(html.contains("(1.2)"));
+    assert!(html.contains(r#"class="tag""#));
+    assert!(!html.contains("leqno"));
+
+    let html = render_numbered(r"x", r"a_{1}\ & 50% ~^ #$").unwrap();
+    assert!(html.contains(r"<mtext>a_{1}\</mtext>"), "{html}");
+    // Spaces in text are rendered as no-break spaces.
+    assert!(html.contains("&amp;\u{a0}50%\u{a0}~</mtext>"));
+    assert!(html.contains("<mtext>^</mtext>"));
+    assert!(html.contains("#$</mtext>"));
+
+    let opts = Opts::builder().leqno(true).fleqn(true).build().unwrap();
+    let html = render_numbered_with_opts("x", "1", &opts).unwrap();
+    assert!(
+        html.contains(r#"class="katex-display leqno fleqn""#),
+        "{html}"
+    );
+    assert!(html.contains("(1)"));
+
+    let inline = Opts::builder().display_mode(false).build().unwrap();
+    assert!(render_numbered_with_opts("x", "1", inline).is_err());
+    let err = render_numbered(r"\frac{1}{2", "1").unwrap_err();
+    assert!(err.to_string().contains("unbalanced"), "{err}");
+}
+
+#[test]
+fn test_check() {
+    assert!(check(r"\sum_{i=1}^n i").is_ok());
+
+    let Err(Error::ParseError(err)) = check(r"é + \frac{1}") else {
+        panic!("expected a parse error");
+    };
+    assert_eq!(
+        err.message,
+        "Unexpected end of input in a macro argument, expected '}'"
+    );
+    assert_eq!(err.span, Some(13..13));
+
+    // Spans are in bytes, while KaTeX counts UTF-16 code units.
+    let Err(Error::ParseError(err)) = check(r"é😀 + \foo + 1") else {
+        panic!("expected a parse error");
+    };
+    assert_eq!(err.message, r"Undefined control sequence: \foo");
+    // KaTeX's token includes the space after the control word.
+    assert_eq!(err.span, Some(9..14));
+    assert_eq!(err.char_span, Some(5..10));
+
+    // Spans are relative to the input before preprocessing.
+    let input = r"\text{😀😀} &lt; \foo";
+    let opts = Opts::builder()
+        .text_unicode_policy(TextUnicodePolicy::StripEmoji)
+        .escaped_input_policy(EscapedInputPolicy::Unescape)
+        .build()
+        .unwrap();
+    let Err(Error::ParseError(err)) = check_with_opts(input, opts) else {
+        panic!("expected a parse error");
+    };
+    let span = err.span.unwrap();
+    assert_eq!(&input[span], r"\foo");
+    assert_eq!(err.char_span, Some(15..19));
+    // The span ends before a replacement following the error.
+    let opts = Opts::builder()
+        .escaped_input_policy(EscapedInputPolicy::Unescape)
+        .build()
+        .unwrap();
+    let Err(Error::ParseError(err)) = check_with_opts(r"\foo&amp;", opts) else {
+        panic!("expected a parse error");
+    };
+    assert_eq!(err.span, Some(0..4));
+
+    let opts = Opts::builder()
+        .add_macro(r"\foo".to_owned(), "x".to_owned())
+        .build()
+        .unwrap();
+    assert!(check(r"\foo").is_err());
+    assert!(check_with_opts(r"\foo", opts).is_ok());
+}
+
+#[test]
+fn test_error_codes() {
+    assert_eq!(check(r"\frac{1}").unwrap_err().code(), "KTX2003");
+    assert_eq!(
+        Error::JsExecError("ParseError".to_owned()).code(),
+        "KTX1002"
+    );
+    let opts = Opts::builder()
+        .add_macro(r"\a".to_owned(), r"\b".to_owned())
+        .add_macro(r"\b".to_owned(), r"\a".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(render_with_opts(r"\a", opts).unwrap_err().code(), "KTX2004");
+}
+
+#[test]
+fn test_render_to_writer() {
+    let expected = render("a^2").unwrap();
+    let mut html = String::new();
+    render_to_writer("a^2", Opts::default(), &mut html).unwrap();
+    assert_eq!(html, expected);
+
+    let mut bytes = Vec::new();
+    render_to_io_writer("a^2", Opts::default(), &mut bytes).unwrap();
+    assert_eq!(bytes, expected.as_bytes());
+
+    let mut html = String::new();
+    let err =
+        render_all_to_writer(["a^2", r"\frac{1}", "b"], Opts::default(), &mut html).unwrap_err();
+    assert!(matches!(err, Error::JsExecError(_)));
+    assert_eq!(html, expected);
+
+    struct Full;
+    impl core::fmt::Write for Full {
+        fn write_str(&mut self, _: &str) -> core::fmt::Result {
+            Err(core::fmt::Error)
         }
     }
-    simulate_deep_stack(100);
-    simulate_deep_stack(0);
+    let err = render_to_writer("a^2", Opts::default(), &mut Full).unwrap_err();
+    assert!(matches!(err, Error::WriteError(_)));
+    assert_eq!(err.code(), "KTX4003");
 }
 
 #[test]
-fn test_opts_sync_send() {
-    fn is_sync_send<T: Sync + Send>(_: T) {}
-    let opts = Opts::builder().build().unwrap();
-    is_sync_send(opts);
+fn test_lint() {
+    let input = r"\newcommand{\R}{\mathbb{R}} \begin{eqnarray} x \in \R & \text{\textbackslash} \\ \begin{tikzcd} \end{tikzcd} \end{eqnarray} \vec{v} \vec{w}";
+    let issues = lint(input).unwrap();
+    let names: Vec<&str> = issues.iter().map(|issue| issue.name.as_str()).collect();
+    assert_eq!(names, ["eqnarray", "tikzcd"]);
+    assert_eq!(&input[issues[0].span.clone()], r"\begin{eqnarray}");
+    assert_eq!(
+        issues[0].message,
+        "KaTeX does not support the eqnarray environment"
+    );
+    assert_eq!(issues[1].suggestion.as_deref(), Some(r"use `\begin{CD}`"));
+    assert!(lint(r"\frac{a}{b} + \sqrt{x}").unwrap().is_empty());
+
+    // Every occurrence is reported, not only the first error.
+    let issues = lint(r"\label{a} x \label{b} {\rm d}").unwrap();
+    assert_eq!(issues.len(), 3);
+    assert_eq!(issues[1].span, 12..18);
+    assert_eq!(issues[2].severity, LintSeverity::Warning);
+    assert_eq!(issues[2].suggestion.as_deref(), Some(r"use `\mathrm{...}`"));
+
+    let opts = Opts::builder()
+        .add_macro(r"\label".to_owned(), String::new())
+        .build()
+        .unwrap();
+    assert_eq!(lint_with_opts(r"\label{a} {\rm d}", opts).unwrap().len(), 1);
 }
 
 #[test]
-fn test_katex_version() {
-    assert!(!crate::KATEX_VERSION.is_empty());
-    assert!(!crate::KATEX_VERSION.contains('\n'));
+fn test_alt_text() {
+    assert_eq!(a11y::alt_text("E = mc^2").unwrap(), "E equals m c squared");
+    assert_eq!(
+        a11y::alt_text(r"\sum_{i=1}^n i^2").unwrap(),
+        "sum from i equals 1 to n i squared"
+    );
+    assert_eq!(
+        a11y::alt_text(r"\lim_{x \to 0} \frac{\sin x}{x} = 1").unwrap(),
+        "the limit as x to 0 the fraction sine of x over x, end fraction equals 1"
+    );
+    assert_eq!(
+        a11y::alt_text(r"\hat{x} + \vec{v} \ne \sqrt[3]{\alpha}").unwrap(),
+        "x hat plus v vector is not equal to the cube root of alpha"
+    );
+    // Macros are expanded.
+    let opts = Opts::builder()
+        .add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned())
+        .display_mode(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        a11y::alt_text_with_opts(r"x \in \RR", &opts).unwrap(),
+        "x is in R"
+    );
+    assert!(a11y::alt_text(r"\frac{").is_err());
+
+    let output = render_to_output("x^3", Opts::default()).unwrap();
+    assert_eq!(output.alt_text().as_deref(), Some("x cubed"));
+    let opts = Opts::builder()
+        .output_type(OutputType::Html)
+        .build()
+        .unwrap();
+    let output = render_to_output("x^3", &opts).unwrap();
+    assert_eq!(output.alt_text(), None);
+
+    // The description is escaped in the attribute.
+    let opts = Opts::builder()
+        .aria_label(true)
+        .display_mode(true)
+        .build()
+        .unwrap();
+    let html = render_with_opts("a < b", &opts).unwrap();
+    let attribute = r#"<span class="katex" role="img" aria-label="a is less than b">"#;
+    assert_eq!(html.matches("aria-label").count(), 1);
+    assert_eq!(
+        html.replacen(attribute, r#"<span class="katex">"#, 1),
+        render_with_opts("a < b", Opts::builder().display_mode(true).build().unwrap()).unwrap()
+    );
+    let html = render_with_opts(r#"\text{"}"#, &opts).unwrap();
+    assert!(html.contains(r#"aria-label="&quot;""#), "{html}");
+
+    let err = Opts::builder()
+        .aria_label(true)
+        .output_type(OutputType::Html)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("aria_label"), "{err}");
+    let mut opts = Opts::default();
+    opts.set_aria_label(true);
+    opts.set_output_type(OutputType::Html);
+    assert!(matches!(
+        opts.validate(),
+        Err(Error::InvalidOption {
+            field: "aria_label",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_colors() {
+    let colors = colors(r"\red{a} + \fcolorbox{blue}{ffeeee}{\color{red} b}").unwrap();
+    assert_eq!(
+        colors,
+        [
+            // `\red` is a KaTeX macro.
+            ColorUse {
+                color: "#df0030".to_owned(),
+                role: ColorRole::Text,
+            },
+            ColorUse {
+                color: "#ffeeee".to_owned(),
+                role: ColorRole::Background,
+            },
+            ColorUse {
+                color: "blue".to_owned(),
+                role: ColorRole::Border,
+            },
+            ColorUse {
+                color: "red".to_owned(),
+                role: ColorRole::Text,
+            },
+        ]
+    );
+    assert!(crate::colors("x").unwrap().is_empty());
+
+    assert!(crate::colors(r"\frac{a}").is_err());
+    let opts = Opts::builder()
+        .throw_on_error(false)
+        .error_color("#ff0000".to_owned())
+        .build()
+        .unwrap();
+    let colors = colors_with_opts(r"\frac{a}", opts).unwrap();
+    assert_eq!(colors.len(), 1);
+    assert_eq!(colors[0].color, "#ff0000");
+    assert_eq!(colors[0].role, ColorRole::Error);
+}
+
+#[test]
+fn test_check_coverage() {
+    use fonts::{check_coverage, Fallback, MissingGlyph};
+
+    assert!(check_coverage(r"\alpha + \mathbb{R} \text{é}")
+        .unwrap()
+        .is_empty());
+    let missing = check_coverage("☃ + ☃ + \\text{日本}").unwrap();
+    assert_eq!(
+        missing,
+        [
+            MissingGlyph {
+                character: '☃',
+                fallback: Fallback::Unsupported {
+                    font: "Main-Regular".to_owned()
+                },
+            },
+            MissingGlyph {
+                character: '日',
+                fallback: Fallback::Script {
+                    script: "cjk".to_owned()
+                },
+            },
+            MissingGlyph {
+                character: '本',
+                fallback: Fallback::Script {
+                    script: "cjk".to_owned()
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_allow_commands() {
+    let input = r#"\htmlClass{foo}{x} + \href{https://katex.org}{y}"#;
+    let opts = Opts::builder()
+        .allow_commands(&[r#"\htmlClass"#])
+        .build()
+        .unwrap();
+    let html = render_with_opts(input, opts).unwrap();
+    assert!(html.contains("foo"));
+    assert!(!html.contains(r#"href="https://katex.org""#));
+
+    let opts = Opts::builder()
+        .allow_commands(&[r#"\htmlClass"#, r#"\href"#])
+        .build()
+        .unwrap();
+    let html = render_with_opts(input, opts).unwrap();
+    assert!(html.contains("foo"));
+    assert!(html.contains(r#"href="https://katex.org""#));
+
+    assert!(Opts::builder().allow_commands(&["href"]).build().is_err());
+}
+
+#[test]
+fn test_strict_overrides() {
+    let input = "x = 中";
+    let opts = Opts::builder().strict(StrictMode::Error).build().unwrap();
+    assert!(render_with_opts(input, &opts).is_err());
+
+    let overrides = std::collections::HashMap::from([(
+        StrictErrorCode::UnicodeTextInMathMode,
+        StrictMode::Ignore,
+    )]);
+    let opts = Opts::builder()
+        .strict(StrictMode::Error)
+        .strict_overrides(overrides.clone())
+        .build()
+        .unwrap();
+    assert!(render_with_opts(input, &opts).is_ok());
+    assert!(render_with_opts("x % comment", &opts).is_err());
+
+    // Without `strict`, the other codes keep KaTeX's default of warning.
+    let opts = Opts::builder().strict_overrides(overrides).build().unwrap();
+    let output = render_to_output("x % comment", &opts).unwrap();
+    assert!(output
+        .logs
+        .iter()
+        .any(|log| log.message.contains("commentAtEnd")));
+
+    let effective = opts.effective();
+    assert_eq!(effective.strict, StrictMode::Warn);
+    assert_eq!(
+        effective.strict_overrides[&StrictErrorCode::UnicodeTextInMathMode],
+        StrictMode::Ignore
+    );
+    assert_eq!(
+        StrictErrorCode::NewLineInDisplayMode.to_string(),
+        "newLineInDisplayMode"
+    );
+}
+
+#[test]
+fn test_macro_with_args() {
+    let opts = Opts::builder()
+        .add_macro(r"\pair".to_owned(), r"(#1, #2)".to_owned())
+        .add_macro_with_args(
+            r"\first".to_owned(),
+            Macro {
+                num_args: Some(2),
+                ..Macro::new("#1")
+            },
+        )
+        .add_macro_with_args(
+            r"\d".to_owned(),
+            Macro {
+                expansion_mode: ExpansionMode::Unexpandable,
+                ..Macro::new("y")
+            },
+        )
+        .build()
+        .unwrap();
+    let html = render_with_opts(r"\pair{a}{\first{b}{c}}", &opts).unwrap();
+    assert!(html.contains("<mi>a</mi>"));
+    assert!(html.contains("<mi>b</mi>"));
+    assert!(!html.contains("<mi>c</mi>"));
+    // `\edef` keeps unexpandable macros, which are expanded when used.
+    let html = render_with_opts(r"\edef\e{\d}\def\d{z}\e", &opts).unwrap();
+    assert!(html.contains("<mi>z</mi>"));
+
+    let err = Opts::builder()
+        .add_macro_with_args(
+            r"\many".to_owned(),
+            Macro {
+                num_args: Some(10),
+                ..Macro::new("#1")
+            },
+        )
+        .build();
+    assert!(err.is_err());
+}
+
+#[test]
+fn test_macro_files() {
+    let latex = r#"
+        \NeedsTeXFormat{LaTeX2e} % ignored
+        \newcommand{\RR}{\mathbb{R}}
+        \newcommand*\pair[2]{(#1, #2)}
+        \providecommand{\RR}{R}
+        \def\first#1#2{#1}
+        \DeclareMathOperator{\tr}{tr}
+        % \newcommand{\commented}{x}
+    "#;
+    let mut opts = Opts::default();
+    opts.load_macros_from_str(MacroFormat::Latex, latex)
+        .unwrap();
+    assert_eq!(opts.macros.len(), 4);
+    assert_eq!(opts.macros[r"\RR"], Macro::new(r"\mathbb{R}"));
+    assert_eq!(opts.macros[r"\pair"].num_args, Some(2));
+    assert_eq!(opts.macros[r"\tr"].body, r"\operatorname{tr}");
+    let html = render_with_opts(r"\tr \pair{\first{a}{b}}{c}", &opts).unwrap();
+    assert!(html.contains("<mi>a</mi>"));
+    assert!(!html.contains("<mi>b</mi>"));
+
+    let json = r#"{"\\RR": "\\mathbb{R}", "\\d": {"body": "\\mathrm{d}", "unexpandable": true}}"#;
+    let toml = r#"
+        '\RR' = '\mathbb{R}' # comment
+        "\\d" = { body = "\\mathrm{d}", unexpandable = true }
+
+        ['\first']
+        body = '#1'
+        num_args = 2
+    "#;
+    let mut from_json = Opts::default();
+    from_json
+        .load_macros_from_str(MacroFormat::Json, json)
+        .unwrap();
+    let mut from_toml = Opts::default();
+    from_toml
+        .load_macros_from_str(MacroFormat::Toml, toml)
+        .unwrap();
+    for opts in [&from_json, &from_toml] {
+        assert_eq!(opts.macros[r"\RR"], Macro::new(r"\mathbb{R}"));
+        assert_eq!(
+            opts.macros[r"\d"].expansion_mode,
+            ExpansionMode::Unexpandable
+        );
+    }
+    assert_eq!(from_toml.macros[r"\first"].num_args, Some(2));
+
+    let err = Opts::default()
+        .load_macros_from_str(MacroFormat::Latex, "\n\\newcommand{\\x}[1][0]{#1}")
+        .unwrap_err();
+    assert!(err.to_string().contains("line 2: optional arguments"));
+    assert!(Opts::default()
+        .load_macros_from_str(MacroFormat::Json, r#"{"RR": "x"}"#)
+        .is_err());
+    assert!(Opts::default()
+        .load_macros_from_str(MacroFormat::Toml, "'\\x' = 'x' 'y'")
+        .is_err());
+    assert_eq!(
+        MacroFormat::from_path("macros.sty"),
+        Some(MacroFormat::Latex)
+    );
+    assert_eq!(MacroFormat::from_path("macros.yaml"), None);
+}
+
+#[test]
+fn test_render_batch_resumable() {
+    use batch::render_batch_resumable;
+    use std::collections::HashMap;
+
+    let opts = Opts::default();
+    let mut store = HashMap::new();
+    let err = render_batch_resumable(["a", r"\frac", "b"], &opts, &mut store);
+    assert!(err.is_err());
+    assert!(store.contains_key(&cache_key("a", &opts)));
+    assert!(!store.contains_key(&cache_key("b", &opts)));
+
+    // Stored results are not rendered again.
+    store.insert(cache_key("a", &opts), "stored".to_owned());
+    let html = render_batch_resumable(["a", "b"], &opts, &mut store).unwrap();
+    assert_eq!(html[0], "stored");
+    assert_eq!(html[1], render("b").unwrap());
+    assert_eq!(store[&cache_key("b", &opts)], html[1]);
+
+    // Renders with a resolver without id are not stored.
+    let opts = Opts::builder()
+        .resource_resolver(ResourceResolver::new(|_| None))
+        .build()
+        .unwrap();
+    let mut store = HashMap::new();
+    store.insert(cache_key("a", &opts), "stored".to_owned());
+    let html = render_batch_resumable(["a"], &opts, &mut store).unwrap();
+    assert_eq!(html[0], render("a").unwrap());
+    assert_eq!(store.len(), 1);
+}
+
+#[cfg(feature = "build-support")]
+#[test]
+fn test_build_support_render_dir() {
+    use build_support::{render_dir, TABLE_FILE_NAME};
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("katex-render-dir-{}", std::process::id()));
+    let pages = dir.join("pages");
+    fs::create_dir_all(&pages).unwrap();
+    fs::write(pages.join("a.md"), r"Let $x$ be $$\frac{1}{2}$$ or $x$.").unwrap();
+    fs::write(pages.join("b.md"), r#"Also \(x\) and $\text{"q"}$."#).unwrap();
+    fs::write(pages.join("c.txt"), r"Not $\frac{a}$ scanned.").unwrap();
+    let glob = format!("{}/*.md", pages.display());
+
+    let rendered = render_dir(&glob, &dir, Opts::default()).unwrap();
+    assert_eq!(rendered.table, dir.join(TABLE_FILE_NAME));
+    assert_eq!(rendered.sources.len(), 2);
+    assert_eq!(rendered.equations, 3);
+    let table = fs::read_to_string(&rendered.table).unwrap();
+    let display = Opts::builder().display_mode(true).build().unwrap();
+    let html = render_with_opts(r"\frac{1}{2}", display).unwrap();
+    assert!(table.contains(&format!(r#"("\\frac{{1}}{{2}}", true) => Some({html:?}),"#)));
+    assert!(table.contains(r#"("\\text{\"q\"}", false)"#));
+
+    // An unchanged table is not written again.
+    let modified = fs::metadata(&rendered.table).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    render_dir(&glob, &dir, Opts::default()).unwrap();
+    assert_eq!(
+        fs::metadata(&rendered.table).unwrap().modified().unwrap(),
+        modified
+    );
+
+    let err = render_dir(&format!("{}/*", pages.display()), &dir, Opts::default()).unwrap_err();
+    assert!(err.to_string().contains("c.txt"), "{err}");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_testing_suite() {
+    use testing::{output_hash, parse_suite, run_suite, SuiteFormat};
+
+    let hash = output_hash(&render("x").unwrap());
+    let suite = format!(
+        r#"{{"test": [
+            {{"name": "hash", "input": "x", "hash": "{hash}"}},
+            {{"input": "\\frac{{a}}{{b}}", "opts": {{"display_mode": true, "output": "mathml"}},
+              "contains": ["<mfrac>", "display=\"block\""], "not_contains": ["katex-html"]}},
+            {{"input": "\\RR", "opts": {{"macros": {{"\\RR": "\\mathbb{{R}}"}}}}, "contains": ["double-struck"]}},
+            {{"input": "\\frac{{a}}", "error": true}},
+            {{"name": "wrong", "input": "y", "contains": ["<mi>x</mi>"]}}
+        ]}}"#
+    );
+    let tests = parse_suite(SuiteFormat::Json, &suite).unwrap();
+    assert_eq!(tests.len(), 5);
+    let failures = run_suite(&tests);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(
+        failures[0].to_string(),
+        r#"wrong: output does not contain "<mi>x</mi>""#
+    );
+
+    let err = parse_suite(SuiteFormat::Toml, "[[test]]\nname = 'no input'").unwrap_err();
+    assert!(err.to_string().contains("test 1: missing input"));
+}
+
+#[test]
+fn test_testing_suite_syntax() {
+    use testing::{parse_suite, SuiteFormat};
+
+    let tests = parse_suite(
+        SuiteFormat::Json,
+        r#"{"test": [{"input": "\ud83d\ude00 \u00e9", "opts": {"max_size": 1.5e1}}]}"#,
+    )
+    .unwrap();
+    assert_eq!(tests[0].input, "\u{1f600} \u{e9}");
+    assert_eq!(tests[0].opts.effective().max_size, Limit::Finite(15.0));
+    for suite in [
+        r#"{"test": [{"input": "\ud83d"}]}"#,
+        r#"{"test": [{"input": "\ud83dA"}]}"#,
+        "{\"test\": [ # comment\n {\"input\": \"x\"}]}",
+    ] {
+        assert!(parse_suite(SuiteFormat::Json, suite).is_err(), "{suite}");
+    }
+
+    let suite = r#"
+        [[test]]
+        input = """
+\\frac{a}{b} \
+          + "c"""""
+        opts = { min_rule_thickness = 0.04, max_size = 1_0.0 }
+        contains = [
+            # A comment between items.
+            '''"mfrac"''',
+        ]
+    "#;
+    let tests = parse_suite(SuiteFormat::Toml, suite).unwrap();
+    assert_eq!(tests[0].input, r#"\frac{a}{b} + "c"""#);
+    assert_eq!(tests[0].contains, [r#""mfrac""#]);
+    let effective = tests[0].opts.effective();
+    assert_eq!(effective.min_rule_thickness, 0.04);
+    assert_eq!(effective.max_size, Limit::Finite(10.0));
+    assert!(parse_suite(SuiteFormat::Toml, "[[test]]\ninput = '''x").is_err());
+}
+
+#[test]
+fn test_shared_renderer() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedRenderer>();
+
+    let mut renderer =
+        SharedRenderer::with_opts(Opts::builder().display_mode(true).build().unwrap());
+    renderer.add_post_processor(postprocess::ClassPrefix::new("math"));
+    let html = renderer.render("x").unwrap();
+    assert!(html.starts_with(r#"<span class="math-display">"#));
+    let html = renderer.render_with_opts("x", Opts::default()).unwrap();
+    assert!(html.starts_with(r#"<span class="math">"#));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let renderer = std::sync::Arc::new(renderer);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| scope.spawn(|| renderer.render("x").unwrap()))
+                .collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), renderer.render("x").unwrap());
+            }
+        });
+    }
+}
+
+#[test]
+fn test_to_katex_json() {
+    assert_eq!(Opts::default().to_katex_json(), "{}");
+
+    let opts = Opts::builder()
+        .add_macro_with_args(r"\b".to_owned(), Macro::new("b"))
+        .add_macro(r"\a".to_owned(), "\"a\"".to_owned())
+        .allow_commands(&[r"\href"])
+        .output_type(OutputType::HtmlAndMathmlWithAnnotation)
+        .throw_on_error(false)
+        .error_color("#c00".to_owned())
+        .min_rule_thickness(0.05)
+        .max_expand(None)
+        .deterministic(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        opts.to_katex_json(),
+        r##"{
+  "output": "htmlAndMathml",
+  "throwOnError": false,
+  "errorColor": "#c00",
+  "macros": {
+    "\\a": "\"a\"",
+    "\\b": "b"
+  },
+  "minRuleThickness": 0.05,
+  "maxExpand": 4294967295
+}"##
+    );
+    assert_eq!(OutputType::Mathml.to_string(), "mathml");
+}
+
+#[test]
+fn test_opts_validation() {
+    let err = Opts::builder().error_color("#12345").build().unwrap_err();
+    assert!(err.to_string().contains("invalid option error_color"));
+    assert!(Opts::builder().error_color("#c00").build().is_ok());
+    assert!(Opts::builder().error_color("crimson").build().is_ok());
+    assert!(Opts::builder()
+        .add_macro("RR".to_owned(), r"\mathbb{R}".to_owned())
+        .build()
+        .is_err());
+    assert!(Opts::builder()
+        .add_macro(r"\,".to_owned(), r"\;".to_owned())
+        .build()
+        .is_ok());
+    assert!(Opts::builder().min_rule_thickness(-0.1).build().is_err());
+
+    let mut opts = Opts::default();
+    assert!(opts.validate().is_ok());
+    opts.set_error_color("#ggg".to_owned());
+    match opts.validate() {
+        Err(Error::InvalidOption { field, reason }) => {
+            assert_eq!(field, "error_color");
+            assert_eq!(reason, r##"invalid hex color "#ggg""##);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+
+    // `try_build` returns the typed error of `validate`.
+    match Opts::builder().error_color("#ggg").try_build() {
+        Err(Error::InvalidOption { field, reason }) => {
+            assert_eq!(field, "error_color");
+            assert_eq!(reason, r##"invalid hex color "#ggg""##);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert!(Opts::builder().display_mode(true).try_build().is_ok());
+}
+
+#[test]
+fn test_host_function() {
+    use crate::js_engine::JsEngine;
+
+    let engine = Engine::new().unwrap();
+    engine
+        .register_function(
+            "hostJoin",
+            Box::new(|args| match args.is_empty() {
+                true => Err("no arguments".to_owned()),
+                false => Ok(args.join("+")),
+            }),
+        )
+        .unwrap();
+    let eval = |code: &str| engine.eval(code).and_then(|v| v.into_string());
+    assert_eq!(eval("hostJoin('a', 'b', 'c')").unwrap(), "a+b+c");
+    let caught = eval("try { hostJoin() } catch (e) { 'caught: ' + String(e) }").unwrap();
+    assert!(caught.starts_with("caught: "));
+    assert!(caught.contains("no arguments"));
+    assert!(eval("hostJoin(1)").is_err());
+}
+
+#[test]
+fn test_renderer_handlers() {
+    use crate::renderer::ConsoleLevel;
+    use std::{cell::RefCell, rc::Rc};
+
+    let mut renderer = Renderer::new().unwrap();
+    let messages = Rc::new(RefCell::new(Vec::new()));
+    let sink = messages.clone();
+    renderer
+        .set_console_handler(move |level, message| {
+            sink.borrow_mut().push((level, message.to_owned()))
+        })
+        .unwrap();
+    let output = renderer.render_to_output("é", Opts::default()).unwrap();
+    let messages = messages.borrow();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].0, ConsoleLevel::Warn);
+    assert!(messages[0].1.contains("strict mode"));
+    assert_eq!(output.logs.len(), 1);
+    assert_eq!(output.logs[0].level, ConsoleLevel::Warn);
+    assert_eq!(output.logs[0].message, messages[0].1);
+
+    renderer
+        .set_trust_handler(|context| {
+            context.command == r#"\href"# && context.protocol == Some("https")
+        })
+        .unwrap();
+    let html = renderer.render(r#"\href{https://katex.org}{x}"#).unwrap();
+    assert!(html.contains(r#"href="https://katex.org""#));
+    let html = renderer.render(r#"\url{http://katex.org}"#).unwrap();
+    assert!(!html.contains("href="));
+    let opts = Opts::builder().trust(false).build().unwrap();
+    let html = renderer
+        .render_with_opts(r#"\href{https://katex.org}{x}"#, opts)
+        .unwrap();
+    assert!(!html.contains("href="));
+}
+
+#[test]
+fn test_post_processors() {
+    let mut renderer = Renderer::new().unwrap();
+    renderer.add_post_processor(postprocess::ClassPrefix::new("math"));
+    renderer.add_post_processor(|input: &str, html: String| {
+        Ok(format!("<div title=\"{input}\">{html}</div>"))
+    });
+    let html = renderer
+        .render_with_opts(
+            "a = b + c",
+            Opts::builder().display_mode(true).build().unwrap(),
+        )
+        .unwrap();
+    assert!(html.starts_with(r#"<div title="a = b + c"><span class="math-display">"#));
+    assert!(html.contains(r#"span class="math""#));
+    assert!(html.contains(r#"span class="math-mathml""#));
+    assert!(html.contains(r#"span class="mord mathnormal""#));
+    assert!(!html.contains("katex"));
+}
+
+#[test]
+fn test_used_fonts() {
+    use output::FontName;
+
+    let output = render_to_output("a = b", Opts::default()).unwrap();
+    let fonts = output.used_fonts();
+    assert!(fonts.contains(&FontName::MainRegular));
+    assert!(fonts.contains(&FontName::MathItalic));
+    assert!(!fonts.contains(&FontName::AmsRegular));
+    assert!(output.logs.is_empty());
+
+    let output = render_to_output(
+        r#"\mathbb{R} \mathcal{C} \sum_{i=0}^n \left(\frac{1}{2}\right) {\tiny x}"#,
+        Opts::builder().display_mode(true).build().unwrap(),
+    )
+    .unwrap();
+    let fonts = output.used_fonts();
+    assert!(fonts.contains(&FontName::AmsRegular));
+    assert!(fonts.contains(&FontName::CaligraphicRegular));
+    assert!(fonts.contains(&FontName::Size2Regular));
+    assert!(!fonts.contains(&FontName::Size4Regular));
+    assert_eq!(FontName::AmsRegular.file_stem(), "KaTeX_AMS-Regular");
+
+    // Messages of earlier renders are not reported again.
+    render("é").unwrap();
+    let output = render_to_output("é", Opts::default()).unwrap();
+    assert_eq!(output.logs.len(), 1);
+    assert!(output.logs[0].message.contains("unicodeTextInMathMode"));
+}
+
+#[test]
+fn test_output_metrics() {
+    let x = render_to_output("x", Opts::default()).unwrap();
+    assert_eq!(x.baseline_em, Some(0.0));
+    assert!(x.height_em.unwrap() > 0.4);
+
+    let frac = render_to_output(r"\frac{a}{b}", Opts::default()).unwrap();
+    assert!(frac.baseline_em.unwrap() > 0.0);
+    assert!(frac.height_em.unwrap() > x.height_em.unwrap());
+    let display = render_to_output(
+        r"\frac{a}{b}",
+        Opts::builder().display_mode(true).build().unwrap(),
+    )
+    .unwrap();
+    assert!(display.baseline_em.unwrap() > frac.baseline_em.unwrap());
+
+    let mathml = render_to_output(
+        r"\frac{a}{b}",
+        Opts::builder()
+            .output_type(OutputType::Mathml)
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(mathml.height_em, None);
+    assert_eq!(mathml.baseline_em, None);
+}
+
+#[test]
+fn test_render_ansi() {
+    let text = term::render_ansi("x^2 + y_1 = z^{\\alpha}").unwrap();
+    assert_eq!(text.lines().count(), 2);
+    assert!(text.contains("\x1b[3mx\x1b[23m² + \x1b[3my\x1b[23m₁ = "));
+    assert!(text.contains("α"));
+
+    let text = term::render_ansi(r#"\frac{a+b}{2}"#).unwrap();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("─────"));
+    assert_eq!(lines[2].trim(), "2");
+}
+
+#[test]
+fn test_render_svg() {
+    let svg = svg::render_svg(r#"\frac{a}{b} + \sqrt{x^2}"#, Opts::default()).unwrap();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg""#));
+    assert!(svg.ends_with("</svg>"));
+    assert!(svg.contains(r#"font-style="italic">a</text>"#));
+    assert!(svg.contains(">√</text>"));
+    // Fraction bar and radical overline.
+    assert_eq!(svg.matches("<rect").count(), 2);
+    assert!(!svg.contains("class="));
+
+    // Sized with the metrics of KaTeX_Math-Italic, with 0.1em of padding.
+    let svg = svg::render_svg("x", Opts::default()).unwrap();
+    assert!(svg.contains(r#"width="0.772em" height="0.631em""#), "{svg}");
+    let svg = svg::render_svg("y", Opts::default()).unwrap();
+    assert!(svg.contains(r#"width="0.69em" height="0.825em""#), "{svg}");
+}
+
+#[test]
+fn test_digit_grouping() {
+    let input = "1234567.891 + 1234";
+    let opts = Opts::builder()
+        .digit_grouping(DigitGrouping::thin_space())
+        .build()
+        .unwrap();
+    let html = render_with_opts(input, opts).unwrap();
+    assert!(html.contains("1\u{2009}234\u{2009}567.891"));
+    assert!(!html.contains("1\u{2009}234<"));
+    assert!(html.contains(r#"<annotation encoding="application/x-tex">1234567.891 + 1234"#));
+
+    let opts = Opts::builder()
+        .digit_grouping(DigitGrouping::for_locale("en-US").unwrap())
+        .build()
+        .unwrap();
+    let html = render_with_opts(input, opts).unwrap();
+    assert!(html.contains("1,234,567.891"));
+    assert!(html.contains("1,234<"));
+
+    assert_eq!(
+        DigitGrouping::for_locale("de-CH").unwrap().separator,
+        "\u{2019}"
+    );
+    assert!(DigitGrouping::for_locale("xx").is_none());
+}
+
+#[cfg(feature = "markdown")]
+#[test]
+fn test_markdown() {
+    use pulldown_cmark::{Event, Parser};
+
+    let source = "Text $a^2$ and\n\n$$\\frac{1}{2}$$\n\n```\n$not math$\n```\n";
+    let events = markdown::process_events(Parser::new(source), Opts::default()).unwrap();
+    let html: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            Event::InlineHtml(html) => Some(html),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(html.len(), 2);
+    assert!(!html[0].contains("katex-display"));
+    assert!(html[1].contains("katex-display"));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, Event::Text(text) if text.contains("$not math$"))));
+}
+
+#[cfg(feature = "sanitize")]
+#[test]
+fn test_sanitize() {
+    let sanitized = Opts::builder().sanitize(true).build().unwrap();
+    for input in [
+        r#"\frac{a}{b} + \sqrt[3]{x}"#,
+        r#"\begin{pmatrix}1&2\\3&4\end{pmatrix}"#,
+        r#"\overrightarrow{AB} \color{red}{x}"#,
+    ] {
+        // Only quoting may differ.
+        assert_eq!(
+            html::parse(&render_with_opts(input, &sanitized).unwrap()),
+            html::parse(&render(input).unwrap())
+        );
+    }
+
+    let opts = Opts::builder().trust(true).sanitize(true).build().unwrap();
+    let html = render_with_opts(
+        r#"\htmlClass{evil}{\htmlStyle{position:fixed}{\htmlId{x}{\htmlData{a=b}{x}}}}"#,
+        &opts,
+    )
+    .unwrap();
+    // The TeX source is still found in the annotation.
+    assert!(!html.contains("enclosing evil"));
+    assert!(!html.contains(r#"style="position"#));
+    assert!(!html.contains(r#"id="x""#));
+    assert!(!html.contains(r#"data-a="b""#));
+    let html = render_with_opts(r#"\href{java\ script:alert(1)}{x}"#, &opts).unwrap();
+    assert!(!html.contains("href="));
+    let html = render_with_opts(r#"\href{https://katex.org}{x}"#, &opts).unwrap();
+    assert!(html.contains(r#"href="https://katex.org""#));
+    let html = render_with_opts(r#"\includegraphics{javascript:alert(1)}"#, &opts).unwrap();
+    assert!(!html.contains("src="));
+    let html = render_with_opts(r#"\includegraphics{https://katex.org/a.png}"#, &opts).unwrap();
+    assert!(html.contains(r#"<img src="https://katex.org/a.png"#));
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_metrics() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Sink(Mutex<Vec<(&'static str, String)>>);
+
+    impl metrics::MetricsSink for Arc<Sink> {
+        fn increment_counter(&self, name: &'static str, labels: &[(&'static str, &str)], _: u64) {
+            let labels: Vec<_> = labels.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            self.0.lock().unwrap().push((name, labels.join(",")));
+        }
+
+        fn record_histogram(&self, name: &'static str, _: &[(&'static str, &str)], value: f64) {
+            assert!(value >= 0.0);
+            self.0.lock().unwrap().push((name, String::new()));
+        }
+    }
+
+    let sink = Arc::new(Sink::default());
+    metrics::set_sink(Arc::clone(&sink));
+    let renderer = Renderer::new().unwrap();
+    renderer.render("x").unwrap();
+    renderer.render(r"\frac{").unwrap_err();
+    metrics::clear_sink();
+    renderer.render("y").unwrap();
+
+    let events = sink.0.lock().unwrap();
+    let count = |name, labels: &str| {
+        events
+            .iter()
+            .filter(|(n, l)| *n == name && l == labels)
+            .count()
+    };
+    // Other tests may render concurrently with the sink installed.
+    assert!(count(metrics::ENGINE_INITS, "result=ok") >= 1);
+    assert!(count(metrics::ENGINE_INIT_DURATION, "") >= 1);
+    assert!(count(metrics::RENDERS, "result=ok") >= 1);
+    assert!(count(metrics::RENDERS, "result=error") >= 1);
+    assert!(count(metrics::RENDER_ERRORS, "code=KTX1002") >= 1);
+    assert!(count(metrics::RENDER_DURATION, "") >= 2);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing() {
+    use std::sync::{Arc, Mutex};
+    use tracing::{span, subscriber, Event, Id, Metadata, Subscriber};
+
+    #[derive(Clone, Default)]
+    struct Spans(Arc<Mutex<Vec<&'static str>>>);
+
+    impl Subscriber for Spans {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> Id {
+            let mut spans = self.0.lock().unwrap();
+            spans.push(span.metadata().name());
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    let spans = Spans::default();
+    subscriber::with_default(spans.clone(), || {
+        Renderer::new().unwrap().render("x").unwrap();
+    });
+    let spans = spans.0.lock().unwrap();
+    assert_eq!(spans[0], "katex::engine_init");
+    assert!(spans.contains(&"katex::render"));
+    assert!(spans.contains(&"katex::js_call"));
+}
+
+#[cfg(feature = "failpoints")]
+#[test]
+fn test_failpoints() {
+    use failpoints::Failpoint;
+
+    for &failpoint in Failpoint::ALL {
+        let err = render(&format!("{} x", failpoint.input())).unwrap_err();
+        match failpoint {
+            Failpoint::Init => assert!(matches!(err, Error::JsInitError(_))),
+            Failpoint::Value => assert!(matches!(err, Error::JsValueError(_))),
+            Failpoint::Corrupt => assert!(matches!(err, Error::EngineRestarted(_))),
+            Failpoint::Timeout => assert!(matches!(
+                err,
+                Error::ResourceExhausted {
+                    kind: ResourceKind::Interrupt,
+                    ..
+                }
+            )),
+            Failpoint::OutOfMemory => assert!(matches!(
+                err,
+                Error::ResourceExhausted {
+                    kind: ResourceKind::Memory,
+                    ..
+                }
+            )),
+            _ => assert!(matches!(err, Error::JsExecError(_))),
+        }
+    }
+    assert!(render("x").is_ok());
+
+    // The engine is only restarted once broken.
+    assert!(matches!(
+        render(Failpoint::Corrupt.input()),
+        Err(Error::EngineRestarted(_))
+    ));
+    assert!(render("x").is_ok());
+    assert!(matches!(render(r"\frac{"), Err(Error::JsExecError(_))));
+
+    let renderer = Renderer::new().unwrap();
+    assert!(matches!(
+        renderer.render(Failpoint::Corrupt.input()),
+        Err(Error::JsExecError(_))
+    ));
+    assert!(renderer.render("x").is_err());
+}
+
+#[cfg(feature = "raster")]
+#[test]
+fn test_render_png() {
+    // Return the size of the SVG in ems, and of the PNG in pixels.
+    fn sizes(input: &str, scale: f32) -> ((f32, f32), (u32, u32)) {
+        let svg = svg::render_svg(input, Opts::default()).unwrap();
+        let attr = |name: &str| -> f32 {
+            let start = svg.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            let len = svg[start..].find("em\"").unwrap();
+            svg[start..start + len].parse().unwrap()
+        };
+        let png = raster::render_png(input, Opts::default(), scale).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // The width and the height are the first fields of the IHDR chunk.
+        let field = |i: usize| u32::from_be_bytes(png[i..i + 4].try_into().unwrap());
+        ((attr("width"), attr("height")), (field(16), field(20)))
+    }
+
+    for (input, scale) in [("x", 32.0), (r"\frac{a}{b}", 40.0), ("y", 0.5)] {
+        let ((width, height), png) = sizes(input, scale);
+        let expected = (
+            (width * scale).ceil() as u32,
+            (height * scale).ceil() as u32,
+        );
+        assert_eq!(png, expected, "{input} at {scale}");
+    }
+    assert!(raster::render_png("x", Opts::default(), 0.0).is_err());
+}
+
+#[cfg(feature = "omml")]
+#[test]
+fn test_render_omml() {
+    use convert::{mathml_to_omml, render_omml};
+
+    let omml = render_omml(r"\sqrt{x^2} + \binom{n}{k}", Opts::default()).unwrap();
+    assert_eq!(
+        omml,
+        concat!(
+            r#"<m:oMath xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math">"#,
+            r#"<m:rad><m:radPr><m:degHide m:val="1"/></m:radPr><m:deg/><m:e>"#,
+            r#"<m:sSup><m:e><m:r><m:t>x</m:t></m:r></m:e>"#,
+            r#"<m:sup><m:r><m:rPr><m:sty m:val="p"/></m:rPr><m:t>2</m:t></m:r></m:sup></m:sSup>"#,
+            r#"</m:e></m:rad><m:r><m:rPr><m:sty m:val="p"/></m:rPr><m:t>+</m:t></m:r>"#,
+            r#"<m:d><m:dPr><m:begChr m:val="("/><m:endChr m:val=")"/></m:dPr><m:e>"#,
+            r#"<m:f><m:fPr><m:type m:val="noBar"/></m:fPr>"#,
+            r#"<m:num><m:r><m:t>n</m:t></m:r></m:num><m:den><m:r><m:t>k</m:t></m:r></m:den>"#,
+            r#"</m:f></m:e></m:d></m:oMath>"#,
+        )
+    );
+
+    let opts = Opts::builder().display_mode(true).build().unwrap();
+    let omml = render_omml(r"\hat{x} \mathbf{v} \sin", opts).unwrap();
+    assert!(omml.starts_with("<m:oMathPara"));
+    assert!(omml.contains(r#"<m:acc><m:accPr><m:chr m:val="^"/></m:accPr>"#));
+    assert!(omml.contains(r#"<m:sty m:val="b"/></m:rPr><m:t>v</m:t>"#));
+    assert!(omml.contains(r#"<m:sty m:val="p"/></m:rPr><m:t>sin</m:t>"#));
+
+    assert_eq!(mathml_to_omml("<p>no math</p>"), "");
+}
+
+#[cfg(feature = "braille")]
+#[test]
+fn test_render_braille() {
+    use braille::{render_braille, render_braille_with_opts};
+
+    // The quadratic formula, `X .K ?-B+->B^2"-4AC]/2A#` in braille ASCII.
+    assert_eq!(
+        render_braille(r"x = \frac{-b \pm \sqrt{b^2-4ac}}{2a}").unwrap(),
+        "⠭⠀⠨⠅⠀⠹⠤⠃⠬⠤⠜⠃⠘⠆⠐⠤⠲⠁⠉⠻⠌⠆⠁⠼"
+    );
+    // Numeric indicators, numeric subscripts and Greek letters.
+    assert_eq!(
+        render_braille(r"2x_1 - 3 \ge \Delta").unwrap(),
+        "⠼⠆⠭⠂⠤⠒⠀⠨⠂⠱⠀⠨⠠⠙"
+    );
+    // Complex fractions, nested radicals and nested superscripts.
+    assert_eq!(
+        render_braille(r"\frac{\frac{a}{b}}{c}").unwrap(),
+        "⠰⠹⠹⠁⠌⠃⠼⠰⠌⠉⠰⠼"
+    );
+    assert_eq!(render_braille(r"\sqrt{x + \sqrt{y}}").unwrap(), "⠜⠭⠬⠨⠜⠽⠨⠻⠻");
+    assert_eq!(render_braille(r"e^{x^2} \sin x").unwrap(), "⠑⠘⠭⠘⠘⠆⠐⠎⠊⠝⠀⠭");
+
+    let opts = Opts::builder()
+        .add_macro(r"\half".to_owned(), r"\frac{1}{2}".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(render_braille_with_opts(r"\half", &opts).unwrap(), "⠹⠂⠌⠆⠼");
+    assert!(matches!(
+        render_braille(r"\begin{matrix}a\end{matrix}"),
+        Err(Error::InvalidInput(_))
+    ));
+    assert!(matches!(
+        render_braille(r"\frac{"),
+        Err(Error::JsExecError(_))
+    ));
+}
+
+#[cfg(feature = "print")]
+#[test]
+fn test_render_print() {
+    use print::{Font, Item};
+
+    let layout = print::render_print(r"\frac{x}{2}", Opts::default(), 10.0).unwrap();
+    assert!(layout.width > 0.0 && layout.height > 0.0 && layout.depth > 0.0);
+    let rule = layout
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Rule { y, .. } => Some(*y),
+            _ => None,
+        })
+        .unwrap();
+    let text = |t: &str| {
+        layout.items.iter().find_map(|item| match item {
+            Item::Text { text, font, y, .. } if text == t => Some((*font, *y)),
+            _ => None,
+        })
+    };
+    let (font, numerator) = text("x").unwrap();
+    assert_eq!(font, Font::MathItalic);
+    assert!(numerator > rule);
+    let (font, denominator) = text("2").unwrap();
+    assert_eq!(font, Font::MainRegular);
+    assert!(denominator < rule);
+    assert!(print::render_print("x", Opts::default(), -1.0).is_err());
+}
+
+#[test]
+fn test_split_math() {
+    let config = DelimiterConfig::default();
+    let text = r#"$$\frac{a}{b}$$ or \(x\), \$y$ and $\text{$z$}$ $ open"#;
+    let segments = split_math(text, &config);
+    let math: Vec<_> = segments.iter().filter_map(Segment::math).collect();
+    assert_eq!(
+        math,
+        [
+            (r#"\frac{a}{b}"#, true),
+            ("x", false),
+            (r#"\text{$z$}"#, false)
+        ]
+    );
+    assert_eq!(split::join(&segments), text);
+    assert_eq!(split_math("", &config), []);
+    assert_eq!(
+        split_math("Costs $5 and $10.", &config),
+        [Segment::Text("Costs $5 and $10.")]
+    );
+    assert_eq!(
+        split_math(r#"\[x\]"#, &config),
+        [Segment::Math {
+            display: true,
+            content: "x",
+            left: r#"\["#,
+            right: r#"\]"#,
+        }]
+    );
+}
+
+#[test]
+fn test_cache_key() {
+    let key = cache_key("a +\tb", Opts::default());
+    assert_eq!(key, format!("v1-{KATEX_VERSION}-4fbc06b367761bb7"));
+    assert_eq!(key, cache_key(" a + b\n", Opts::default()));
+    assert_ne!(key, cache_key("a + b % c\n+ d", Opts::default()));
+    assert_ne!(
+        cache_key("a % b\nc", Opts::default()),
+        cache_key("a % b c", Opts::default())
+    );
+    // Whitespace in `\verb` and in control spaces is significant.
+    assert_ne!(
+        cache_key(r"\verb|a  b|", Opts::default()),
+        cache_key(r"\verb|a b|", Opts::default())
+    );
+    assert_ne!(
+        render(r"\verb|a  b|").unwrap(),
+        render(r"\verb|a b|").unwrap()
+    );
+    assert_eq!(
+        cache_key(r"x  \verb*|a  b|  y", Opts::default()),
+        cache_key(r"x \verb*|a  b| y", Opts::default())
+    );
+    assert_ne!(
+        cache_key(r"\text{a\ }", Opts::default()),
+        cache_key(r"\text{a\}", Opts::default())
+    );
+    assert_ne!(
+        cache_key(r"a\ ", Opts::default()),
+        cache_key("a", Opts::default())
+    );
+    assert_eq!(
+        cache_key(r"a\  ", Opts::default()),
+        cache_key(r"a\ ", Opts::default())
+    );
+    // Not `\verb`.
+    assert_eq!(
+        cache_key(r"\verbatim  x", Opts::default()),
+        cache_key(r"\verbatim x", Opts::default())
+    );
+
+    let opts = Opts::builder()
+        .add_macro(r#"\a"#.to_owned(), "x".to_owned())
+        .add_macro(r#"\b"#.to_owned(), "y".to_owned())
+        .display_mode(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        opts.fingerprint(),
+        r#"{"displayMode":true,"macros":{"\\a":"x","\\b":"y"}}"#
+    );
+    assert_ne!(key, cache_key("a + b", &opts));
+}
+
+#[test]
+fn test_content_hash() {
+    let hash = content_hash("a + b", Opts::default());
+    let mut expected = cache::Fnv1a::new();
+    expected.write(format!("{KATEX_VERSION}\n{{}}\na + b").as_bytes());
+    assert_eq!(hash, expected.finish());
+    assert_ne!(hash, content_hash("a +  b", Opts::default()));
+
+    let opts = Opts::builder()
+        .display_mode(false)
+        .output_type(OutputType::HtmlAndMathml)
+        .throw_on_error(true)
+        .error_color("#CC0000")
+        .max_expand(1000)
+        .build()
+        .unwrap();
+    assert_eq!(content_hash("a + b", &opts), hash);
+    let opts = Opts::builder().display_mode(true).build().unwrap();
+    assert_ne!(content_hash("a + b", &opts), hash);
+    let opts = Opts::builder().deterministic(true).build().unwrap();
+    assert_ne!(content_hash("a + b", &opts), hash);
+}
+
+#[test]
+fn test_complexity() {
+    let score = complexity("x").unwrap();
+    assert_eq!(score.nodes, 1);
+    assert_eq!(score.depth, 1);
+    assert_eq!(score.macro_expansions, 0);
+
+    let score = complexity(r#"\frac{a}{b^2}"#).unwrap();
+    assert_eq!(score.nodes, 7);
+    assert_eq!(score.depth, 4);
+
+    let opts = Opts::builder()
+        .add_macro(r#"\pair"#.to_owned(), r#"\one\one"#.to_owned())
+        .add_macro(r#"\one"#.to_owned(), "1".to_owned())
+        .build()
+        .unwrap();
+    let score = complexity_with_opts(r#"\pair + \pair"#, &opts).unwrap();
+    assert_eq!(score.macro_expansions, 6);
+    assert!(score.total() > complexity("1 + 1").unwrap().total());
+
+    assert!(complexity(r#"\frac{"#).is_err());
+}
+
+#[test]
+fn test_unicode_preprocessing() {
+    let nfc = |input| unicode::nfc(input, &mut Vec::new()).into_owned();
+    assert_eq!(nfc("a\u{307}\u{323} + x"), "\u{1EA1}\u{307} + x");
+    assert_eq!(nfc("\u{212B}"), "\u{C5}");
+    assert_eq!(nfc("\u{1100}\u{1161}\u{11A8}"), "\u{AC01}");
+    // Excluded from composition.
+    assert_eq!(nfc("\u{958}"), "\u{915}\u{93C}");
+
+    let input = "\\text{cafe\u{301}}";
+    assert!(render(input).unwrap().contains("e\u{301}"));
+    let opts = Opts::builder().normalize_unicode(true).build().unwrap();
+    let html = render_with_opts(input, &opts).unwrap();
+    assert!(html.contains("caf\u{E9}"));
+    assert!(!html.contains('\u{301}'));
+
+    let opts = Opts::builder().replace_unicode_math(true).build().unwrap();
+    assert_eq!(
+        render_with_opts("a \u{2212} b \u{D7} c \u{2264} \u{3B1}x", &opts).unwrap(),
+        render(r"a - b \times c \le \alpha x").unwrap()
+    );
+    // Text is left alone.
+    let html = render_with_opts("\\text{\u{3B1}}", &opts).unwrap();
+    assert!(html.contains('\u{3B1}'));
+    assert!(!html.contains("\\alpha"));
+    assert_eq!(opts.fingerprint(), r#"{"replaceUnicodeMath":true}"#);
+}
+
+#[test]
+fn test_escaped_input_policy() {
+    let input = r#"\begin{matrix} a &amp; b \end{matrix} &lt; 1"#;
+    let opts = Opts::builder()
+        .escaped_input_policy(EscapedInputPolicy::Unescape)
+        .build()
+        .unwrap();
+    assert_eq!(
+        render_with_opts(input, &opts).unwrap(),
+        render(r#"\begin{matrix} a & b \end{matrix} < 1"#).unwrap()
+    );
+
+    let opts = Opts::builder()
+        .escaped_input_policy(EscapedInputPolicy::Reject)
+        .build()
+        .unwrap();
+    match render_with_opts(input, &opts) {
+        Err(Error::InvalidInput(msg)) => assert!(msg.contains(r#""&amp;" at byte offset 17"#)),
+        r => panic!("unexpected result: {r:?}"),
+    }
+    assert!(render_with_opts(r#"a \& b \text{R\&amp}"#, &opts).is_ok());
+}
+
+#[test]
+fn test_resource_resolver() {
+    let input =
+        r"\includegraphics[height=1em]{img/a.png?x=1&y=2} \includegraphics[height=1em]{b.png}";
+    let opts = Opts::builder()
+        .trust(true)
+        .resource_resolver(ResourceResolver::new(|url| {
+            url.strip_prefix("img/")
+                .map(|path| format!("https://cdn.example.com/{path}"))
+        }))
+        .build()
+        .unwrap();
+    let html = render_with_opts(input, &opts).unwrap();
+    assert!(html.contains("src='https://cdn.example.com/a.png?x=1&amp;y=2 '"));
+    assert!(html.contains("src='b.png '"));
+    assert_ne!(cache_key(input, &opts), cache_key(input, Opts::default()));
+
+    let with_id = |id: &str| {
+        Opts::builder()
+            .resource_resolver(ResourceResolver::with_id(id, |_| None))
+            .build()
+            .unwrap()
+    };
+    assert_eq!(
+        cache_key(input, with_id("a")),
+        cache_key(input, with_id("a"))
+    );
+    assert_ne!(
+        cache_key(input, with_id("a")),
+        cache_key(input, with_id("b"))
+    );
+    assert_ne!(cache_key(input, with_id("a")), cache_key(input, &opts));
+}
+
+#[test]
+fn test_deterministic() {
+    let opts = Opts::builder()
+        .trust(true)
+        .deterministic(true)
+        .build()
+        .unwrap();
+    let html = render_with_opts(r"\htmlData{zeta=1, alpha=2}{x}", &opts).unwrap();
+    assert!(html.contains(r#"<span class="enclosing" data-alpha="2" data-zeta="1">"#));
+    let html = render_with_opts(r"\includegraphics[height=1em]{a.png}", &opts).unwrap();
+    assert!(html.contains(r#"<img alt='a' src='a.png ' style="height:1em;"'/>"#));
+
+    let mut opts = opts;
+    opts.set_deterministic(false);
+    let html = render_with_opts(r"\htmlData{zeta=1, alpha=2}{x}", &opts).unwrap();
+    assert!(html.contains(r#"<span class="enclosing" data-zeta="1" data-alpha="2">"#));
+}
+
+#[test]
+fn test_size_and_style() {
+    let opts = Opts::builder().force_display_style(true).build().unwrap();
+    let html = render_with_opts(r"\sum_{i=1}^n \frac{a}{b}", &opts).unwrap();
+    assert_eq!(
+        html,
+        render(r"\displaystyle \sum_{i=1}^n \frac{a}{b}").unwrap()
+    );
+    assert!(!html.contains("katex-display"));
+    // Spans refer to the input as given.
+    let Err(Error::ParseError(err)) = check_with_opts(r"x + \foo", &opts) else {
+        panic!()
+    };
+    assert_eq!(err.span, Some(4..8));
+
+    let opts = Opts::builder().font_size_em(2.0).build().unwrap();
+    let html = render_with_opts("x", &opts).unwrap();
+    assert!(html.starts_with(r#"<span class="katex" style="font-size:2em;">"#));
+    let opts = Opts::builder()
+        .font_size_em(0.8)
+        .minimal_inline_css(true)
+        .display_mode(true)
+        .build()
+        .unwrap();
+    let html = render_with_opts("x", &opts).unwrap();
+    assert!(html.contains("white-space:nowrap;font-size:0.8em;\">"));
+
+    for size in [0.0, -1.0, f64::NAN] {
+        let err = Opts::builder().font_size_em(size).build().unwrap_err();
+        assert!(err.to_string().contains("font_size_em"));
+    }
+}
+
+#[test]
+fn test_mathjax_compat() {
+    // The annotations keep the rewritten input.
+    let plain = Opts::builder().strip_annotations(true).build().unwrap();
+    let render = |input: &str| render_with_opts(input, &plain);
+    let opts = Opts::builder()
+        .mathjax_compat(true)
+        .strip_annotations(true)
+        .build()
+        .unwrap();
+    let render_compat = |input: &str| render_with_opts(input, &opts).unwrap();
+    let input = r"\require{cancel} \DeclareMathOperator*{\argmax}{arg\,max} \argmax_x \unicode{x2192} \unicode{35} \label{eq} \color[rgb]{1,0,0.5}{y}";
+    assert!(render(input).is_err());
+    assert_eq!(
+        render_compat(input),
+        render(
+            r#"\def\argmax{\operatorname*{arg\,max}} \argmax_x → {\char"23} \color{#ff0080}{y}"#
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        render_compat(r"\fcolorbox[HTML]{FF0000}{EEEEEE}{\mbox{a b}}"),
+        render(r"\fcolorbox{#FF0000}{#EEEEEE}{\text{a b}}").unwrap()
+    );
+    // Invalid colors are left to KaTeX.
+    let err = render_with_opts(r"\color[rgb]{2,0,0}{y}", &opts).unwrap_err();
+    assert!(err.to_string().contains("Invalid color"), "{err}");
+
+    // Macros override the emulated commands.
+    let opts = Opts::builder()
+        .add_macro(r"\label".to_owned(), r"\text{#1}".to_owned())
+        .mathjax_compat(true)
+        .build()
+        .unwrap();
+    let html = render_with_opts(r"\label{eq}", &opts).unwrap();
+    assert!(html.contains("<mtext>eq</mtext>"));
+    assert_eq!(lint(r"\unicode{x41} \require{ams}").unwrap().len(), 2);
+    assert!(lint_with_opts(r"\unicode{x41} \require{ams}", &opts)
+        .unwrap()
+        .is_empty());
+
+    // Spans of parse errors refer to the original input.
+    let err = check_with_opts(r"\unicode{x2192} \foo", &opts).unwrap_err();
+    let Error::ParseError(err) = err else {
+        panic!("{err}")
+    };
+    assert_eq!(err.span, Some(16..20));
+}
+
+#[test]
+fn test_strip_annotations() {
+    let opts = Opts::builder().strip_annotations(true).build().unwrap();
+    let input = r"\frac{a}{b} + \text{<semantics>}";
+    let full = render(input).unwrap();
+    let html = render_with_opts(input, &opts).unwrap();
+    assert!(full.contains("<annotation"));
+    assert!(!html.contains("<annotation"));
+    assert!(!html.contains("<semantics>"));
+    assert!(html.contains("<mfrac>"));
+    assert!(html.contains("&lt;semantics&gt;"));
+    assert!(html.len() < full.len());
+    let stripped = full
+        .replace("<semantics>", "")
+        .replacen(r#"<annotation encoding="application/x-tex">\frac{a}{b} + \text{&lt;semantics&gt;}</annotation></semantics>"#, "", 1);
+    assert_eq!(html, stripped);
+
+    let opts = Opts::builder()
+        .strip_annotations(true)
+        .output_type(OutputType::Html)
+        .build()
+        .unwrap();
+    assert!(render_with_opts(input, &opts).is_ok());
+    let err = Opts::builder()
+        .strip_annotations(true)
+        .output_type(OutputType::HtmlAndMathmlWithAnnotation)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("strip_annotations"));
+
+    let opts = Opts::builder().include_annotation(false).build().unwrap();
+    assert_eq!(render_with_opts(input, &opts).unwrap(), stripped);
+    let mut opts = Opts::builder()
+        .strip_annotations(true)
+        .include_annotation(true)
+        .build()
+        .unwrap();
+    assert_eq!(render_with_opts(input, &opts).unwrap(), full);
+    opts.set_include_annotation(false);
+    assert_eq!(render_with_opts(input, &opts).unwrap(), stripped);
+}
+
+#[test]
+fn test_opts_merge() {
+    let global = Opts::builder()
+        .add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned())
+        .allow_commands(&[r"\href"])
+        .throw_on_error(false)
+        .max_expand(100)
+        .deterministic(true)
+        .build()
+        .unwrap();
+    let chapter = Opts::builder()
+        .add_macro(r"\RR".to_owned(), r"\mathbf{R}".to_owned())
+        .allow_commands(&[r"\url", r"\href"])
+        .throw_on_error(true)
+        .error_color("#ff0000")
+        .build()
+        .unwrap();
+    let opts = global.merge(&chapter);
+    assert_eq!(
+        opts.fingerprint(),
+        global
+            .merge(&Opts::default())
+            .merge(&chapter)
+            .merge(&Opts::default())
+            .fingerprint()
+    );
+    let effective = opts.effective();
+    assert!(effective.throw_on_error);
+    assert_eq!(effective.error_color, "#ff0000");
+    assert_eq!(effective.max_expand, Limit::Finite(100));
+    assert_eq!(effective.macros[r"\RR"].body, r"\mathbf{R}");
+    assert_eq!(effective.trusted_commands, [r"\href", r"\url"]);
+    assert_eq!(opts.deterministic, Some(true));
+    assert_eq!(
+        render_with_opts(r"\RR", &opts).unwrap(),
+        render_with_opts(
+            r"\RR",
+            chapter.merge(&Opts::builder().deterministic(true).build().unwrap())
+        )
+        .unwrap()
+    );
+
+    let book = Opts::builder()
+        .normalize_unicode(true)
+        .strip_annotations(true)
+        .text_unicode_policy(TextUnicodePolicy::StripEmoji)
+        .build()
+        .unwrap();
+    let mut equation = Opts::default();
+    equation.set_normalize_unicode(false);
+    equation.set_include_annotation(true);
+    equation.set_text_unicode_policy(TextUnicodePolicy::PassThrough);
+    let opts = book.merge(&equation);
+    assert_eq!(opts.normalize_unicode, Some(false));
+    assert_eq!(opts.strip_annotations, Some(false));
+    assert_eq!(
+        opts.text_unicode_policy,
+        Some(TextUnicodePolicy::PassThrough)
+    );
+    assert_eq!(
+        book.merge(&Opts::default()).fingerprint(),
+        book.fingerprint()
+    );
+
+    let effective = Opts::default().effective();
+    assert!(!effective.display_mode);
+    assert_eq!(effective.output_type, OutputType::HtmlAndMathml);
+    assert!(effective.macros.is_empty());
+    let effective = Opts::builder()
+        .allow_commands(&[r"\href"])
+        .trust(true)
+        .max_size(None)
+        .build()
+        .unwrap()
+        .effective();
+    assert!(effective.trust);
+    assert!(effective.trusted_commands.is_empty());
+    assert_eq!(effective.max_size, Limit::Unlimited);
+}
+
+#[test]
+fn test_embed_source() {
+    let input = r#"a < b \text{"x" \& 'y'}"#;
+    let opts = Opts::builder()
+        .display_mode(true)
+        .embed_source(true)
+        .build()
+        .unwrap();
+    let html = render_with_opts(input, &opts).unwrap();
+    let attribute =
+        r#"<span class="katex" data-latex="a &lt; b \text{&quot;x&quot; \&amp; &#x27;y&#x27;}">"#;
+    assert!(
+        html.starts_with(r#"<span class="katex-display">"#),
+        "{html}"
+    );
+    assert_eq!(html.matches("data-latex").count(), 1);
+    assert_eq!(
+        html.replacen(attribute, r#"<span class="katex">"#, 1),
+        render_with_opts(input, Opts::builder().display_mode(true).build().unwrap()).unwrap()
+    );
+
+    // The source is the input, before the preprocessing.
+    let opts = Opts::builder()
+        .replace_unicode_math(true)
+        .embed_source(true)
+        .font_size_em(1.5)
+        .build()
+        .unwrap();
+    let html = render_with_opts("α", &opts).unwrap();
+    assert!(
+        html.starts_with(r#"<span class="katex" data-latex="α" style="font-size:1.5em;">"#),
+        "{html}"
+    );
+}
+
+#[test]
+fn test_minimal_inline_css() {
+    let opts = Opts::builder()
+        .display_mode(true)
+        .minimal_inline_css(true)
+        .build()
+        .unwrap();
+    let html = render_with_opts("x", &opts).unwrap();
+    assert!(html.starts_with(
+        r#"<span class="katex-display" style="display:block;margin:1em 0;text-align:center;"><span class="katex" style="font:"#
+    ));
+    assert!(html.contains(r#"<span class="katex-html" aria-hidden="true" style="display:none;">"#));
+
+    let opts = Opts::builder()
+        .output_type(OutputType::Html)
+        .minimal_inline_css(true)
+        .build()
+        .unwrap();
+    let html = render_with_opts("x", &opts).unwrap();
+    assert!(html.starts_with(r#"<span class="katex" style="font:"#));
+    assert!(!html.contains("display:none"));
+}
+
+#[test]
+fn test_macro_recursion() {
+    let opts = Opts::builder()
+        .add_macro(r#"\foo"#.to_owned(), r#"\bar+1"#.to_owned())
+        .add_macro(r#"\bar"#.to_owned(), r#"\frac{\foo}{2}"#.to_owned())
+        .build()
+        .unwrap();
+    match render_with_opts(r#"x + \foo"#, &opts) {
+        Err(Error::MacroRecursion(cycle)) => {
+            assert_eq!(cycle, [r#"\foo"#, r#"\bar"#, r#"\foo"#]);
+        }
+        r => panic!("unexpected result: {r:?}"),
+    }
+    // Recursive macros which are never expanded are accepted, like KaTeX does.
+    assert!(render_with_opts("x", &opts).is_ok());
+    assert!(render(r#"\def\x{\x} 1"#).is_ok());
+    assert_eq!(complexity(r#"\def\x{\x} 1"#).unwrap().macro_expansions, 0);
+    assert!(matches!(
+        render(r#"\def\x{\x} \x"#),
+        Err(Error::MacroRecursion(_))
+    ));
+    // Also through a macro which is not recursive itself.
+    assert!(matches!(
+        render(r#"\def\x{\x} \def\y{\x} \y"#),
+        Err(Error::MacroRecursion(_))
+    ));
+    assert!(matches!(
+        render(r#"\def\a#1{\a{#1}} \a{x}"#),
+        Err(Error::MacroRecursion(_))
+    ));
+    assert!(matches!(
+        render(r#"\newcommand{\a}[1]{\b{#1}} \gdef\b{\a} \a{x}"#),
+        Err(Error::MacroRecursion(_))
+    ));
+    let html = render(r#"\def\a{\b} \def\b{x} \a"#).unwrap();
+    assert!(html.contains(r#"span class="katex""#));
+}
+
+#[test]
+fn test_warm_up() {
+    std::thread::spawn(|| {
+        warm_up().unwrap();
+        assert!(render("x").is_ok());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_hardened() {
+    let opts = Opts::hardened();
+    assert!(render_with_opts("x^2", &opts).is_ok());
+    let err = render_with_opts(&"x".repeat(10_001), &opts).unwrap_err();
+    assert_eq!(err.code(), "KTX2001");
+    assert!(render_with_opts(r"\frac{", &opts).is_err());
+    // 511 expansions, under KaTeX's default limit of 1000.
+    let macros = r"\def\a{\b\b}\def\b{\c\c}\def\c{\d\d}\def\d{\e\e}\def\e{\f\f}
+        \def\f{\g\g}\def\g{\h\h}\def\h{\i\i}\def\i{x}\a";
+    assert!(render_with_opts(macros, &opts).is_err());
+    assert!(render_with_opts(macros, Opts::default()).is_ok());
+}
+
+#[test]
+fn test_limit() {
+    // 1023 expansions, over KaTeX's default limit of 1000.
+    let macros = r"\def\a{\b\b}\def\b{\c\c}\def\c{\d\d}\def\d{\e\e}\def\e{\f\f}
+        \def\f{\g\g}\def\g{\h\h}\def\h{\i\i}\def\i{\j\j}\def\j{x}\a";
+    let opts = |max_expand: Limit<u32>| Opts::builder().max_expand(max_expand).build().unwrap();
+    assert!(render_with_opts(macros, opts(Limit::Default)).is_err());
+    assert!(render_with_opts(macros, opts(Limit::Finite(2000))).is_ok());
+    assert!(render_with_opts(macros, opts(Limit::Unlimited)).is_ok());
+    assert!(render_with_opts(macros, opts(Some(100).into())).is_err());
+
+    // KaTeX receives a numeric `Infinity`.
+    let renderer = EngineBuilder::new()
+        .add_script(
+            r"var render = katex.__renderToDomTree;
+            katex.__renderToDomTree = function (expr, opts) {
+                return render('\\text{' + typeof opts.maxSize + String(opts.maxSize) + '}', opts);
+            };",
+        )
+        .build()
+        .unwrap();
+    let opts = Opts::builder().max_size(None).build().unwrap();
+    let html = renderer.render_with_opts("x", &opts).unwrap();
+    assert!(html.contains("numberInfinity"), "{html}");
+    assert_eq!(opts.fingerprint(), r#"{"maxSize":null}"#);
+}
+
+#[cfg(all(
+    any(feature = "quick-js", feature = "rquickjs"),
+    not(all(feature = "android", target_os = "android"))
+))]
+#[test]
+fn test_memory_limit() {
+    let renderer = EngineBuilder::new().memory_limit(64 << 20).build().unwrap();
+    let html = renderer.render("x^2").unwrap();
+    let err = renderer
+        .render_with_opts(&r"\sqrt{x}".repeat(100_000), Opts::hardened())
+        .unwrap_err();
+    assert_eq!(err.code(), "KTX2001");
+    let err = renderer
+        .render(&r"\sqrt{\sqrt{x}}".repeat(200_000))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ResourceExhausted {
+            kind: ResourceKind::Memory,
+            ..
+        }
+    ));
+    assert_eq!(err.code(), "KTX1005");
+
+    let depth = 3000;
+    let input = format!("{}x{}", "{".repeat(depth), "}".repeat(depth));
+    let err = renderer.render(&input).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ResourceExhausted {
+            kind: ResourceKind::Stack,
+            ..
+        }
+    ));
+    let opts = Opts::builder().throw_on_error(true).build().unwrap();
+    let err = renderer
+        .render_with_opts(r"\text{out of memory} \frac{", &opts)
+        .unwrap_err();
+    assert!(matches!(err, Error::JsExecError(_)));
+    assert_eq!(renderer.render("x^2").unwrap(), html);
+}
+
+#[test]
+fn test_shutdown_thread_engine() {
+    std::thread::spawn(|| {
+        assert!(!shutdown_thread_engine());
+        assert!(!release_idle_engine());
+        let html = render("x").unwrap();
+        assert!(shutdown_thread_engine());
+        assert_eq!(render("x").unwrap(), html);
+        let resolver = ResourceResolver::new(|_| Some(shutdown_thread_engine().to_string()));
+        let opts = Opts::builder()
+            .trust(true)
+            .resource_resolver(resolver)
+            .build()
+            .unwrap();
+        let html = render_with_opts(r"\includegraphics{a.png}", &opts).unwrap();
+        assert!(html.contains("src='false '"), "{html}");
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_stack_overflow() {
+    #[inline(never)]
+    fn simulate_deep_stack(i: i32) {
+        if i > 0 {
+            simulate_deep_stack(i - 1);
+        } else {
+            let html = render("a = b + c").unwrap();
+            assert!(html.contains(r#"span class="katex""#));
+        }
+    }
+    simulate_deep_stack(100);
+    simulate_deep_stack(0);
+}
+
+#[test]
+fn test_renderer_with_stack_size() {
+    let depth = 50;
+    let input = format!("{}x{}", "{".repeat(depth), "}".repeat(depth));
+    // The calling thread's stack is too small for KaTeX itself.
+    let html = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || {
+            let renderer = crate::Renderer::with_stack_size(16 * 1024 * 1024).unwrap();
+            renderer.render(&input).unwrap()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(html.contains(r#"<mi>x</mi>"#));
+
+    let mut renderer = crate::Renderer::with_stack_size(8 * 1024 * 1024).unwrap();
+    let opts = Opts::builder().throw_on_error(true).build().unwrap();
+    assert!(matches!(
+        renderer.render_with_opts(r"\frac{", &opts),
+        Err(Error::JsExecError(msg)) if msg.contains("KaTeX parse error")
+    ));
+    let output = renderer.render_to_output("a + b", &opts).unwrap();
+    assert!(output.html.contains("katex"));
+    assert!(matches!(
+        renderer.set_console_handler(|_, _| {}),
+        Err(Error::JsInitError(_))
+    ));
+}
+
+#[cfg(all(
+    feature = "differential",
+    feature = "duktape",
+    any(feature = "quick-js", feature = "rquickjs"),
+    any(unix, windows),
+    not(target_os = "android")
+))]
+#[test]
+fn test_differential_backends() {
+    let backends = differential::backends().unwrap();
+    assert_eq!(backends.len(), 2);
+    let (reference, others) = backends.split_first().unwrap();
+    for seed in 0..300 {
+        let case = differential::generate(seed);
+        let expected = reference.render(&case.input, &case.opts);
+        for backend in others {
+            let actual = backend.render(&case.input, &case.opts);
+            let context = format!(
+                "seed {seed}, {} vs {}: {case:?}",
+                reference.name, backend.name
+            );
+            match (&expected, &actual) {
+                (Ok(expected), Ok(actual)) => assert_eq!(expected, actual, "{context}"),
+                (Err(expected), Err(actual)) => {
+                    assert_eq!(expected.code(), actual.code(), "{context}")
+                }
+                _ => panic!("{context}: {expected:?} vs {actual:?}"),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_testing_utilities() {
+    let html = assert_renders_ok!(r"\frac{a}{b}");
+    assert_has_class!(html, "mfrac");
+    assert_lacks_class!(html, "mfra");
+    assert!(testing::classes(&html).contains("katex-html"));
+    let opts = Opts::builder().throw_on_error(true).build().unwrap();
+    let err = assert_renders_err!(r"\frac{", &opts);
+    assert!(err.to_string().contains("ParseError"));
+    let result = std::panic::catch_unwind(|| {
+        assert_renders_ok!(
+            String::from(r"\frac{"),
+            Opts::builder().throw_on_error(true).build().unwrap()
+        )
+    });
+    assert!(result.is_err());
+
+    let canonical = testing::canonicalize(&assert_renders_ok!(r"\sqrt{x}"));
+    assert!(!canonical.contains("style="));
+    assert!(!canonical.contains("viewBox"));
+    assert!(canonical.contains("<svg"));
+    assert_eq!(
+        testing::canonicalize(r#"<span style="top:1em" class="b a" aria-hidden="true">x</span>"#),
+        r#"<span aria-hidden="true" class="b a">x</span>"#
+    );
+}
+
+#[test]
+fn test_ast() {
+    use ast::{MatrixKind, Node};
+
+    let node = Node::row([
+        Node::command("alpha"),
+        Node::symbol("x").with_sup("2").with_sub("i"),
+        Node::symbol("{a_b}"),
+        Node::symbol("^1"),
+        Node::text(r"if $x$ \ 100%"),
+    ]);
+    assert_eq!(
+        node.to_latex().unwrap(),
+        r#"\alpha x_{i}^{2}\{a\_b\}{\char"5E}1\text{if \$x\$ \textbackslash{} 100\%}"#
+    );
+    assert!(ast::render_ast(&node).is_ok());
+
+    let node = Node::fenced(
+        "(",
+        Node::row([Node::symbol("xy").with_sup("n"), Node::root("3", "z")]),
+        "",
+    );
+    assert_eq!(
+        node.to_latex().unwrap(),
+        r"\left({xy}^{n}\sqrt[{3}]{z}\right."
+    );
+    let node = Node::matrix(
+        MatrixKind::Bar,
+        vec![
+            vec!["a".into(), "b".into()],
+            vec!["c".into(), Node::Operator("tr".to_owned())],
+        ],
+    );
+    assert_eq!(
+        node.to_latex().unwrap(),
+        r"\begin{vmatrix}a&b\\c&\operatorname{tr}\end{vmatrix}"
+    );
+    let opts = Opts::builder().display_mode(true).build().unwrap();
+    let html = ast::render_ast_with_opts(&node, &opts).unwrap();
+    assert!(html.contains("katex-display"));
+
+    for node in [
+        Node::command("al pha"),
+        Node::fenced("", "x", "a b"),
+        Node::Raw("}{".to_owned()),
+    ] {
+        assert!(matches!(node.to_latex(), Err(Error::InvalidInput(_))));
+    }
+    assert_eq!(
+        Node::Raw(r"\mathbb{R}".to_owned())
+            .with_sup("n")
+            .to_latex()
+            .unwrap(),
+        r"{\mathbb{R}}^{n}"
+    );
+}
+
+#[test]
+fn test_latex_builder() {
+    use latex::Latex;
+
+    let latex = Latex::new()
+        .raw(r"\alpha")
+        .symbol("x")
+        .raw(r"\\")
+        .symbol("y")
+        .sup("e", Latex::new().raw(r"\pi").symbol("i"))
+        .sqrt("}{");
+    assert_eq!(latex.as_str(), r"\alpha x\\y{e}^{\pi i}\sqrt{\}\{}");
+    assert!(render(latex.as_str()).is_ok());
+    assert_eq!(Latex::from(r"\").to_string(), r"{\backslash}");
+    assert_eq!(
+        latex::escape_text("~^"),
+        r"\textasciitilde{}\textasciicircum{}"
+    );
+}
+
+#[test]
+fn test_vars() {
+    use crate::{num::NumberFormat, vars::substitute_vars};
+
+    let vars: std::collections::HashMap<String, MathValue> = [
+        ("a", MathValue::from(-3)),
+        ("b", MathValue::from(1.5)),
+        (
+            "c",
+            MathValue::from(12345.678).with_format(NumberFormat::scientific(2)),
+        ),
+        (
+            "d",
+            MathValue::from(2.0).with_format(NumberFormat::scientific(1)),
+        ),
+        ("e", MathValue::from(f64::NEG_INFINITY)),
+        ("name", MathValue::from(r"} \href{x")),
+        ("frac", MathValue::from(latex::Latex::new().frac("1", "2"))),
+        ("nan", MathValue::from(f64::NAN)),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_owned(), value))
+    .collect();
+    assert_eq!(
+        substitute_vars(r"x^\var{a} + \var {b} \\var{b} \variable", &vars).unwrap(),
+        r"x^{-3} + {1.5} \\var{b} \variable"
+    );
+    assert_eq!(
+        substitute_vars(r"\var{c}, \var{d}, \var{e}", &vars).unwrap(),
+        r"{1.23\times10^{4}}, {2.0}, {-\infty}"
+    );
+    assert_eq!(
+        substitute_vars(r"\var{name} = \var{frac}", &vars).unwrap(),
+        r"{\text{\} \textbackslash{}href\{x}} = {\frac{1}{2}}"
+    );
+    for (input, reason) in [
+        (r"\var{x}", "undefined variable"),
+        (r"\var{nan}", "not a number"),
+        (r"\var{a b}", "malformed placeholder at byte 0"),
+        (r"x\var", "malformed placeholder at byte 1"),
+    ] {
+        let err = substitute_vars(input, &vars).unwrap_err();
+        assert!(err.to_string().contains(reason), "{err}");
+    }
+
+    let html = render_with_vars(r"\var{b}^\var{a}", &vars, Opts::default()).unwrap();
+    assert_eq!(html, render("{1.5}^{-3}").unwrap());
+}
+
+#[test]
+fn test_number_format() {
+    use crate::num::NumberFormat;
+
+    let cases = [
+        (NumberFormat::default(), 1234.5, "1234.5"),
+        (NumberFormat::default(), -0.0, "0"),
+        (NumberFormat::fixed(2), -1.23456, "-1.23"),
+        (
+            NumberFormat::scientific(2),
+            0.000123456,
+            r"1.23\times10^{-4}",
+        ),
+        (NumberFormat::scientific(1), 5.0, "5.0"),
+        (NumberFormat::significant(2), 123456.0, "120000"),
+        (NumberFormat::significant(2), 9.99, "10"),
+        (NumberFormat::significant(3), 0.0012, "0.00120"),
+        (NumberFormat::significant(3), 0.0, "0.00"),
+        (
+            NumberFormat::significant(3).with_grouping("{,}"),
+            -98765.0,
+            "-98{,}800",
+        ),
+        (
+            NumberFormat::fixed(1).with_grouping(r"\,"),
+            1234.56,
+            r"1\,234.6",
+        ),
+        (NumberFormat::default(), f64::INFINITY, r"\infty"),
+    ];
+    for (format, value, expected) in cases {
+        assert_eq!(
+            format.format(value).unwrap(),
+            expected,
+            "{format:?} {value}"
+        );
+    }
+    assert!(NumberFormat::default().format(f64::NAN).is_err());
+
+    let grouping = NumberFormat::default().with_grouping("{,}");
+    assert_eq!(
+        grouping.format_int(i64::MIN),
+        "-9{,}223{,}372{,}036{,}854{,}775{,}808"
+    );
+    assert_eq!(grouping.format_int(999), "999");
+    assert_eq!(
+        NumberFormat::scientific(1).format_int(-1500),
+        r"-1.5\times10^{3}"
+    );
+
+    let vars = [(
+        "n".to_owned(),
+        MathValue::from(1234567).with_format(grouping),
+    )]
+    .into_iter()
+    .collect();
+    let latex = crate::vars::substitute_vars(r"\var{n}", &vars).unwrap();
+    assert_eq!(latex, "{1{,}234{,}567}");
+}
+
+#[test]
+fn test_max_output_bytes() {
+    let html = render("é^2").unwrap();
+    let mut opts = Opts::builder()
+        .max_output_bytes(html.len())
+        .build()
+        .unwrap();
+    assert_eq!(render_with_opts("é^2", &opts).unwrap(), html);
+    opts.set_max_output_bytes(html.len() - 1);
+    let err = render_with_opts("é^2", &opts).unwrap_err();
+    assert_eq!(err.code(), "KTX2005");
+    assert!(matches!(
+        err,
+        Error::OutputTooLarge { size, limit } if size == html.len() && limit == html.len() - 1
+    ));
+
+    // Post-processing counts too.
+    let mut opts = Opts::builder()
+        .max_output_bytes(html.len())
+        .minimal_inline_css(true)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        render_with_opts("é^2", &opts),
+        Err(Error::OutputTooLarge { .. })
+    ));
+    opts.set_max_output_bytes(10 * html.len());
+    assert!(render_to_output("é^2", &opts).is_ok());
+    assert!(render_with_opts(&r"\sqrt{x}".repeat(500), Opts::hardened()).is_ok());
+    assert!(matches!(
+        render_with_opts(&r"\sqrt{x}".repeat(1200), Opts::hardened()),
+        Err(Error::OutputTooLarge { .. })
+    ));
+}
+
+#[test]
+fn test_track_macros() {
+    let opts = Opts::builder()
+        .add_macro(r"\RR".to_owned(), r"\mathbb{R}".to_owned())
+        .add_macro(r"\NN".to_owned(), r"\mathbb{N}".to_owned())
+        .add_macro(r"\set".to_owned(), r"\{#1\}".to_owned())
+        .add_macro(r"\unused".to_owned(), "u".to_owned())
+        .track_macros(true)
+        .build()
+        .unwrap();
+    let output = render_to_output(r"\def\x{\NN} \set{\x} \cup \RR \cup \NN", &opts).unwrap();
+    assert_eq!(output.used_macros, [r"\set", r"\NN", r"\RR"]);
+    let output = render_to_output("x", &opts).unwrap();
+    assert!(output.used_macros.is_empty());
+    let renderer = Renderer::new().unwrap();
+    let output = renderer.render_to_output(r"\RR", &opts).unwrap();
+    assert_eq!(output.used_macros, [r"\RR"]);
+
+    let mut opts = opts;
+    opts.set_track_macros(false);
+    let output = render_to_output(r"\RR", &opts).unwrap();
+    assert!(output.used_macros.is_empty());
+    assert_eq!(output.html, render_with_opts(r"\RR", &opts).unwrap());
+}
+
+#[test]
+fn test_engine_info() {
+    let info = engine_info();
+    #[cfg(all(feature = "android", target_os = "android"))]
+    assert_eq!(info.backend, BackendKind::Duktape);
+    #[cfg(all(
+        feature = "quick-js",
+        not(all(feature = "android", target_os = "android"))
+    ))]
+    assert_eq!(info.backend, BackendKind::QuickJs);
+    #[cfg(all(
+        feature = "rquickjs",
+        not(feature = "quick-js"),
+        not(all(feature = "android", target_os = "android"))
+    ))]
+    assert_eq!(info.backend, BackendKind::Rquickjs);
+    #[cfg(all(
+        feature = "jsc",
+        not(any(
+            feature = "quick-js",
+            feature = "duktape",
+            feature = "wasm-js",
+            feature = "rquickjs",
+            feature = "android"
+        ))
+    ))]
+    assert_eq!(info.backend, BackendKind::Jsc);
+    assert!(!info.version.is_empty());
+    assert_eq!(BackendKind::Duktape.to_string(), "duktape");
+}
+
+#[test]
+fn test_opts_sync_send() {
+    fn is_sync_send<T: Sync + Send>(_: T) {}
+    let opts = Opts::builder().build().unwrap();
+    is_sync_send(opts);
+}
+
+#[test]
+fn test_katex_version() {
+    assert!(!crate::KATEX_VERSION.is_empty());
+    assert!(!crate::KATEX_VERSION.contains('\n'));
+}
+
+#[cfg(all(feature = "wasm-bindings", target_arch = "wasm32"))]
+#[test]
+fn test_wasm_bindings() {
+    use crate::wasm_bindings::render_katex;
+    use wasm_bindgen::JsValue;
+
+    let html = render_katex("E = mc^2", JsValue::UNDEFINED).unwrap();
+    assert_eq!(html, crate::render("E = mc^2").unwrap());
+
+    let opts = js_sys::Object::new();
+    let macros = js_sys::Object::new();
+    js_sys::Reflect::set(&macros, &"\\RR".into(), &"\\mathbb{R}".into()).unwrap();
+    js_sys::Reflect::set(&opts, &"displayMode".into(), &true.into()).unwrap();
+    js_sys::Reflect::set(&opts, &"macros".into(), &macros).unwrap();
+    let html = render_katex(r"x \in \RR", opts.into()).unwrap();
+    assert!(html.contains("katex-display"));
+    assert!(html.contains("mathbb"));
+
+    let opts = js_sys::Object::new();
+    js_sys::Reflect::set(&opts, &"displayMode".into(), &"yes".into()).unwrap();
+    assert!(render_katex("x", opts.into()).is_err());
+    assert!(render_katex(r"\frac", JsValue::UNDEFINED).is_err());
+}
+
+#[cfg(any(feature = "leptos", feature = "yew"))]
+#[test]
+fn test_frontend_fallback() {
+    use crate::frontend::render_or_fallback;
+
+    let html = render_or_fallback("E = mc^2", false, None);
+    assert_eq!(
+        html,
+        render_with_opts("E = mc^2", Opts::hardened()).unwrap()
+    );
+    let html = render_or_fallback("E = mc^2", true, None);
+    assert!(html.contains("katex-display"));
+    let html = render_or_fallback(r"\frac{<b>}", false, None);
+    assert!(html.starts_with(r#"<span class="katex-error" title="#));
+    assert!(html.ends_with(r"\frac{&lt;b&gt;}</span>"));
+    let opts = Opts::builder().throw_on_error(false).build().unwrap();
+    let html = render_or_fallback(r"\frac", false, Some(&opts));
+    assert!(html.contains("color:#cc0000"));
+}
+
+// On wasm, the renders wait for the event loop, which polling cannot run.
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn test_render_async() {
+    use core::{
+        future::Future,
+        pin::Pin,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Context, Poll, Waker},
+    };
+    use std::sync::Arc;
+
+    // Poll the futures in turn until they are all done.
+    fn run_all(
+        mut futures: Vec<Pin<Box<dyn Future<Output = Result<String>> + '_>>>,
+    ) -> Vec<Result<String>> {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut results: Vec<_> = futures.iter().map(|_| None).collect();
+        while results.iter().any(Option::is_none) {
+            for (future, result) in futures.iter_mut().zip(&mut results) {
+                if result.is_none() {
+                    if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                        *result = Some(output);
+                    }
+                }
+            }
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    let resolved = Arc::new(AtomicUsize::new(0));
+    let counter = resolved.clone();
+    let opts = Opts::builder()
+        .trust(true)
+        .resource_resolver(ResourceResolver::with_id("counter", move |url| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Some(url.to_owned())
+        }))
+        .build()
+        .unwrap();
+    let image = r"\includegraphics[height=1em]{a.png}";
+    let expected = render_with_opts(image, &opts).unwrap();
+    let per_render = resolved.swap(0, Ordering::SeqCst);
+    assert!(per_render > 0);
+    let results = run_all(vec![
+        Box::pin(queue::render_async(image, &opts)),
+        Box::pin(queue::render_async(image, &opts)),
+        Box::pin(queue::render_async("x^2", &opts)),
+        Box::pin(queue::render_async(r"\frac", &opts)),
+    ]);
+    // The identical renders are rendered once.
+    assert_eq!(resolved.load(Ordering::SeqCst), per_render);
+    assert_eq!(results[0].as_ref().unwrap(), &expected);
+    assert_eq!(results[1].as_ref().unwrap(), &expected);
+    assert_eq!(
+        results[2].as_ref().unwrap(),
+        &render_with_opts("x^2", &opts).unwrap()
+    );
+    assert!(results[3].is_err());
+
+    // Renders with a resolver without id are not shared.
+    let counter = resolved.clone();
+    let opts = Opts::builder()
+        .trust(true)
+        .resource_resolver(ResourceResolver::new(move |url| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Some(url.to_owned())
+        }))
+        .build()
+        .unwrap();
+    resolved.store(0, Ordering::SeqCst);
+    let results = run_all(vec![
+        Box::pin(queue::render_async(image, &opts)),
+        Box::pin(queue::render_async(image, &opts)),
+    ]);
+    assert_eq!(resolved.load(Ordering::SeqCst), 2 * per_render);
+    assert!(results
+        .iter()
+        .all(|html| html.as_ref().unwrap() == &expected));
+
+    // A dropped render leaves the queue to the others.
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut first = Box::pin(queue::render_async("a", &opts));
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    let second = Box::pin(queue::render_async("a", &opts));
+    drop(first);
+    assert!(run_all(vec![second]).pop().unwrap().is_ok());
+}
+
+#[cfg(all(
+    feature = "wasm-worker",
+    feature = "wasm-js-test-in-browser",
+    target_arch = "wasm32"
+))]
+#[test]
+async fn test_render_in_worker() {
+    let opts = Opts::builder().display_mode(true).build().unwrap();
+    let html = render_in_worker("E = mc^2", &opts).await.unwrap();
+    assert_eq!(html, render_with_opts("E = mc^2", &opts).unwrap());
+    assert!(render_in_worker(r"\frac", &opts).await.is_err());
+}
+
+#[cfg(all(feature = "wasm-js-test-in-browser", target_arch = "wasm32"))]
+#[test]
+fn test_render_to_element() {
+    use wasm_bindgen::JsCast;
+
+    let element: web_sys::Element = js_sys::eval("document.createElement('div')")
+        .unwrap()
+        .unchecked_into();
+    element.set_inner_html("<p>stale</p>");
+    let opts = Opts::builder().display_mode(true).build().unwrap();
+    wasm::render_to_element("E = mc^2", &element, &opts).unwrap();
+    let html = element.inner_html();
+    assert!(html.contains(r#"span class="katex-display""#));
+    assert!(!html.contains("stale"));
+    assert!(wasm::render_to_element(r"\frac", &element, &opts).is_err());
+    let opts = Opts::builder().embed_source(true).build().unwrap();
+    assert!(matches!(
+        wasm::render_to_element("x", &element, &opts),
+        Err(Error::InvalidOption {
+            field: "embed_source",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_entry_namespace() {
+    use crate::js_engine::{Engine, JsEngine, JsValue as _};
+
+    let engine: Engine = init_katex().unwrap();
+    let eval = |code| engine.eval(code).unwrap().into_string().unwrap();
+    // Only the namespace is global, besides KaTeX and the console.
+    assert_eq!(
+        eval("[typeof __katex_rs, typeof katexRenderToString, typeof prepareOpts, typeof logs].join()"),
+        "function,undefined,undefined,undefined"
+    );
+    assert_eq!(eval("__katex_rs.version"), "0.16.7");
+    let args = [
+        engine.create_string_value("x^2".into()).unwrap(),
+        engine.create_object_value(core::iter::empty()).unwrap(),
+    ];
+    let tree = engine
+        .call_function("__katex_rs.parse", args.into_iter())
+        .and_then(|value| value.into_string())
+        .unwrap();
+    assert!(tree.starts_with(r#"[{"type":"supsub""#));
+    assert!(!tree.contains(r#""loc""#));
+}
+
+#[test]
+fn test_unicode_round_trip() {
+    use crate::js_engine::{Engine, JsEngine, JsValue as _};
+
+    // Emoji, CJK, a combining accent and astral-plane symbols.
+    let samples = ["😀👍🏽", "中文テキスト", "e\u{301}", "𝔸𝕓𝐀𝑥", "a😀b中c"];
+    let engine = Engine::new().unwrap();
+    engine
+        .eval("var katexRsEcho = function (s) { return s; };")
+        .unwrap();
+    for sample in samples {
+        let value = engine.create_string_value(sample.into()).unwrap();
+        let echoed = engine
+            .call_function("katexRsEcho", core::iter::once(value))
+            .and_then(|value| value.into_string())
+            .unwrap();
+        assert_eq!(echoed, sample);
+    }
+    // Surrogate pairs created in JS rather than passed from Rust.
+    let created = engine.eval(r#""\uD83D\uDE00""#).unwrap();
+    assert_eq!(created.into_string().unwrap(), "😀");
+    let lone = engine
+        .eval(r#""a\uD800b""#)
+        .and_then(|value| value.into_string());
+    assert_eq!(lone.unwrap_err().code(), "KTX1006");
+
+    for sample in samples {
+        let html = render(&format!(r"\text{{{sample}}}")).unwrap();
+        assert!(html.contains(sample), "{sample} is lost in {html}");
+    }
+    let html = render(r"\text{😀} + \mathbb{A}").unwrap();
+    assert!(html.contains("😀"));
+}
+
+#[test]
+fn test_preview_session() {
+    use crate::preview::PreviewSession;
+    use core::time::Duration;
+
+    let opts = Opts::builder().throw_on_error(false).build().unwrap();
+    let mut session = PreviewSession::new(&opts);
+    let update = session.update("a", r"\sqrt{");
+    assert!(update.is_stale);
+    assert_eq!(update.html, None);
+    let good = session.update("a", r"\sqrt{x}").html.unwrap();
+    // A parse error keeps the previous render rather than KaTeX's error span.
+    let update = session.update("a", r"\sqrt{x} + \frac{");
+    assert!(update.is_stale);
+    assert_eq!(update.html.as_deref(), Some(good.as_str()));
+    assert!(matches!(update.error, Some(Error::ParseError(_))));
+    // Equations are independent.
+    assert_eq!(session.update("b", "y").html, Some(render("y").unwrap()));
+
+    let mut session = PreviewSession::new(&opts).with_debounce(Duration::from_secs(3600));
+    let first = session.update("a", "x").html.unwrap();
+    let update = session.update("a", "x^2");
+    assert!(update.is_stale && update.error.is_none());
+    assert_eq!(update.html.as_deref(), Some(first.as_str()));
+    assert!(session.is_pending("a"));
+    assert!(session.flush_due().is_empty());
+    // Going back to the rendered input needs no render.
+    let update = session.update("a", "x");
+    assert!(!update.is_stale && !session.is_pending("a"));
+    session.update("a", "x^2");
+    let update = session.flush("a").unwrap();
+    assert!(!update.is_stale);
+    assert_eq!(update.html, Some(render("x^2").unwrap()));
+    assert!(session.flush("a").is_none());
+}
+
+#[test]
+fn test_opts_from_katex_json() {
+    let string = |s: &str| JsonValue::String(s.to_owned());
+    let json = JsonValue::Object(vec![
+        ("displayMode".to_owned(), JsonValue::Bool(true)),
+        ("trust".to_owned(), JsonValue::Array(vec![string(r"\href")])),
+        (
+            "macros".to_owned(),
+            JsonValue::Object(vec![(r"\RR".to_owned(), string(r"\mathbb{R}"))]),
+        ),
+        ("maxExpand".to_owned(), JsonValue::Number(10.0)),
+    ]);
+    let opts = Opts::from_katex_json(json).unwrap();
+    let html = render_with_opts(r"\href{https://a.b}{\RR}", &opts).unwrap();
+    assert!(html.contains("katex-display") && html.contains(r#"href="https://a.b""#));
+    assert!(opts.fingerprint().contains(r#""maxExpand":10"#));
+    assert_eq!(
+        Opts::from_katex_json(JsonValue::Null)
+            .unwrap()
+            .fingerprint(),
+        Opts::default().fingerprint()
+    );
+    let err = Opts::from_katex_json(JsonValue::Bool(true)).unwrap_err();
+    assert_eq!(err.code(), "KTX2001");
+}
+
+#[test]
+fn test_number_marshaling() {
+    use crate::js_engine::{Engine, JsEngine, JsValue as _};
+
+    // Every backend passes numbers to KaTeX exactly, as JS numbers.
+    let engine = Engine::new().unwrap();
+    engine
+        .eval(
+            "var katexRsDescribe = function (v) { \
+                return typeof v + ':' + (Object.is(v, -0) ? '-0' : String(v)); \
+            };",
+        )
+        .unwrap();
+    let describe = |value| {
+        engine
+            .call_function("katexRsDescribe", core::iter::once(value))
+            .and_then(|value| value.into_string())
+            .unwrap()
+    };
+    let floats = [
+        (0.0, "number:0"),
+        (-0.0, "number:-0"),
+        (1.5, "number:1.5"),
+        (2147483647.0, "number:2147483647"),
+        (4294967295.0, "number:4294967295"),
+        (9007199254740992.0, "number:9007199254740992"),
+        (1e308, "number:1e+308"),
+        (5e-324, "number:5e-324"),
+        (f64::INFINITY, "number:Infinity"),
+        (f64::NEG_INFINITY, "number:-Infinity"),
+        (f64::NAN, "number:NaN"),
+    ];
+    for (value, expected) in floats {
+        assert_eq!(
+            describe(engine.create_float_value(value).unwrap()),
+            expected
+        );
+    }
+    let ints = [
+        (0, "number:0"),
+        (-1, "number:-1"),
+        (i32::MAX, "number:2147483647"),
+        (i32::MIN, "number:-2147483648"),
+    ];
+    for (value, expected) in ints {
+        assert_eq!(describe(engine.create_int_value(value).unwrap()), expected);
+    }
+}
+
+#[test]
+fn test_option_number_edge_cases() {
+    let nested = r"\def\a{x}\def\b{\a\a}\def\c{\b\b}\c";
+    for max_expand in [i32::MAX as u32, u32::MAX] {
+        let opts = Opts::builder().max_expand(max_expand).build().unwrap();
+        assert!(render_with_opts(nested, &opts).is_ok());
+        let opts = Opts::builder()
+            .raw_option("maxExpand", f64::from(max_expand))
+            .build()
+            .unwrap();
+        assert!(render_with_opts(nested, &opts).is_ok());
+    }
+    let opts = Opts::builder().max_expand(2).build().unwrap();
+    assert!(render_with_opts(nested, &opts).is_err());
+
+    // Sizes are clamped to the exact limit, however small or large.
+    let rule = r"\rule{1em}{100em}";
+    for (max_size, height) in [(5e-324, "0em"), (0.5, "0.5em"), (1e308, "100em")] {
+        let opts = Opts::builder().max_size(max_size).build().unwrap();
+        let html = render_with_opts(rule, &opts).unwrap();
+        assert!(
+            html.contains(&format!("height:{height}")),
+            "{max_size}: {html}"
+        );
+    }
 }
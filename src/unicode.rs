@@ -0,0 +1,279 @@
+//! Unicode normalization of the input, and replacement of Unicode math
+//! characters with LaTeX commands.
+
+mod tables;
+
+use crate::preprocess::Edit;
+use core::ops::Range;
+use std::borrow::Cow;
+
+/// Unicode math characters and their LaTeX replacement.
+///
+/// They are the characters commonly found in formulas copied from word
+/// processors and web pages.
+const UNICODE_MATH: &[(char, &str)] = &[
+    ('\u{2212}', "-"),
+    ('\u{00D7}', r"\times"),
+    ('\u{00F7}', r"\div"),
+    ('\u{00B1}', r"\pm"),
+    ('\u{2213}', r"\mp"),
+    ('\u{00B7}', r"\cdot"),
+    ('\u{22C5}', r"\cdot"),
+    ('\u{2264}', r"\le"),
+    ('\u{2265}', r"\ge"),
+    ('\u{2260}', r"\ne"),
+    ('\u{2248}', r"\approx"),
+    ('\u{2261}', r"\equiv"),
+    ('\u{221D}', r"\propto"),
+    ('\u{221E}', r"\infty"),
+    ('\u{2202}', r"\partial"),
+    ('\u{2207}', r"\nabla"),
+    ('\u{2211}', r"\sum"),
+    ('\u{220F}', r"\prod"),
+    ('\u{222B}', r"\int"),
+    ('\u{2200}', r"\forall"),
+    ('\u{2203}', r"\exists"),
+    ('\u{2208}', r"\in"),
+    ('\u{2209}', r"\notin"),
+    ('\u{2282}', r"\subset"),
+    ('\u{2286}', r"\subseteq"),
+    ('\u{222A}', r"\cup"),
+    ('\u{2229}', r"\cap"),
+    ('\u{2205}', r"\emptyset"),
+    ('\u{2192}', r"\to"),
+    ('\u{2190}', r"\leftarrow"),
+    ('\u{21D2}', r"\Rightarrow"),
+    ('\u{21D4}', r"\Leftrightarrow"),
+    ('\u{2032}', "'"),
+    ('\u{2033}', "''"),
+    ('\u{2026}', r"\ldots"),
+    ('\u{03B1}', r"\alpha"),
+    ('\u{03B2}', r"\beta"),
+    ('\u{03B3}', r"\gamma"),
+    ('\u{03B4}', r"\delta"),
+    ('\u{03B5}', r"\varepsilon"),
+    ('\u{03B6}', r"\zeta"),
+    ('\u{03B7}', r"\eta"),
+    ('\u{03B8}', r"\theta"),
+    ('\u{03B9}', r"\iota"),
+    ('\u{03BA}', r"\kappa"),
+    ('\u{03BB}', r"\lambda"),
+    ('\u{03BC}', r"\mu"),
+    ('\u{03BD}', r"\nu"),
+    ('\u{03BE}', r"\xi"),
+    ('\u{03C0}', r"\pi"),
+    ('\u{03C1}', r"\rho"),
+    ('\u{03C3}', r"\sigma"),
+    ('\u{03C4}', r"\tau"),
+    ('\u{03C5}', r"\upsilon"),
+    ('\u{03C6}', r"\varphi"),
+    ('\u{03C7}', r"\chi"),
+    ('\u{03C8}', r"\psi"),
+    ('\u{03C9}', r"\omega"),
+    ('\u{0393}', r"\Gamma"),
+    ('\u{0394}', r"\Delta"),
+    ('\u{0398}', r"\Theta"),
+    ('\u{039B}', r"\Lambda"),
+    ('\u{039E}', r"\Xi"),
+    ('\u{03A0}', r"\Pi"),
+    ('\u{03A3}', r"\Sigma"),
+    ('\u{03A5}', r"\Upsilon"),
+    ('\u{03A6}', r"\Phi"),
+    ('\u{03A8}', r"\Psi"),
+    ('\u{03A9}', r"\Omega"),
+];
+
+/// Normalize the input to NFC, recording the changed runs in `edits`.
+pub(crate) fn nfc<'a>(input: &'a str, edits: &mut Vec<Edit>) -> Cow<'a, str> {
+    if input.is_ascii() {
+        return Cow::Borrowed(input);
+    }
+    let mut output = String::with_capacity(input.len());
+    let mut changed = false;
+    // ASCII characters are neither decomposed nor composed with the previous
+    // character, so the runs starting at each of them are normalized on their
+    // own.
+    let bytes = input.as_bytes();
+    let mut start = 0;
+    while start < input.len() {
+        let end = (start + 1..input.len())
+            .find(|&i| bytes[i].is_ascii())
+            .unwrap_or(input.len());
+        let run = &input[start..end];
+        let normalized = if run.is_ascii() {
+            Cow::Borrowed(run)
+        } else {
+            Cow::Owned(nfc_run(run))
+        };
+        if normalized != run {
+            changed = true;
+            let output_start = output.len();
+            output.push_str(&normalized);
+            edits.push((start..end, output_start..output.len()));
+        } else {
+            output.push_str(run);
+        }
+        start = end;
+    }
+    if changed {
+        Cow::Owned(output)
+    } else {
+        Cow::Borrowed(input)
+    }
+}
+
+fn nfc_run(run: &str) -> String {
+    let mut decomposed = Vec::with_capacity(run.len());
+    for c in run.chars() {
+        decompose(c, &mut decomposed);
+    }
+    // Canonical ordering: sort marks by combining class, keeping the order of
+    // marks of the same class.
+    let mut i = 0;
+    while i < decomposed.len() {
+        let end = decomposed[i..]
+            .iter()
+            .position(|&(_, class)| class == 0)
+            .map_or(decomposed.len(), |n| i + n);
+        decomposed[i..end].sort_by_key(|&(_, class)| class);
+        i = end + 1;
+    }
+    compose(&decomposed)
+}
+
+/// Push the full canonical decomposition of `c` with the combining classes.
+fn decompose(c: char, output: &mut Vec<(char, u8)>) {
+    if let Some((l, v, t)) = decompose_hangul(c) {
+        output.extend([(l, 0), (v, 0)]);
+        output.extend(t.map(|t| (t, 0)));
+        return;
+    }
+    match tables::DECOMPOSITIONS.binary_search_by_key(&c, |&(c, _, _)| c) {
+        Ok(i) => {
+            let (_, first, second) = tables::DECOMPOSITIONS[i];
+            decompose(first, output);
+            if second != '\0' {
+                decompose(second, output);
+            }
+        }
+        Err(_) => output.push((c, combining_class(c))),
+    }
+}
+
+fn compose(decomposed: &[(char, u8)]) -> String {
+    let mut output: Vec<char> = Vec::with_capacity(decomposed.len());
+    let mut starter: Option<usize> = None;
+    let mut last_class = 0;
+    for &(c, class) in decomposed {
+        if let Some(s) = starter {
+            // A character is blocked from the starter by a character in
+            // between of the same or a higher class, or by another starter.
+            let adjacent = output.len() == s + 1;
+            if adjacent || (last_class != 0 && last_class < class) {
+                if let Some(composed) = compose_pair(output[s], c) {
+                    output[s] = composed;
+                    continue;
+                }
+            }
+        }
+        if class == 0 {
+            starter = Some(output.len());
+        }
+        last_class = class;
+        output.push(c);
+    }
+    output.into_iter().collect()
+}
+
+const HANGUL_S_BASE: u32 = 0xAC00;
+const HANGUL_L_BASE: u32 = 0x1100;
+const HANGUL_V_BASE: u32 = 0x1161;
+const HANGUL_T_BASE: u32 = 0x11A7;
+const HANGUL_V_COUNT: u32 = 21;
+const HANGUL_T_COUNT: u32 = 28;
+const HANGUL_S_COUNT: u32 = 19 * HANGUL_V_COUNT * HANGUL_T_COUNT;
+
+fn decompose_hangul(c: char) -> Option<(char, char, Option<char>)> {
+    let s = (c as u32).checked_sub(HANGUL_S_BASE)?;
+    if s >= HANGUL_S_COUNT {
+        return None;
+    }
+    let l = char::from_u32(HANGUL_L_BASE + s / (HANGUL_V_COUNT * HANGUL_T_COUNT))?;
+    let v = char::from_u32(HANGUL_V_BASE + s % (HANGUL_V_COUNT * HANGUL_T_COUNT) / HANGUL_T_COUNT)?;
+    let t = s % HANGUL_T_COUNT;
+    let t = if t == 0 {
+        None
+    } else {
+        char::from_u32(HANGUL_T_BASE + t)
+    };
+    Some((l, v, t))
+}
+
+fn compose_pair(first: char, second: char) -> Option<char> {
+    let (a, b) = (first as u32, second as u32);
+    if (HANGUL_L_BASE..HANGUL_L_BASE + 19).contains(&a)
+        && (HANGUL_V_BASE..HANGUL_V_BASE + HANGUL_V_COUNT).contains(&b)
+    {
+        let lv = (a - HANGUL_L_BASE) * HANGUL_V_COUNT + (b - HANGUL_V_BASE);
+        return char::from_u32(HANGUL_S_BASE + lv * HANGUL_T_COUNT);
+    }
+    if (HANGUL_S_BASE..HANGUL_S_BASE + HANGUL_S_COUNT).contains(&a)
+        && (a - HANGUL_S_BASE).is_multiple_of(HANGUL_T_COUNT)
+        && (HANGUL_T_BASE + 1..HANGUL_T_BASE + HANGUL_T_COUNT).contains(&b)
+    {
+        return char::from_u32(a + b - HANGUL_T_BASE);
+    }
+    tables::COMPOSITIONS
+        .binary_search_by_key(&(first, second), |&(a, b, _)| (a, b))
+        .ok()
+        .map(|i| tables::COMPOSITIONS[i].2)
+}
+
+fn combining_class(c: char) -> u8 {
+    tables::COMBINING_CLASSES
+        .binary_search_by(|&(start, end, _)| {
+            if end < c {
+                core::cmp::Ordering::Less
+            } else if start > c {
+                core::cmp::Ordering::Greater
+            } else {
+                core::cmp::Ordering::Equal
+            }
+        })
+        .map_or(0, |i| tables::COMBINING_CLASSES[i].2)
+}
+
+/// Replace the Unicode math characters of the ranges `math` of the input with
+/// LaTeX commands, recording the replacements in `edits`.
+pub(crate) fn replace_unicode_math<'a>(
+    input: &'a str,
+    math: impl Iterator<Item = Range<usize>>,
+    edits: &mut Vec<Edit>,
+) -> Cow<'a, str> {
+    let mut output = String::new();
+    let mut last = 0;
+    for range in math {
+        for (pos, c) in input[range.clone()].char_indices() {
+            let Some(&(_, replacement)) = UNICODE_MATH.iter().find(|(u, _)| *u == c) else {
+                continue;
+            };
+            let start = range.start + pos;
+            let end = start + c.len_utf8();
+            output.push_str(&input[last..start]);
+            let output_start = output.len();
+            output.push_str(replacement);
+            // Keep a command from running into the following letters.
+            let next = input[end..].chars().next();
+            if replacement.starts_with('\\') && next.is_some_and(|c| c.is_ascii_alphabetic()) {
+                output.push(' ');
+            }
+            edits.push((start..end, output_start..output.len()));
+            last = end;
+        }
+    }
+    if last == 0 {
+        return Cow::Borrowed(input);
+    }
+    output.push_str(&input[last..]);
+    Cow::Owned(output)
+}
@@ -0,0 +1,179 @@
+//! Substitute variables into formulas before rendering.
+//!
+//! Placeholders such as `\var{x0}` in the input are replaced by the value of
+//! the variable, escaped and grouped, so that a value cannot change the rest
+//! of the formula, e.g. for dashboards rendering the same formula with live
+//! numbers.
+//!
+//! # Examples
+//!
+//! ```
+//! use katex::{num::NumberFormat, vars::{render_with_vars, MathValue}};
+//! use std::collections::HashMap;
+//!
+//! let mut vars = HashMap::new();
+//! vars.insert("rate".to_owned(), MathValue::from(0.0525).with_format(NumberFormat::fixed(2)));
+//! vars.insert("n".to_owned(), MathValue::from(12));
+//! vars.insert("label".to_owned(), MathValue::from("APR {max}"));
+//! assert_eq!(
+//!     katex::vars::substitute_vars(r"\var{label}: (1 + \var{rate})^\var{n}", &vars).unwrap(),
+//!     r"{\text{APR \{max\}}}: (1 + {0.05})^{12}"
+//! );
+//! let html = render_with_vars(r"(1 + \var{rate})^\var{n}", &vars, katex::Opts::default()).unwrap();
+//! assert!(html.contains("0.05"));
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    latex::{escape_text, Latex},
+    num::NumberFormat,
+    opts::Opts,
+};
+use std::collections::HashMap;
+
+/// Value of a variable.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq)]
+pub enum MathValue {
+    /// An integer, written according to its format.
+    Integer(i64, NumberFormat),
+    /// A number, written according to its format.
+    Number(f64, NumberFormat),
+    /// Text typeset in text mode, escaped.
+    Text(String),
+    /// LaTeX source, inserted as is.
+    Latex(Latex),
+}
+
+impl MathValue {
+    /// Set the format of an [integer](MathValue::Integer) or a
+    /// [number](MathValue::Number), leaving other values unchanged.
+    pub fn with_format(self, format: NumberFormat) -> Self {
+        match self {
+            MathValue::Integer(value, _) => MathValue::Integer(value, format),
+            MathValue::Number(value, _) => MathValue::Number(value, format),
+            value => value,
+        }
+    }
+
+    fn to_latex(&self) -> Option<String> {
+        match self {
+            MathValue::Integer(value, format) => Some(format.format_int(*value)),
+            MathValue::Number(value, format) => format.format(*value).ok(),
+            MathValue::Text(text) => Some(format!(r"\text{{{}}}", escape_text(text))),
+            MathValue::Latex(latex) => Some(latex.as_str().to_owned()),
+        }
+    }
+}
+
+impl From<i64> for MathValue {
+    fn from(value: i64) -> Self {
+        MathValue::Integer(value, NumberFormat::default())
+    }
+}
+
+impl From<i32> for MathValue {
+    fn from(value: i32) -> Self {
+        MathValue::Integer(value.into(), NumberFormat::default())
+    }
+}
+
+impl From<u32> for MathValue {
+    fn from(value: u32) -> Self {
+        MathValue::Integer(value.into(), NumberFormat::default())
+    }
+}
+
+impl From<f64> for MathValue {
+    fn from(value: f64) -> Self {
+        MathValue::Number(value, NumberFormat::default())
+    }
+}
+
+impl From<&str> for MathValue {
+    fn from(text: &str) -> Self {
+        MathValue::Text(text.to_owned())
+    }
+}
+
+impl From<String> for MathValue {
+    fn from(text: String) -> Self {
+        MathValue::Text(text)
+    }
+}
+
+impl From<Latex> for MathValue {
+    fn from(latex: Latex) -> Self {
+        MathValue::Latex(latex)
+    }
+}
+
+/// Replace the `\var{name}` placeholders in the input by the LaTeX of the
+/// variables, each in a group.
+///
+/// Names are made of ASCII letters, digits and `_`. Fail with
+/// [`Error::InvalidInput`] on a malformed placeholder, an undefined variable
+/// or a NaN number.
+pub fn substitute_vars(input: &str, vars: &HashMap<String, MathValue>) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = find_placeholder(rest) {
+        output.push_str(&rest[..start]);
+        let after = rest[start + r"\var".len()..].trim_start();
+        let (name, after) = after
+            .strip_prefix('{')
+            .and_then(|after| after.split_once('}'))
+            .filter(|(name, _)| {
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            })
+            .ok_or_else(|| {
+                invalid(format!(
+                    "malformed placeholder at byte {}",
+                    input.len() - rest.len() + start
+                ))
+            })?;
+        let value = vars
+            .get(name)
+            .ok_or_else(|| invalid(format!("undefined variable {name:?}")))?;
+        let latex = value
+            .to_latex()
+            .ok_or_else(|| invalid(format!("variable {name:?} is not a number")))?;
+        output.push('{');
+        output.push_str(&latex);
+        output.push('}');
+        rest = after;
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Render the input after [substituting](substitute_vars) the variables.
+pub fn render_with_vars(
+    input: &str,
+    vars: &HashMap<String, MathValue>,
+    opts: impl AsRef<Opts>,
+) -> Result<String> {
+    crate::render_with_opts(&substitute_vars(input, vars)?, opts)
+}
+
+/// Find the next `\var` control word, which is not e.g. `\variable` or a line
+/// break `\\` followed by `var`.
+fn find_placeholder(input: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(i) = input[offset..].find(r"\var") {
+        let start = offset + i;
+        let backslashes = input[..start].len() - input[..start].trim_end_matches('\\').len();
+        let end = start + r"\var".len();
+        let is_escaped = backslashes % 2 == 1;
+        let is_word = !input[end..].starts_with(|c: char| c.is_ascii_alphabetic());
+        if !is_escaped && is_word {
+            return Some(start);
+        }
+        offset = end;
+    }
+    None
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidInput(format!("vars: {reason}"))
+}
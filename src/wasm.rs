@@ -0,0 +1,80 @@
+//! Rendering into the DOM, with the `wasm-js` backend.
+//!
+//! [`render_to_element`] calls KaTeX's `render` on an element of the page,
+//! which builds the DOM nodes directly, instead of rendering to a string
+//! that the browser parses again when it is assigned to `innerHTML`.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! let document = web_sys::window().unwrap().document().unwrap();
+//! let element = document.get_element_by_id("equation").unwrap();
+//! let opts = katex::Opts::builder().display_mode(true).build().unwrap();
+//! katex::wasm::render_to_element("E = mc^2", &element, &opts).unwrap();
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    js_engine::{JsEngine, JsValue},
+    opts::Opts,
+    preprocess, with_engine,
+};
+
+/// Render LaTeX equation into an element with additional
+/// [options](`Opts`), replacing its children.
+///
+/// The options changing the rendered HTML in Rust, such as
+/// [`sanitize`](crate::OptsBuilder::sanitize) or
+/// [`embed_source`](crate::OptsBuilder::embed_source), cannot apply to the
+/// DOM nodes, so they fail with [`Error::InvalidOption`].
+/// [`max_output_bytes`](crate::OptsBuilder::max_output_bytes) is ignored.
+pub fn render_to_element(
+    input: &str,
+    element: &web_sys::Element,
+    opts: impl AsRef<Opts>,
+) -> Result<()> {
+    let opts = opts.as_ref();
+    check_dom_opts(opts)?;
+    with_engine(|engine| {
+        let args = [
+            engine.create_string_value(preprocess::preprocess(input, opts)?)?,
+            opts.to_js_value(engine)?,
+            wasm_bindgen::JsValue::from(element.clone()).into(),
+        ];
+        engine
+            .call_function("__katex_rs.renderToElement", args.into_iter())?
+            .into_string()
+            .map(drop)
+    })
+}
+
+/// Reject the options which post-process the rendered HTML.
+fn check_dom_opts(opts: &Opts) -> Result<()> {
+    #[cfg(feature = "sanitize")]
+    let sanitize = opts.sanitize.unwrap_or_default();
+    #[cfg(not(feature = "sanitize"))]
+    let sanitize = false;
+    let post_processed = [
+        ("digit_grouping", opts.digit_grouping.is_some()),
+        ("resource_resolver", opts.resource_resolver.is_some()),
+        (
+            "minimal_inline_css",
+            opts.minimal_inline_css.unwrap_or_default(),
+        ),
+        ("font_size_em", opts.font_size_em.is_some()),
+        (
+            "strip_annotations",
+            opts.strip_annotations.unwrap_or_default(),
+        ),
+        ("embed_source", opts.embed_source.unwrap_or_default()),
+        ("aria_label", opts.aria_label.unwrap_or_default()),
+        ("sanitize", sanitize),
+    ];
+    match post_processed.into_iter().find(|(_, set)| *set) {
+        Some((field, _)) => Err(Error::InvalidOption {
+            field,
+            reason: "cannot apply to a render into the DOM".to_owned(),
+        }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,69 @@
+//! [wasm-bindgen](https://crates.io/crates/wasm-bindgen) bindings, to render
+//! equations from JS in modules built with wasm-pack.
+//!
+//! ```js
+//! import { renderKatex } from "./pkg/katex.js";
+//!
+//! const html = renderKatex("E = mc^2", { displayMode: true });
+//! ```
+
+use crate::opts::{JsonValue, Opts};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+
+/// Render LaTeX equation to HTML with KaTeX's options, e.g.
+/// `{ displayMode: true }`, or `undefined` for the defaults.
+///
+/// Options unknown to this crate are passed to KaTeX as
+/// [raw options](crate::OptsBuilder::raw_option). Errors are thrown as JS
+/// `Error`s.
+#[wasm_bindgen(js_name = renderKatex)]
+pub fn render_katex(input: &str, opts: JsValue) -> Result<String, JsValue> {
+    to_opts(&opts)
+        .and_then(|opts| crate::render_with_opts(input, opts).map_err(|e| e.to_string()))
+        .map_err(|e| js_sys::Error::new(&e).into())
+}
+
+/// Convert KaTeX's options to [`Opts`].
+fn to_opts(opts: &JsValue) -> Result<Opts, String> {
+    if opts.is_undefined() || opts.is_null() {
+        return Ok(Opts::default());
+    }
+    Opts::from_katex_json(to_json(opts)?).map_err(|e| e.to_string())
+}
+
+/// Convert a JS value to JSON, failing on functions and other values JSON
+/// cannot represent.
+fn to_json(value: &JsValue) -> Result<JsonValue, String> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(JsonValue::Null);
+    }
+    if let Some(flag) = value.as_bool() {
+        return Ok(JsonValue::Bool(flag));
+    }
+    if let Some(number) = value.as_f64() {
+        return Ok(JsonValue::Number(number));
+    }
+    if let Some(string) = value.as_string() {
+        return Ok(JsonValue::String(string));
+    }
+    if js_sys::Array::is_array(value) {
+        let array: &js_sys::Array = value.unchecked_ref();
+        let items = array
+            .iter()
+            .map(|item| to_json(&item))
+            .collect::<Result<_, _>>()?;
+        return Ok(JsonValue::Array(items));
+    }
+    if value.is_object() && !value.is_function() {
+        let fields = js_sys::Object::entries(value.unchecked_ref())
+            .iter()
+            .map(|entry| {
+                let entry: js_sys::Array = entry.unchecked_into();
+                let key = entry.get(0).as_string().unwrap_or_default();
+                Ok((key, to_json(&entry.get(1))?))
+            })
+            .collect::<Result<_, String>>()?;
+        return Ok(JsonValue::Object(fields));
+    }
+    Err(format!("unsupported option value {value:?}"))
+}
@@ -0,0 +1,259 @@
+//! Rendering in a Web Worker, with the `wasm-worker` feature.
+//!
+//! Rendering a page of equations on the main thread of a browser delays input
+//! and painting until all of them are done. [`render_in_worker`] posts the
+//! render to a dedicated Web Worker instead, which runs KaTeX with the same
+//! entry points as the `wasm-js` backend, and only pre- and post-processes
+//! the equation on the calling thread.
+//!
+//! The worker is started by the first render of the thread, from a `blob:`
+//! URL bundling the worker script, so the page must allow such workers in its
+//! Content Security Policy, e.g. `worker-src blob:`. Host callbacks, such as
+//! a trust callback of an [`EngineBuilder`](crate::EngineBuilder), are not
+//! available in the worker.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! wasm_bindgen_futures::spawn_local(async {
+//!     let opts = katex::Opts::builder().display_mode(true).build().unwrap();
+//!     let html = katex::render_in_worker("E = mc^2", &opts).await.unwrap();
+//!     // Insert the HTML into the page.
+//! });
+//! ```
+
+use crate::{
+    error::{Error, Result},
+    finish_render,
+    js_engine::{Engine, JsEngine},
+    opts::Opts,
+    preprocess, BUNDLED_SRC, JS_EPILOGUE_SRC, JS_PRELUDE_SRC,
+};
+use core::{
+    cell::{Cell, RefCell},
+    future::poll_fn,
+    task::{Poll, Waker},
+};
+use std::{collections::HashMap, rc::Rc};
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+/// Script answering the render jobs, after KaTeX and the entry points.
+const WORKER_SRC: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/js/worker.js"));
+
+#[wasm_bindgen]
+extern "C" {
+    type Worker;
+
+    #[wasm_bindgen(catch, constructor)]
+    fn new(url: &str) -> core::result::Result<Worker, JsValue>;
+
+    #[wasm_bindgen(catch, method, js_name = postMessage)]
+    fn post_message(this: &Worker, message: &JsValue) -> core::result::Result<(), JsValue>;
+
+    #[wasm_bindgen(method, setter)]
+    fn set_onmessage(this: &Worker, handler: &js_sys::Function);
+
+    #[wasm_bindgen(method, setter)]
+    fn set_onerror(this: &Worker, handler: &js_sys::Function);
+
+    #[wasm_bindgen(method)]
+    fn terminate(this: &Worker);
+}
+
+#[wasm_bindgen]
+extern "C" {
+    type Blob;
+
+    #[wasm_bindgen(catch, constructor)]
+    fn new(parts: &js_sys::Array, options: &JsValue) -> core::result::Result<Blob, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = URL, js_name = createObjectURL)]
+    fn create_object_url(blob: &Blob) -> core::result::Result<String, JsValue>;
+
+    #[wasm_bindgen(js_namespace = URL, js_name = revokeObjectURL)]
+    fn revoke_object_url(url: &str);
+}
+
+thread_local! {
+    static WORKER: RefCell<Option<Rc<WorkerState>>> = const { RefCell::new(None) };
+}
+
+/// Worker of the thread, with the jobs waiting for its answer.
+struct WorkerState {
+    worker: Worker,
+    next_id: Cell<u64>,
+    jobs: Rc<RefCell<HashMap<u64, Job>>>,
+    /// Whether the worker failed, e.g. to load its script.
+    failed: Rc<Cell<bool>>,
+    _on_message: Closure<dyn FnMut(JsValue)>,
+    _on_error: Closure<dyn FnMut(JsValue)>,
+}
+
+/// Answer of the worker to a job.
+#[derive(Default)]
+struct Job {
+    result: Option<Result<String>>,
+    waker: Option<Waker>,
+}
+
+impl Drop for WorkerState {
+    fn drop(&mut self) {
+        self.worker.terminate();
+    }
+}
+
+/// Render LaTeX equation to HTML with additional [options](`Opts`) in the
+/// Web Worker of the thread, starting it if needed.
+///
+/// Fail with [`Error::JsInitError`] if the worker cannot be started.
+pub async fn render_in_worker(input: &str, opts: impl AsRef<Opts>) -> Result<String> {
+    let opts = opts.as_ref();
+    let engine = Engine::new()?;
+    let message = js_sys::Object::new();
+    let set = |key: &str, value: &JsValue| {
+        js_sys::Reflect::set(&message, &JsValue::from_str(key), value)
+            .map_err(|e| Error::JsValueError(format!("{e:?}")))
+    };
+    set(
+        "input",
+        &JsValue::from_str(&preprocess::preprocess(input, opts)?),
+    )?;
+    set("opts", &opts.to_js_value(&engine)?.into_inner())?;
+
+    let state = worker()?;
+    let id = state.next_id.get();
+    state.next_id.set(id + 1);
+    set("id", &JsValue::from_f64(id as f64))?;
+    state.jobs.borrow_mut().insert(id, Job::default());
+    let _job = JobGuard {
+        jobs: state.jobs.clone(),
+        id,
+    };
+    state
+        .worker
+        .post_message(&message)
+        .map_err(|e| Error::JsValueError(format!("{e:?}")))?;
+    let result = poll_fn(|cx| {
+        let mut jobs = state.jobs.borrow_mut();
+        let Some(job) = jobs.get_mut(&id) else {
+            return Poll::Ready(Err(Error::JsExecError(
+                "the worker dropped the job".to_owned(),
+            )));
+        };
+        match job.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                job.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    })
+    .await?;
+    finish_render(input, &result, opts).map(|rendered| rendered.html)
+}
+
+/// Removes a job when its render ends or is dropped.
+struct JobGuard {
+    jobs: Rc<RefCell<HashMap<u64, Job>>>,
+    id: u64,
+}
+
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        self.jobs.borrow_mut().remove(&self.id);
+    }
+}
+
+/// Return the worker of the thread, starting a new one if there is none or
+/// it failed.
+fn worker() -> Result<Rc<WorkerState>> {
+    WORKER.with(|worker| {
+        let mut worker = worker.borrow_mut();
+        if let Some(state) = worker.as_ref().filter(|state| !state.failed.get()) {
+            return Ok(state.clone());
+        }
+        let state = Rc::new(start_worker()?);
+        *worker = Some(state.clone());
+        Ok(state)
+    })
+}
+
+fn start_worker() -> Result<WorkerState> {
+    let init_error = |e: JsValue| Error::JsInitError(format!("{e:?}"));
+    let parts = js_sys::Array::new();
+    for src in [JS_PRELUDE_SRC]
+        .into_iter()
+        .chain(BUNDLED_SRC.iter().copied())
+        .chain([JS_EPILOGUE_SRC, WORKER_SRC])
+    {
+        parts.push(&JsValue::from_str(src));
+        // Scripts may end without a newline or a semicolon.
+        parts.push(&JsValue::from_str("\n;\n"));
+    }
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(&options, &"type".into(), &"text/javascript".into())
+        .map_err(init_error)?;
+    let blob = Blob::new(&parts, &options).map_err(init_error)?;
+    let url = create_object_url(&blob).map_err(init_error)?;
+    let worker = Worker::new(&url);
+    // The worker keeps the script once started.
+    revoke_object_url(&url);
+    let worker = worker.map_err(init_error)?;
+
+    let jobs: Rc<RefCell<HashMap<u64, Job>>> = Rc::default();
+    let failed = Rc::new(Cell::new(false));
+    let on_message = {
+        let jobs = jobs.clone();
+        Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+            let data = js_sys::Reflect::get(&event, &"data".into()).unwrap_or_default();
+            let field = |key: &str| js_sys::Reflect::get(&data, &key.into()).unwrap_or_default();
+            let Some(id) = field("id").as_f64() else {
+                return;
+            };
+            let result = match field("result").as_string() {
+                Some(result) => Ok(result),
+                None => Err(Error::js_exec(
+                    field("error").as_string().unwrap_or_default(),
+                )),
+            };
+            complete(&jobs, Some(id as u64), result);
+        })
+    };
+    let on_error = {
+        let jobs = jobs.clone();
+        let failed = failed.clone();
+        Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+            failed.set(true);
+            let message = js_sys::Reflect::get(&event, &"message".into())
+                .ok()
+                .and_then(|message| message.as_string())
+                .unwrap_or_else(|| "failed to run the worker".to_owned());
+            complete(&jobs, None, Err(Error::JsInitError(message)));
+        })
+    };
+    worker.set_onmessage(on_message.as_ref().unchecked_ref());
+    worker.set_onerror(on_error.as_ref().unchecked_ref());
+    Ok(WorkerState {
+        worker,
+        next_id: Cell::new(0),
+        jobs,
+        failed,
+        _on_message: on_message,
+        _on_error: on_error,
+    })
+}
+
+/// Answer the job `id`, or all the jobs if `None`, waking their renders.
+fn complete(jobs: &RefCell<HashMap<u64, Job>>, id: Option<u64>, result: Result<String>) {
+    let wakers: Vec<_> = {
+        let mut jobs = jobs.borrow_mut();
+        jobs.iter_mut()
+            .filter(|(job_id, _)| id.is_none_or(|id| **job_id == id))
+            .filter_map(|(_, job)| {
+                job.result = Some(result.clone());
+                job.waker.take()
+            })
+            .collect()
+    };
+    wakers.into_iter().for_each(Waker::wake);
+}